@@ -0,0 +1,313 @@
+//! Historical inputs: CSV rows and recorded `MarketDataUpdate` streams, turned
+//! into `(RegimeContext, QuoteSnapshot)` frames in timestamp order.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use exchange_core::{MarketDataUpdate, QuoteSnapshot};
+use strategy_core::{MarketRegime, RegimeConfig, RegimeContext, RegimeEngine};
+
+/// One step of history: what the strategy sees and the book it trades against.
+#[derive(Debug, Clone)]
+pub struct BacktestFrame {
+    pub context: RegimeContext,
+    pub quote: QuoteSnapshot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataError {
+    Io { path: String, message: String },
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, message } => write!(f, "failed to read {}: {}", path, message),
+            Self::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+fn read_input(path: &Path) -> Result<String, DataError> {
+    std::fs::read_to_string(path).map_err(|err| DataError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    })
+}
+
+pub fn load_csv_frames(path: &Path) -> Result<Vec<BacktestFrame>, DataError> {
+    parse_csv_frames(&read_input(path)?, &RegimeConfig::default())
+}
+
+pub fn load_market_data_frames(path: &Path) -> Result<Vec<BacktestFrame>, DataError> {
+    parse_market_data_frames(&read_input(path)?, &RegimeConfig::default())
+}
+
+/// Parses comma-separated history with a header row. `ts_ms`, `venue`,
+/// `symbol` and at least one of `bid`/`ask`/`last` are required. Rows carrying
+/// a `regime` use their own context columns (missing ones default to zero);
+/// otherwise a `RegimeEngine` derives the context from the prices so far.
+/// Rows without a usable price are skipped.
+pub fn parse_csv_frames(
+    input: &str,
+    regime: &RegimeConfig,
+) -> Result<Vec<BacktestFrame>, DataError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let Some((header_line, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: HashMap<&str, usize> = header
+        .split(',')
+        .enumerate()
+        .map(|(index, name)| (name.trim(), index))
+        .collect();
+    for required in ["ts_ms", "venue", "symbol"] {
+        if !columns.contains_key(required) {
+            return Err(DataError::Parse {
+                line: header_line,
+                message: format!("missing required column '{}'", required),
+            });
+        }
+    }
+    if !["bid", "ask", "last"]
+        .iter()
+        .any(|name| columns.contains_key(name))
+    {
+        return Err(DataError::Parse {
+            line: header_line,
+            message: "one of 'bid', 'ask' or 'last' is required".to_string(),
+        });
+    }
+
+    let mut engine = RegimeEngine::new(regime.clone());
+    let mut frames = Vec::new();
+    for (line, row) in lines {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let field = |name: &str| {
+            columns
+                .get(name)
+                .and_then(|index| fields.get(*index).copied())
+                .filter(|value| !value.is_empty())
+        };
+        let number = |name: &str| -> Result<Option<f64>, DataError> {
+            field(name)
+                .map(|value| {
+                    value.parse::<f64>().map_err(|_| DataError::Parse {
+                        line,
+                        message: format!("column '{}' is not a number: {}", name, value),
+                    })
+                })
+                .transpose()
+        };
+
+        let ts_ms = field("ts_ms")
+            .unwrap_or_default()
+            .parse::<i64>()
+            .map_err(|_| DataError::Parse {
+                line,
+                message: "column 'ts_ms' must be an integer".to_string(),
+            })?;
+        let quote = QuoteSnapshot {
+            venue: field("venue").unwrap_or_default().to_string(),
+            venue_symbol: field("symbol").unwrap_or_default().to_string(),
+            bid: number("bid")?,
+            ask: number("ask")?,
+            last: number("last")?,
+            mark: None,
+            ts_ms,
+        };
+        if quote.venue.is_empty() || quote.venue_symbol.is_empty() {
+            return Err(DataError::Parse {
+                line,
+                message: "venue and symbol must not be empty".to_string(),
+            });
+        }
+
+        let derived = engine.observe_quote(&quote).map(|update| update.context);
+        let context = match field("regime") {
+            Some(regime) => RegimeContext {
+                venue: quote.venue.clone(),
+                symbol: quote.venue_symbol.clone(),
+                regime: parse_regime(regime).ok_or_else(|| DataError::Parse {
+                    line,
+                    message: format!("unknown regime: {}", regime),
+                })?,
+                spread_bps: number("spread_bps")?.unwrap_or(0.0),
+                realized_volatility: number("realized_volatility")?.unwrap_or(0.0),
+                momentum_lookback_return: number("momentum_lookback_return")?.unwrap_or(0.0),
+                order_book_imbalance: number("order_book_imbalance")?.unwrap_or(0.0),
+                ts_ms,
+            },
+            None => match derived {
+                Some(context) => context,
+                None => continue,
+            },
+        };
+        frames.push(BacktestFrame { context, quote });
+    }
+    frames.sort_by_key(|frame| frame.quote.ts_ms);
+    Ok(frames)
+}
+
+/// Parses one JSON `MarketDataUpdate` per line, as recorded from a venue
+/// stream, through a `RegimeEngine`. Trades refresh `last` on the symbol's most
+/// recent quote; every quote with a usable price becomes a frame.
+pub fn parse_market_data_frames(
+    input: &str,
+    regime: &RegimeConfig,
+) -> Result<Vec<BacktestFrame>, DataError> {
+    let mut engine = RegimeEngine::new(regime.clone());
+    let mut latest: HashMap<(String, String), QuoteSnapshot> = HashMap::new();
+    let mut frames = Vec::new();
+    for (index, raw) in input.lines().enumerate() {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            continue;
+        }
+        let update: MarketDataUpdate =
+            serde_json::from_str(raw).map_err(|err| DataError::Parse {
+                line: index + 1,
+                message: err.to_string(),
+            })?;
+        let Some(context) = engine.apply(&update).map(|update| update.context) else {
+            continue;
+        };
+        let quote = match update {
+            MarketDataUpdate::Quote(quote) => quote,
+            MarketDataUpdate::Trade(trade) => {
+                let key = (trade.venue.clone(), trade.venue_symbol.clone());
+                let Some(previous) = latest.get(&key) else {
+                    continue;
+                };
+                QuoteSnapshot {
+                    last: Some(trade.price),
+                    ts_ms: trade.ts_ms,
+                    ..previous.clone()
+                }
+            }
+            MarketDataUpdate::Disconnected { .. } => continue,
+        };
+        latest.insert(
+            (quote.venue.clone(), quote.venue_symbol.clone()),
+            quote.clone(),
+        );
+        frames.push(BacktestFrame { context, quote });
+    }
+    frames.sort_by_key(|frame| frame.quote.ts_ms);
+    Ok(frames)
+}
+
+fn parse_regime(raw: &str) -> Option<MarketRegime> {
+    let normalized: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    Some(match normalized.as_str() {
+        "trending" => MarketRegime::Trending,
+        "meanreverting" => MarketRegime::MeanReverting,
+        "highvolatility" => MarketRegime::HighVolatility,
+        "lowvolatility" => MarketRegime::LowVolatility,
+        "rangebound" => MarketRegime::RangeBound,
+        "eventdriven" => MarketRegime::EventDriven,
+        "unknown" => MarketRegime::Unknown,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_rows_with_regime_columns_are_used_verbatim() {
+        let input = "ts_ms,venue,symbol,bid,ask,regime,momentum_lookback_return\n\
+                     2000,coinbase_spot,BTC-USD,99,101,trending,0.05\n\
+                     1000,coinbase_spot,BTC-USD,98,100,range_bound,\n";
+        let frames = parse_csv_frames(input, &RegimeConfig::default()).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].quote.ts_ms, 1000);
+        assert_eq!(frames[0].context.regime, MarketRegime::RangeBound);
+        assert_eq!(frames[1].context.regime, MarketRegime::Trending);
+        assert_eq!(frames[1].context.momentum_lookback_return, 0.05);
+        assert_eq!(frames[1].quote.ask, Some(101.0));
+    }
+
+    #[test]
+    fn csv_without_context_derives_it_from_prices() {
+        let mut input = "ts_ms,venue,symbol,last\n".to_string();
+        for step in 0..6 {
+            input.push_str(&format!(
+                "{},coinbase_spot,ETH-USD,{}\n",
+                step * 1000,
+                100.0 + step as f64
+            ));
+        }
+        let config = RegimeConfig {
+            min_samples: 3,
+            ..RegimeConfig::default()
+        };
+        let frames = parse_csv_frames(&input, &config).unwrap();
+
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[1].context.regime, MarketRegime::Unknown);
+        assert_eq!(frames[2].context.regime, MarketRegime::Trending);
+        assert!((frames[5].context.momentum_lookback_return - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn csv_reports_bad_rows_with_line_numbers() {
+        let config = RegimeConfig::default();
+        let err = parse_csv_frames("ts_ms,venue,symbol,last\n1,a,B,abc\n", &config).unwrap_err();
+        assert_eq!(
+            err,
+            DataError::Parse {
+                line: 2,
+                message: "column 'last' is not a number: abc".to_string()
+            }
+        );
+        assert!(parse_csv_frames("venue,symbol,last\n", &config).is_err());
+    }
+
+    #[test]
+    fn recorded_trades_refresh_the_last_quote() {
+        let quote = |ts_ms: i64, mid: f64| {
+            serde_json::to_string(&MarketDataUpdate::Quote(QuoteSnapshot {
+                venue: "coinbase_spot".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                bid: Some(mid - 1.0),
+                ask: Some(mid + 1.0),
+                last: None,
+                mark: None,
+                ts_ms,
+            }))
+            .unwrap()
+        };
+        let trade = serde_json::json!({"Trade": {
+            "venue": "coinbase_spot", "venue_symbol": "BTC-USD", "price": 150.0,
+            "size": 2.0, "side": "Buy", "ts_ms": 3000
+        }});
+        let input = format!(
+            "{}\n{}\n{}\n{{\"Disconnected\":{{\"venue\":\"coinbase_spot\",\"reason\":\"eof\"}}}}\n",
+            quote(1000, 100.0),
+            quote(2000, 101.0),
+            trade
+        );
+        let frames = parse_market_data_frames(&input, &RegimeConfig::default()).unwrap();
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].quote.ts_ms, 3000);
+        assert_eq!(frames[2].quote.last, Some(150.0));
+        assert_eq!(frames[2].quote.bid, Some(100.0));
+        assert_eq!(frames[2].context.order_book_imbalance, 1.0);
+    }
+}
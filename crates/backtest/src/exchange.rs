@@ -0,0 +1,614 @@
+//! Exchange adapter that fills against historical quotes.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use exchange_core::{
+    BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth, ExchangeResultFuture,
+    ExchangeValueFuture, FillReport, MarketDataSink, NormalizedOrderRequest, OpenOrderSnapshot,
+    OrderAck, OrderSide, OrderSnapshot, OrderStatus, OrderType, PositionSnapshot, QuoteSnapshot,
+    SharedClock, TimeInForce,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::report::{position_key, PositionState};
+
+/// Fee rates charged on fill notional.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeModel {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        Self {
+            maker_bps: 40.0,
+            taker_bps: 60.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SimulatedState {
+    quotes: HashMap<String, QuoteSnapshot>,
+    orders: HashMap<String, OrderSnapshot>,
+    /// Resting limit orders with their reduce-only flag, in arrival order.
+    resting: Vec<(String, bool)>,
+    fills: Vec<FillReport>,
+    positions: HashMap<String, (PositionSnapshot, PositionState)>,
+    cash: HashMap<String, f64>,
+    next_id: u64,
+}
+
+/// Market orders take the touch plus `slippage_bps`; marketable limits fill the
+/// same way capped at their limit. Other limits rest and fill at their price
+/// once a later quote crosses them, unless IOC/FOK, which cancel instead.
+pub struct SimulatedExchange {
+    venue: String,
+    clock: SharedClock,
+    fees: FeeModel,
+    slippage_bps: f64,
+    state: Mutex<SimulatedState>,
+}
+
+impl SimulatedExchange {
+    pub fn new(
+        venue: impl Into<String>,
+        clock: SharedClock,
+        fees: FeeModel,
+        slippage_bps: f64,
+    ) -> Self {
+        Self {
+            venue: venue.into(),
+            clock,
+            fees,
+            slippage_bps,
+            state: Mutex::new(SimulatedState::default()),
+        }
+    }
+
+    /// Fills booked after the first `seen`, in booking order.
+    pub(crate) fn fills_after(&self, seen: usize) -> Vec<FillReport> {
+        let state = self.state.lock().unwrap();
+        state.fills.get(seen..).unwrap_or_default().to_vec()
+    }
+
+    /// Installs the current book for the quote's symbol and fills any resting
+    /// limit orders it crosses.
+    pub fn update_quote(&self, quote: QuoteSnapshot) {
+        let mut state = self.state.lock().unwrap();
+        let key = position_key(&quote.venue, &quote.venue_symbol);
+        state.quotes.insert(key, quote.clone());
+
+        let resting = std::mem::take(&mut state.resting);
+        for (venue_order_id, reduce_only) in resting {
+            let Some(order) = state.orders.get(&venue_order_id).cloned() else {
+                continue;
+            };
+            let crosses = match (order.side.clone(), order.limit_price) {
+                (OrderSide::Buy, Some(limit)) => quote.ask.is_some_and(|ask| ask <= limit),
+                (OrderSide::Sell, Some(limit)) => quote.bid.is_some_and(|bid| bid >= limit),
+                (_, None) => false,
+            };
+            let same_book = order.instrument.venue == quote.venue
+                && order.instrument.venue_symbol == quote.venue_symbol;
+            if !(same_book && crosses) {
+                state.resting.push((venue_order_id, reduce_only));
+                continue;
+            }
+            let Some(limit) = order.limit_price else {
+                continue;
+            };
+            let qty = order.qty - order.filled_qty;
+            let qty = if reduce_only {
+                qty.min(Self::reducible_qty(&state, &order.instrument, &order.side))
+            } else {
+                qty
+            };
+            if qty <= 0.0 {
+                if let Some(order) = state.orders.get_mut(&venue_order_id) {
+                    order.status = OrderStatus::Canceled;
+                    order.updated_at_ms = quote.ts_ms;
+                }
+                continue;
+            }
+            self.book_fill(&mut state, &order, qty, limit, "maker", quote.ts_ms);
+        }
+    }
+
+    fn reducible_qty(
+        state: &SimulatedState,
+        instrument: &exchange_core::InstrumentRef,
+        side: &OrderSide,
+    ) -> f64 {
+        let held = state
+            .positions
+            .get(&position_key(&instrument.venue, &instrument.venue_symbol))
+            .map(|(_, position)| position.qty)
+            .unwrap_or(0.0);
+        match side {
+            OrderSide::Buy if held < 0.0 => -held,
+            OrderSide::Sell if held > 0.0 => held,
+            _ => 0.0,
+        }
+    }
+
+    fn book_fill(
+        &self,
+        state: &mut SimulatedState,
+        order: &OrderSnapshot,
+        qty: f64,
+        price: f64,
+        liquidity: &str,
+        ts_ms: i64,
+    ) -> FillReport {
+        let fee_bps = if liquidity == "maker" {
+            self.fees.maker_bps
+        } else {
+            self.fees.taker_bps
+        };
+        let notional = qty * price;
+        let fee = notional * fee_bps / 10_000.0;
+        let quote_asset = order
+            .instrument
+            .quote
+            .clone()
+            .unwrap_or_else(|| "USD".to_string());
+
+        state.next_id += 1;
+        let fill = FillReport {
+            venue: self.venue.clone(),
+            venue_fill_id: format!("bt-fill-{}", state.next_id),
+            venue_order_id: order.venue_order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            strategy_id: order.strategy_id.clone(),
+            instrument: order.instrument.clone(),
+            side: order.side.clone(),
+            qty,
+            price,
+            fee,
+            fee_asset: Some(quote_asset.clone()),
+            liquidity: Some(liquidity.to_string()),
+            simulated: true,
+            ts_ms,
+        };
+
+        let cash_delta = match order.side {
+            OrderSide::Buy => -notional,
+            OrderSide::Sell => notional,
+        };
+        *state.cash.entry(quote_asset).or_insert(0.0) += cash_delta - fee;
+
+        let (snapshot, position) = state
+            .positions
+            .entry(position_key(
+                &order.instrument.venue,
+                &order.instrument.venue_symbol,
+            ))
+            .or_insert_with(|| {
+                (
+                    PositionSnapshot {
+                        venue: self.venue.clone(),
+                        instrument: order.instrument.clone(),
+                        qty: 0.0,
+                        avg_price: price,
+                        mark_price: Some(price),
+                        unrealized_pnl: Some(0.0),
+                    },
+                    PositionState::default(),
+                )
+            });
+        position.apply_fill(&order.side, qty, price);
+        snapshot.qty = position.qty;
+        snapshot.avg_price = position.avg_price;
+        snapshot.mark_price = Some(price);
+        snapshot.unrealized_pnl = Some((price - position.avg_price) * position.qty);
+
+        if let Some(stored) = state.orders.get_mut(&order.venue_order_id) {
+            let previous = stored.filled_qty;
+            stored.filled_qty += qty;
+            stored.avg_fill_price = Some(
+                (stored.avg_fill_price.unwrap_or(price) * previous + price * qty)
+                    / stored.filled_qty,
+            );
+            stored.status = if stored.filled_qty + f64::EPSILON >= stored.qty {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+            stored.updated_at_ms = ts_ms;
+        }
+        state.fills.push(fill.clone());
+        fill
+    }
+
+    fn reject(req: &NormalizedOrderRequest, reason: &str, ts_ms: i64) -> OrderAck {
+        OrderAck {
+            venue_order_id: String::new(),
+            client_order_id: req.client_order_id.clone(),
+            accepted: false,
+            status: OrderStatus::Rejected,
+            filled_qty: 0.0,
+            avg_fill_price: None,
+            simulated: true,
+            reason: Some(reason.to_string()),
+            ts_ms,
+        }
+    }
+}
+
+impl ExchangeAdapter for SimulatedExchange {
+    fn venue(&self) -> &'static str {
+        "backtest"
+    }
+
+    fn connect_market_data(
+        &self,
+        _venue_symbols: Vec<String>,
+        _sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
+        // History is pushed through `update_quote`; there is no live feed.
+        Box::pin(async { Ok(()) })
+    }
+
+    fn place_order(&self, req: NormalizedOrderRequest) -> ExchangeResultFuture<'_, OrderAck> {
+        Box::pin(async move {
+            let now = self.clock.now_ms();
+            if !(req.qty.is_finite() && req.qty > 0.0) {
+                return Ok(Self::reject(&req, "quantity must be positive", now));
+            }
+            let mut state = self.state.lock().unwrap();
+            let Some(quote) = state
+                .quotes
+                .get(&position_key(
+                    &req.instrument.venue,
+                    &req.instrument.venue_symbol,
+                ))
+                .cloned()
+            else {
+                return Err(ExchangeError::new(
+                    "no_quote",
+                    format!("no historical quote for {}", req.instrument.venue_symbol),
+                    false,
+                ));
+            };
+
+            let qty = if req.reduce_only {
+                req.qty
+                    .min(Self::reducible_qty(&state, &req.instrument, &req.side))
+            } else {
+                req.qty
+            };
+            if qty <= 0.0 {
+                return Ok(Self::reject(
+                    &req,
+                    "reduce-only order would open a position",
+                    now,
+                ));
+            }
+
+            let touch = quote.reference_price(&req.side).map(|(price, _)| price);
+            let slippage = self.slippage_bps / 10_000.0;
+            let taker_price = touch.map(|price| match req.side {
+                OrderSide::Buy => price * (1.0 + slippage),
+                OrderSide::Sell => price * (1.0 - slippage),
+            });
+            let fill_price = match (&req.order_type, req.limit_price) {
+                (OrderType::Market, _) => taker_price,
+                (OrderType::Limit, Some(limit)) => taker_price.and_then(|price| match req.side {
+                    OrderSide::Buy if touch.is_some_and(|touch| touch <= limit) => {
+                        Some(price.min(limit))
+                    }
+                    OrderSide::Sell if touch.is_some_and(|touch| touch >= limit) => {
+                        Some(price.max(limit))
+                    }
+                    _ => None,
+                }),
+                (OrderType::Limit, None) => {
+                    return Ok(Self::reject(&req, "limit order without a limit price", now));
+                }
+            };
+            if fill_price.is_some() && req.post_only {
+                return Ok(Self::reject(
+                    &req,
+                    "post-only order would cross the book",
+                    now,
+                ));
+            }
+
+            state.next_id += 1;
+            let venue_order_id = format!("bt-{}", state.next_id);
+            let order = OrderSnapshot {
+                venue: self.venue.clone(),
+                venue_order_id: venue_order_id.clone(),
+                client_order_id: req.client_order_id.clone(),
+                strategy_id: req.strategy_id.clone(),
+                instrument: req.instrument.clone(),
+                side: req.side.clone(),
+                order_type: req.order_type.clone(),
+                status: OrderStatus::New,
+                qty,
+                filled_qty: 0.0,
+                limit_price: req.limit_price,
+                avg_fill_price: None,
+                created_at_ms: now,
+                updated_at_ms: now,
+                simulated: true,
+            };
+            state.orders.insert(venue_order_id.clone(), order.clone());
+
+            match fill_price {
+                Some(price) => {
+                    self.book_fill(&mut state, &order, qty, price, "taker", now);
+                }
+                None if matches!(req.order_type, OrderType::Market) => {
+                    state.orders.remove(&venue_order_id);
+                    return Ok(Self::reject(&req, "no price to fill a market order", now));
+                }
+                None if matches!(req.tif, Some(TimeInForce::Ioc | TimeInForce::Fok)) => {
+                    if let Some(order) = state.orders.get_mut(&venue_order_id) {
+                        order.status = OrderStatus::Canceled;
+                    }
+                }
+                None => state
+                    .resting
+                    .push((venue_order_id.clone(), req.reduce_only)),
+            }
+
+            let order = &state.orders[&venue_order_id];
+            Ok(OrderAck {
+                venue_order_id: venue_order_id.clone(),
+                client_order_id: req.client_order_id,
+                accepted: true,
+                status: order.status.clone(),
+                filled_qty: order.filled_qty,
+                avg_fill_price: order.avg_fill_price,
+                simulated: true,
+                reason: None,
+                ts_ms: now,
+            })
+        })
+    }
+
+    fn cancel_order(&self, venue_order_id: &str) -> ExchangeResultFuture<'_, ()> {
+        let venue_order_id = venue_order_id.to_string();
+        Box::pin(async move {
+            let mut state = self.state.lock().unwrap();
+            state.resting.retain(|(id, _)| *id != venue_order_id);
+            let order = state.orders.get_mut(&venue_order_id).ok_or_else(|| {
+                ExchangeError::new(
+                    "order_not_found",
+                    format!("unknown order id: {}", venue_order_id),
+                    false,
+                )
+            })?;
+            if matches!(
+                order.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at_ms = self.clock.now_ms();
+            }
+            Ok(())
+        })
+    }
+
+    fn get_order(&self, venue_order_id: &str) -> ExchangeResultFuture<'_, Option<OrderSnapshot>> {
+        let venue_order_id = venue_order_id.to_string();
+        Box::pin(async move {
+            Ok(self
+                .state
+                .lock()
+                .unwrap()
+                .orders
+                .get(&venue_order_id)
+                .cloned())
+        })
+    }
+
+    fn open_orders(&self) -> ExchangeResultFuture<'_, Vec<OpenOrderSnapshot>> {
+        Box::pin(async move {
+            let state = self.state.lock().unwrap();
+            Ok(state
+                .resting
+                .iter()
+                .filter_map(|(id, _)| state.orders.get(id))
+                .map(|order| OpenOrderSnapshot {
+                    order: order.clone(),
+                })
+                .collect())
+        })
+    }
+
+    fn fills_since(
+        &self,
+        since_ts_ms: i64,
+        limit: usize,
+    ) -> ExchangeResultFuture<'_, Vec<FillReport>> {
+        Box::pin(async move {
+            Ok(self
+                .state
+                .lock()
+                .unwrap()
+                .fills
+                .iter()
+                .filter(|fill| fill.ts_ms >= since_ts_ms)
+                .take(limit)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn sync_positions(&self) -> ExchangeResultFuture<'_, Vec<PositionSnapshot>> {
+        Box::pin(async move {
+            Ok(self
+                .state
+                .lock()
+                .unwrap()
+                .positions
+                .values()
+                .map(|(snapshot, _)| snapshot.clone())
+                .collect())
+        })
+    }
+
+    fn sync_balances(&self) -> ExchangeResultFuture<'_, Vec<BalanceSnapshot>> {
+        Box::pin(async move {
+            Ok(self
+                .state
+                .lock()
+                .unwrap()
+                .cash
+                .iter()
+                .map(|(asset, total)| BalanceSnapshot {
+                    venue: self.venue.clone(),
+                    asset: asset.clone(),
+                    total: *total,
+                    available: *total,
+                })
+                .collect())
+        })
+    }
+
+    fn quote(&self, venue_symbol: &str) -> ExchangeResultFuture<'_, Option<QuoteSnapshot>> {
+        let venue_symbol = venue_symbol.to_string();
+        Box::pin(async move {
+            Ok(self
+                .state
+                .lock()
+                .unwrap()
+                .quotes
+                .values()
+                .find(|quote| quote.venue_symbol == venue_symbol)
+                .cloned())
+        })
+    }
+
+    fn health(&self) -> ExchangeValueFuture<'_, ExchangeHealth> {
+        Box::pin(async move {
+            ExchangeHealth {
+                venue: self.venue.clone(),
+                healthy: true,
+                connected_market_data: true,
+                connected_trading: true,
+                message: Some("historical simulation".to_string()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exchange_core::{AssetClass, InstrumentRef, InstrumentType, SimulatedClock};
+    use std::sync::Arc;
+
+    fn quote(ts_ms: i64, bid: f64, ask: f64) -> QuoteSnapshot {
+        QuoteSnapshot {
+            venue: "coinbase_spot".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(bid),
+            ask: Some(ask),
+            last: None,
+            mark: None,
+            ts_ms,
+        }
+    }
+
+    fn order(side: OrderSide, qty: f64, limit: Option<f64>) -> NormalizedOrderRequest {
+        NormalizedOrderRequest {
+            venue: "coinbase_spot".to_string(),
+            symbol: "BTC-USD".to_string(),
+            instrument: InstrumentRef {
+                venue: "coinbase_spot".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                asset_class: AssetClass::Crypto,
+                instrument_type: InstrumentType::Spot,
+                base: Some("BTC".to_string()),
+                quote: Some("USD".to_string()),
+                expiry_ts_ms: None,
+                strike: None,
+                option_right: None,
+                contract_multiplier: Some(1.0),
+            },
+            strategy_id: "test".to_string(),
+            client_order_id: format!("c-{:?}-{}", side, qty),
+            intent_id: None,
+            side,
+            order_type: if limit.is_some() {
+                OrderType::Limit
+            } else {
+                OrderType::Market
+            },
+            qty,
+            quote_size: None,
+            limit_price: limit,
+            stop_price: None,
+            tif: None,
+            post_only: false,
+            reduce_only: false,
+            requested_notional_cents: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn market_orders_pay_slippage_and_taker_fees() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let exchange = SimulatedExchange::new(
+            "coinbase_spot",
+            clock,
+            FeeModel {
+                maker_bps: 0.0,
+                taker_bps: 10.0,
+            },
+            100.0,
+        );
+        exchange.update_quote(quote(1_000, 99.0, 100.0));
+
+        let ack = exchange
+            .place_order(order(OrderSide::Buy, 2.0, None))
+            .await
+            .unwrap();
+        assert_eq!(ack.status, OrderStatus::Filled);
+        assert_eq!(ack.avg_fill_price, Some(101.0));
+
+        let fills = exchange.fills_since(0, 10).await.unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!((fills[0].fee - 0.202).abs() < 1e-9);
+        assert_eq!(fills[0].liquidity.as_deref(), Some("taker"));
+
+        let mut reduce = order(OrderSide::Sell, 5.0, None);
+        reduce.reduce_only = true;
+        let ack = exchange.place_order(reduce).await.unwrap();
+        assert_eq!(ack.filled_qty, 2.0);
+        let positions = exchange.sync_positions().await.unwrap();
+        assert_eq!(positions[0].qty, 0.0);
+    }
+
+    #[tokio::test]
+    async fn resting_limits_fill_when_a_later_quote_crosses() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let exchange = SimulatedExchange::new("coinbase_spot", clock, FeeModel::default(), 0.0);
+        exchange.update_quote(quote(1_000, 99.0, 100.0));
+
+        let ack = exchange
+            .place_order(order(OrderSide::Buy, 1.0, Some(98.0)))
+            .await
+            .unwrap();
+        assert_eq!(ack.status, OrderStatus::New);
+        assert_eq!(exchange.open_orders().await.unwrap().len(), 1);
+
+        exchange.update_quote(quote(2_000, 97.0, 97.5));
+        let snapshot = exchange
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.status, OrderStatus::Filled);
+        assert_eq!(snapshot.avg_fill_price, Some(98.0));
+        let fills = exchange.fills_since(0, 10).await.unwrap();
+        assert_eq!(fills[0].liquidity.as_deref(), Some("maker"));
+        assert!(exchange.open_orders().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+//! Historical backtesting for `StrategyPlugin` implementations.
+//!
+//! Frames of `RegimeContext` plus the quote in force are fed to a plugin in
+//! time order. Its intents pass through the hard safety cage and then execute
+//! against a `SimulatedExchange` that charges fees and slippage; the resulting
+//! fills drive the PnL curve, drawdown, hit rate and turnover in the report.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use exchange_core::{ExchangeAdapter, SimulatedClock};
+use risk_core::{HardSafetyCage, HardSafetyPolicy, RiskDecision, RiskSnapshot};
+use serde::{Deserialize, Serialize};
+use strategy_core::StrategyPlugin;
+
+mod data;
+mod exchange;
+mod report;
+
+pub use data::{
+    load_csv_frames, load_market_data_frames, parse_csv_frames, parse_market_data_frames,
+    BacktestFrame, DataError,
+};
+pub use exchange::{FeeModel, SimulatedExchange};
+pub use report::{BacktestReport, EquityPoint, PositionState, RiskDenial};
+
+use report::Ledger;
+
+/// Denials kept verbatim in the report.
+const MAX_REPORTED_DENIALS: usize = 100;
+const ORDER_RATE_WINDOW_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    pub policy: HardSafetyPolicy,
+    pub fees: FeeModel,
+    pub slippage_bps: f64,
+    /// Gate: the run must place at least this many orders...
+    pub min_orders: u64,
+    /// ...and finish with at least this net PnL, in quote currency. The
+    /// drawdown limit comes from `policy.max_drawdown_cents`.
+    pub min_net_pnl: f64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            policy: HardSafetyPolicy::default(),
+            fees: FeeModel::default(),
+            slippage_bps: 5.0,
+            min_orders: 1,
+            min_net_pnl: 0.0,
+        }
+    }
+}
+
+/// Runs `plugin` over `frames` (expected in timestamp order).
+///
+/// Exposure, order rate and drawdown in the cage's `RiskSnapshot` are rebuilt
+/// from the simulated book before every intent, so limits apply to open risk
+/// rather than to a running total of everything ever traded.
+pub async fn run_backtest(
+    plugin: &dyn StrategyPlugin,
+    frames: &[BacktestFrame],
+    config: &BacktestConfig,
+) -> BacktestReport {
+    let start_ms = frames.first().map(|frame| frame.quote.ts_ms).unwrap_or(0);
+    let clock = Arc::new(SimulatedClock::new(start_ms));
+    let exchange = SimulatedExchange::new(
+        "backtest",
+        clock.clone(),
+        config.fees.clone(),
+        config.slippage_bps,
+    );
+    let cage = HardSafetyCage::new(config.policy.clone());
+
+    let mut ledger = Ledger::default();
+    let mut fills_seen = 0_usize;
+    let mut recent_orders: VecDeque<i64> = VecDeque::new();
+    let mut peak_equity = 0.0_f64;
+    let mut report = BacktestReport {
+        strategy_id: plugin.id().to_string(),
+        frames: frames.len(),
+        from_ms: frames.first().map(|frame| frame.quote.ts_ms),
+        to_ms: frames.last().map(|frame| frame.quote.ts_ms),
+        intents: 0,
+        orders: 0,
+        rejected: 0,
+        risk_denied: 0,
+        strategy_errors: 0,
+        fills: 0,
+        turnover: 0.0,
+        fees: 0.0,
+        realized_pnl: 0.0,
+        unrealized_pnl: 0.0,
+        net_pnl: 0.0,
+        max_drawdown: 0.0,
+        closed_trades: 0,
+        winning_trades: 0,
+        hit_rate: None,
+        equity_curve: Vec::with_capacity(frames.len()),
+        risk_denials: Vec::new(),
+        passed: false,
+    };
+
+    for frame in frames {
+        let ts_ms = frame.quote.ts_ms;
+        clock.set(ts_ms);
+        exchange.update_quote(frame.quote.clone());
+        ledger.mark(&frame.quote);
+
+        if plugin.supports_venue(&frame.context.venue) {
+            match plugin.evaluate(&frame.context) {
+                Ok(Some(intent)) => {
+                    report.intents += 1;
+                    while recent_orders
+                        .front()
+                        .is_some_and(|placed| ts_ms - placed >= ORDER_RATE_WINDOW_MS)
+                    {
+                        recent_orders.pop_front();
+                    }
+
+                    let order = intent.order;
+                    let priced_cents = frame
+                        .quote
+                        .reference_price(&order.side)
+                        .map(|(price, _)| order.instrument.notional_cents(price, order.qty));
+                    let notional_cents = priced_cents
+                        .unwrap_or(0)
+                        .max(order.requested_notional_cents);
+                    let (total, venue_notional, asset_class_notional) = ledger.exposure_cents();
+                    let snapshot = RiskSnapshot {
+                        total_notional_cents: total,
+                        drawdown_cents: ((peak_equity - ledger.equity()) * 100.0).round() as i64,
+                        orders_last_minute: recent_orders.len() as u32,
+                        strategy_canary_notional: [(order.strategy_id.clone(), total)]
+                            .into_iter()
+                            .collect(),
+                        venue_notional,
+                        asset_class_notional,
+                        ..RiskSnapshot::default()
+                    };
+
+                    match cage.evaluate_order_with_scope(
+                        &order.strategy_id,
+                        &order.venue,
+                        &order.instrument.asset_class,
+                        notional_cents,
+                        &snapshot,
+                    ) {
+                        RiskDecision::Deny { reason } => {
+                            report.risk_denied += 1;
+                            if report.risk_denials.len() < MAX_REPORTED_DENIALS {
+                                report.risk_denials.push(RiskDenial {
+                                    ts_ms,
+                                    client_order_id: order.client_order_id.clone(),
+                                    reason,
+                                });
+                            }
+                        }
+                        RiskDecision::Allow => match exchange.place_order(order).await {
+                            Ok(ack) if ack.accepted => {
+                                report.orders += 1;
+                                recent_orders.push_back(ts_ms);
+                            }
+                            Ok(_) | Err(_) => report.rejected += 1,
+                        },
+                    }
+                }
+                Ok(None) => {}
+                Err(_) => report.strategy_errors += 1,
+            }
+        }
+
+        // Fills from resting orders crossed by this quote and from any order
+        // placed above, in the order the exchange booked them.
+        for fill in exchange.fills_after(fills_seen) {
+            ledger.apply_fill(&fill);
+            fills_seen += 1;
+        }
+
+        let equity = ledger.equity();
+        peak_equity = peak_equity.max(equity);
+        let drawdown = peak_equity - equity;
+        report.max_drawdown = report.max_drawdown.max(drawdown);
+        report.equity_curve.push(EquityPoint {
+            ts_ms,
+            equity,
+            drawdown,
+        });
+    }
+
+    report.fills = ledger.fills;
+    report.turnover = ledger.turnover;
+    report.fees = ledger.fees;
+    report.realized_pnl = ledger.realized_pnl;
+    report.unrealized_pnl = ledger.unrealized_pnl();
+    report.net_pnl = ledger.equity();
+    report.closed_trades = ledger.closed_trades;
+    report.winning_trades = ledger.winning_trades;
+    report.hit_rate = (ledger.closed_trades > 0)
+        .then(|| ledger.winning_trades as f64 / ledger.closed_trades as f64);
+    report.passed = report.strategy_errors == 0
+        && report.orders >= config.min_orders
+        && report.net_pnl >= config.min_net_pnl
+        && (report.max_drawdown * 100.0).round() as i64 <= config.policy.max_drawdown_cents;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exchange_core::{
+        AssetClass, InstrumentRef, InstrumentType, NormalizedOrderRequest, OrderSide, OrderType,
+        QuoteSnapshot,
+    };
+    use strategy_core::{MarketRegime, RegimeContext, SignalIntent, StrategyError, StrategyFamily};
+
+    /// Buys when the lookback return is positive, sells (reduce-only) otherwise.
+    struct FollowTrend;
+
+    impl StrategyPlugin for FollowTrend {
+        fn id(&self) -> &'static str {
+            "test.follow_trend"
+        }
+
+        fn family(&self) -> StrategyFamily {
+            StrategyFamily::Momentum
+        }
+
+        fn evaluate(&self, ctx: &RegimeContext) -> Result<Option<SignalIntent>, StrategyError> {
+            if ctx.regime != MarketRegime::Trending {
+                return Ok(None);
+            }
+            let side = if ctx.momentum_lookback_return > 0.0 {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            };
+            let order = NormalizedOrderRequest {
+                venue: ctx.venue.clone(),
+                symbol: ctx.symbol.clone(),
+                instrument: InstrumentRef {
+                    venue: ctx.venue.clone(),
+                    venue_symbol: ctx.symbol.clone(),
+                    asset_class: AssetClass::Crypto,
+                    instrument_type: InstrumentType::Spot,
+                    base: Some("BTC".to_string()),
+                    quote: Some("USD".to_string()),
+                    expiry_ts_ms: None,
+                    strike: None,
+                    option_right: None,
+                    contract_multiplier: Some(1.0),
+                },
+                strategy_id: self.id().to_string(),
+                client_order_id: format!("ft-{}", ctx.ts_ms),
+                intent_id: None,
+                reduce_only: side == OrderSide::Sell,
+                side,
+                order_type: OrderType::Market,
+                qty: 1.0,
+                quote_size: None,
+                limit_price: None,
+                stop_price: None,
+                tif: None,
+                post_only: false,
+                requested_notional_cents: 100,
+            };
+            Ok(Some(SignalIntent {
+                strategy_id: self.id().to_string(),
+                family: self.family(),
+                confidence: 1.0,
+                horizon_ms: 60_000,
+                expected_slippage_bps: 0.0,
+                requested_risk_budget_cents: 100,
+                order,
+                rationale: "test".to_string(),
+            }))
+        }
+    }
+
+    /// `FollowTrend` trading contracts worth `.0` units of the quoted price.
+    struct Contracts(f64);
+
+    impl StrategyPlugin for Contracts {
+        fn id(&self) -> &'static str {
+            FollowTrend.id()
+        }
+
+        fn family(&self) -> StrategyFamily {
+            FollowTrend.family()
+        }
+
+        fn evaluate(&self, ctx: &RegimeContext) -> Result<Option<SignalIntent>, StrategyError> {
+            let mut intent = FollowTrend.evaluate(ctx)?;
+            if let Some(intent) = intent.as_mut() {
+                intent.order.instrument.contract_multiplier = Some(self.0);
+            }
+            Ok(intent)
+        }
+    }
+
+    fn frame(ts_ms: i64, mid: f64, regime: MarketRegime, momentum: f64) -> BacktestFrame {
+        BacktestFrame {
+            context: RegimeContext {
+                venue: "coinbase_spot".to_string(),
+                symbol: "BTC-USD".to_string(),
+                regime,
+                spread_bps: 0.0,
+                realized_volatility: 0.0,
+                momentum_lookback_return: momentum,
+                order_book_imbalance: 0.0,
+                ts_ms,
+            },
+            quote: QuoteSnapshot {
+                venue: "coinbase_spot".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                bid: Some(mid),
+                ask: Some(mid),
+                last: Some(mid),
+                mark: None,
+                ts_ms,
+            },
+        }
+    }
+
+    fn config(policy: HardSafetyPolicy) -> BacktestConfig {
+        BacktestConfig {
+            policy,
+            fees: FeeModel {
+                maker_bps: 0.0,
+                taker_bps: 10.0,
+            },
+            slippage_bps: 0.0,
+            min_orders: 1,
+            min_net_pnl: 0.0,
+        }
+    }
+
+    fn generous_policy() -> HardSafetyPolicy {
+        HardSafetyPolicy {
+            max_total_notional_cents: 1_000_000,
+            max_strategy_canary_notional_cents: 1_000_000,
+            max_venue_notional_cents: 1_000_000,
+            max_asset_class_notional_cents: 1_000_000,
+            ..HardSafetyPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_pnl_fees_hit_rate_and_turnover() {
+        let frames = vec![
+            frame(0, 100.0, MarketRegime::Trending, 0.01),
+            frame(1_000, 110.0, MarketRegime::RangeBound, 0.0),
+            frame(2_000, 120.0, MarketRegime::Trending, -0.01),
+            frame(3_000, 130.0, MarketRegime::Trending, -0.01),
+        ];
+        let report = run_backtest(&FollowTrend, &frames, &config(generous_policy())).await;
+
+        assert_eq!(report.intents, 3);
+        assert_eq!(report.orders, 2);
+        // The last sell has nothing left to reduce.
+        assert_eq!(report.rejected, 1);
+        assert_eq!(report.fills, 2);
+        assert_eq!(report.turnover, 220.0);
+        assert!((report.fees - 0.22).abs() < 1e-9);
+        assert_eq!(report.realized_pnl, 20.0);
+        assert!((report.net_pnl - 19.78).abs() < 1e-9);
+        assert_eq!(report.closed_trades, 1);
+        assert_eq!(report.hit_rate, Some(1.0));
+        assert_eq!(report.equity_curve.len(), 4);
+        assert!((report.equity_curve[1].equity - 9.9).abs() < 1e-9);
+        assert!(report.passed);
+    }
+
+    #[tokio::test]
+    async fn cage_limits_open_exposure_and_drawdown_fails_the_gate() {
+        let policy = HardSafetyPolicy {
+            max_strategy_canary_notional_cents: 15_000,
+            max_drawdown_cents: 1_000,
+            ..generous_policy()
+        };
+        let frames = vec![
+            frame(0, 100.0, MarketRegime::Trending, 0.01),
+            frame(1_000, 100.0, MarketRegime::Trending, 0.01),
+            frame(2_000, 50.0, MarketRegime::RangeBound, 0.0),
+        ];
+        let report = run_backtest(&FollowTrend, &frames, &config(policy)).await;
+
+        assert_eq!(report.orders, 1);
+        assert_eq!(report.risk_denied, 1);
+        assert!(report.risk_denials[0]
+            .reason
+            .starts_with("strategy canary notional breached"));
+        assert!(report.max_drawdown > 50.0);
+        assert!(!report.passed);
+    }
+
+    #[tokio::test]
+    async fn cage_sizes_orders_by_their_contract_multiplier() {
+        let policy = HardSafetyPolicy {
+            max_total_notional_cents: 5_000,
+            ..generous_policy()
+        };
+        let frames = vec![frame(0, 100.0, MarketRegime::Trending, 0.01)];
+
+        let report = run_backtest(&Contracts(0.1), &frames, &config(policy.clone())).await;
+        assert_eq!((report.orders, report.risk_denied), (1, 0));
+
+        let report = run_backtest(&Contracts(1.0), &frames, &config(policy)).await;
+        assert_eq!((report.orders, report.risk_denied), (0, 1));
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use backtest::{load_csv_frames, load_market_data_frames, run_backtest, BacktestConfig};
+use strategy_core::StrategyPlugin;
+use strategy_crypto_momentum::CryptoMomentumStrategy;
+use strategy_kalshi_arbitrage::KalshiArbitrageStrategy;
+use strategy_kalshi_weather::KalshiWeatherStrategy;
+
+const USAGE: &str = "usage: backtest --strategy <id> (--csv <file> | --market-data <file>) \
+                     [--fee-bps <taker>] [--maker-fee-bps <maker>] [--slippage-bps <bps>] \
+                     [--min-orders <n>]";
+
+fn builtin_strategies() -> Vec<Box<dyn StrategyPlugin>> {
+    vec![
+        Box::new(CryptoMomentumStrategy),
+        Box::new(KalshiWeatherStrategy),
+        Box::new(KalshiArbitrageStrategy),
+    ]
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T> {
+    value
+        .with_context(|| format!("{} requires a value", flag))?
+        .parse()
+        .map_err(|_| anyhow::anyhow!("{} has an invalid value", flag))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let mut strategy_id = None;
+    let mut csv = None;
+    let mut market_data = None;
+    let mut config = BacktestConfig::default();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--strategy" => strategy_id = Some(parse_value::<String>(flag, args.next())?),
+            "--csv" => csv = Some(parse_value::<String>(flag, args.next())?),
+            "--market-data" => market_data = Some(parse_value::<String>(flag, args.next())?),
+            "--fee-bps" => config.fees.taker_bps = parse_value(flag, args.next())?,
+            "--maker-fee-bps" => config.fees.maker_bps = parse_value(flag, args.next())?,
+            "--slippage-bps" => config.slippage_bps = parse_value(flag, args.next())?,
+            "--min-orders" => config.min_orders = parse_value(flag, args.next())?,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            other => anyhow::bail!("Unknown backtest argument {}\n{}", other, USAGE),
+        }
+    }
+
+    let strategy_id = strategy_id.context(USAGE)?;
+    let strategies = builtin_strategies();
+    let plugin = strategies
+        .iter()
+        .find(|plugin| plugin.id() == strategy_id)
+        .with_context(|| {
+            let known: Vec<&str> = strategies.iter().map(|plugin| plugin.id()).collect();
+            format!(
+                "unknown strategy {}; known: {}",
+                strategy_id,
+                known.join(", ")
+            )
+        })?;
+
+    let frames = match (csv, market_data) {
+        (Some(path), None) => load_csv_frames(Path::new(&path))?,
+        (None, Some(path)) => load_market_data_frames(Path::new(&path))?,
+        _ => anyhow::bail!(
+            "exactly one of --csv or --market-data is required\n{}",
+            USAGE
+        ),
+    };
+
+    let report = run_backtest(plugin.as_ref(), &frames, &config).await;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.passed {
+        anyhow::bail!(
+            "backtest did not pass: {} orders, net pnl {:.2}, max drawdown {:.2}",
+            report.orders,
+            report.net_pnl,
+            report.max_drawdown
+        );
+    }
+    Ok(())
+}
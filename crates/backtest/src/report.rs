@@ -0,0 +1,221 @@
+//! Fill ledger and the report it produces.
+
+use std::collections::HashMap;
+
+use exchange_core::{AssetClass, FillReport, InstrumentRef, OrderSide, QuoteSnapshot};
+use serde::{Deserialize, Serialize};
+
+const POSITION_EPSILON: f64 = 1e-12;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EquityPoint {
+    pub ts_ms: i64,
+    /// Realized plus unrealized PnL net of fees, in quote currency.
+    pub equity: f64,
+    pub drawdown: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RiskDenial {
+    pub ts_ms: i64,
+    pub client_order_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub strategy_id: String,
+    pub frames: usize,
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub intents: u64,
+    pub orders: u64,
+    pub rejected: u64,
+    pub risk_denied: u64,
+    pub strategy_errors: u64,
+    pub fills: u64,
+    /// Traded notional across all fills, in quote currency.
+    pub turnover: f64,
+    pub fees: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub net_pnl: f64,
+    pub max_drawdown: f64,
+    /// Fills that reduced a position, and how many of those closed at a profit.
+    pub closed_trades: u64,
+    pub winning_trades: u64,
+    pub hit_rate: Option<f64>,
+    pub equity_curve: Vec<EquityPoint>,
+    /// First denials in time order; `risk_denied` has the full count.
+    pub risk_denials: Vec<RiskDenial>,
+    /// Whether the run meets the configured gate; suitable for `paper_passed`.
+    pub passed: bool,
+}
+
+/// Net position on one instrument, with average entry on the open side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionState {
+    pub qty: f64,
+    pub avg_price: f64,
+}
+
+impl PositionState {
+    /// Applies a fill and returns the PnL it realized against the average entry.
+    pub fn apply_fill(&mut self, side: &OrderSide, qty: f64, price: f64) -> Option<f64> {
+        let signed = if *side == OrderSide::Buy { qty } else { -qty };
+        let old_qty = self.qty;
+        let new_qty = old_qty + signed;
+        let mut realized = None;
+
+        if old_qty.abs() <= POSITION_EPSILON || old_qty.signum() == signed.signum() {
+            let new_abs = new_qty.abs();
+            if new_abs > POSITION_EPSILON {
+                self.avg_price = (self.avg_price * old_qty.abs() + price * qty) / new_abs;
+            }
+        } else {
+            let closed = qty.min(old_qty.abs());
+            realized = Some(closed * (price - self.avg_price) * old_qty.signum());
+            if qty > old_qty.abs() + POSITION_EPSILON {
+                // Crossed through zero: the remainder opens at this fill.
+                self.avg_price = price;
+            }
+        }
+
+        self.qty = if new_qty.abs() <= POSITION_EPSILON {
+            0.0
+        } else {
+            new_qty
+        };
+        if self.qty == 0.0 {
+            self.avg_price = 0.0;
+        }
+        realized
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LedgerPosition {
+    pub(crate) instrument: InstrumentRef,
+    pub(crate) state: PositionState,
+    pub(crate) mark: f64,
+}
+
+/// Books fills and marks positions; the source of every PnL figure in the report.
+#[derive(Debug, Default)]
+pub(crate) struct Ledger {
+    pub(crate) positions: HashMap<String, LedgerPosition>,
+    pub(crate) fills: u64,
+    pub(crate) turnover: f64,
+    pub(crate) fees: f64,
+    pub(crate) realized_pnl: f64,
+    pub(crate) closed_trades: u64,
+    pub(crate) winning_trades: u64,
+}
+
+pub(crate) fn position_key(venue: &str, venue_symbol: &str) -> String {
+    format!("{}:{}", venue, venue_symbol)
+}
+
+impl Ledger {
+    pub(crate) fn apply_fill(&mut self, fill: &FillReport) {
+        self.fills += 1;
+        self.turnover += fill.qty * fill.price;
+        self.fees += fill.fee;
+        let position = self
+            .positions
+            .entry(position_key(
+                &fill.instrument.venue,
+                &fill.instrument.venue_symbol,
+            ))
+            .or_insert_with(|| LedgerPosition {
+                instrument: fill.instrument.clone(),
+                state: PositionState::default(),
+                mark: fill.price,
+            });
+        position.mark = fill.price;
+        if let Some(realized) = position.state.apply_fill(&fill.side, fill.qty, fill.price) {
+            self.realized_pnl += realized;
+            self.closed_trades += 1;
+            if realized > 0.0 {
+                self.winning_trades += 1;
+            }
+        }
+    }
+
+    /// Marks the quote's instrument at mid, falling back to last.
+    pub(crate) fn mark(&mut self, quote: &QuoteSnapshot) {
+        let mid = match (quote.bid, quote.ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => quote.last.or(quote.mark),
+        };
+        if let (Some(mid), Some(position)) = (
+            mid.filter(|price| price.is_finite() && *price > 0.0),
+            self.positions
+                .get_mut(&position_key(&quote.venue, &quote.venue_symbol)),
+        ) {
+            position.mark = mid;
+        }
+    }
+
+    pub(crate) fn unrealized_pnl(&self) -> f64 {
+        self.positions
+            .values()
+            .map(|position| (position.mark - position.state.avg_price) * position.state.qty)
+            .sum()
+    }
+
+    pub(crate) fn equity(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl() - self.fees
+    }
+
+    /// Gross open exposure in cents: total, per venue and per asset class.
+    pub(crate) fn exposure_cents(&self) -> (i64, HashMap<String, i64>, HashMap<AssetClass, i64>) {
+        let mut total = 0_i64;
+        let mut venues = HashMap::new();
+        let mut asset_classes = HashMap::new();
+        for position in self.positions.values() {
+            let cents = (position.state.qty.abs() * position.mark * 100.0).round() as i64;
+            if cents == 0 {
+                continue;
+            }
+            total = total.saturating_add(cents);
+            *venues
+                .entry(position.instrument.venue.clone())
+                .or_insert(0_i64) += cents;
+            *asset_classes
+                .entry(position.instrument.asset_class.clone())
+                .or_insert(0_i64) += cents;
+        }
+        (total, venues, asset_classes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_realizes_pnl_on_reduction_and_flip() {
+        let mut position = PositionState::default();
+        assert_eq!(position.apply_fill(&OrderSide::Buy, 2.0, 100.0), None);
+        assert_eq!(position.apply_fill(&OrderSide::Buy, 2.0, 110.0), None);
+        assert_eq!(position.avg_price, 105.0);
+
+        assert_eq!(
+            position.apply_fill(&OrderSide::Sell, 1.0, 115.0),
+            Some(10.0)
+        );
+        assert_eq!(position.qty, 3.0);
+
+        // Selling 5 closes 3 at a loss and opens a 2 short at the fill.
+        assert_eq!(
+            position.apply_fill(&OrderSide::Sell, 5.0, 100.0),
+            Some(-15.0)
+        );
+        assert_eq!(position.qty, -2.0);
+        assert_eq!(position.avg_price, 100.0);
+
+        assert_eq!(position.apply_fill(&OrderSide::Buy, 2.0, 90.0), Some(20.0));
+        assert_eq!(position, PositionState::default());
+    }
+}
@@ -0,0 +1,312 @@
+//! REST transport policy: per-endpoint-class rate limits, request timeouts and
+//! bounded retries with jittered exponential backoff.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::Method;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::time::Instant;
+
+pub(crate) const ORDERS_PATH: &str = "/api/v3/brokerage/orders";
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// Coinbase meters public and private REST endpoints separately; order entry
+/// gets its own bucket so reconciliation reads cannot starve it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointClass {
+    /// Unauthenticated endpoints (`time`, `market/*`).
+    Public,
+    /// Authenticated reads: orders, fills, accounts, books.
+    Read,
+    /// Order placement and cancellation.
+    Order,
+}
+
+impl EndpointClass {
+    pub(crate) fn of(method: &Method, path: &str) -> Self {
+        if path == "/api/v3/brokerage/time" || path.starts_with("/api/v3/brokerage/market/") {
+            Self::Public
+        } else if method == Method::GET {
+            Self::Read
+        } else {
+            Self::Order
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_sec: f64,
+}
+
+impl RateLimit {
+    pub const fn new(burst: u32, per_sec: f64) -> Self {
+        Self { burst, per_sec }
+    }
+}
+
+/// Timeouts, retries and rate limits for the adapter's REST calls.
+///
+/// Reads are retried on send failures, timeouts, 5xx and 429. Order placement
+/// is retried on the same errors with the original `client_order_id`, which
+/// Coinbase deduplicates, so an ambiguous failure cannot place a second order.
+/// Cancels are sent once.
+#[derive(Debug, Clone)]
+pub struct HttpPolicy {
+    pub request_timeout: Duration,
+    /// Attempts per retried call, including the first.
+    pub max_attempts: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Longest 429 `Retry-After` the adapter waits out; longer ones fail the call.
+    pub max_retry_after: Duration,
+    /// Public endpoints allow 10 requests/second per IP.
+    pub public_limit: RateLimit,
+    /// Private endpoints allow 30 requests/second per key, split between reads
+    /// and order entry.
+    pub read_limit: RateLimit,
+    pub order_limit: RateLimit,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(10),
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+            max_retry_after: Duration::from_secs(10),
+            public_limit: RateLimit::new(10, 10.0),
+            read_limit: RateLimit::new(10, 10.0),
+            order_limit: RateLimit::new(20, 20.0),
+        }
+    }
+}
+
+impl HttpPolicy {
+    pub(crate) fn retries(method: &Method, path: &str) -> bool {
+        method == Method::GET || (method == Method::POST && path == ORDERS_PATH)
+    }
+
+    /// Wait before retry number `attempt` (1-based): the capped exponential
+    /// delay, half of it fixed and half jittered so clients spread out.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_backoff);
+        exp / 2 + exp.mul_f64(jitter_fraction() / 2.0)
+    }
+}
+
+/// Uniform in `[0, 1)`.
+fn jitter_fraction() -> f64 {
+    let mut bytes = [0u8; 8];
+    if SystemRandom::new().fill(&mut bytes).is_err() {
+        return 0.5;
+    }
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// How long a 429 asks the client to back off: `Retry-After` in seconds or as
+/// an HTTP date, else `x-ratelimit-reset` as epoch seconds.
+pub(crate) fn parse_retry_after(headers: &HeaderMap, now_ms: i64) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let until_ms = |at_ms: i64| Duration::from_millis(at_ms.saturating_sub(now_ms).max(0) as u64);
+
+    if let Some(raw) = header(RETRY_AFTER.as_str()).map(str::trim) {
+        if let Ok(secs) = raw.parse::<f64>() {
+            return (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs));
+        }
+        if let Ok(at) = chrono::DateTime::parse_from_rfc2822(raw) {
+            return Some(until_ms(at.timestamp_millis()));
+        }
+    }
+    header(RATE_LIMIT_RESET)
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .map(|epoch_secs| until_ms(epoch_secs.saturating_mul(1_000)))
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    refilled_at: Instant,
+    /// Set by a 429; nothing in this class is sent before it.
+    paused_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+            paused_until: None,
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = self.paused_until.filter(|until| *until > now) {
+            return Err(until - now);
+        }
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_sec).min(f64::from(self.limit.burst));
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.limit.per_sec,
+        ))
+    }
+}
+
+/// Client-side token buckets, one per endpoint class, shared by every clone
+/// of the adapter.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    buckets: HashMap<EndpointClass, Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(policy: &HttpPolicy) -> Self {
+        let now = Instant::now();
+        Self {
+            buckets: [
+                (EndpointClass::Public, policy.public_limit),
+                (EndpointClass::Read, policy.read_limit),
+                (EndpointClass::Order, policy.order_limit),
+            ]
+            .into_iter()
+            .map(|(class, limit)| (class, Mutex::new(Bucket::new(limit, now))))
+            .collect(),
+        }
+    }
+
+    fn bucket(&self, class: EndpointClass) -> std::sync::MutexGuard<'_, Bucket> {
+        self.buckets[&class]
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Waits until a request in `class` may be sent.
+    pub(crate) async fn acquire(&self, class: EndpointClass) {
+        loop {
+            let wait = self.bucket(class).try_take(Instant::now());
+            match wait {
+                Ok(()) => return,
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Holds every request in `class` for `duration`, after the venue said 429.
+    pub(crate) fn pause(&self, class: EndpointClass, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut bucket = self.bucket(class);
+        if bucket.paused_until.is_none_or(|paused| paused < until) {
+            bucket.paused_until = Some(until);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn retry_after_reads_seconds_dates_and_reset_epochs() {
+        let now_ms = 1_704_067_200_000;
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers, now_ms), None);
+
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from_static("1704067203"));
+        assert_eq!(
+            parse_retry_after(&headers, now_ms),
+            Some(Duration::from_secs(3))
+        );
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Mon, 01 Jan 2024 00:00:05 GMT"),
+        );
+        assert_eq!(
+            parse_retry_after(&headers, now_ms),
+            Some(Duration::from_secs(5))
+        );
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(
+            parse_retry_after(&headers, now_ms),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter() {
+        let policy = HttpPolicy {
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            ..HttpPolicy::default()
+        };
+        for (attempt, full) in [(1, 100), (2, 200), (3, 300), (10, 300)] {
+            let wait = policy.backoff(attempt);
+            let full = Duration::from_millis(full);
+            assert!(
+                wait >= full / 2 && wait <= full,
+                "attempt {attempt}: {wait:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn endpoint_classes_follow_method_and_path() {
+        assert_eq!(
+            EndpointClass::of(&Method::GET, "/api/v3/brokerage/time"),
+            EndpointClass::Public
+        );
+        assert_eq!(
+            EndpointClass::of(&Method::GET, "/api/v3/brokerage/accounts"),
+            EndpointClass::Read
+        );
+        assert_eq!(
+            EndpointClass::of(&Method::POST, ORDERS_PATH),
+            EndpointClass::Order
+        );
+        assert!(HttpPolicy::retries(&Method::POST, ORDERS_PATH));
+        assert!(!HttpPolicy::retries(
+            &Method::POST,
+            "/api/v3/brokerage/orders/batch_cancel"
+        ));
+    }
+
+    #[tokio::test]
+    async fn buckets_pace_each_class_and_hold_after_a_429() {
+        let limiter = RateLimiter::new(&HttpPolicy {
+            read_limit: RateLimit::new(2, 20.0),
+            ..HttpPolicy::default()
+        });
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire(EndpointClass::Read).await;
+        }
+        let paced = start.elapsed();
+        assert!(paced >= Duration::from_millis(45), "{paced:?}");
+
+        limiter.pause(EndpointClass::Read, Duration::from_millis(200));
+        limiter.acquire(EndpointClass::Order).await;
+        assert!(start.elapsed() < paced + Duration::from_millis(100));
+        limiter.acquire(EndpointClass::Read).await;
+        assert!(start.elapsed() >= paced + Duration::from_millis(200));
+    }
+}
@@ -1,67 +1,236 @@
+mod auth;
+mod http;
+mod market_data;
+mod orders;
+mod products;
+mod user_channel;
+
 use std::collections::HashMap;
 use std::env;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use exchange_core::{
     BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth, ExchangeResultFuture,
-    ExchangeValueFuture, FillReport, InstrumentType, NormalizedOrderRequest, OpenOrderSnapshot,
-    OrderAck, OrderSide, OrderSnapshot, OrderStatus, OrderType, PositionSnapshot,
+    ExchangeValueFuture, FillReport, InstrumentType, MarketDataSink, NormalizedOrderRequest,
+    OpenOrderSnapshot, OrderAck, OrderSide, OrderSnapshot, OrderStatus, PositionSnapshot,
+    ProductInfo, QuoteSnapshot,
 };
 use reqwest::{header, Method};
 use serde_json::{json, Value};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
+use tracing::Instrument;
+
+pub use auth::{CdpKey, Credentials, JWT_TTL_SECS};
+use http::{parse_retry_after, RateLimiter, CONNECT_TIMEOUT, ORDERS_PATH};
+pub use http::{EndpointClass, HttpPolicy, RateLimit};
+use market_data::parse_f64;
+use orders::{
+    next_cursor, order_configuration, parse_fill, parse_order, spot_instrument, CASH_CURRENCIES,
+};
+use products::Product;
+pub use user_channel::{UserUpdate, DEFAULT_USER_WS_URL};
+
+/// Upper bound on pages followed by one paginated query.
+const MAX_PAGES: usize = 50;
+const ORDERS_PAGE_SIZE: usize = 100;
+const FILLS_PAGE_SIZE: usize = 100;
+const ACCOUNTS_PAGE_SIZE: usize = 250;
+/// User updates buffered per subscriber before a slow one starts lagging.
+const USER_UPDATES_CAPACITY: usize = 1024;
+/// How long the product list is trusted before it is reloaded, so increment
+/// and trading-status changes are picked up.
+const PRODUCTS_TTL_MS: i64 = 15 * 60_000;
+
+/// Spot products by id, as of `loaded_at_ms`.
+#[derive(Debug, Default)]
+struct ProductCache {
+    products: HashMap<String, Product>,
+    loaded_at_ms: Option<i64>,
+}
+
+/// `name` parsed as `T`; `None` when unset.
+fn env_parse<T: std::str::FromStr>(name: &str) -> Result<Option<T>, ExchangeError>
+where
+    T::Err: std::fmt::Display,
+{
+    match env::var(name) {
+        Ok(raw) => raw.trim().parse().map(Some).map_err(|e| {
+            ExchangeError::new(
+                "invalid_config",
+                format!("invalid {name} `{raw}`: {e}"),
+                false,
+            )
+        }),
+        Err(_) => Ok(None),
+    }
+}
 
 #[derive(Clone)]
 pub struct CoinbaseAdvancedTradeAdapter {
     api_base: String,
+    ws_url: String,
+    user_ws_url: String,
     http_client: reqwest::Client,
-    bearer_token: String,
+    /// `host[:port]` of `api_base`, which CDP request JWTs are bound to.
+    api_host: String,
+    credentials: Credentials,
+    http_policy: HttpPolicy,
+    rate_limiter: Arc<RateLimiter>,
+    /// Orders placed through this adapter, which know the strategy and full
+    /// instrument the venue does not report.
     orders: Arc<Mutex<HashMap<String, OrderSnapshot>>>,
-    fills: Arc<Mutex<Vec<FillReport>>>,
+    products: Arc<Mutex<ProductCache>>,
+    user_updates: broadcast::Sender<UserUpdate>,
+    /// Market data feeds hold a weak reference and stop once every clone is dropped.
+    feed_guard: Arc<()>,
 }
 
 impl CoinbaseAdvancedTradeAdapter {
+    /// Adapter authenticated by the CDP key in `COINBASE_API_KEY_FILE` (named
+    /// by the file or `COINBASE_API_KEY_NAME`), falling back to a static
+    /// `COINBASE_BEARER_TOKEN`.
     pub fn from_env() -> Result<Self, ExchangeError> {
-        let bearer_token = env::var("COINBASE_BEARER_TOKEN").map_err(|_| {
-            ExchangeError::new(
-                "missing_credentials",
-                "COINBASE_BEARER_TOKEN is required",
-                false,
-            )
-        })?;
+        let credentials = match env::var("COINBASE_API_KEY_FILE") {
+            Ok(path) => Credentials::Cdp(CdpKey::from_file(
+                Path::new(&path),
+                env::var("COINBASE_API_KEY_NAME").ok(),
+            )?),
+            Err(_) => Credentials::Bearer(env::var("COINBASE_BEARER_TOKEN").map_err(|_| {
+                ExchangeError::new(
+                    "missing_credentials",
+                    "COINBASE_API_KEY_FILE or COINBASE_BEARER_TOKEN is required",
+                    false,
+                )
+            })?),
+        };
         let api_base = env::var("COINBASE_API_BASE_URL")
             .unwrap_or_else(|_| "https://api.coinbase.com".to_string())
             .trim_end_matches('/')
             .to_string();
-        let http_client = reqwest::Client::builder().build().map_err(|e| {
-            ExchangeError::new(
-                "http_client_init",
-                format!("failed to initialize HTTP client: {e}"),
-                false,
-            )
-        })?;
+        let ws_url =
+            env::var("COINBASE_WS_URL").unwrap_or_else(|_| market_data::DEFAULT_WS_URL.to_string());
+        let mut http_policy = HttpPolicy::default();
+        if let Some(timeout_ms) = env_parse::<u64>("COINBASE_HTTP_TIMEOUT_MS")? {
+            http_policy.request_timeout = Duration::from_millis(timeout_ms);
+        }
+        if let Some(max_attempts) = env_parse::<u32>("COINBASE_HTTP_MAX_ATTEMPTS")? {
+            http_policy.max_attempts = max_attempts.max(1);
+        }
+        let adapter = Self::new(api_base, ws_url, credentials)?.with_http_policy(http_policy);
+        Ok(match env::var("COINBASE_USER_WS_URL") {
+            Ok(user_ws_url) => adapter.with_user_ws_url(user_ws_url),
+            Err(_) => adapter,
+        })
+    }
+
+    pub fn new(
+        api_base: impl Into<String>,
+        ws_url: impl Into<String>,
+        credentials: Credentials,
+    ) -> Result<Self, ExchangeError> {
+        let api_base = api_base.into().trim_end_matches('/').to_string();
+        let api_host = reqwest::Url::parse(&api_base)
+            .ok()
+            .and_then(|url| {
+                let host = url.host_str()?.to_string();
+                Some(match url.port() {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host,
+                })
+            })
+            .ok_or_else(|| {
+                ExchangeError::new(
+                    "invalid_api_base",
+                    format!("COINBASE_API_BASE_URL `{api_base}` has no host"),
+                    false,
+                )
+            })?;
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| {
+                ExchangeError::new(
+                    "http_client_init",
+                    format!("failed to initialize HTTP client: {e}"),
+                    false,
+                )
+            })?;
 
         Ok(Self {
             api_base,
+            ws_url: ws_url.into().trim_end_matches('/').to_string(),
+            user_ws_url: DEFAULT_USER_WS_URL.to_string(),
             http_client,
-            bearer_token,
+            api_host,
+            credentials,
+            rate_limiter: Arc::new(RateLimiter::new(&HttpPolicy::default())),
+            http_policy: HttpPolicy::default(),
             orders: Arc::new(Mutex::new(HashMap::new())),
-            fills: Arc::new(Mutex::new(Vec::new())),
+            products: Arc::default(),
+            user_updates: broadcast::channel(USER_UPDATES_CAPACITY).0,
+            feed_guard: Arc::new(()),
         })
     }
 
+    pub fn with_user_ws_url(mut self, user_ws_url: impl Into<String>) -> Self {
+        self.user_ws_url = user_ws_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Replaces the REST timeouts, retries and rate limits; the limits are
+    /// shared by clones made after this call.
+    pub fn with_http_policy(mut self, http_policy: HttpPolicy) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(&http_policy));
+        self.http_policy = http_policy;
+        self
+    }
+
+    /// Order and fill updates from the user channel, once it is connected.
+    pub fn subscribe_user_updates(&self) -> broadcast::Receiver<UserUpdate> {
+        self.user_updates.subscribe()
+    }
+
+    /// Connects the authenticated `user` channel and streams it to
+    /// `subscribe_user_updates` receivers until every adapter clone is
+    /// dropped. Requires a CDP key; bearer tokens cannot sign subscriptions.
+    pub async fn connect_user_channel(&self) -> Result<(), ExchangeError> {
+        let Credentials::Cdp(key) = &self.credentials else {
+            return Err(ExchangeError::new(
+                "user_channel_unsupported",
+                "coinbase_at user channel requires a CDP API key (COINBASE_API_KEY_FILE)",
+                false,
+            ));
+        };
+        let ws = user_channel::connect(&self.user_ws_url, key).await?;
+        // The worker's clone must not keep the guard, and so itself, alive.
+        let mut worker = self.clone();
+        worker.feed_guard = Arc::new(());
+        tokio::spawn(user_channel::run_user_channel(
+            worker,
+            key.clone(),
+            ws,
+            Arc::downgrade(&self.feed_guard),
+        ));
+        Ok(())
+    }
+
     pub fn credentials_present() -> bool {
-        env::var("COINBASE_BEARER_TOKEN").is_ok()
+        env::var("COINBASE_API_KEY_FILE").is_ok() || env::var("COINBASE_BEARER_TOKEN").is_ok()
     }
 
     fn now_ms() -> i64 {
         chrono::Utc::now().timestamp_millis()
     }
 
+    /// Sends one REST call within its endpoint class's rate limit, retrying
+    /// reads and order placement on retriable failures per `http_policy`.
     async fn run_http_json(
         &self,
         method: &str,
         path: &str,
+        query: &[(&str, String)],
         body: Option<&Value>,
     ) -> Result<Value, ExchangeError> {
         let method = Method::from_bytes(method.as_bytes()).map_err(|e| {
@@ -71,37 +240,130 @@ impl CoinbaseAdvancedTradeAdapter {
                 false,
             )
         })?;
+        let class = EndpointClass::of(&method, path);
+        let max_attempts = if HttpPolicy::retries(&method, path) {
+            self.http_policy.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut attempt = 1;
+        loop {
+            self.rate_limiter.acquire(class).await;
+            let (err, retry_after) = match self.send_once(&method, path, query, body).await {
+                Ok(payload) => return Ok(payload),
+                Err(failure) => failure,
+            };
+            if let Some(retry_after) = retry_after {
+                self.rate_limiter.pause(class, retry_after);
+            }
+            let waits_too_long = retry_after
+                .is_some_and(|retry_after| retry_after > self.http_policy.max_retry_after);
+            if !err.retriable || attempt >= max_attempts || waits_too_long {
+                return Err(err);
+            }
+            // A 429's pause already holds the next acquire.
+            let backoff = if retry_after.is_some() {
+                Duration::ZERO
+            } else {
+                self.http_policy.backoff(attempt)
+            };
+            tracing::warn!(
+                venue = "coinbase_at",
+                http.method = %method,
+                http.path = path,
+                attempt,
+                backoff_ms = backoff.as_millis() as u64,
+                code = %err.code,
+                "retrying coinbase_at request: {}",
+                err.message
+            );
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// One attempt at a REST call; failures carry the wait a 429 asked for.
+    async fn send_once(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&Value>,
+    ) -> Result<Value, (ExchangeError, Option<Duration>)> {
+        // Child of the daemon's request span, so venue calls share its trace.
+        let span = tracing::info_span!(
+            "venue.http",
+            venue = "coinbase_at",
+            http.method = %method,
+            http.path = path,
+            http.status_code = tracing::field::Empty,
+        );
+        // CDP tokens are signed per request, bound to the path without its query.
+        let token = self
+            .credentials
+            .authorization(
+                method.as_str(),
+                &self.api_host,
+                path,
+                Self::now_ms() / 1_000,
+            )
+            .map_err(|e| (e, None))?;
         let mut request = self
             .http_client
-            .request(method, format!("{}{}", self.api_base, path))
+            .request(method.clone(), format!("{}{}", self.api_base, path))
+            .query(query)
+            .timeout(self.http_policy.request_timeout)
             .header(header::CONTENT_TYPE, "application/json")
-            .bearer_auth(&self.bearer_token);
+            .bearer_auth(token);
 
         if let Some(body) = body {
             request = request.json(body);
         }
 
-        let response = request.send().await.map_err(|e| {
-            ExchangeError::new("venue_http_send", format!("request failed: {e}"), true)
-        })?;
-        let status = response.status();
-        let response_text = response.text().await.map_err(|e| {
-            ExchangeError::new(
-                "venue_http_read",
-                format!("failed to read response body: {e}"),
-                true,
+        let send_error = |e: reqwest::Error, code: &str, action: &str| {
+            let code = if e.is_timeout() {
+                "venue_http_timeout"
+            } else {
+                code
+            };
+            (
+                ExchangeError::new(code, format!("{action}: {e}"), true),
+                None,
             )
-        })?;
+        };
+        let response = request
+            .send()
+            .instrument(span.clone())
+            .await
+            .map_err(|e| send_error(e, "venue_http_send", "request failed"))?;
+        let status = response.status();
+        span.record("http.status_code", status.as_u16());
+        let retry_after = (status == reqwest::StatusCode::TOO_MANY_REQUESTS)
+            .then(|| parse_retry_after(response.headers(), Self::now_ms()))
+            .flatten();
+        let response_text = response
+            .text()
+            .await
+            .map_err(|e| send_error(e, "venue_http_read", "failed to read response body"))?;
 
         if !status.is_success() {
-            return Err(ExchangeError::new(
-                "venue_http_error",
-                format!(
-                    "coinbase_at returned status={} body={}",
-                    status,
-                    response_text.trim()
+            let code = match status {
+                reqwest::StatusCode::NOT_FOUND => "venue_not_found",
+                reqwest::StatusCode::TOO_MANY_REQUESTS => "venue_rate_limited",
+                _ => "venue_http_error",
+            };
+            return Err((
+                ExchangeError::new(
+                    code,
+                    format!(
+                        "coinbase_at returned status={} body={}",
+                        status,
+                        response_text.trim()
+                    ),
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
                 ),
-                status.is_server_error(),
+                retry_after,
             ));
         }
 
@@ -110,17 +372,173 @@ impl CoinbaseAdvancedTradeAdapter {
         }
 
         serde_json::from_str::<Value>(&response_text).map_err(|e| {
-            ExchangeError::new(
-                "json_decode",
-                format!(
-                    "invalid venue json response: {e}; body={}",
-                    response_text.trim()
+            (
+                ExchangeError::new(
+                    "json_decode",
+                    format!(
+                        "invalid venue json response: {e}; body={}",
+                        response_text.trim()
+                    ),
+                    false,
                 ),
-                false,
+                None,
             )
         })
     }
 
+    /// Follows Coinbase's cursor pagination on a GET endpoint, collecting the
+    /// `key` array of each page until the last page or `max_items`.
+    async fn paginate(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        key: &str,
+        max_items: usize,
+    ) -> Result<Vec<Value>, ExchangeError> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let mut page_query = query.to_vec();
+            if let Some(cursor) = cursor.take() {
+                page_query.push(("cursor", cursor));
+            }
+            let payload = self.run_http_json("GET", path, &page_query, None).await?;
+            if let Some(page) = payload.get(key).and_then(Value::as_array) {
+                items.extend(page.iter().cloned());
+            }
+            cursor = next_cursor(&payload);
+            if cursor.is_none() || items.len() >= max_items {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Rules for `product_id` from the cached product list, reloading it once
+    /// stale and asking for the single product when the list lacks it.
+    async fn product_rules(&self, product_id: &str) -> Result<Option<Product>, ExchangeError> {
+        // Held across the reload so concurrent orders share one request.
+        let mut cache = self.products.lock().await;
+        let now = Self::now_ms();
+        if cache
+            .loaded_at_ms
+            .is_none_or(|loaded| now - loaded >= PRODUCTS_TTL_MS)
+        {
+            let payload = self
+                .run_http_json(
+                    "GET",
+                    "/api/v3/brokerage/products",
+                    &[("product_type", "SPOT".to_string())],
+                    None,
+                )
+                .await?;
+            cache.products = payload
+                .get("products")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Product::parse)
+                .map(|product| (product.info.venue_symbol.clone(), product))
+                .collect();
+            cache.loaded_at_ms = Some(now);
+        }
+        if let Some(product) = cache.products.get(product_id) {
+            return Ok(Some(product.clone()));
+        }
+
+        let product = match self
+            .run_http_json(
+                "GET",
+                &format!("/api/v3/brokerage/products/{product_id}"),
+                &[],
+                None,
+            )
+            .await
+        {
+            Ok(payload) => Product::parse(&payload),
+            Err(err) if err.code == "venue_not_found" => None,
+            Err(err) => return Err(err),
+        };
+        if let Some(product) = &product {
+            cache
+                .products
+                .insert(product.info.venue_symbol.clone(), product.clone());
+        }
+        Ok(product)
+    }
+
+    /// Records the venue's view of an order, keeping the strategy and
+    /// instrument of orders this adapter placed.
+    async fn remember_order(&self, mut order: OrderSnapshot) -> OrderSnapshot {
+        let mut orders = self.orders.lock().await;
+        if let Some(placed) = orders.get(&order.venue_order_id) {
+            order.strategy_id = placed.strategy_id.clone();
+            order.instrument = placed.instrument.clone();
+            if order.client_order_id.is_empty() {
+                order.client_order_id = placed.client_order_id.clone();
+            }
+        }
+        orders.insert(order.venue_order_id.clone(), order.clone());
+        order
+    }
+
+    /// The raw `orders/historical/{order_id}` order; `None` when the venue
+    /// does not know it.
+    async fn fetch_order(&self, venue_order_id: &str) -> Result<Option<Value>, ExchangeError> {
+        match self
+            .run_http_json(
+                "GET",
+                &format!("/api/v3/brokerage/orders/historical/{venue_order_id}"),
+                &[],
+                None,
+            )
+            .await
+        {
+            Ok(mut payload) => Ok(payload.get_mut("order").map(Value::take)),
+            Err(err) if err.code == "venue_not_found" => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn fetch_balances(&self) -> Result<Vec<BalanceSnapshot>, ExchangeError> {
+        let accounts = self
+            .paginate(
+                "/api/v3/brokerage/accounts",
+                &[("limit", ACCOUNTS_PAGE_SIZE.to_string())],
+                "accounts",
+                usize::MAX,
+            )
+            .await?;
+
+        let mut balances = Vec::new();
+        for account in accounts {
+            let currency = account
+                .get("currency")
+                .and_then(Value::as_str)
+                .unwrap_or("UNKNOWN")
+                .to_string();
+            let available = account
+                .get("available_balance")
+                .and_then(|v| v.get("value"))
+                .and_then(Value::as_str)
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let hold = account
+                .get("hold")
+                .and_then(|v| v.get("value"))
+                .and_then(Value::as_str)
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            balances.push(BalanceSnapshot {
+                venue: self.venue().to_string(),
+                asset: currency,
+                total: available + hold,
+                available,
+            });
+        }
+        Ok(balances)
+    }
+
     fn extract_order_id(payload: &Value) -> Option<String> {
         payload
             .get("success_response")
@@ -160,6 +578,73 @@ impl CoinbaseAdvancedTradeAdapter {
             })
     }
 
+    /// Why `batch_cancel` did not cancel `venue_order_id`; `None` when it
+    /// did or reported no result for it.
+    fn cancel_failure(payload: &Value, venue_order_id: &str) -> Option<ExchangeError> {
+        let result = payload
+            .get("results")
+            .and_then(Value::as_array)?
+            .iter()
+            .find(|result| {
+                result.get("order_id").and_then(Value::as_str) == Some(venue_order_id)
+            })?;
+        if result
+            .get("success")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        let reason = result
+            .get("failure_reason")
+            .and_then(Value::as_str)
+            .unwrap_or("UNKNOWN_CANCEL_FAILURE_REASON");
+        Some(match reason {
+            "UNKNOWN_CANCEL_ORDER" => ExchangeError::new(
+                "order_not_found",
+                format!("unknown order id: {venue_order_id}"),
+                false,
+            ),
+            _ => ExchangeError::new(
+                "cancel_rejected",
+                format!("coinbase_at did not cancel {venue_order_id}: {reason}"),
+                false,
+            ),
+        })
+    }
+
+    fn parse_best_bid_ask(payload: &Value, product_id: &str, now_ms: i64) -> Option<QuoteSnapshot> {
+        let book = payload
+            .get("pricebooks")
+            .and_then(Value::as_array)?
+            .iter()
+            .find(|book| book.get("product_id").and_then(Value::as_str) == Some(product_id))?;
+        let top = |levels: &str| {
+            book.get(levels)
+                .and_then(Value::as_array)
+                .and_then(|levels| levels.first())
+                .and_then(|level| level.get("price"))
+                .and_then(Value::as_str)
+                .and_then(|price| price.parse::<f64>().ok())
+        };
+        let bid = top("bids");
+        let ask = top("asks");
+        let mark = match (bid, ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => bid.or(ask),
+        };
+
+        Some(QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: product_id.to_string(),
+            bid,
+            ask,
+            last: None,
+            mark,
+            ts_ms: now_ms,
+        })
+    }
+
     fn parse_order_id(payload: &Value) -> Result<String, ExchangeError> {
         if let Some(order_id) = Self::extract_order_id(payload) {
             return Ok(order_id);
@@ -184,11 +669,21 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
         "coinbase_at"
     }
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()> {
+    fn connect_market_data(
+        &self,
+        venue_symbols: Vec<String>,
+        sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
         Box::pin(async move {
-            self.run_http_json("GET", "/api/v3/brokerage/time", None)
-                .await
-                .map(|_| ())
+            let ws = market_data::connect(&self.ws_url, &venue_symbols).await?;
+            tokio::spawn(market_data::run_feed(
+                self.ws_url.clone(),
+                venue_symbols,
+                ws,
+                sink,
+                Arc::downgrade(&self.feed_guard),
+            ));
+            Ok(())
         })
     }
 
@@ -202,29 +697,27 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
                 ));
             }
 
-            let order_configuration = if req.order_type == OrderType::Market {
-                json!({
-                    "market_market_ioc": {
-                        "base_size": format!("{}", req.qty)
-                    }
-                })
-            } else {
-                let limit_price = req.limit_price.ok_or_else(|| {
+            let product = self
+                .product_rules(&req.instrument.venue_symbol)
+                .await?
+                .ok_or_else(|| {
                     ExchangeError::new(
-                        "missing_limit_price",
-                        "limit_price required for limit orders",
+                        "unknown_product",
+                        format!("coinbase_at has no product {}", req.instrument.venue_symbol),
                         false,
                     )
                 })?;
-
-                json!({
-                    "limit_limit_gtc": {
-                        "base_size": format!("{}", req.qty),
-                        "limit_price": format!("{}", limit_price),
-                        "post_only": req.post_only
-                    }
-                })
-            };
+            let order_configuration = order_configuration(&req, &product, Self::now_ms())?;
+            // The rounded terms actually sent, for the local order record.
+            let terms = order_configuration
+                .as_object()
+                .and_then(|config| config.values().next());
+            let qty = terms
+                .and_then(|terms| parse_f64(terms.get("base_size")))
+                .unwrap_or(req.qty);
+            let limit_price = terms
+                .and_then(|terms| parse_f64(terms.get("limit_price")))
+                .or(req.limit_price);
 
             let body = json!({
                 "client_order_id": req.client_order_id,
@@ -234,7 +727,7 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
             });
 
             let response = self
-                .run_http_json("POST", "/api/v3/brokerage/orders", Some(&body))
+                .run_http_json("POST", ORDERS_PATH, &[], Some(&body))
                 .await?;
 
             let now = Self::now_ms();
@@ -249,9 +742,9 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
                 side: req.side,
                 order_type: req.order_type,
                 status: OrderStatus::New,
-                qty: req.qty,
+                qty,
                 filled_qty: 0.0,
-                limit_price: req.limit_price,
+                limit_price,
                 avg_fill_price: None,
                 created_at_ms: now,
                 updated_at_ms: now,
@@ -281,8 +774,17 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
         let venue_order_id = venue_order_id.to_string();
         Box::pin(async move {
             let body = json!({ "order_ids": [venue_order_id.clone()] });
-            self.run_http_json("POST", "/api/v3/brokerage/orders/batch_cancel", Some(&body))
+            let response = self
+                .run_http_json(
+                    "POST",
+                    "/api/v3/brokerage/orders/batch_cancel",
+                    &[],
+                    Some(&body),
+                )
                 .await?;
+            if let Some(err) = Self::cancel_failure(&response, &venue_order_id) {
+                return Err(err);
+            }
 
             if let Some(order) = self.orders.lock().await.get_mut(&venue_order_id) {
                 order.status = OrderStatus::Canceled;
@@ -295,23 +797,40 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
 
     fn get_order(&self, venue_order_id: &str) -> ExchangeResultFuture<'_, Option<OrderSnapshot>> {
         let venue_order_id = venue_order_id.to_string();
-        Box::pin(async move { Ok(self.orders.lock().await.get(&venue_order_id).cloned()) })
+        Box::pin(async move {
+            let order = self.fetch_order(&venue_order_id).await?;
+            match order.as_ref().and_then(parse_order) {
+                Some(order) => Ok(Some(self.remember_order(order).await)),
+                None => Ok(None),
+            }
+        })
     }
 
     fn open_orders(&self) -> ExchangeResultFuture<'_, Vec<OpenOrderSnapshot>> {
         Box::pin(async move {
-            let orders = self.orders.lock().await;
-            Ok(orders
-                .values()
-                .filter(|order| {
-                    matches!(
-                        order.status,
-                        OrderStatus::New | OrderStatus::PartiallyFilled
-                    )
-                })
-                .cloned()
-                .map(|order| OpenOrderSnapshot { order })
-                .collect())
+            let orders = self
+                .paginate(
+                    "/api/v3/brokerage/orders/historical/batch",
+                    &[
+                        ("order_status", "OPEN".to_string()),
+                        ("limit", ORDERS_PAGE_SIZE.to_string()),
+                    ],
+                    "orders",
+                    usize::MAX,
+                )
+                .await?;
+
+            let mut open = Vec::new();
+            for order in orders.iter().filter_map(parse_order) {
+                let order = self.remember_order(order).await;
+                if matches!(
+                    order.status,
+                    OrderStatus::New | OrderStatus::PartiallyFilled
+                ) {
+                    open.push(OpenOrderSnapshot { order });
+                }
+            }
+            Ok(open)
         })
     }
 
@@ -321,11 +840,34 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
         limit: usize,
     ) -> ExchangeResultFuture<'_, Vec<FillReport>> {
         Box::pin(async move {
-            let fills = self.fills.lock().await;
+            let start = chrono::DateTime::from_timestamp_millis(since_ts_ms)
+                .unwrap_or_default()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            let fills = self
+                .paginate(
+                    "/api/v3/brokerage/orders/historical/fills",
+                    &[
+                        ("start_sequence_timestamp", start),
+                        ("limit", FILLS_PAGE_SIZE.min(limit.max(1)).to_string()),
+                    ],
+                    "fills",
+                    limit,
+                )
+                .await?;
+
+            let placed = self.orders.lock().await;
             let mut filtered: Vec<FillReport> = fills
                 .iter()
+                .filter_map(parse_fill)
                 .filter(|fill| fill.ts_ms >= since_ts_ms)
-                .cloned()
+                .map(|mut fill| {
+                    if let Some(order) = placed.get(&fill.venue_order_id) {
+                        fill.client_order_id = order.client_order_id.clone();
+                        fill.strategy_id = order.strategy_id.clone();
+                        fill.instrument = order.instrument.clone();
+                    }
+                    fill
+                })
                 .collect();
             filtered.sort_by_key(|fill| fill.ts_ms);
             if filtered.len() > limit {
@@ -335,53 +877,93 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
         })
     }
 
+    /// Spot holdings as positions: every non-cash currency with a balance,
+    /// marked against USD. Coinbase reports no cost basis, so `avg_price` is
+    /// the mark and unrealized PnL is left to the daemon's fill history.
     fn sync_positions(&self) -> ExchangeResultFuture<'_, Vec<PositionSnapshot>> {
-        Box::pin(async { Ok(Vec::new()) })
+        Box::pin(async move {
+            let holdings: Vec<BalanceSnapshot> = self
+                .fetch_balances()
+                .await?
+                .into_iter()
+                .filter(|balance| !CASH_CURRENCIES.contains(&balance.asset.as_str()))
+                .filter(|balance| balance.total > 0.0)
+                .collect();
+            if holdings.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let product_ids: Vec<String> = holdings
+                .iter()
+                .map(|balance| format!("{}-USD", balance.asset))
+                .collect();
+            let query: Vec<(&str, String)> = product_ids
+                .iter()
+                .map(|product_id| ("product_ids", product_id.clone()))
+                .collect();
+            // Positions are still reported when marks are unavailable.
+            let books = self
+                .run_http_json("GET", "/api/v3/brokerage/best_bid_ask", &query, None)
+                .await
+                .unwrap_or_default();
+            let now = Self::now_ms();
+
+            Ok(holdings
+                .iter()
+                .zip(&product_ids)
+                .map(|(balance, product_id)| {
+                    let mark = Self::parse_best_bid_ask(&books, product_id, now)
+                        .and_then(|quote| quote.mark);
+                    PositionSnapshot {
+                        venue: self.venue().to_string(),
+                        instrument: spot_instrument(product_id),
+                        qty: balance.total,
+                        avg_price: mark.unwrap_or(0.0),
+                        mark_price: mark,
+                        unrealized_pnl: None,
+                    }
+                })
+                .collect())
+        })
     }
 
     fn sync_balances(&self) -> ExchangeResultFuture<'_, Vec<BalanceSnapshot>> {
+        Box::pin(self.fetch_balances())
+    }
+
+    fn quote(&self, venue_symbol: &str) -> ExchangeResultFuture<'_, Option<QuoteSnapshot>> {
+        let venue_symbol = venue_symbol.to_string();
         Box::pin(async move {
             let payload = self
-                .run_http_json("GET", "/api/v3/brokerage/accounts", None)
+                .run_http_json(
+                    "GET",
+                    "/api/v3/brokerage/best_bid_ask",
+                    &[("product_ids", venue_symbol.clone())],
+                    None,
+                )
                 .await?;
+            Ok(Self::parse_best_bid_ask(
+                &payload,
+                &venue_symbol,
+                Self::now_ms(),
+            ))
+        })
+    }
 
-            let mut balances = Vec::new();
-            if let Some(accounts) = payload.get("accounts").and_then(Value::as_array) {
-                for account in accounts {
-                    let currency = account
-                        .get("currency")
-                        .and_then(Value::as_str)
-                        .unwrap_or("UNKNOWN")
-                        .to_string();
-                    let available = account
-                        .get("available_balance")
-                        .and_then(|v| v.get("value"))
-                        .and_then(Value::as_str)
-                        .and_then(|v| v.parse::<f64>().ok())
-                        .unwrap_or(0.0);
-                    let hold = account
-                        .get("hold")
-                        .and_then(|v| v.get("value"))
-                        .and_then(Value::as_str)
-                        .and_then(|v| v.parse::<f64>().ok())
-                        .unwrap_or(0.0);
-                    balances.push(BalanceSnapshot {
-                        venue: self.venue().to_string(),
-                        asset: currency,
-                        total: available + hold,
-                        available,
-                    });
-                }
-            }
-
-            Ok(balances)
+    fn product(&self, venue_symbol: &str) -> ExchangeResultFuture<'_, Option<ProductInfo>> {
+        let venue_symbol = venue_symbol.to_string();
+        Box::pin(async move {
+            Ok(self
+                .product_rules(&venue_symbol)
+                .await?
+                .map(|product| product.info))
         })
     }
 
     fn health(&self) -> ExchangeValueFuture<'_, ExchangeHealth> {
         Box::pin(async move {
             let healthy = self
-                .run_http_json("GET", "/api/v3/brokerage/time", None)
+                .run_http_json("GET", "/api/v3/brokerage/time", &[], None)
                 .await
                 .is_ok();
 
@@ -402,8 +984,504 @@ impl ExchangeAdapter for CoinbaseAdvancedTradeAdapter {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    use exchange_core::{AssetClass, InstrumentRef, OrderType, TimeInForce};
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
     use super::*;
 
+    type Requests = Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// A scripted response: status line, extra headers, JSON body, and how
+    /// long the server stalls before sending it.
+    struct Reply {
+        status: &'static str,
+        headers: Vec<(&'static str, &'static str)>,
+        body: Value,
+        delay: Duration,
+    }
+
+    impl Reply {
+        fn status(status: &'static str) -> Self {
+            Self {
+                status,
+                headers: Vec::new(),
+                body: json!({}),
+                delay: Duration::ZERO,
+            }
+        }
+    }
+
+    impl From<Value> for Reply {
+        fn from(body: Value) -> Self {
+            Self {
+                body,
+                ..Self::status("200 OK")
+            }
+        }
+    }
+
+    async fn serve(routes: Vec<(&'static str, Value)>) -> (CoinbaseAdvancedTradeAdapter, Requests) {
+        serve_replies(
+            routes
+                .into_iter()
+                .map(|(path, body)| (path, Reply::from(body)))
+                .collect(),
+        )
+        .await
+    }
+
+    /// Minimal HTTP server answering each request with the next queued reply
+    /// for its path (404 when none is left) and recording request targets,
+    /// followed by the body when there is one.
+    async fn serve_replies(
+        routes: Vec<(&'static str, Reply)>,
+    ) -> (CoinbaseAdvancedTradeAdapter, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let routes = Arc::new(std::sync::Mutex::new(
+            routes.into_iter().collect::<VecDeque<_>>(),
+        ));
+        let requests: Requests = Arc::default();
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = Arc::clone(&routes);
+                let seen = Arc::clone(&seen);
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    loop {
+                        let mut request_line = String::new();
+                        if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let target = request_line
+                            .split_whitespace()
+                            .nth(1)
+                            .unwrap_or_default()
+                            .to_string();
+                        let mut content_length = 0;
+                        loop {
+                            let mut header = String::new();
+                            stream.read_line(&mut header).await.expect("header");
+                            if header.trim().is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = header.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap_or(0);
+                                }
+                            }
+                        }
+                        let mut body = vec![0; content_length];
+                        stream.read_exact(&mut body).await.expect("body");
+
+                        let path = target.split('?').next().unwrap_or_default().to_string();
+                        seen.lock().unwrap().push(if body.is_empty() {
+                            target
+                        } else {
+                            format!("{target} {}", String::from_utf8_lossy(&body))
+                        });
+                        let reply = {
+                            let mut routes = routes.lock().unwrap();
+                            routes
+                                .iter()
+                                .position(|(route, _)| *route == path)
+                                .and_then(|index| routes.remove(index))
+                                .map(|(_, reply)| reply)
+                        }
+                        .unwrap_or_else(|| Reply::status("404 Not Found"));
+                        tokio::time::sleep(reply.delay).await;
+                        let body = reply.body.to_string();
+                        let headers: String = reply
+                            .headers
+                            .iter()
+                            .map(|(name, value)| format!("{name}: {value}\r\n"))
+                            .collect();
+                        let response = format!(
+                            "HTTP/1.1 {}\r\ncontent-type: application/json\r\n{headers}content-length: {}\r\n\r\n{body}",
+                            reply.status,
+                            body.len()
+                        );
+                        // The client may have timed out and hung up.
+                        if stream.write_all(response.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        let adapter = CoinbaseAdvancedTradeAdapter::new(
+            format!("http://{addr}"),
+            "ws://unused",
+            Credentials::Bearer("token".to_string()),
+        )
+        .expect("adapter");
+        (adapter, requests)
+    }
+
+    fn venue_order(order_id: &str, status: &str, filled_size: &str) -> Value {
+        json!({
+            "order_id": order_id,
+            "client_order_id": format!("client-{order_id}"),
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "status": status,
+            "order_type": "LIMIT",
+            "filled_size": filled_size,
+            "average_filled_price": "100",
+            "created_time": "2024-01-01T00:00:00Z",
+            "order_configuration": {
+                "limit_limit_gtc": { "base_size": "1", "limit_price": "100", "post_only": false }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn open_orders_follow_the_cursor_across_pages() {
+        let (adapter, requests) = serve(vec![
+            (
+                "/api/v3/brokerage/orders/historical/batch",
+                json!({
+                    "orders": [venue_order("ord-1", "OPEN", "0")],
+                    "has_next": true,
+                    "cursor": "page-2"
+                }),
+            ),
+            (
+                "/api/v3/brokerage/orders/historical/batch",
+                json!({
+                    "orders": [venue_order("ord-2", "OPEN", "0.25")],
+                    "has_next": false,
+                    "cursor": ""
+                }),
+            ),
+        ])
+        .await;
+
+        let open = adapter.open_orders().await.expect("open orders");
+        assert_eq!(open.len(), 2);
+        assert_eq!(open[0].order.status, OrderStatus::New);
+        assert_eq!(open[1].order.status, OrderStatus::PartiallyFilled);
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("order_status=OPEN"));
+        assert!(requests[1].contains("cursor=page-2"));
+    }
+
+    #[tokio::test]
+    async fn venue_orders_and_fills_keep_the_placing_strategy() {
+        let (adapter, _) = serve(vec![
+            (
+                "/api/v3/brokerage/products",
+                json!({ "products": [products::tests::btc_usd()] }),
+            ),
+            (
+                "/api/v3/brokerage/orders",
+                json!({ "success": true, "success_response": { "order_id": "ord-1" } }),
+            ),
+            (
+                "/api/v3/brokerage/orders/historical/ord-1",
+                json!({ "order": venue_order("ord-1", "FILLED", "1") }),
+            ),
+            (
+                "/api/v3/brokerage/orders/historical/fills",
+                json!({
+                    "fills": [{
+                        "entry_id": "fill-1",
+                        "order_id": "ord-1",
+                        "product_id": "BTC-USD",
+                        "trade_time": "2024-01-01T00:00:01Z",
+                        "price": "100",
+                        "size": "1",
+                        "commission": "0.6",
+                        "side": "BUY",
+                        "liquidity_indicator": "MAKER"
+                    }],
+                    "cursor": ""
+                }),
+            ),
+        ])
+        .await;
+        let instrument = InstrumentRef {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            asset_class: AssetClass::Crypto,
+            instrument_type: InstrumentType::Spot,
+            base: Some("BTC".to_string()),
+            quote: Some("USD".to_string()),
+            expiry_ts_ms: None,
+            strike: None,
+            option_right: None,
+            contract_multiplier: Some(1.0),
+        };
+        adapter
+            .place_order(NormalizedOrderRequest {
+                venue: "coinbase_at".to_string(),
+                symbol: "BTC-USD".to_string(),
+                instrument,
+                strategy_id: "core.momentum".to_string(),
+                client_order_id: "client-ord-1".to_string(),
+                intent_id: None,
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                qty: 1.0,
+                quote_size: None,
+                limit_price: Some(100.0),
+                stop_price: None,
+                tif: Some(TimeInForce::Gtc),
+                post_only: false,
+                reduce_only: false,
+                requested_notional_cents: 10_000,
+            })
+            .await
+            .expect("placed");
+
+        let order = adapter.get_order("ord-1").await.unwrap().expect("order");
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.strategy_id, "core.momentum");
+        assert!(adapter.get_order("ord-404").await.unwrap().is_none());
+
+        let fills = adapter.fills_since(0, 10).await.expect("fills");
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].strategy_id, "core.momentum");
+        assert_eq!(fills[0].client_order_id, "client-ord-1");
+        assert_eq!(fills[0].liquidity.as_deref(), Some("maker"));
+    }
+
+    #[tokio::test]
+    async fn spot_positions_come_from_non_cash_balances() {
+        let (adapter, requests) = serve(vec![
+            (
+                "/api/v3/brokerage/accounts",
+                json!({
+                    "accounts": [
+                        { "currency": "USD", "available_balance": { "value": "500" }, "hold": { "value": "0" } },
+                        { "currency": "BTC", "available_balance": { "value": "0.5" }, "hold": { "value": "0.25" } },
+                        { "currency": "ETH", "available_balance": { "value": "0" }, "hold": { "value": "0" } }
+                    ],
+                    "has_next": false
+                }),
+            ),
+            (
+                "/api/v3/brokerage/best_bid_ask",
+                json!({
+                    "pricebooks": [{
+                        "product_id": "BTC-USD",
+                        "bids": [{ "price": "99", "size": "1" }],
+                        "asks": [{ "price": "101", "size": "1" }]
+                    }]
+                }),
+            ),
+        ])
+        .await;
+
+        let positions = adapter.sync_positions().await.expect("positions");
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].instrument.venue_symbol, "BTC-USD");
+        assert_eq!(positions[0].qty, 0.75);
+        assert_eq!(positions[0].mark_price, Some(100.0));
+        assert!(requests.lock().unwrap()[1].contains("product_ids=BTC-USD"));
+    }
+
+    fn fast_retries() -> HttpPolicy {
+        HttpPolicy {
+            request_timeout: Duration::from_millis(200),
+            base_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(20),
+            max_retry_after: Duration::from_secs(2),
+            ..HttpPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_retry_server_errors_and_wait_out_rate_limits() {
+        let (adapter, requests) = serve_replies(vec![
+            ("/api/v3/brokerage/accounts", Reply::status("503 Service Unavailable")),
+            (
+                "/api/v3/brokerage/accounts",
+                Reply {
+                    headers: vec![("retry-after", "1")],
+                    ..Reply::status("429 Too Many Requests")
+                },
+            ),
+            (
+                "/api/v3/brokerage/accounts",
+                Reply::from(json!({
+                    "accounts": [{ "currency": "USD", "available_balance": { "value": "5" }, "hold": { "value": "0" } }],
+                    "has_next": false
+                })),
+            ),
+            (
+                "/api/v3/brokerage/best_bid_ask",
+                Reply {
+                    headers: vec![("retry-after", "60")],
+                    ..Reply::status("429 Too Many Requests")
+                },
+            ),
+        ])
+        .await;
+        let adapter = adapter.with_http_policy(fast_retries());
+
+        let started = std::time::Instant::now();
+        let balances = adapter
+            .sync_balances()
+            .await
+            .expect("balances after retries");
+        assert_eq!(balances[0].total, 5.0);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        let err = adapter
+            .quote("BTC-USD")
+            .await
+            .expect_err("retry-after beyond max_retry_after fails fast");
+        assert_eq!(err.code, "venue_rate_limited");
+        assert!(err.retriable);
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn order_placement_retries_idempotently_and_cancels_are_sent_once() {
+        let (adapter, requests) = serve_replies(vec![
+            (
+                "/api/v3/brokerage/products",
+                Reply::from(json!({ "products": [products::tests::btc_usd()] })),
+            ),
+            (
+                ORDERS_PATH,
+                Reply {
+                    delay: Duration::from_millis(500),
+                    ..Reply::from(json!({ "success_response": { "order_id": "ord-1" } }))
+                },
+            ),
+            (
+                ORDERS_PATH,
+                Reply::from(json!({ "success_response": { "order_id": "ord-1" } })),
+            ),
+            (ORDERS_PATH, Reply::status("400 Bad Request")),
+            (
+                "/api/v3/brokerage/orders/batch_cancel",
+                Reply::status("503 Service Unavailable"),
+            ),
+        ])
+        .await;
+        let adapter = adapter.with_http_policy(fast_retries());
+        let order = |client_order_id: &str| NormalizedOrderRequest {
+            venue: "coinbase_at".to_string(),
+            symbol: "BTC-USD".to_string(),
+            instrument: spot_instrument("BTC-USD"),
+            strategy_id: "core.momentum".to_string(),
+            client_order_id: client_order_id.to_string(),
+            intent_id: None,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            qty: 1.0,
+            quote_size: None,
+            limit_price: Some(100.0),
+            stop_price: None,
+            tif: None,
+            post_only: false,
+            reduce_only: false,
+            requested_notional_cents: 10_000,
+        };
+
+        let ack = adapter
+            .place_order(order("client-1"))
+            .await
+            .expect("placed");
+        assert_eq!(ack.venue_order_id, "ord-1");
+        let err = adapter
+            .place_order(order("client-2"))
+            .await
+            .expect_err("bad request");
+        assert_eq!(err.code, "venue_http_error");
+        let err = adapter.cancel_order("ord-1").await.expect_err("cancel");
+        assert!(err.retriable);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 5, "{requests:?}");
+        assert_eq!(requests[1], requests[2]);
+        assert!(requests[1].contains("\"client_order_id\":\"client-1\""));
+        assert!(requests[3].contains("client-2"));
+    }
+
+    #[tokio::test]
+    async fn orders_are_sent_on_cached_product_increments() {
+        let mut eth_usd = products::tests::btc_usd();
+        eth_usd["product_id"] = json!("ETH-USD");
+        eth_usd["base_increment"] = json!("0.0001");
+        let (adapter, requests) = serve(vec![
+            (
+                "/api/v3/brokerage/products",
+                json!({ "products": [products::tests::btc_usd()] }),
+            ),
+            (
+                ORDERS_PATH,
+                json!({ "success_response": { "order_id": "ord-1" } }),
+            ),
+            (
+                ORDERS_PATH,
+                json!({ "success_response": { "order_id": "ord-2" } }),
+            ),
+            ("/api/v3/brokerage/products/ETH-USD", eth_usd),
+        ])
+        .await;
+        let order = |qty: f64, limit_price: f64| NormalizedOrderRequest {
+            venue: "coinbase_at".to_string(),
+            symbol: "BTC-USD".to_string(),
+            instrument: spot_instrument("BTC-USD"),
+            strategy_id: "core.momentum".to_string(),
+            client_order_id: format!("client-{qty}"),
+            intent_id: None,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            qty,
+            quote_size: None,
+            limit_price: Some(limit_price),
+            stop_price: None,
+            tif: None,
+            post_only: false,
+            reduce_only: false,
+            requested_notional_cents: 3_000,
+        };
+
+        adapter
+            .place_order(order(0.1 + 0.2, 100.004))
+            .await
+            .expect("placed");
+        let err = adapter
+            .place_order(order(0.000_001, 100.0))
+            .await
+            .expect_err("below base_min_size");
+        assert_eq!(err.code, "size_below_minimum");
+        adapter
+            .place_order(order(0.5, 100.0))
+            .await
+            .expect("placed");
+
+        let eth = adapter
+            .product("ETH-USD")
+            .await
+            .unwrap()
+            .expect("single product");
+        assert_eq!(eth.base_increment, 0.0001);
+        assert!(adapter.product("DOGE-USD").await.unwrap().is_none());
+
+        let requests = requests.lock().unwrap();
+        let products = requests
+            .iter()
+            .filter(|target| target.starts_with("/api/v3/brokerage/products?"))
+            .count();
+        assert_eq!(products, 1, "the product list is loaded once: {requests:?}");
+        assert!(requests[1].contains("\"base_size\":\"0.3\""));
+        assert!(requests[1].contains("\"limit_price\":\"100\""));
+    }
+
     #[test]
     fn parse_order_id_accepts_success_response_shape() {
         let payload = json!({
@@ -429,6 +1507,42 @@ mod tests {
         assert!(err.message.contains("INSUFFICIENT_FUND"));
     }
 
+    #[test]
+    fn cancel_failures_map_to_order_not_found_or_cancel_rejected() {
+        let payload = json!({
+            "results": [
+                { "success": true, "order_id": "ord-1" },
+                { "success": false, "failure_reason": "UNKNOWN_CANCEL_ORDER", "order_id": "ord-2" },
+                { "success": false, "failure_reason": "COMMANDER_REJECTED_CANCEL_ORDER", "order_id": "ord-3" }
+            ]
+        });
+        let code = |order_id: &str| {
+            CoinbaseAdvancedTradeAdapter::cancel_failure(&payload, order_id).map(|err| err.code)
+        };
+        assert_eq!(code("ord-1"), None);
+        assert_eq!(code("ord-2").as_deref(), Some("order_not_found"));
+        assert_eq!(code("ord-3").as_deref(), Some("cancel_rejected"));
+        assert_eq!(code("ord-4"), None);
+    }
+
+    #[test]
+    fn parse_best_bid_ask_reads_top_of_book() {
+        let payload = json!({
+            "pricebooks": [{
+                "product_id": "BTC-USD",
+                "bids": [{ "price": "99.5", "size": "1" }],
+                "asks": [{ "price": "100.5", "size": "2" }],
+                "time": "2024-01-01T00:00:00Z"
+            }]
+        });
+        let quote = CoinbaseAdvancedTradeAdapter::parse_best_bid_ask(&payload, "BTC-USD", 1)
+            .expect("quote should parse");
+        assert_eq!(quote.bid, Some(99.5));
+        assert_eq!(quote.ask, Some(100.5));
+        assert_eq!(quote.mark, Some(100.0));
+        assert!(CoinbaseAdvancedTradeAdapter::parse_best_bid_ask(&payload, "ETH-USD", 1).is_none());
+    }
+
     #[test]
     fn parse_order_id_fails_when_response_shape_is_unknown() {
         let payload = json!({ "success": true });
@@ -436,4 +1550,111 @@ mod tests {
             .expect_err("missing order id must fail");
         assert_eq!(err.code, "missing_order_id");
     }
+
+    #[tokio::test]
+    async fn user_channel_streams_fills_and_resyncs_after_reconnect() {
+        let (rest, requests) = serve(vec![(
+            "/api/v3/brokerage/orders/historical/ord-1",
+            json!({ "order": venue_order("ord-1", "FILLED", "1") }),
+        )])
+        .await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let ws_addr = listener.local_addr().expect("addr");
+        let (subscribed_tx, mut subscribed) = tokio::sync::mpsc::unbounded_channel::<Value>();
+        tokio::spawn(async move {
+            for connection in 0.. {
+                let (stream, _) = listener.accept().await.expect("accept");
+                let mut ws = tokio_tungstenite::accept_async(stream)
+                    .await
+                    .expect("handshake");
+                for _ in 0..2 {
+                    let Some(Ok(Message::Text(text))) = ws.next().await else {
+                        return;
+                    };
+                    let _ = subscribed_tx.send(serde_json::from_str(&text).expect("json"));
+                }
+                if connection > 0 {
+                    while ws.next().await.is_some() {}
+                    return;
+                }
+                let order = |status: &str, cumulative: &str, leaves: &str, fees: &str| {
+                    json!({
+                        "order_id": "ord-1",
+                        "client_order_id": "client-ord-1",
+                        "product_id": "BTC-USD",
+                        "order_side": "BUY",
+                        "order_type": "Limit",
+                        "status": status,
+                        "cumulative_quantity": cumulative,
+                        "leaves_quantity": leaves,
+                        "avg_price": "100",
+                        "total_fees": fees,
+                        "limit_price": "100"
+                    })
+                };
+                for (kind, order) in [
+                    ("snapshot", order("OPEN", "0", "1", "0")),
+                    ("update", order("OPEN", "0.4", "0.6", "0.1")),
+                ] {
+                    let msg = json!({
+                        "channel": "user",
+                        "timestamp": "2024-01-01T00:00:01Z",
+                        "events": [{ "type": kind, "orders": [order] }]
+                    });
+                    ws.send(Message::text(msg.to_string())).await.expect("send");
+                }
+                ws.close(None).await.expect("close");
+            }
+        });
+
+        let key = CdpKey::from_pem(auth::tests::KEY_NAME, auth::tests::SEC1_PEM).expect("key");
+        let adapter = CoinbaseAdvancedTradeAdapter::new(
+            rest.api_base.clone(),
+            "ws://unused",
+            Credentials::Cdp(key),
+        )
+        .expect("adapter")
+        .with_user_ws_url(format!("ws://{ws_addr}"));
+        let mut updates = adapter.subscribe_user_updates();
+        adapter.connect_user_channel().await.expect("user channel");
+
+        let mut fills = Vec::new();
+        let mut disconnected = false;
+        while fills.len() < 2 {
+            match tokio::time::timeout(Duration::from_secs(5), updates.recv())
+                .await
+                .expect("update before timeout")
+                .expect("channel open")
+            {
+                UserUpdate::Fill(fill) => fills.push(fill),
+                UserUpdate::Disconnected { .. } => disconnected = true,
+                UserUpdate::Order(order) => assert_eq!(order.client_order_id, "client-ord-1"),
+            }
+        }
+        assert!(disconnected);
+        assert_eq!(
+            (fills[0].qty, fills[0].price, fills[0].fee),
+            (0.4, 100.0, 0.1)
+        );
+        assert!((fills[1].qty - 0.6).abs() < 1e-9);
+        assert_eq!(fills[1].venue_fill_id, "ord-1:1");
+        assert!(requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|target| target == "/api/v3/brokerage/orders/historical/ord-1"));
+
+        let mut channels = Vec::new();
+        while channels.len() < 4 {
+            let subscribe = subscribed.recv().await.expect("subscribe");
+            assert!(subscribe["jwt"].as_str().is_some_and(|jwt| !jwt.is_empty()));
+            channels.push(
+                subscribe["channel"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+        assert_eq!(channels, ["heartbeats", "user", "heartbeats", "user"]);
+    }
 }
@@ -0,0 +1,380 @@
+//! Coinbase Advanced Trade public WebSocket feed (ticker, level2, market_trades)
+//! normalized into `MarketDataUpdate`s.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Weak;
+use std::time::Duration;
+
+use exchange_core::{
+    ExchangeError, MarketDataSink, MarketDataUpdate, OrderSide, QuoteSnapshot, TradePrint,
+};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+pub const DEFAULT_WS_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+const CHANNELS: [&str; 4] = ["heartbeats", "ticker", "level2", "market_trades"];
+const VENUE: &str = "coinbase_at";
+pub(crate) const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+pub(crate) const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub(crate) fn parse_f64(value: Option<&Value>) -> Option<f64> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|raw| raw.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+}
+
+pub(crate) fn parse_ts_ms(value: Option<&Value>) -> Option<i64> {
+    value
+        .and_then(Value::as_str)
+        .and_then(|raw| chrono::DateTime::parse_from_rfc3339(raw).ok())
+        .map(|ts| ts.timestamp_millis())
+}
+
+fn midpoint(bid: Option<f64>, ask: Option<f64>) -> Option<f64> {
+    match (bid, ask) {
+        (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+        _ => None,
+    }
+}
+
+/// Price levels keyed by the bit pattern of the (always positive) price, which
+/// orders the same way as the price itself.
+#[derive(Debug, Default)]
+struct Level2Book {
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+impl Level2Book {
+    fn apply(&mut self, side: &str, price: f64, qty: f64) {
+        if price <= 0.0 {
+            return;
+        }
+        let levels = match side {
+            "bid" => &mut self.bids,
+            "offer" | "ask" => &mut self.asks,
+            _ => return,
+        };
+        if qty > 0.0 {
+            levels.insert(price.to_bits(), qty);
+        } else {
+            levels.remove(&price.to_bits());
+        }
+    }
+
+    fn best_bid(&self) -> Option<f64> {
+        self.bids
+            .keys()
+            .next_back()
+            .map(|bits| f64::from_bits(*bits))
+    }
+
+    fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().map(|bits| f64::from_bits(*bits))
+    }
+}
+
+/// Per-connection feed state: level2 books are rebuilt from the snapshot sent
+/// after every (re)subscribe.
+#[derive(Debug, Default)]
+pub(crate) struct FeedState {
+    books: HashMap<String, Level2Book>,
+}
+
+impl FeedState {
+    pub(crate) fn handle_message(&mut self, msg: &Value, now_ms: i64) -> Vec<MarketDataUpdate> {
+        let ts_ms = parse_ts_ms(msg.get("timestamp")).unwrap_or(now_ms);
+        let events = msg
+            .get("events")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        match msg.get("channel").and_then(Value::as_str) {
+            Some("ticker") => events
+                .iter()
+                .filter_map(|event| event.get("tickers").and_then(Value::as_array))
+                .flatten()
+                .filter_map(|ticker| Self::ticker_quote(ticker, ts_ms))
+                .map(MarketDataUpdate::Quote)
+                .collect(),
+            Some("l2_data") => events
+                .iter()
+                .filter_map(|event| self.apply_level2_event(event, ts_ms))
+                .map(MarketDataUpdate::Quote)
+                .collect(),
+            Some("market_trades") => events
+                .iter()
+                .filter_map(|event| event.get("trades").and_then(Value::as_array))
+                .flatten()
+                .filter_map(|trade| Self::trade_print(trade, ts_ms))
+                .map(MarketDataUpdate::Trade)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn ticker_quote(ticker: &Value, ts_ms: i64) -> Option<QuoteSnapshot> {
+        let product_id = ticker.get("product_id").and_then(Value::as_str)?;
+        let bid = parse_f64(ticker.get("best_bid"));
+        let ask = parse_f64(ticker.get("best_ask"));
+        Some(QuoteSnapshot {
+            venue: VENUE.to_string(),
+            venue_symbol: product_id.to_string(),
+            bid,
+            ask,
+            last: parse_f64(ticker.get("price")),
+            mark: midpoint(bid, ask),
+            ts_ms,
+        })
+    }
+
+    fn apply_level2_event(&mut self, event: &Value, ts_ms: i64) -> Option<QuoteSnapshot> {
+        let product_id = event.get("product_id").and_then(Value::as_str)?;
+        let book = self.books.entry(product_id.to_string()).or_default();
+        if event.get("type").and_then(Value::as_str) == Some("snapshot") {
+            *book = Level2Book::default();
+        }
+        for update in event
+            .get("updates")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            let side = update.get("side").and_then(Value::as_str).unwrap_or("");
+            let price = parse_f64(update.get("price_level"));
+            let qty = parse_f64(update.get("new_quantity"));
+            if let (Some(price), Some(qty)) = (price, qty) {
+                book.apply(side, price, qty);
+            }
+        }
+
+        let bid = book.best_bid();
+        let ask = book.best_ask();
+        Some(QuoteSnapshot {
+            venue: VENUE.to_string(),
+            venue_symbol: product_id.to_string(),
+            bid,
+            ask,
+            last: None,
+            mark: midpoint(bid, ask),
+            ts_ms,
+        })
+    }
+
+    fn trade_print(trade: &Value, ts_ms: i64) -> Option<TradePrint> {
+        let product_id = trade.get("product_id").and_then(Value::as_str)?;
+        let price = parse_f64(trade.get("price")).filter(|p| *p > 0.0)?;
+        let side = match trade.get("side").and_then(Value::as_str) {
+            Some("BUY") => Some(OrderSide::Buy),
+            Some("SELL") => Some(OrderSide::Sell),
+            _ => None,
+        };
+        Some(TradePrint {
+            venue: VENUE.to_string(),
+            venue_symbol: product_id.to_string(),
+            price,
+            size: parse_f64(trade.get("size")),
+            side,
+            ts_ms: parse_ts_ms(trade.get("time")).unwrap_or(ts_ms),
+        })
+    }
+}
+
+/// Opens a WebSocket to `url` and subscribes every channel to `product_ids`.
+pub(crate) async fn connect(url: &str, product_ids: &[String]) -> Result<WsStream, ExchangeError> {
+    let (mut ws, _) = connect_async(url).await.map_err(|e| {
+        ExchangeError::new(
+            "market_data_connect",
+            format!("coinbase_at websocket connect failed: {e}"),
+            true,
+        )
+    })?;
+    for channel in CHANNELS {
+        let subscribe = json!({
+            "type": "subscribe",
+            "product_ids": product_ids,
+            "channel": channel,
+        });
+        ws.send(Message::text(subscribe.to_string()))
+            .await
+            .map_err(|e| {
+                ExchangeError::new(
+                    "market_data_subscribe",
+                    format!("coinbase_at {channel} subscribe failed: {e}"),
+                    true,
+                )
+            })?;
+    }
+    Ok(ws)
+}
+
+/// Pumps `ws` into `sink`, reconnecting with backoff until `guard` is dropped.
+pub(crate) async fn run_feed(
+    url: String,
+    product_ids: Vec<String>,
+    ws: WsStream,
+    sink: MarketDataSink,
+    guard: Weak<()>,
+) {
+    let mut next = Some(ws);
+    let mut backoff = MIN_RECONNECT_BACKOFF;
+
+    while guard.upgrade().is_some() {
+        let mut ws = match next.take() {
+            Some(ws) => ws,
+            None => match connect(&url, &product_ids).await {
+                Ok(ws) => ws,
+                Err(err) => {
+                    sink(MarketDataUpdate::Disconnected {
+                        venue: VENUE.to_string(),
+                        reason: err.message,
+                    });
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                    continue;
+                }
+            },
+        };
+
+        let mut state = FeedState::default();
+        let reason = loop {
+            if guard.upgrade().is_none() {
+                return;
+            }
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(msg) = serde_json::from_str::<Value>(&text) else {
+                        continue;
+                    };
+                    backoff = MIN_RECONNECT_BACKOFF;
+                    let now_ms = chrono::Utc::now().timestamp_millis();
+                    for update in state.handle_message(&msg, now_ms) {
+                        sink(update);
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    break format!("coinbase_at websocket closed: {frame:?}");
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break format!("coinbase_at websocket error: {e}"),
+                None => break "coinbase_at websocket stream ended".to_string(),
+            }
+        };
+
+        sink(MarketDataUpdate::Disconnected {
+            venue: VENUE.to_string(),
+            reason,
+        });
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(update: &MarketDataUpdate) -> &QuoteSnapshot {
+        match update {
+            MarketDataUpdate::Quote(quote) => quote,
+            other => panic!("expected quote, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ticker_message_yields_top_of_book_and_last() {
+        let msg = json!({
+            "channel": "ticker",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "events": [{
+                "type": "update",
+                "tickers": [{
+                    "product_id": "BTC-USD",
+                    "price": "100.5",
+                    "best_bid": "100",
+                    "best_ask": "101"
+                }]
+            }]
+        });
+
+        let updates = FeedState::default().handle_message(&msg, 0);
+        let quote = quote(&updates[0]);
+        assert_eq!(quote.bid, Some(100.0));
+        assert_eq!(quote.ask, Some(101.0));
+        assert_eq!(quote.last, Some(100.5));
+        assert_eq!(quote.mark, Some(100.5));
+        assert_eq!(quote.ts_ms, 1_704_067_200_000);
+    }
+
+    #[test]
+    fn level2_updates_maintain_best_bid_and_ask() {
+        let mut state = FeedState::default();
+        let snapshot = json!({
+            "channel": "l2_data",
+            "events": [{
+                "type": "snapshot",
+                "product_id": "ETH-USD",
+                "updates": [
+                    {"side": "bid", "price_level": "99", "new_quantity": "1"},
+                    {"side": "bid", "price_level": "100", "new_quantity": "2"},
+                    {"side": "offer", "price_level": "102", "new_quantity": "1"},
+                    {"side": "offer", "price_level": "101", "new_quantity": "3"}
+                ]
+            }]
+        });
+        let updates = state.handle_message(&snapshot, 5);
+        assert_eq!(quote(&updates[0]).bid, Some(100.0));
+        assert_eq!(quote(&updates[0]).ask, Some(101.0));
+
+        let update = json!({
+            "channel": "l2_data",
+            "events": [{
+                "type": "update",
+                "product_id": "ETH-USD",
+                "updates": [
+                    {"side": "bid", "price_level": "100", "new_quantity": "0"},
+                    {"side": "offer", "price_level": "100.5", "new_quantity": "1"}
+                ]
+            }]
+        });
+        let updates = state.handle_message(&update, 6);
+        assert_eq!(quote(&updates[0]).bid, Some(99.0));
+        assert_eq!(quote(&updates[0]).ask, Some(100.5));
+        assert_eq!(quote(&updates[0]).ts_ms, 6);
+    }
+
+    #[test]
+    fn market_trades_yield_trade_prints() {
+        let msg = json!({
+            "channel": "market_trades",
+            "events": [{
+                "type": "update",
+                "trades": [{
+                    "product_id": "BTC-USD",
+                    "price": "100.25",
+                    "size": "0.5",
+                    "side": "SELL",
+                    "time": "2024-01-01T00:00:01Z"
+                }]
+            }]
+        });
+
+        let updates = FeedState::default().handle_message(&msg, 0);
+        match &updates[0] {
+            MarketDataUpdate::Trade(trade) => {
+                assert_eq!(trade.price, 100.25);
+                assert_eq!(trade.size, Some(0.5));
+                assert_eq!(trade.side, Some(OrderSide::Sell));
+                assert_eq!(trade.ts_ms, 1_704_067_201_000);
+            }
+            other => panic!("expected trade, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! Advanced Trade historical orders and fills normalized into `OrderSnapshot`s
+//! and `FillReport`s, and order requests mapped to `order_configuration`s.
+
+use exchange_core::{
+    AssetClass, ExchangeError, FillReport, InstrumentRef, InstrumentType, NormalizedOrderRequest,
+    OrderSide, OrderSnapshot, OrderStatus, OrderType, TimeInForce,
+};
+use serde_json::{json, Value};
+
+use crate::market_data::{parse_f64, parse_ts_ms};
+use crate::products::Product;
+
+const VENUE: &str = "coinbase_at";
+const QTY_EPSILON: f64 = 1e-12;
+const DAY_MS: i64 = 86_400_000;
+
+/// Currencies held as cash rather than as a position.
+pub(crate) const CASH_CURRENCIES: [&str; 5] = ["USD", "USDC", "USDT", "EUR", "GBP"];
+
+pub(crate) fn spot_instrument(product_id: &str) -> InstrumentRef {
+    let (base, quote) = match product_id.split_once('-') {
+        Some((base, quote)) => (Some(base.to_string()), Some(quote.to_string())),
+        None => (None, None),
+    };
+    InstrumentRef {
+        venue: VENUE.to_string(),
+        venue_symbol: product_id.to_string(),
+        asset_class: AssetClass::Crypto,
+        instrument_type: InstrumentType::Spot,
+        base,
+        quote,
+        expiry_ts_ms: None,
+        strike: None,
+        option_right: None,
+        contract_multiplier: None,
+    }
+}
+
+pub(crate) fn parse_side(value: Option<&Value>) -> Option<OrderSide> {
+    match value.and_then(Value::as_str)? {
+        "BUY" => Some(OrderSide::Buy),
+        "SELL" => Some(OrderSide::Sell),
+        _ => None,
+    }
+}
+
+/// Coinbase order status as an `OrderStatus`; working orders with fills are
+/// `PartiallyFilled`. `None` for statuses Coinbase has not documented.
+pub(crate) fn map_status(status: &str, filled_qty: f64) -> Option<OrderStatus> {
+    let working = if filled_qty > QTY_EPSILON {
+        OrderStatus::PartiallyFilled
+    } else {
+        OrderStatus::New
+    };
+    match status {
+        "PENDING" | "OPEN" | "QUEUED" | "CANCEL_QUEUED" | "EDIT_QUEUED" => Some(working),
+        "FILLED" => Some(OrderStatus::Filled),
+        "CANCELLED" | "EXPIRED" => Some(OrderStatus::Canceled),
+        "FAILED" => Some(OrderStatus::Rejected),
+        _ => None,
+    }
+}
+
+/// Reads an order from `orders/historical`. The venue does not know which
+/// strategy placed it, so `strategy_id` is empty.
+pub(crate) fn parse_order(order: &Value) -> Option<OrderSnapshot> {
+    let venue_order_id = order.get("order_id").and_then(Value::as_str)?;
+    let product_id = order.get("product_id").and_then(Value::as_str)?;
+    let side = parse_side(order.get("side"))?;
+    let filled_qty = parse_f64(order.get("filled_size")).unwrap_or(0.0);
+    let status = map_status(order.get("status").and_then(Value::as_str)?, filled_qty)?;
+
+    // One configuration key (e.g. `limit_limit_gtc`) holds the order's terms.
+    let terms = order
+        .get("order_configuration")
+        .and_then(Value::as_object)
+        .and_then(|config| config.values().next());
+    let qty = terms
+        .and_then(|terms| parse_f64(terms.get("base_size")))
+        .unwrap_or(filled_qty);
+    let limit_price = terms.and_then(|terms| parse_f64(terms.get("limit_price")));
+    let order_type = match order.get("order_type").and_then(Value::as_str) {
+        Some("MARKET") => OrderType::Market,
+        _ if limit_price.is_none() => OrderType::Market,
+        _ => OrderType::Limit,
+    };
+    let created_at_ms = parse_ts_ms(order.get("created_time")).unwrap_or(0);
+    let updated_at_ms = parse_ts_ms(order.get("last_fill_time")).unwrap_or(created_at_ms);
+
+    Some(OrderSnapshot {
+        venue: VENUE.to_string(),
+        venue_order_id: venue_order_id.to_string(),
+        client_order_id: order
+            .get("client_order_id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        strategy_id: String::new(),
+        instrument: spot_instrument(product_id),
+        side,
+        order_type,
+        status,
+        qty,
+        filled_qty,
+        limit_price,
+        avg_fill_price: parse_f64(order.get("average_filled_price"))
+            .filter(|_| filled_qty > QTY_EPSILON),
+        created_at_ms,
+        updated_at_ms,
+        simulated: false,
+    })
+}
+
+/// Reads a fill from `orders/historical/fills`. `client_order_id` and
+/// `strategy_id` are empty; the adapter fills them in from orders it placed.
+pub(crate) fn parse_fill(fill: &Value) -> Option<FillReport> {
+    let venue_order_id = fill.get("order_id").and_then(Value::as_str)?;
+    let product_id = fill.get("product_id").and_then(Value::as_str)?;
+    let price = parse_f64(fill.get("price")).filter(|price| *price > 0.0)?;
+    let size = parse_f64(fill.get("size"))?;
+    let size_in_quote = fill
+        .get("size_in_quote")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let qty = if size_in_quote { size / price } else { size };
+    let instrument = spot_instrument(product_id);
+
+    Some(FillReport {
+        venue: VENUE.to_string(),
+        venue_fill_id: fill
+            .get("entry_id")
+            .or_else(|| fill.get("trade_id"))
+            .and_then(Value::as_str)?
+            .to_string(),
+        venue_order_id: venue_order_id.to_string(),
+        client_order_id: String::new(),
+        strategy_id: String::new(),
+        fee_asset: instrument.quote.clone(),
+        instrument,
+        side: parse_side(fill.get("side"))?,
+        qty,
+        price,
+        fee: parse_f64(fill.get("commission")).unwrap_or(0.0),
+        liquidity: match fill.get("liquidity_indicator").and_then(Value::as_str) {
+            Some("MAKER") => Some("maker".to_string()),
+            Some("TAKER") => Some("taker".to_string()),
+            _ => None,
+        },
+        simulated: false,
+        ts_ms: parse_ts_ms(fill.get("trade_time"))?,
+    })
+}
+
+fn unsupported(code: &str, message: impl Into<String>) -> ExchangeError {
+    ExchangeError::new(code, message, false)
+}
+
+fn positive(value: Option<f64>, code: &str, field: &str) -> Result<Option<f64>, ExchangeError> {
+    match value {
+        Some(v) if !(v.is_finite() && v > 0.0) => Err(unsupported(
+            code,
+            format!("{field} must be a positive number, got {v}"),
+        )),
+        _ => Ok(value),
+    }
+}
+
+/// The `order_configuration` Coinbase expects for `req`:
+///
+/// | order | GTC | Day | IOC | FOK |
+/// |---|---|---|---|---|
+/// | market | - | - | `market_market_ioc` | - |
+/// | limit | `limit_limit_gtc` | `limit_limit_gtd` | `sor_limit_ioc` | `limit_limit_fok` |
+/// | stop-limit | `stop_limit_stop_limit_gtc` | `stop_limit_stop_limit_gtd` | - | - |
+///
+/// A missing `tif` is IOC for market orders and GTC otherwise. Day orders are
+/// good until the end of the UTC day of `now_ms`. Buy stops trigger as the
+/// price rises to `stop_price`, sell stops as it falls. Spot sells cannot
+/// open a position, so only they may be `reduce_only`. Sizes and prices are
+/// rounded onto `product`'s increments and checked against its limits.
+pub(crate) fn order_configuration(
+    req: &NormalizedOrderRequest,
+    product: &Product,
+    now_ms: i64,
+) -> Result<Value, ExchangeError> {
+    if req.reduce_only && req.side == OrderSide::Buy {
+        return Err(unsupported(
+            "reduce_only_unsupported",
+            "coinbase_at spot buys cannot be reduce-only",
+        ));
+    }
+    let quote_size = positive(req.quote_size, "invalid_quote_size", "quote_size")?;
+    if quote_size.is_none() {
+        positive(Some(req.qty), "invalid_quantity", "qty")?;
+    }
+    let stop_price = positive(req.stop_price, "invalid_stop_price", "stop_price")?;
+
+    if req.order_type == OrderType::Market {
+        if stop_price.is_some() {
+            return Err(unsupported(
+                "stop_requires_limit",
+                "coinbase_at stop orders need a limit_price (stop-limit)",
+            ));
+        }
+        if req.post_only {
+            return Err(unsupported(
+                "post_only_unsupported",
+                "market orders cannot be post-only",
+            ));
+        }
+        if !matches!(req.tif, None | Some(TimeInForce::Ioc)) {
+            return Err(unsupported(
+                "tif_unsupported",
+                format!("coinbase_at market orders are IOC, got {:?}", req.tif),
+            ));
+        }
+        product.check_tradable(false, req.post_only)?;
+        let size = match quote_size {
+            Some(quote_size) => json!({ "quote_size": product.quote_size(quote_size)? }),
+            None => json!({ "base_size": product.base_size(req.qty)? }),
+        };
+        return Ok(json!({ "market_market_ioc": size }));
+    }
+
+    if quote_size.is_some() {
+        return Err(unsupported(
+            "quote_size_unsupported",
+            "quote_size applies to market orders only",
+        ));
+    }
+    let limit_price = req.limit_price.ok_or_else(|| {
+        unsupported(
+            "missing_limit_price",
+            "limit_price required for limit orders",
+        )
+    })?;
+    let tif = req.tif.clone().unwrap_or(TimeInForce::Gtc);
+    if req.post_only && (stop_price.is_some() || matches!(tif, TimeInForce::Ioc | TimeInForce::Fok))
+    {
+        return Err(unsupported(
+            "post_only_unsupported",
+            "coinbase_at post-only orders must be GTC or Day limits",
+        ));
+    }
+    if stop_price.is_some() && matches!(tif, TimeInForce::Ioc | TimeInForce::Fok) {
+        return Err(unsupported(
+            "tif_unsupported",
+            format!("coinbase_at stop-limit orders are GTC or Day, got {tif:?}"),
+        ));
+    }
+    product.check_tradable(true, req.post_only)?;
+    let base_size = product.base_size(req.qty)?;
+    let limit_price = product.limit_price(limit_price, &req.side)?;
+    let end_time =
+        chrono::DateTime::from_timestamp_millis((now_ms.div_euclid(DAY_MS) + 1) * DAY_MS)
+            .unwrap_or_default()
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let Some(stop_price) = stop_price else {
+        return Ok(match tif {
+            TimeInForce::Gtc => json!({ "limit_limit_gtc": {
+                "base_size": base_size,
+                "limit_price": limit_price,
+                "post_only": req.post_only,
+            }}),
+            TimeInForce::Day => json!({ "limit_limit_gtd": {
+                "base_size": base_size,
+                "limit_price": limit_price,
+                "end_time": end_time,
+                "post_only": req.post_only,
+            }}),
+            TimeInForce::Ioc => json!({ "sor_limit_ioc": {
+                "base_size": base_size,
+                "limit_price": limit_price,
+            }}),
+            TimeInForce::Fok => json!({ "limit_limit_fok": {
+                "base_size": base_size,
+                "limit_price": limit_price,
+            }}),
+        });
+    };
+
+    let stop_direction = match req.side {
+        OrderSide::Buy => "STOP_DIRECTION_STOP_UP",
+        OrderSide::Sell => "STOP_DIRECTION_STOP_DOWN",
+    };
+    let mut terms = json!({
+        "base_size": base_size,
+        "limit_price": limit_price,
+        "stop_price": product.stop_price(stop_price)?,
+        "stop_direction": stop_direction,
+    });
+    // IOC and FOK stop-limits were rejected above.
+    if tif == TimeInForce::Gtc {
+        return Ok(json!({ "stop_limit_stop_limit_gtc": terms }));
+    }
+    terms["end_time"] = json!(end_time);
+    Ok(json!({ "stop_limit_stop_limit_gtd": terms }))
+}
+
+/// Cursor of the next page, or `None` on the last one.
+pub(crate) fn next_cursor(payload: &Value) -> Option<String> {
+    if payload.get("has_next").and_then(Value::as_bool) == Some(false) {
+        return None;
+    }
+    payload
+        .get("cursor")
+        .and_then(Value::as_str)
+        .filter(|cursor| !cursor.is_empty())
+        .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn working_orders_with_fills_are_partially_filled() {
+        let order = json!({
+            "order_id": "ord-1",
+            "client_order_id": "client-1",
+            "product_id": "BTC-USD",
+            "side": "BUY",
+            "status": "OPEN",
+            "order_type": "LIMIT",
+            "filled_size": "0.4",
+            "average_filled_price": "100.5",
+            "created_time": "2024-01-01T00:00:00Z",
+            "last_fill_time": "2024-01-01T00:00:01Z",
+            "order_configuration": {
+                "limit_limit_gtc": { "base_size": "1", "limit_price": "101", "post_only": false }
+            }
+        });
+        let order = parse_order(&order).expect("order parses");
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!((order.qty, order.filled_qty), (1.0, 0.4));
+        assert_eq!(order.limit_price, Some(101.0));
+        assert_eq!(order.avg_fill_price, Some(100.5));
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.instrument.base.as_deref(), Some("BTC"));
+        assert_eq!(order.updated_at_ms - order.created_at_ms, 1_000);
+
+        assert_eq!(map_status("OPEN", 0.0), Some(OrderStatus::New));
+        assert_eq!(map_status("FILLED", 1.0), Some(OrderStatus::Filled));
+        assert_eq!(map_status("EXPIRED", 0.2), Some(OrderStatus::Canceled));
+        assert_eq!(map_status("FAILED", 0.0), Some(OrderStatus::Rejected));
+        assert_eq!(map_status("UNKNOWN_ORDER_STATUS", 0.0), None);
+    }
+
+    #[test]
+    fn fills_convert_quote_sizes_and_liquidity() {
+        let fill = json!({
+            "entry_id": "entry-1",
+            "trade_id": "trade-1",
+            "order_id": "ord-1",
+            "product_id": "ETH-USD",
+            "trade_time": "2024-01-01T00:00:00.500Z",
+            "price": "2000",
+            "size": "1000",
+            "size_in_quote": true,
+            "commission": "6",
+            "side": "SELL",
+            "liquidity_indicator": "TAKER"
+        });
+        let fill = parse_fill(&fill).expect("fill parses");
+        assert_eq!(fill.venue_fill_id, "entry-1");
+        assert_eq!(fill.qty, 0.5);
+        assert_eq!(fill.fee, 6.0);
+        assert_eq!(fill.fee_asset.as_deref(), Some("USD"));
+        assert_eq!(fill.liquidity.as_deref(), Some("taker"));
+        assert_eq!(fill.side, OrderSide::Sell);
+        assert_eq!(fill.ts_ms, 1_704_067_200_500);
+    }
+
+    #[test]
+    fn pagination_stops_without_a_next_page() {
+        assert_eq!(
+            next_cursor(&json!({"has_next": true, "cursor": "abc"})).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            next_cursor(&json!({"has_next": false, "cursor": "abc"})),
+            None
+        );
+        assert_eq!(next_cursor(&json!({"cursor": ""})), None);
+        assert_eq!(
+            next_cursor(&json!({"cursor": "def"})).as_deref(),
+            Some("def")
+        );
+    }
+
+    fn request(order_type: OrderType, tif: Option<TimeInForce>) -> NormalizedOrderRequest {
+        NormalizedOrderRequest {
+            venue: VENUE.to_string(),
+            symbol: "BTC-USD".to_string(),
+            instrument: spot_instrument("BTC-USD"),
+            strategy_id: "s1".to_string(),
+            client_order_id: "client-1".to_string(),
+            intent_id: None,
+            side: OrderSide::Buy,
+            order_type,
+            qty: 0.5,
+            quote_size: None,
+            limit_price: Some(100.0),
+            stop_price: None,
+            tif,
+            post_only: false,
+            reduce_only: false,
+            requested_notional_cents: 5_000,
+        }
+    }
+
+    fn btc_usd() -> Product {
+        Product::parse(&crate::products::tests::btc_usd()).expect("product")
+    }
+
+    fn configuration_key(req: &NormalizedOrderRequest) -> String {
+        let config =
+            order_configuration(req, &btc_usd(), 1_704_067_200_500).expect("supported order");
+        let keys: Vec<&String> = config.as_object().expect("object").keys().collect();
+        assert_eq!(keys.len(), 1);
+        keys[0].clone()
+    }
+
+    #[test]
+    fn time_in_force_selects_the_order_configuration() {
+        use TimeInForce::*;
+
+        let limit = |tif| request(OrderType::Limit, tif);
+        assert_eq!(configuration_key(&limit(None)), "limit_limit_gtc");
+        assert_eq!(configuration_key(&limit(Some(Gtc))), "limit_limit_gtc");
+        assert_eq!(configuration_key(&limit(Some(Ioc))), "sor_limit_ioc");
+        assert_eq!(configuration_key(&limit(Some(Fok))), "limit_limit_fok");
+        let day = order_configuration(&limit(Some(Day)), &btc_usd(), 1_704_067_200_500)
+            .expect("day order");
+        assert_eq!(
+            day["limit_limit_gtd"]["end_time"], "2024-01-02T00:00:00Z",
+            "day orders expire at the end of the UTC day"
+        );
+
+        let mut market = request(OrderType::Market, None);
+        market.quote_size = Some(250.0);
+        let config = order_configuration(&market, &btc_usd(), 0).expect("quote-sized market");
+        assert_eq!(config["market_market_ioc"], json!({ "quote_size": "250" }));
+
+        let mut stop = limit(None);
+        stop.qty = 0.1 + 0.2;
+        stop.limit_price = Some(100.004);
+        stop.stop_price = Some(99.0);
+        let config = order_configuration(&stop, &btc_usd(), 0).expect("stop-limit");
+        let terms = &config["stop_limit_stop_limit_gtc"];
+        assert_eq!(
+            (&terms["base_size"], &terms["limit_price"]),
+            (&json!("0.3"), &json!("100")),
+            "sizes and prices are sent on the product's increments"
+        );
+        assert_eq!(terms["stop_price"], "99");
+        assert_eq!(terms["stop_direction"], "STOP_DIRECTION_STOP_UP");
+        stop.side = OrderSide::Sell;
+        stop.reduce_only = true;
+        stop.tif = Some(Day);
+        let config = order_configuration(&stop, &btc_usd(), 0).expect("reduce-only sell stop");
+        assert_eq!(
+            config["stop_limit_stop_limit_gtd"]["stop_direction"],
+            "STOP_DIRECTION_STOP_DOWN"
+        );
+    }
+
+    #[test]
+    fn unsupported_combinations_are_rejected_with_precise_codes() {
+        let code = |req: NormalizedOrderRequest| {
+            order_configuration(&req, &btc_usd(), 0)
+                .expect_err("combination must be rejected")
+                .code
+        };
+
+        let mut req = request(OrderType::Market, Some(TimeInForce::Gtc));
+        assert_eq!(code(req.clone()), "tif_unsupported");
+        req.tif = None;
+        req.post_only = true;
+        assert_eq!(code(req.clone()), "post_only_unsupported");
+        req.post_only = false;
+        req.stop_price = Some(99.0);
+        assert_eq!(code(req.clone()), "stop_requires_limit");
+        req.stop_price = None;
+        req.reduce_only = true;
+        assert_eq!(code(req.clone()), "reduce_only_unsupported");
+
+        let mut req = request(OrderType::Limit, Some(TimeInForce::Ioc));
+        req.post_only = true;
+        assert_eq!(code(req.clone()), "post_only_unsupported");
+        req.post_only = false;
+        req.quote_size = Some(100.0);
+        assert_eq!(code(req.clone()), "quote_size_unsupported");
+        req.quote_size = None;
+        req.stop_price = Some(99.0);
+        assert_eq!(code(req.clone()), "tif_unsupported");
+        req.stop_price = Some(-1.0);
+        assert_eq!(code(req.clone()), "invalid_stop_price");
+        req.stop_price = None;
+        req.qty = 0.000_001;
+        assert_eq!(code(req.clone()), "size_below_minimum");
+        req.limit_price = None;
+        assert_eq!(code(req), "missing_limit_price");
+    }
+}
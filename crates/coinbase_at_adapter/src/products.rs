@@ -0,0 +1,351 @@
+//! Advanced Trade product metadata (`/api/v3/brokerage/products`) and the
+//! rounding of order sizes and prices onto each product's increments.
+
+use exchange_core::{ExchangeError, OrderSide, ProductInfo};
+use serde_json::Value;
+
+use crate::market_data::parse_f64;
+
+const VENUE: &str = "coinbase_at";
+/// Relative distance from a whole step still treated as on the step, so
+/// `0.29` in steps of `0.01` is 29 steps rather than 28.999….
+const STEP_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rounding {
+    Down,
+    Up,
+    Nearest,
+}
+
+/// A decimal step such as `0.00000001`, held exactly as `units × 10^-scale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Increment {
+    units: u64,
+    scale: u32,
+}
+
+impl Increment {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let (whole, fraction) = raw.split_once('.').unwrap_or((raw, ""));
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > 18 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let units = format!("{whole}{fraction}").parse::<u64>().ok()?;
+        (units > 0).then_some(Self {
+            units,
+            scale: fraction.len() as u32,
+        })
+    }
+
+    fn as_f64(self) -> f64 {
+        self.units as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// `value` as a whole number of increments, formatted as a decimal string
+    /// without float artifacts or trailing zeros. `None` for negative or
+    /// non-finite values.
+    pub(crate) fn quantize(self, value: f64, rounding: Rounding) -> Option<(String, f64)> {
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        let steps = value * 10f64.powi(self.scale as i32) / self.units as f64;
+        let nearest = steps.round();
+        let steps = if (steps - nearest).abs() <= STEP_TOLERANCE * nearest.max(1.0) {
+            nearest
+        } else {
+            match rounding {
+                Rounding::Down => steps.floor(),
+                Rounding::Up => steps.ceil(),
+                Rounding::Nearest => nearest,
+            }
+        };
+        let total = steps as u128 * u128::from(self.units);
+        let divisor = 10u128.pow(self.scale);
+        let mut text = (total / divisor).to_string();
+        if self.scale > 0 {
+            let fraction = format!("{:0width$}", total % divisor, width = self.scale as usize);
+            let fraction = fraction.trim_end_matches('0');
+            if !fraction.is_empty() {
+                text.push('.');
+                text.push_str(fraction);
+            }
+        }
+        let quantized = text.parse().ok()?;
+        Some((text, quantized))
+    }
+}
+
+/// A product's published rules plus its increments kept as exact decimals.
+#[derive(Debug, Clone)]
+pub(crate) struct Product {
+    pub(crate) info: ProductInfo,
+    base_increment: Increment,
+    quote_increment: Increment,
+    price_increment: Increment,
+}
+
+fn rejected(code: &str, message: String) -> ExchangeError {
+    ExchangeError::new(code, message, false)
+}
+
+impl Product {
+    /// Reads one entry of `products`; `None` when an increment is missing.
+    pub(crate) fn parse(product: &Value) -> Option<Self> {
+        let field = |key: &str| product.get(key).and_then(Value::as_str);
+        let flag = |key: &str| product.get(key).and_then(Value::as_bool).unwrap_or(false);
+        let base_increment = Increment::parse(field("base_increment")?)?;
+        let quote_increment = Increment::parse(field("quote_increment")?)?;
+        let price_increment = field("price_increment")
+            .and_then(Increment::parse)
+            .unwrap_or(quote_increment);
+        let positive = |key: &str| parse_f64(product.get(key)).filter(|v| *v > 0.0);
+
+        Some(Self {
+            info: ProductInfo {
+                venue: VENUE.to_string(),
+                venue_symbol: field("product_id")?.to_string(),
+                base: field("base_currency_id")
+                    .or_else(|| field("base_display_symbol"))
+                    .map(str::to_string),
+                quote: field("quote_currency_id")
+                    .or_else(|| field("quote_display_symbol"))
+                    .map(str::to_string),
+                base_increment: base_increment.as_f64(),
+                base_min_size: parse_f64(product.get("base_min_size")).unwrap_or(0.0),
+                base_max_size: positive("base_max_size"),
+                quote_increment: quote_increment.as_f64(),
+                quote_min_size: positive("quote_min_size"),
+                quote_max_size: positive("quote_max_size"),
+                price_increment: price_increment.as_f64(),
+                status: field("status").unwrap_or("online").to_string(),
+                trading_disabled: flag("trading_disabled") || flag("is_disabled"),
+                cancel_only: flag("cancel_only"),
+                limit_only: flag("limit_only"),
+                post_only: flag("post_only"),
+            },
+            base_increment,
+            quote_increment,
+            price_increment,
+        })
+    }
+
+    /// Refuses new orders the product's trading status does not allow.
+    pub(crate) fn check_tradable(&self, limit: bool, post_only: bool) -> Result<(), ExchangeError> {
+        let info = &self.info;
+        if info.trading_disabled || info.cancel_only || !info.status.eq_ignore_ascii_case("online")
+        {
+            return Err(rejected(
+                "product_unavailable",
+                format!(
+                    "{} is not accepting orders (status {})",
+                    info.venue_symbol, info.status
+                ),
+            ));
+        }
+        if info.limit_only && !limit {
+            return Err(rejected(
+                "product_limit_only",
+                format!("{} accepts limit orders only", info.venue_symbol),
+            ));
+        }
+        if info.post_only && !post_only {
+            return Err(rejected(
+                "product_post_only",
+                format!("{} accepts post-only orders only", info.venue_symbol),
+            ));
+        }
+        Ok(())
+    }
+
+    /// `qty` rounded down onto `base_increment`, within the size limits.
+    pub(crate) fn base_size(&self, qty: f64) -> Result<String, ExchangeError> {
+        let (text, size) = self
+            .base_increment
+            .quantize(qty, Rounding::Down)
+            .ok_or_else(|| rejected("invalid_qty", format!("invalid qty {qty}")))?;
+        check_size(
+            size,
+            self.info.base_min_size.max(self.info.base_increment),
+            self.info.base_max_size,
+            "base size",
+            &self.info.venue_symbol,
+        )?;
+        Ok(text)
+    }
+
+    /// `amount` rounded down onto `quote_increment`, within the size limits.
+    pub(crate) fn quote_size(&self, amount: f64) -> Result<String, ExchangeError> {
+        let (text, size) = self
+            .quote_increment
+            .quantize(amount, Rounding::Down)
+            .ok_or_else(|| {
+                rejected("invalid_quote_size", format!("invalid quote_size {amount}"))
+            })?;
+        check_size(
+            size,
+            self.info
+                .quote_min_size
+                .unwrap_or(0.0)
+                .max(self.info.quote_increment),
+            self.info.quote_max_size,
+            "quote size",
+            &self.info.venue_symbol,
+        )?;
+        Ok(text)
+    }
+
+    /// A limit price on `price_increment`, rounded so the order never trades
+    /// worse than asked: down for buys, up for sells.
+    pub(crate) fn limit_price(
+        &self,
+        price: f64,
+        side: &OrderSide,
+    ) -> Result<String, ExchangeError> {
+        let rounding = match side {
+            OrderSide::Buy => Rounding::Down,
+            OrderSide::Sell => Rounding::Up,
+        };
+        self.price(price, rounding, "invalid_limit_price", "limit_price")
+    }
+
+    /// A stop trigger on `price_increment`, rounded to the nearest step.
+    pub(crate) fn stop_price(&self, price: f64) -> Result<String, ExchangeError> {
+        self.price(price, Rounding::Nearest, "invalid_stop_price", "stop_price")
+    }
+
+    fn price(
+        &self,
+        price: f64,
+        rounding: Rounding,
+        code: &str,
+        field: &str,
+    ) -> Result<String, ExchangeError> {
+        match self.price_increment.quantize(price, rounding) {
+            Some((text, quantized)) if quantized > 0.0 => Ok(text),
+            _ => Err(rejected(
+                code,
+                format!(
+                    "{field} {price} is below the {} price increment {}",
+                    self.info.venue_symbol, self.info.price_increment
+                ),
+            )),
+        }
+    }
+}
+
+fn check_size(
+    size: f64,
+    min: f64,
+    max: Option<f64>,
+    what: &str,
+    venue_symbol: &str,
+) -> Result<(), ExchangeError> {
+    if size < min {
+        return Err(rejected(
+            "size_below_minimum",
+            format!("{venue_symbol} {what} {size} is below the minimum {min}"),
+        ));
+    }
+    if let Some(max) = max.filter(|max| size > *max) {
+        return Err(rejected(
+            "size_above_maximum",
+            format!("{venue_symbol} {what} {size} is above the maximum {max}"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    pub(crate) fn btc_usd() -> Value {
+        json!({
+            "product_id": "BTC-USD",
+            "base_currency_id": "BTC",
+            "quote_currency_id": "USD",
+            "base_increment": "0.00000001",
+            "base_min_size": "0.00001",
+            "base_max_size": "3400",
+            "quote_increment": "0.01",
+            "quote_min_size": "1",
+            "quote_max_size": "150000000",
+            "price_increment": "0.01",
+            "status": "online",
+            "trading_disabled": false,
+            "cancel_only": false,
+            "limit_only": false,
+            "post_only": false
+        })
+    }
+
+    #[test]
+    fn increments_round_onto_exact_decimal_strings() {
+        let cents = Increment::parse("0.01").expect("increment");
+        assert_eq!(cents.quantize(0.29, Rounding::Down).unwrap().0, "0.29");
+        assert_eq!(cents.quantize(0.1 + 0.2, Rounding::Up).unwrap().0, "0.3");
+        assert_eq!(cents.quantize(101.239, Rounding::Down).unwrap().0, "101.23");
+        assert_eq!(cents.quantize(101.231, Rounding::Up).unwrap().0, "101.24");
+        assert_eq!(cents.quantize(100.0, Rounding::Nearest).unwrap().0, "100");
+
+        let satoshi = Increment::parse("0.00000001").expect("increment");
+        assert_eq!(
+            satoshi.quantize(0.123456789, Rounding::Down).unwrap().0,
+            "0.12345678"
+        );
+        let nickel = Increment::parse("0.05").expect("increment");
+        assert_eq!(nickel.quantize(1.07, Rounding::Down).unwrap().0, "1.05");
+        assert!(Increment::parse("0").is_none());
+        assert!(cents.quantize(f64::NAN, Rounding::Down).is_none());
+    }
+
+    #[test]
+    fn products_validate_sizes_and_trading_status() {
+        let product = Product::parse(&btc_usd()).expect("product");
+        assert_eq!(product.info.base_increment, 0.00000001);
+        assert_eq!(product.base_size(0.5 + 1e-12).unwrap(), "0.5");
+        assert_eq!(
+            product.base_size(0.000001).unwrap_err().code,
+            "size_below_minimum"
+        );
+        assert_eq!(
+            product.base_size(5_000.0).unwrap_err().code,
+            "size_above_maximum"
+        );
+        assert_eq!(
+            product.quote_size(0.5).unwrap_err().code,
+            "size_below_minimum"
+        );
+        assert_eq!(
+            product.limit_price(100.005, &OrderSide::Sell).unwrap(),
+            "100.01"
+        );
+        assert_eq!(
+            product
+                .limit_price(0.001, &OrderSide::Buy)
+                .unwrap_err()
+                .code,
+            "invalid_limit_price"
+        );
+        assert!(product.check_tradable(false, false).is_ok());
+
+        let mut raw = btc_usd();
+        raw["limit_only"] = json!(true);
+        let limit_only = Product::parse(&raw).expect("product");
+        assert_eq!(
+            limit_only.check_tradable(false, false).unwrap_err().code,
+            "product_limit_only"
+        );
+        raw["cancel_only"] = json!(true);
+        let cancel_only = Product::parse(&raw).expect("product");
+        assert_eq!(
+            cancel_only.check_tradable(true, false).unwrap_err().code,
+            "product_unavailable"
+        );
+    }
+}
@@ -0,0 +1,462 @@
+//! Coinbase Advanced Trade `user` WebSocket channel: order status changes of
+//! the authenticated account, with fills derived from each order's growing
+//! cumulative quantity.
+
+use std::collections::HashMap;
+use std::sync::Weak;
+use std::time::Duration;
+
+use exchange_core::{ExchangeError, FillReport, OrderSnapshot, OrderStatus, OrderType};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::market_data::{
+    parse_f64, parse_ts_ms, WsStream, MAX_RECONNECT_BACKOFF, MIN_RECONNECT_BACKOFF,
+};
+use crate::orders::{map_status, parse_order, parse_side, spot_instrument};
+use crate::{CdpKey, CoinbaseAdvancedTradeAdapter};
+
+pub const DEFAULT_USER_WS_URL: &str = "wss://advanced-trade-ws-user.coinbase.com";
+const CHANNELS: [&str; 2] = ["heartbeats", "user"];
+/// Heartbeats arrive every second, so a silent connection is treated as lost.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+const VENUE: &str = "coinbase_at";
+const QTY_EPSILON: f64 = 1e-12;
+
+/// Account activity streamed from the `user` channel.
+#[derive(Debug, Clone)]
+pub enum UserUpdate {
+    Order(OrderSnapshot),
+    /// Quantity an order executed since it was last seen. `venue_fill_id` is
+    /// `{order_id}:{cumulative_qty}`, stable across resyncs.
+    Fill(FillReport),
+    /// The channel dropped; updates resume after reconnecting, and orders
+    /// that changed meanwhile are resynced over REST.
+    Disconnected {
+        reason: String,
+    },
+}
+
+/// An order as the venue reports it, with the fees charged on it so far.
+#[derive(Debug, Clone)]
+pub(crate) struct VenueOrder {
+    pub(crate) order: OrderSnapshot,
+    pub(crate) total_fees: f64,
+}
+
+impl VenueOrder {
+    /// Reads an order from a `user` channel event.
+    pub(crate) fn from_user_event(order: &Value, ts_ms: i64) -> Option<Self> {
+        let venue_order_id = order.get("order_id").and_then(Value::as_str)?;
+        let product_id = order.get("product_id").and_then(Value::as_str)?;
+        let filled_qty = parse_f64(order.get("cumulative_quantity")).unwrap_or(0.0);
+        let leaves_qty = parse_f64(order.get("leaves_quantity")).unwrap_or(0.0);
+        let status = map_status(order.get("status").and_then(Value::as_str)?, filled_qty)?;
+        let limit_price = parse_f64(order.get("limit_price")).filter(|price| *price > 0.0);
+        let order_type = match order.get("order_type").and_then(Value::as_str) {
+            Some(kind) if kind.eq_ignore_ascii_case("market") => OrderType::Market,
+            _ if limit_price.is_none() => OrderType::Market,
+            _ => OrderType::Limit,
+        };
+
+        Some(Self {
+            order: OrderSnapshot {
+                venue: VENUE.to_string(),
+                venue_order_id: venue_order_id.to_string(),
+                client_order_id: order
+                    .get("client_order_id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                strategy_id: String::new(),
+                instrument: spot_instrument(product_id),
+                side: parse_side(order.get("order_side"))?,
+                order_type,
+                status,
+                qty: filled_qty + leaves_qty,
+                filled_qty,
+                limit_price,
+                avg_fill_price: parse_f64(order.get("avg_price"))
+                    .filter(|_| filled_qty > QTY_EPSILON),
+                created_at_ms: parse_ts_ms(order.get("creation_time")).unwrap_or(ts_ms),
+                updated_at_ms: ts_ms,
+                simulated: false,
+            },
+            total_fees: parse_f64(order.get("total_fees")).unwrap_or(0.0),
+        })
+    }
+
+    /// Reads an order from `orders/historical/{order_id}`.
+    pub(crate) fn from_historical(order: &Value) -> Option<Self> {
+        Some(Self {
+            order: parse_order(order)?,
+            total_fees: parse_f64(order.get("total_fees")).unwrap_or(0.0),
+        })
+    }
+}
+
+fn is_terminal(status: &OrderStatus) -> bool {
+    !matches!(status, OrderStatus::New | OrderStatus::PartiallyFilled)
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Progress {
+    filled_qty: f64,
+    notional: f64,
+    fees: f64,
+}
+
+impl Progress {
+    fn of(order: &OrderSnapshot, fees: f64) -> Self {
+        Self {
+            filled_qty: order.filled_qty,
+            notional: order.avg_fill_price.unwrap_or(0.0) * order.filled_qty,
+            fees,
+        }
+    }
+}
+
+/// Execution progress of working orders, so each update yields only the
+/// quantity filled since the previous one.
+#[derive(Debug, Default)]
+pub(crate) struct FillTracker {
+    progress: HashMap<String, Progress>,
+}
+
+impl FillTracker {
+    /// Working orders being tracked.
+    pub(crate) fn order_ids(&self) -> impl Iterator<Item = &String> {
+        self.progress.keys()
+    }
+
+    /// Records `order`'s progress and returns the fill since it was last seen.
+    /// An order seen for the first time starts from `known` (the adapter's
+    /// last view of it); unknown orders start empty unless they arrive in a
+    /// subscription `snapshot`, whose earlier executions are not reported.
+    pub(crate) fn advance(
+        &mut self,
+        order: &OrderSnapshot,
+        total_fees: f64,
+        known: Option<&OrderSnapshot>,
+        snapshot: bool,
+    ) -> Option<FillReport> {
+        let current = Progress::of(order, total_fees);
+        let previous = match (self.progress.get(&order.venue_order_id), known) {
+            (Some(progress), _) => *progress,
+            (None, Some(known)) => Progress::of(known, 0.0),
+            (None, None) if snapshot => current,
+            (None, None) => Progress::default(),
+        };
+        if is_terminal(&order.status) {
+            self.progress.remove(&order.venue_order_id);
+        } else {
+            self.progress.insert(order.venue_order_id.clone(), current);
+        }
+
+        let qty = current.filled_qty - previous.filled_qty;
+        if qty <= QTY_EPSILON {
+            return None;
+        }
+        let price = Some((current.notional - previous.notional) / qty)
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .or(order.avg_fill_price)?;
+        Some(FillReport {
+            venue: VENUE.to_string(),
+            venue_fill_id: format!("{}:{}", order.venue_order_id, order.filled_qty),
+            venue_order_id: order.venue_order_id.clone(),
+            client_order_id: order.client_order_id.clone(),
+            strategy_id: order.strategy_id.clone(),
+            instrument: order.instrument.clone(),
+            side: order.side.clone(),
+            qty,
+            price,
+            fee: (current.fees - previous.fees).max(0.0),
+            fee_asset: order.instrument.quote.clone(),
+            liquidity: None,
+            simulated: false,
+            ts_ms: order.updated_at_ms,
+        })
+    }
+}
+
+/// Opens the user channel, subscribing it and its heartbeats with fresh JWTs.
+pub(crate) async fn connect(url: &str, key: &CdpKey) -> Result<WsStream, ExchangeError> {
+    let (mut ws, _) = connect_async(url).await.map_err(|e| {
+        ExchangeError::new(
+            "user_channel_connect",
+            format!("coinbase_at user channel connect failed: {e}"),
+            true,
+        )
+    })?;
+    for channel in CHANNELS {
+        let subscribe = json!({
+            "type": "subscribe",
+            "channel": channel,
+            "jwt": key.sign(None, chrono::Utc::now().timestamp())?,
+        });
+        ws.send(Message::text(subscribe.to_string()))
+            .await
+            .map_err(|e| {
+                ExchangeError::new(
+                    "user_channel_subscribe",
+                    format!("coinbase_at {channel} subscribe failed: {e}"),
+                    true,
+                )
+            })?;
+    }
+    Ok(ws)
+}
+
+/// Merges `venue` into the adapter's orders and publishes it with any fill.
+async fn apply(
+    adapter: &CoinbaseAdvancedTradeAdapter,
+    tracker: &mut FillTracker,
+    mut venue: VenueOrder,
+    snapshot: bool,
+) {
+    let known = adapter
+        .orders
+        .lock()
+        .await
+        .get(&venue.order.venue_order_id)
+        .cloned();
+    if let Some(known) = &known {
+        // Updates buffered during a resync may predate what it learned.
+        if is_terminal(&known.status) && !is_terminal(&venue.order.status) {
+            return;
+        }
+        // Canceled orders report no leaves quantity; keep the original size.
+        venue.order.qty = venue.order.qty.max(known.qty);
+    }
+    let order = adapter.remember_order(venue.order).await;
+    let fill = tracker.advance(&order, venue.total_fees, known.as_ref(), snapshot);
+    let _ = adapter.user_updates.send(UserUpdate::Order(order));
+    if let Some(fill) = fill {
+        let _ = adapter.user_updates.send(UserUpdate::Fill(fill));
+    }
+}
+
+async fn handle_message(
+    adapter: &CoinbaseAdvancedTradeAdapter,
+    tracker: &mut FillTracker,
+    msg: &Value,
+) {
+    if msg.get("channel").and_then(Value::as_str) != Some("user") {
+        return;
+    }
+    let ts_ms =
+        parse_ts_ms(msg.get("timestamp")).unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    for event in msg
+        .get("events")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let snapshot = event.get("type").and_then(Value::as_str) == Some("snapshot");
+        for order in event
+            .get("orders")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            if let Some(venue) = VenueOrder::from_user_event(order, ts_ms) {
+                apply(adapter, tracker, venue, snapshot).await;
+            }
+        }
+    }
+}
+
+/// Refetches every order that was working before the channel dropped, so
+/// fills and cancels missed while disconnected are still published.
+async fn resync(adapter: &CoinbaseAdvancedTradeAdapter, tracker: &mut FillTracker) {
+    let mut order_ids: Vec<String> = tracker.order_ids().cloned().collect();
+    for order in adapter.orders.lock().await.values() {
+        if !is_terminal(&order.status) && !order_ids.contains(&order.venue_order_id) {
+            order_ids.push(order.venue_order_id.clone());
+        }
+    }
+    for order_id in order_ids {
+        match adapter.fetch_order(&order_id).await {
+            Ok(Some(order)) => {
+                if let Some(venue) = VenueOrder::from_historical(&order) {
+                    apply(adapter, tracker, venue, false).await;
+                }
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(
+                "coinbase_at user channel resync of {order_id} failed: {}",
+                err.message
+            ),
+        }
+    }
+}
+
+/// Pumps `ws` into the adapter's user updates, reconnecting with backoff and
+/// resyncing over REST until `guard` is dropped.
+pub(crate) async fn run_user_channel(
+    adapter: CoinbaseAdvancedTradeAdapter,
+    key: CdpKey,
+    ws: WsStream,
+    guard: Weak<()>,
+) {
+    let mut next = Some(ws);
+    let mut backoff = MIN_RECONNECT_BACKOFF;
+    let mut tracker = FillTracker::default();
+
+    while guard.upgrade().is_some() {
+        let mut ws = match next.take() {
+            Some(ws) => ws,
+            None => match connect(&adapter.user_ws_url, &key).await {
+                Ok(ws) => {
+                    resync(&adapter, &mut tracker).await;
+                    ws
+                }
+                Err(err) => {
+                    let _ = adapter.user_updates.send(UserUpdate::Disconnected {
+                        reason: err.message,
+                    });
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                    continue;
+                }
+            },
+        };
+
+        let reason = loop {
+            if guard.upgrade().is_none() {
+                return;
+            }
+            match tokio::time::timeout(IDLE_TIMEOUT, ws.next()).await {
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    let Ok(msg) = serde_json::from_str::<Value>(&text) else {
+                        continue;
+                    };
+                    backoff = MIN_RECONNECT_BACKOFF;
+                    handle_message(&adapter, &mut tracker, &msg).await;
+                }
+                Ok(Some(Ok(Message::Close(frame)))) => {
+                    break format!("coinbase_at user channel closed: {frame:?}");
+                }
+                Ok(Some(Ok(_))) => {}
+                Ok(Some(Err(e))) => break format!("coinbase_at user channel error: {e}"),
+                Ok(None) => break "coinbase_at user channel stream ended".to_string(),
+                Err(_) => {
+                    break format!(
+                        "coinbase_at user channel silent for {}s",
+                        IDLE_TIMEOUT.as_secs()
+                    )
+                }
+            }
+        };
+
+        let _ = adapter
+            .user_updates
+            .send(UserUpdate::Disconnected { reason });
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_order(status: &str, cumulative: &str, leaves: &str, avg: &str, fees: &str) -> Value {
+        json!({
+            "order_id": "ord-1",
+            "client_order_id": "client-1",
+            "product_id": "BTC-USD",
+            "order_side": "BUY",
+            "order_type": "Limit",
+            "status": status,
+            "cumulative_quantity": cumulative,
+            "leaves_quantity": leaves,
+            "avg_price": avg,
+            "total_fees": fees,
+            "limit_price": "102",
+            "creation_time": "2024-01-01T00:00:00Z"
+        })
+    }
+
+    fn advance(
+        tracker: &mut FillTracker,
+        order: Value,
+        known: Option<&OrderSnapshot>,
+        snapshot: bool,
+    ) -> (OrderSnapshot, Option<FillReport>) {
+        let venue = VenueOrder::from_user_event(&order, 1_000).expect("order parses");
+        let fill = tracker.advance(&venue.order, venue.total_fees, known, snapshot);
+        (venue.order, fill)
+    }
+
+    #[test]
+    fn cumulative_quantities_yield_incremental_fills() {
+        let mut tracker = FillTracker::default();
+        let (order, fill) = advance(
+            &mut tracker,
+            user_order("OPEN", "0", "1", "0", "0"),
+            None,
+            false,
+        );
+        assert_eq!(order.status, OrderStatus::New);
+        assert_eq!((order.qty, order.limit_price), (1.0, Some(102.0)));
+        assert!(fill.is_none());
+
+        let (order, fill) = advance(
+            &mut tracker,
+            user_order("OPEN", "0.4", "0.6", "100", "0.1"),
+            None,
+            false,
+        );
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        let fill = fill.expect("partial fill");
+        assert_eq!((fill.qty, fill.price, fill.fee), (0.4, 100.0, 0.1));
+        assert_eq!(fill.venue_fill_id, "ord-1:0.4");
+
+        let (order, fill) = advance(
+            &mut tracker,
+            user_order("FILLED", "1", "0", "101.2", "0.25"),
+            None,
+            false,
+        );
+        assert_eq!(order.status, OrderStatus::Filled);
+        let fill = fill.expect("final fill");
+        assert!((fill.qty - 0.6).abs() < 1e-9);
+        assert!((fill.price - 102.0).abs() < 1e-9);
+        assert!((fill.fee - 0.15).abs() < 1e-9);
+        assert_eq!(tracker.order_ids().count(), 0);
+
+        // A repeated terminal update reports nothing new.
+        let (_, fill) = advance(
+            &mut tracker,
+            user_order("FILLED", "1", "0", "101.2", "0.25"),
+            Some(&order),
+            false,
+        );
+        assert!(fill.is_none());
+    }
+
+    #[test]
+    fn snapshots_do_not_replay_executions_of_unknown_orders() {
+        let mut tracker = FillTracker::default();
+        let (_, fill) = advance(
+            &mut tracker,
+            user_order("OPEN", "0.5", "0.5", "100", "0.1"),
+            None,
+            true,
+        );
+        assert!(fill.is_none());
+
+        // Orders the adapter placed start from what it last knew.
+        let mut placed = VenueOrder::from_user_event(&user_order("OPEN", "0", "1", "0", "0"), 0)
+            .expect("order parses")
+            .order;
+        placed.venue_order_id = "ord-2".to_string();
+        let mut order = user_order("OPEN", "0.5", "0.5", "100", "0.1");
+        order["order_id"] = json!("ord-2");
+        let (_, fill) = advance(&mut tracker, order, Some(&placed), true);
+        assert_eq!(fill.expect("fill since placement").qty, 0.5);
+    }
+}
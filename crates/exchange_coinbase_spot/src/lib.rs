@@ -5,10 +5,10 @@
 use std::collections::HashMap;
 
 use exchange_core::{
-    AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth,
-    ExchangeResultFuture, ExchangeValueFuture, FillReport, InstrumentRef, InstrumentType,
-    NormalizedOrderRequest, OpenOrderSnapshot, OrderAck, OrderSide, OrderSnapshot, OrderStatus,
-    PositionSnapshot,
+    check_reduce_only, fees_paid, AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError,
+    ExchangeHealth, ExchangeResultFuture, ExchangeValueFuture, FeeSchedule, FillReport,
+    InstrumentRef, InstrumentType, Liquidity, MarketDataSink, NormalizedOrderRequest,
+    OpenOrderSnapshot, OrderAck, OrderSnapshot, OrderStatus, PositionSnapshot,
 };
 use tokio::sync::RwLock;
 use trading_domain::ExecutionMode;
@@ -25,6 +25,8 @@ struct AdapterState {
 pub struct CoinbaseSpotAdapter {
     mode: ExecutionMode,
     state: RwLock<AdapterState>,
+    /// Schedule for simulated fills; `None` uses `FeeSchedule::for_instrument`.
+    fees: Option<FeeSchedule>,
 }
 
 impl CoinbaseSpotAdapter {
@@ -32,9 +34,15 @@ impl CoinbaseSpotAdapter {
         Self {
             mode,
             state: RwLock::new(AdapterState::default()),
+            fees: None,
         }
     }
 
+    pub fn with_fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
     fn now_ms() -> i64 {
         chrono::Utc::now().timestamp_millis()
     }
@@ -58,14 +66,23 @@ impl CoinbaseSpotAdapter {
             ));
         }
 
-        Ok(())
-    }
+        if req.stop_price.is_some() {
+            return Err(ExchangeError::new(
+                "stop_unsupported",
+                "coinbase spot adapter does not simulate stop orders",
+                false,
+            ));
+        }
 
-    fn signed_qty(side: &OrderSide, qty: f64) -> f64 {
-        match side {
-            OrderSide::Buy => qty,
-            OrderSide::Sell => -qty,
+        if req.quote_size.is_some() {
+            return Err(ExchangeError::new(
+                "quote_size_unsupported",
+                "coinbase spot adapter orders are sized by qty",
+                false,
+            ));
         }
+
+        Ok(())
     }
 
     fn fill_price(req: &NormalizedOrderRequest) -> f64 {
@@ -82,7 +99,11 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
         "coinbase_spot"
     }
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()> {
+    fn connect_market_data(
+        &self,
+        _venue_symbols: Vec<String>,
+        _sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
         Box::pin(async { Ok(()) })
     }
 
@@ -126,9 +147,16 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
                 simulated: !self.mode_is_live(),
             };
 
+            let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
             let mut state = self.state.write().await;
+            if req.reduce_only {
+                let held = state
+                    .positions
+                    .get(&key)
+                    .map_or(0.0, |position| position.qty);
+                check_reduce_only(held, &req.side, req.qty)?;
+            }
             if status == OrderStatus::Filled {
-                let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
                 let fill_price = Self::fill_price(&req);
                 let entry = state
                     .positions
@@ -141,10 +169,10 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
                         mark_price: Some(fill_price),
                         unrealized_pnl: Some(0.0),
                     });
-                entry.qty += Self::signed_qty(&req.side, req.qty);
-                entry.avg_price = fill_price;
-                entry.mark_price = Some(fill_price);
+                entry.apply_fill(&req.side, req.qty, fill_price);
 
+                let fee =
+                    FeeSchedule::charge(self.fees.as_ref(), &req, fill_price, &state.fills, now);
                 state.fills.push(FillReport {
                     venue: req.venue.clone(),
                     venue_fill_id: format!("cb-fill-{}", Uuid::new_v4()),
@@ -155,9 +183,9 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
                     side: req.side.clone(),
                     qty: req.qty,
                     price: fill_price,
-                    fee: 0.0,
+                    fee,
                     fee_asset: req.instrument.quote.clone(),
-                    liquidity: Some("taker".to_string()),
+                    liquidity: Some(Liquidity::Taker.as_str().to_string()),
                     simulated: true,
                     ts_ms: now,
                 });
@@ -189,9 +217,14 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
                     false,
                 )
             })?;
-
-            order.status = OrderStatus::Canceled;
-            order.updated_at_ms = Self::now_ms();
+            // Filled and canceled orders stay as they are.
+            if matches!(
+                order.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at_ms = Self::now_ms();
+            }
             Ok(())
         })
     }
@@ -247,7 +280,8 @@ impl ExchangeAdapter for CoinbaseSpotAdapter {
             let (total, available) = if self.mode_is_live() {
                 (0.0, 0.0)
             } else {
-                (50_000.0, 50_000.0)
+                let fees_paid = fees_paid(&self.state.read().await.fills);
+                (50_000.0 - fees_paid, 50_000.0 - fees_paid)
             };
 
             Ok(vec![BalanceSnapshot {
@@ -0,0 +1,252 @@
+//! Conformance suite for `ExchangeAdapter` implementations.
+//!
+//! Every adapter keeps its own books of orders, fills and positions. `run`
+//! drives one adapter through a fixed matrix of scenarios and reports where it
+//! departs from the semantics the daemon relies on:
+//!
+//! - accepted orders are reported by `get_order`, and by `open_orders` while
+//!   they are `New` or `PartiallyFilled`;
+//! - fills are reported by `fills_since` under the placing order's client
+//!   order id and strategy;
+//! - positions are signed and kept at average cost: reducing one keeps its
+//!   basis, and a fill that flips it starts again from the fill price;
+//! - canceling a working order makes it `Canceled` and keeps its fills, while
+//!   canceling a filled order leaves it `Filled`;
+//! - reduce-only orders that would open, grow or flip a position are refused
+//!   with `reduce_only_violation`;
+//! - non-positive quantities are refused with `invalid_quantity`, unknown
+//!   orders with `order_not_found`, instruments the venue does not trade with
+//!   `unsupported_instrument`, and orders sent while the venue is down with a
+//!   retriable error.
+//!
+//! Adapters are wired in through `ConformanceVenue`. Its `Capabilities` decide
+//! which scenarios apply; the rest are reported as skipped.
+
+use std::fmt;
+use std::future::Future;
+
+use exchange_core::{
+    ExchangeAdapter, ExchangeError, ExchangeResultFuture, InstrumentRef, PositionSnapshot,
+};
+
+mod scenarios;
+
+#[cfg(test)]
+mod venues;
+
+/// How orders placed on a venue get filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fills {
+    /// Every accepted order fills in full as it arrives; nothing rests.
+    OnArrival,
+    /// Orders rest until `ConformanceVenue::fill` trades them, so partial
+    /// fills and cancels of working orders can be exercised.
+    Driven,
+    /// Orders are accepted and rest, but nothing ever fills them.
+    Never,
+}
+
+/// How a venue treats `reduce_only`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReduceOnly {
+    /// Checked against the held position (`reduce_only_violation`).
+    Checked,
+    /// Spot venues: sells cannot open a position, so reduce-only sells are
+    /// sent as they are and reduce-only buys are refused with
+    /// `reduce_only_unsupported`.
+    SellsOnly,
+}
+
+/// What a venue supports, which decides the scenarios that apply to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub fills: Fills,
+    /// Selling more than the position opens a short (spot venues cannot).
+    pub short_positions: bool,
+    /// Positions report their average cost. Venues that only report holdings
+    /// are checked on quantity alone.
+    pub cost_basis: bool,
+    pub reduce_only: ReduceOnly,
+    /// `ConformanceVenue::set_outage` can take the venue down.
+    pub outages: bool,
+}
+
+/// An adapter under test and the hooks the scenarios need to drive its venue.
+/// `run` builds a fresh one for every scenario.
+pub trait ConformanceVenue: Send + Sync {
+    fn adapter(&self) -> &dyn ExchangeAdapter;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Instrument the scenarios trade; the venue starts flat in it.
+    fn instrument(&self) -> InstrumentRef;
+
+    /// Two different limit prices for `instrument`, the first for buys and
+    /// the second for sells, that `Fills::Driven` venues do not trade on
+    /// arrival.
+    fn prices(&self) -> [f64; 2];
+
+    /// An instrument the venue refuses with `unsupported_instrument`.
+    fn unsupported_instrument(&self) -> Option<InstrumentRef> {
+        None
+    }
+
+    /// Trades `qty` of the working order `venue_order_id` at its limit price,
+    /// as a counterparty would. Only called on `Fills::Driven` venues.
+    fn fill(&self, _venue_order_id: &str, _qty: f64) -> ExchangeResultFuture<'_, ()> {
+        Box::pin(async {
+            Err(ExchangeError::new(
+                "unsupported",
+                "venue cannot be made to fill orders",
+                false,
+            ))
+        })
+    }
+
+    /// Takes the venue down, or brings it back. Only called on venues with
+    /// `outages`.
+    fn set_outage(&self, _down: bool) -> ExchangeResultFuture<'_, ()> {
+        Box::pin(async {
+            Err(ExchangeError::new(
+                "unsupported",
+                "venue cannot be taken down",
+                false,
+            ))
+        })
+    }
+
+    /// Signed quantity and average price `position` holds of `instrument`, or
+    /// `None` for another instrument's position. Venues that report a short
+    /// through a different instrument (held NO contracts for a short YES
+    /// position) convert it here.
+    fn net_position(&self, position: &PositionSnapshot) -> Option<(f64, f64)> {
+        (position.instrument.venue_symbol == self.instrument().venue_symbol)
+            .then_some((position.qty, position.avg_price))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// An order fills in full and shows up in orders, fills and positions.
+    PlaceAndFill,
+    /// A working order is listed as open until it is canceled.
+    CancelWorking,
+    /// Canceling a filled order does not change it.
+    CancelFilled,
+    /// A partially filled order reports its progress and keeps it on cancel.
+    PartialFill,
+    /// Selling part of a long position keeps its basis, the rest flattens it.
+    Reduce,
+    /// A sell larger than a long position flips it short at the fill price.
+    SideFlip,
+    ReduceOnly,
+    ErrorMapping,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 8] = [
+        Scenario::PlaceAndFill,
+        Scenario::CancelWorking,
+        Scenario::CancelFilled,
+        Scenario::PartialFill,
+        Scenario::Reduce,
+        Scenario::SideFlip,
+        Scenario::ReduceOnly,
+        Scenario::ErrorMapping,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scenario::PlaceAndFill => "place_and_fill",
+            Scenario::CancelWorking => "cancel_working",
+            Scenario::CancelFilled => "cancel_filled",
+            Scenario::PartialFill => "partial_fill",
+            Scenario::Reduce => "reduce",
+            Scenario::SideFlip => "side_flip",
+            Scenario::ReduceOnly => "reduce_only",
+            Scenario::ErrorMapping => "error_mapping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    /// The scenario does not apply to the venue's capabilities.
+    Skipped(&'static str),
+    Failed(String),
+}
+
+/// Outcome of every scenario for one venue.
+#[derive(Debug, Clone)]
+pub struct ConformanceReport {
+    pub venue: String,
+    pub outcomes: Vec<(Scenario, Outcome)>,
+}
+
+impl ConformanceReport {
+    pub fn failures(&self) -> Vec<(Scenario, &str)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(scenario, outcome)| match outcome {
+                Outcome::Failed(reason) => Some((*scenario, reason.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn outcome(&self, scenario: Scenario) -> Option<&Outcome> {
+        self.outcomes
+            .iter()
+            .find(|(known, _)| *known == scenario)
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Panics with the whole report when any scenario failed.
+    pub fn assert_conforms(&self) {
+        assert!(self.failures().is_empty(), "{self}");
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} conformance:", self.venue)?;
+        for (scenario, outcome) in &self.outcomes {
+            match outcome {
+                Outcome::Passed => writeln!(f, "  {}: passed", scenario.name())?,
+                Outcome::Skipped(reason) => {
+                    writeln!(f, "  {}: skipped ({reason})", scenario.name())?
+                }
+                Outcome::Failed(reason) => writeln!(f, "  {}: FAILED: {reason}", scenario.name())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs every scenario against a fresh venue from `new_venue`.
+pub async fn run<V, F, Fut>(mut new_venue: F) -> ConformanceReport
+where
+    V: ConformanceVenue,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = V>,
+{
+    let mut venue_name = String::new();
+    let mut outcomes = Vec::with_capacity(Scenario::ALL.len());
+    for scenario in Scenario::ALL {
+        let venue = new_venue().await;
+        venue_name = venue.adapter().venue().to_string();
+        let outcome = match scenarios::skip_reason(scenario, &venue.capabilities()) {
+            Some(reason) => Outcome::Skipped(reason),
+            None => match scenarios::run(scenario, &venue).await {
+                Ok(()) => Outcome::Passed,
+                Err(reason) => Outcome::Failed(reason),
+            },
+        };
+        outcomes.push((scenario, outcome));
+    }
+    ConformanceReport {
+        venue: venue_name,
+        outcomes,
+    }
+}
@@ -0,0 +1,462 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use exchange_core::{
+    ExchangeError, NormalizedOrderRequest, OrderAck, OrderSide, OrderSnapshot, OrderStatus,
+    OrderType, TimeInForce,
+};
+
+use crate::{Capabilities, ConformanceVenue, Fills, ReduceOnly, Scenario};
+
+const STRATEGY_ID: &str = "conformance";
+const QTY_EPSILON: f64 = 1e-9;
+const PRICE_EPSILON: f64 = 1e-6;
+
+static NEXT_CLIENT_ORDER: AtomicU64 = AtomicU64::new(1);
+
+type Check = Result<(), String>;
+
+pub(crate) fn skip_reason(scenario: Scenario, caps: &Capabilities) -> Option<&'static str> {
+    match scenario {
+        Scenario::CancelWorking if caps.fills == Fills::OnArrival => Some("orders fill on arrival"),
+        Scenario::PlaceAndFill
+        | Scenario::CancelFilled
+        | Scenario::Reduce
+        | Scenario::SideFlip
+        | Scenario::ReduceOnly
+            if caps.fills == Fills::Never =>
+        {
+            Some("orders never fill")
+        }
+        Scenario::PartialFill if caps.fills != Fills::Driven => {
+            Some("venue cannot be made to fill part of an order")
+        }
+        Scenario::SideFlip if !caps.short_positions => Some("venue cannot hold short positions"),
+        _ => None,
+    }
+}
+
+pub(crate) async fn run(scenario: Scenario, venue: &dyn ConformanceVenue) -> Check {
+    match scenario {
+        Scenario::PlaceAndFill => place_and_fill(venue).await,
+        Scenario::CancelWorking => cancel_working(venue).await,
+        Scenario::CancelFilled => cancel_filled(venue).await,
+        Scenario::PartialFill => partial_fill(venue).await,
+        Scenario::Reduce => reduce(venue).await,
+        Scenario::SideFlip => side_flip(venue).await,
+        Scenario::ReduceOnly => reduce_only(venue).await,
+        Scenario::ErrorMapping => error_mapping(venue).await,
+    }
+}
+
+async fn place_and_fill(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, _] = venue.prices();
+    let req = order(venue, OrderSide::Buy, 2.0, buy, false);
+    let ack = place(venue, req.clone()).await?;
+    fill(venue, &ack, 2.0).await?;
+
+    let order = expect_order(venue, &ack.venue_order_id, OrderStatus::Filled, 2.0).await?;
+    ensure(
+        order.client_order_id == req.client_order_id,
+        format!(
+            "order reports client order id {}, placed as {}",
+            order.client_order_id, req.client_order_id
+        ),
+    )?;
+    ensure(
+        order.strategy_id == STRATEGY_ID,
+        format!("order reports strategy {:?}", order.strategy_id),
+    )?;
+    ensure(
+        order.avg_fill_price.is_some_and(|price| close(price, buy)),
+        format!("order filled at {:?}, expected {buy}", order.avg_fill_price),
+    )?;
+    expect_not_open(venue, &ack.venue_order_id).await?;
+
+    let fills = call("fills_since", venue.adapter().fills_since(0, 1_000).await)?;
+    let fills: Vec<_> = fills
+        .into_iter()
+        .filter(|fill| fill.venue_order_id == ack.venue_order_id)
+        .collect();
+    let filled: f64 = fills.iter().map(|fill| fill.qty).sum();
+    ensure(
+        (filled - 2.0).abs() <= QTY_EPSILON,
+        format!("fills for the order add up to {filled}, expected 2"),
+    )?;
+    for fill in &fills {
+        ensure(
+            fill.client_order_id == req.client_order_id && fill.strategy_id == STRATEGY_ID,
+            format!(
+                "fill {} reports client order id {} and strategy {:?}",
+                fill.venue_fill_id, fill.client_order_id, fill.strategy_id
+            ),
+        )?;
+        ensure(
+            fill.side == OrderSide::Buy && close(fill.price, buy),
+            format!(
+                "fill {} is a {:?} at {}, expected a Buy at {buy}",
+                fill.venue_fill_id, fill.side, fill.price
+            ),
+        )?;
+        ensure(
+            fill.fee >= 0.0,
+            format!("fill {} has fee {}", fill.venue_fill_id, fill.fee),
+        )?;
+    }
+
+    expect_position(venue, 2.0, buy).await
+}
+
+async fn cancel_working(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, _] = venue.prices();
+    let ack = place(venue, order(venue, OrderSide::Buy, 2.0, buy, false)).await?;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::New, 0.0).await?;
+    expect_open(venue, &ack.venue_order_id).await?;
+
+    call(
+        "cancel_order",
+        venue.adapter().cancel_order(&ack.venue_order_id).await,
+    )?;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::Canceled, 0.0).await?;
+    expect_not_open(venue, &ack.venue_order_id).await?;
+    expect_position(venue, 0.0, 0.0).await
+}
+
+async fn cancel_filled(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, _] = venue.prices();
+    let ack = place(venue, order(venue, OrderSide::Buy, 2.0, buy, false)).await?;
+    fill(venue, &ack, 2.0).await?;
+
+    // Venues may refuse the cancel or accept it as a no-op; either way the
+    // order and the position must not change.
+    let _ = venue.adapter().cancel_order(&ack.venue_order_id).await;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::Filled, 2.0).await?;
+    expect_not_open(venue, &ack.venue_order_id).await?;
+    expect_position(venue, 2.0, buy).await
+}
+
+async fn partial_fill(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, _] = venue.prices();
+    let ack = place(venue, order(venue, OrderSide::Buy, 4.0, buy, false)).await?;
+    fill(venue, &ack, 1.0).await?;
+
+    expect_order(
+        venue,
+        &ack.venue_order_id,
+        OrderStatus::PartiallyFilled,
+        1.0,
+    )
+    .await?;
+    expect_open(venue, &ack.venue_order_id).await?;
+    expect_position(venue, 1.0, buy).await?;
+
+    call(
+        "cancel_order",
+        venue.adapter().cancel_order(&ack.venue_order_id).await,
+    )?;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::Canceled, 1.0).await?;
+    expect_not_open(venue, &ack.venue_order_id).await?;
+    expect_position(venue, 1.0, buy).await
+}
+
+async fn reduce(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, sell] = venue.prices();
+    place_filled(venue, OrderSide::Buy, 2.0, buy).await?;
+    place_filled(venue, OrderSide::Sell, 1.0, sell).await?;
+    expect_position(venue, 1.0, buy).await?;
+
+    place_filled(venue, OrderSide::Sell, 1.0, sell).await?;
+    expect_position(venue, 0.0, 0.0).await
+}
+
+async fn side_flip(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, sell] = venue.prices();
+    place_filled(venue, OrderSide::Buy, 2.0, buy).await?;
+    place_filled(venue, OrderSide::Sell, 3.0, sell).await?;
+    expect_position(venue, -1.0, sell).await?;
+
+    place_filled(venue, OrderSide::Buy, 1.0, buy).await?;
+    expect_position(venue, 0.0, 0.0).await
+}
+
+async fn reduce_only(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, sell] = venue.prices();
+    match venue.capabilities().reduce_only {
+        ReduceOnly::Checked => {
+            expect_error(
+                "reduce-only sell while flat",
+                venue
+                    .adapter()
+                    .place_order(order(venue, OrderSide::Sell, 1.0, sell, true))
+                    .await,
+                "reduce_only_violation",
+            )?;
+
+            place_filled(venue, OrderSide::Buy, 2.0, buy).await?;
+            expect_error(
+                "reduce-only buy while long",
+                venue
+                    .adapter()
+                    .place_order(order(venue, OrderSide::Buy, 1.0, buy, true))
+                    .await,
+                "reduce_only_violation",
+            )?;
+            expect_error(
+                "reduce-only sell larger than the position",
+                venue
+                    .adapter()
+                    .place_order(order(venue, OrderSide::Sell, 3.0, sell, true))
+                    .await,
+                "reduce_only_violation",
+            )?;
+        }
+        ReduceOnly::SellsOnly => {
+            place_filled(venue, OrderSide::Buy, 2.0, buy).await?;
+            expect_error(
+                "reduce-only buy",
+                venue
+                    .adapter()
+                    .place_order(order(venue, OrderSide::Buy, 1.0, buy, true))
+                    .await,
+                "reduce_only_unsupported",
+            )?;
+        }
+    }
+
+    let ack = place(venue, order(venue, OrderSide::Sell, 2.0, sell, true)).await?;
+    fill(venue, &ack, 2.0).await?;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::Filled, 2.0).await?;
+    expect_position(venue, 0.0, 0.0).await?;
+    expect_no_open_orders(venue).await
+}
+
+async fn error_mapping(venue: &dyn ConformanceVenue) -> Check {
+    let [buy, _] = venue.prices();
+    let err = expect_error(
+        "zero quantity",
+        venue
+            .adapter()
+            .place_order(order(venue, OrderSide::Buy, 0.0, buy, false))
+            .await,
+        "invalid_quantity",
+    )?;
+    ensure(
+        !err.retriable,
+        "invalid_quantity is reported as retriable".to_string(),
+    )?;
+
+    if let Some(instrument) = venue.unsupported_instrument() {
+        let mut req = order(venue, OrderSide::Buy, 1.0, buy, false);
+        req.symbol = instrument.venue_symbol.clone();
+        req.instrument = instrument;
+        expect_error(
+            "unsupported instrument",
+            venue.adapter().place_order(req).await,
+            "unsupported_instrument",
+        )?;
+    }
+
+    expect_error(
+        "cancel of an unknown order",
+        venue.adapter().cancel_order("conformance-unknown").await,
+        "order_not_found",
+    )?;
+    let unknown = call(
+        "get_order",
+        venue.adapter().get_order("conformance-unknown").await,
+    )?;
+    ensure(
+        unknown.is_none(),
+        "get_order reports an order for an unknown id".to_string(),
+    )?;
+    expect_no_open_orders(venue).await?;
+
+    if venue.capabilities().outages {
+        call("set_outage", venue.set_outage(true).await)?;
+        let result = venue
+            .adapter()
+            .place_order(order(venue, OrderSide::Buy, 1.0, buy, false))
+            .await;
+        call("set_outage", venue.set_outage(false).await)?;
+        match result {
+            Err(err) if err.retriable => {}
+            Err(err) => {
+                return Err(format!(
+                    "order sent during an outage failed with non-retriable {}: {}",
+                    err.code, err.message
+                ))
+            }
+            Ok(ack) => {
+                return Err(format!(
+                    "order sent during an outage was acknowledged as {}",
+                    ack.venue_order_id
+                ))
+            }
+        }
+        place(venue, order(venue, OrderSide::Buy, 1.0, buy, false)).await?;
+    }
+    Ok(())
+}
+
+fn order(
+    venue: &dyn ConformanceVenue,
+    side: OrderSide,
+    qty: f64,
+    limit_price: f64,
+    reduce_only: bool,
+) -> NormalizedOrderRequest {
+    let instrument = venue.instrument();
+    let seq = NEXT_CLIENT_ORDER.fetch_add(1, Ordering::Relaxed);
+    NormalizedOrderRequest {
+        venue: venue.adapter().venue().to_string(),
+        symbol: instrument.venue_symbol.clone(),
+        instrument,
+        strategy_id: STRATEGY_ID.to_string(),
+        client_order_id: format!("conformance-{seq}"),
+        intent_id: None,
+        side,
+        order_type: OrderType::Limit,
+        qty,
+        quote_size: None,
+        limit_price: Some(limit_price),
+        stop_price: None,
+        tif: Some(TimeInForce::Gtc),
+        post_only: false,
+        reduce_only,
+        requested_notional_cents: (qty * limit_price * 100.0).round() as i64,
+    }
+}
+
+async fn place(
+    venue: &dyn ConformanceVenue,
+    req: NormalizedOrderRequest,
+) -> Result<OrderAck, String> {
+    let ack = call("place_order", venue.adapter().place_order(req).await)?;
+    ensure(
+        ack.accepted,
+        format!("order was not accepted: {:?}", ack.reason),
+    )?;
+    Ok(ack)
+}
+
+/// Fills `qty` of a placed order on venues whose fills are driven.
+async fn fill(venue: &dyn ConformanceVenue, ack: &OrderAck, qty: f64) -> Check {
+    if venue.capabilities().fills == Fills::Driven {
+        call("fill", venue.fill(&ack.venue_order_id, qty).await)?;
+    }
+    Ok(())
+}
+
+async fn place_filled(
+    venue: &dyn ConformanceVenue,
+    side: OrderSide,
+    qty: f64,
+    price: f64,
+) -> Check {
+    let ack = place(venue, order(venue, side, qty, price, false)).await?;
+    fill(venue, &ack, qty).await?;
+    expect_order(venue, &ack.venue_order_id, OrderStatus::Filled, qty).await?;
+    Ok(())
+}
+
+async fn expect_order(
+    venue: &dyn ConformanceVenue,
+    venue_order_id: &str,
+    status: OrderStatus,
+    filled_qty: f64,
+) -> Result<OrderSnapshot, String> {
+    let order = call("get_order", venue.adapter().get_order(venue_order_id).await)?
+        .ok_or_else(|| format!("get_order does not know order {venue_order_id}"))?;
+    ensure(
+        order.status == status && (order.filled_qty - filled_qty).abs() <= QTY_EPSILON,
+        format!(
+            "order {venue_order_id} is {:?} with {} filled, expected {status:?} with {filled_qty}",
+            order.status, order.filled_qty
+        ),
+    )?;
+    Ok(order)
+}
+
+async fn open_order_ids(venue: &dyn ConformanceVenue) -> Result<Vec<String>, String> {
+    let open = call("open_orders", venue.adapter().open_orders().await)?;
+    Ok(open
+        .into_iter()
+        .map(|open| open.order.venue_order_id)
+        .collect())
+}
+
+async fn expect_open(venue: &dyn ConformanceVenue, venue_order_id: &str) -> Check {
+    let open = open_order_ids(venue).await?;
+    ensure(
+        open.iter().any(|id| id == venue_order_id),
+        format!("open_orders does not list working order {venue_order_id}"),
+    )
+}
+
+async fn expect_not_open(venue: &dyn ConformanceVenue, venue_order_id: &str) -> Check {
+    let open = open_order_ids(venue).await?;
+    ensure(
+        !open.iter().any(|id| id == venue_order_id),
+        format!("open_orders still lists order {venue_order_id}"),
+    )
+}
+
+async fn expect_no_open_orders(venue: &dyn ConformanceVenue) -> Check {
+    let open = open_order_ids(venue).await?;
+    ensure(
+        open.is_empty(),
+        format!("open_orders lists {open:?}, expected none"),
+    )
+}
+
+/// Checks the net position in the venue's instrument. The average price is
+/// only checked on open positions of venues that report a cost basis.
+async fn expect_position(venue: &dyn ConformanceVenue, qty: f64, avg_price: f64) -> Check {
+    let positions = call("sync_positions", venue.adapter().sync_positions().await)?;
+    let (held, held_avg) = positions
+        .iter()
+        .filter_map(|position| venue.net_position(position))
+        .find(|(held, _)| held.abs() > QTY_EPSILON)
+        .unwrap_or((0.0, 0.0));
+    ensure(
+        (held - qty).abs() <= QTY_EPSILON,
+        format!("position is {held}, expected {qty}"),
+    )?;
+    if qty != 0.0 && venue.capabilities().cost_basis {
+        ensure(
+            close(held_avg, avg_price),
+            format!("position of {held} has average price {held_avg}, expected {avg_price}"),
+        )?;
+    }
+    Ok(())
+}
+
+fn expect_error<T>(
+    what: &str,
+    result: Result<T, ExchangeError>,
+    code: &str,
+) -> Result<ExchangeError, String> {
+    match result {
+        Err(err) if err.code == code => Ok(err),
+        Err(err) => Err(format!(
+            "{what} failed with {}: {}, expected {code}",
+            err.code, err.message
+        )),
+        Ok(_) => Err(format!("{what} succeeded, expected {code}")),
+    }
+}
+
+fn call<T>(what: &str, result: Result<T, ExchangeError>) -> Result<T, String> {
+    result.map_err(|err| format!("{what} failed with {}: {}", err.code, err.message))
+}
+
+fn ensure(condition: bool, failure: String) -> Check {
+    if condition {
+        Ok(())
+    } else {
+        Err(failure)
+    }
+}
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() <= PRICE_EPSILON * expected.abs().max(1.0)
+}
@@ -0,0 +1,307 @@
+//! `CoinbaseAdvancedTradeAdapter` against a fake Advanced Trade API that
+//! rests limit orders until the scenarios fill them.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use coinbase_at_adapter::{CoinbaseAdvancedTradeAdapter, Credentials, HttpPolicy, RateLimit};
+use exchange_core::{
+    ExchangeAdapter, ExchangeError, ExchangeResultFuture, InstrumentRef, InstrumentType,
+};
+use serde_json::{json, Value};
+
+use super::{crypto, not_found, serve, unavailable, Request};
+use crate::{run, Capabilities, ConformanceVenue, Fills, ReduceOnly};
+
+struct FakeOrder {
+    id: String,
+    client_order_id: String,
+    product_id: String,
+    side: String,
+    size: f64,
+    limit_price: f64,
+    filled: f64,
+    canceled: bool,
+    created_time: String,
+}
+
+impl FakeOrder {
+    fn status(&self) -> &'static str {
+        if self.filled >= self.size {
+            "FILLED"
+        } else if self.canceled {
+            "CANCELLED"
+        } else {
+            "OPEN"
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "order_id": self.id,
+            "client_order_id": self.client_order_id,
+            "product_id": self.product_id,
+            "side": self.side,
+            "status": self.status(),
+            "order_type": "LIMIT",
+            "filled_size": self.filled.to_string(),
+            "average_filled_price": self.limit_price.to_string(),
+            "created_time": self.created_time,
+            "order_configuration": {
+                "limit_limit_gtc": {
+                    "base_size": self.size.to_string(),
+                    "limit_price": self.limit_price.to_string(),
+                    "post_only": false
+                }
+            }
+        })
+    }
+}
+
+#[derive(Default)]
+struct Exchange {
+    down: bool,
+    orders: Vec<FakeOrder>,
+    fills: Vec<Value>,
+    btc: f64,
+    usd: f64,
+}
+
+impl Exchange {
+    fn handle(&mut self, request: Request) -> (u16, Value) {
+        if self.down {
+            return unavailable();
+        }
+        match request.route() {
+            ("GET", "/api/v3/brokerage/products") => (200, json!({ "products": [btc_usd()] })),
+            ("POST", "/api/v3/brokerage/orders") => self.place(&request.body),
+            ("POST", "/api/v3/brokerage/orders/batch_cancel") => {
+                let results: Vec<Value> = request.body["order_ids"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(|id| self.cancel(id))
+                    .collect();
+                (200, json!({ "results": results }))
+            }
+            ("GET", "/api/v3/brokerage/orders/historical/batch") => {
+                let open: Vec<Value> = self
+                    .orders
+                    .iter()
+                    .filter(|order| order.status() == "OPEN")
+                    .map(FakeOrder::to_json)
+                    .collect();
+                (
+                    200,
+                    json!({ "orders": open, "has_next": false, "cursor": "" }),
+                )
+            }
+            ("GET", "/api/v3/brokerage/orders/historical/fills") => {
+                (200, json!({ "fills": self.fills, "cursor": "" }))
+            }
+            ("GET", "/api/v3/brokerage/accounts") => {
+                let account = |currency: &str, value: f64| {
+                    json!({
+                        "currency": currency,
+                        "available_balance": { "value": value.to_string() },
+                        "hold": { "value": "0" }
+                    })
+                };
+                (
+                    200,
+                    json!({
+                        "accounts": [account("BTC", self.btc), account("USD", self.usd)],
+                        "has_next": false,
+                        "cursor": ""
+                    }),
+                )
+            }
+            ("GET", "/api/v3/brokerage/best_bid_ask") => (
+                200,
+                json!({ "pricebooks": [{
+                    "product_id": "BTC-USD",
+                    "bids": [{ "price": "90", "size": "1" }],
+                    "asks": [{ "price": "110", "size": "1" }]
+                }] }),
+            ),
+            ("GET", path) => match path.strip_prefix("/api/v3/brokerage/orders/historical/") {
+                Some(id) => match self.orders.iter().find(|order| order.id == id) {
+                    Some(order) => (200, json!({ "order": order.to_json() })),
+                    None => not_found(),
+                },
+                None => not_found(),
+            },
+            _ => not_found(),
+        }
+    }
+
+    fn place(&mut self, body: &Value) -> (u16, Value) {
+        let terms = &body["order_configuration"]["limit_limit_gtc"];
+        let parse = |value: &Value| value.as_str().and_then(|raw| raw.parse::<f64>().ok());
+        let (Some(size), Some(limit_price)) =
+            (parse(&terms["base_size"]), parse(&terms["limit_price"]))
+        else {
+            return (400, json!({ "error": "INVALID_ARGUMENT" }));
+        };
+        let id = format!("cb-{}", self.orders.len() + 1);
+        self.orders.push(FakeOrder {
+            id: id.clone(),
+            client_order_id: body["client_order_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            product_id: body["product_id"].as_str().unwrap_or_default().to_string(),
+            side: body["side"].as_str().unwrap_or_default().to_string(),
+            size,
+            limit_price,
+            filled: 0.0,
+            canceled: false,
+            created_time: now_rfc3339(),
+        });
+        (
+            200,
+            json!({ "success": true, "success_response": { "order_id": id } }),
+        )
+    }
+
+    fn cancel(&mut self, id: &str) -> Value {
+        let failure = match self.orders.iter_mut().find(|order| order.id == id) {
+            None => Some("UNKNOWN_CANCEL_ORDER"),
+            Some(order) if order.status() != "OPEN" => Some("INVALID_CANCEL_REQUEST"),
+            Some(order) => {
+                order.canceled = true;
+                None
+            }
+        };
+        json!({
+            "success": failure.is_none(),
+            "failure_reason": failure.unwrap_or("UNKNOWN_CANCEL_FAILURE_REASON"),
+            "order_id": id
+        })
+    }
+
+    fn fill(&mut self, id: &str, qty: f64) -> Result<(), ExchangeError> {
+        let order = self
+            .orders
+            .iter_mut()
+            .find(|order| order.id == id && order.status() == "OPEN")
+            .ok_or_else(|| ExchangeError::new("order_not_found", id.to_string(), false))?;
+        order.filled += qty;
+        let signed = if order.side == "BUY" { qty } else { -qty };
+        self.btc += signed;
+        self.usd -= signed * order.limit_price;
+        self.fills.push(json!({
+            "entry_id": format!("{}-fill-{}", order.id, self.fills.len() + 1),
+            "order_id": order.id,
+            "product_id": order.product_id,
+            "trade_time": now_rfc3339(),
+            "price": order.limit_price.to_string(),
+            "size": qty.to_string(),
+            "commission": (qty * order.limit_price * 0.004).to_string(),
+            "side": order.side,
+            "liquidity_indicator": "MAKER"
+        }));
+        Ok(())
+    }
+}
+
+fn btc_usd() -> Value {
+    json!({
+        "product_id": "BTC-USD",
+        "base_currency_id": "BTC",
+        "quote_currency_id": "USD",
+        "base_increment": "0.00000001",
+        "base_min_size": "0.00001",
+        "base_max_size": "3400",
+        "quote_increment": "0.01",
+        "quote_min_size": "1",
+        "quote_max_size": "150000000",
+        "price_increment": "0.01",
+        "status": "online",
+        "trading_disabled": false,
+        "cancel_only": false,
+        "limit_only": false,
+        "post_only": false
+    })
+}
+
+fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+struct CoinbaseVenue {
+    adapter: CoinbaseAdvancedTradeAdapter,
+    exchange: Arc<Mutex<Exchange>>,
+}
+
+impl CoinbaseVenue {
+    async fn new() -> Self {
+        let exchange = Arc::new(Mutex::new(Exchange {
+            usd: 100_000.0,
+            ..Exchange::default()
+        }));
+        let served = Arc::clone(&exchange);
+        let addr = serve(move |request| served.lock().unwrap().handle(request)).await;
+        let adapter = CoinbaseAdvancedTradeAdapter::new(
+            format!("http://{addr}"),
+            "ws://unused",
+            Credentials::Bearer("test-token".to_string()),
+        )
+        .expect("adapter")
+        .with_http_policy(HttpPolicy {
+            request_timeout: Duration::from_secs(2),
+            max_attempts: 2,
+            base_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            max_retry_after: Duration::ZERO,
+            public_limit: RateLimit::new(1_000, 1_000.0),
+            read_limit: RateLimit::new(1_000, 1_000.0),
+            order_limit: RateLimit::new(1_000, 1_000.0),
+        });
+        Self { adapter, exchange }
+    }
+}
+
+impl ConformanceVenue for CoinbaseVenue {
+    fn adapter(&self) -> &dyn ExchangeAdapter {
+        &self.adapter
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fills: Fills::Driven,
+            short_positions: false,
+            cost_basis: false,
+            reduce_only: ReduceOnly::SellsOnly,
+            outages: true,
+        }
+    }
+
+    fn instrument(&self) -> InstrumentRef {
+        crypto("coinbase_at", "BTC-USD", InstrumentType::Spot)
+    }
+
+    fn prices(&self) -> [f64; 2] {
+        [100.0, 105.0]
+    }
+
+    fn unsupported_instrument(&self) -> Option<InstrumentRef> {
+        Some(crypto("coinbase_at", "BTC-PERP", InstrumentType::Perpetual))
+    }
+
+    fn fill(&self, venue_order_id: &str, qty: f64) -> ExchangeResultFuture<'_, ()> {
+        let filled = self.exchange.lock().unwrap().fill(venue_order_id, qty);
+        Box::pin(async move { filled })
+    }
+
+    fn set_outage(&self, down: bool) -> ExchangeResultFuture<'_, ()> {
+        self.exchange.lock().unwrap().down = down;
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn coinbase_advanced_trade_adapter_conforms() {
+    run(CoinbaseVenue::new).await.assert_conforms();
+}
@@ -0,0 +1,120 @@
+//! Adapters wired into the conformance suite, with the stateful fake venues
+//! the REST adapters trade against.
+
+mod coinbase;
+mod kalshi;
+mod paper;
+mod simulators;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use exchange_core::{AssetClass, InstrumentRef, InstrumentType};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// A request to a fake venue. Query values are kept raw, which is enough for
+/// the ids and numbers the adapters send.
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub body: Value,
+}
+
+impl Request {
+    pub fn route(&self) -> (&str, &str) {
+        (self.method.as_str(), self.path.as_str())
+    }
+}
+
+/// Minimal HTTP server answering every request with `handler`'s status and
+/// JSON body.
+pub(crate) async fn serve<H>(handler: H) -> SocketAddr
+where
+    H: Fn(Request) -> (u16, Value) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = Arc::clone(&handler);
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                loop {
+                    let mut request_line = String::new();
+                    if stream.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let target = parts.next().unwrap_or_default().to_string();
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::new();
+                        stream.read_line(&mut header).await.expect("header");
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    stream.read_exact(&mut body).await.expect("body");
+
+                    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                    let request = Request {
+                        method,
+                        path: path.to_string(),
+                        query: query
+                            .split('&')
+                            .filter_map(|pair| pair.split_once('='))
+                            .map(|(name, value)| (name.to_string(), value.to_string()))
+                            .collect(),
+                        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+                    };
+                    let (status, body) = handler(request);
+                    let body = body.to_string();
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                        body.len()
+                    );
+                    if stream.write_all(response.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    addr
+}
+
+pub(crate) fn not_found() -> (u16, Value) {
+    (404, json!({"error": "not found"}))
+}
+
+pub(crate) fn unavailable() -> (u16, Value) {
+    (503, json!({"error": "service unavailable"}))
+}
+
+pub(crate) fn crypto(venue: &str, symbol: &str, instrument_type: InstrumentType) -> InstrumentRef {
+    let (base, quote) = symbol.split_once('-').unwrap_or((symbol, "USD"));
+    InstrumentRef {
+        venue: venue.to_string(),
+        venue_symbol: symbol.to_string(),
+        asset_class: AssetClass::Crypto,
+        instrument_type,
+        base: Some(base.to_string()),
+        quote: Some(quote.to_string()),
+        expiry_ts_ms: None,
+        strike: None,
+        option_right: None,
+        contract_multiplier: Some(1.0),
+    }
+}
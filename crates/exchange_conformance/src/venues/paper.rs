@@ -0,0 +1,85 @@
+use exchange_core::{
+    ExchangeAdapter, ExchangeError, ExchangeResultFuture, InstrumentRef, InstrumentType,
+    MarketDataUpdate, QuoteSnapshot, TradePrint,
+};
+use paper_exchange_adapter::PaperExchangeAdapter;
+
+use super::crypto;
+use crate::{Capabilities, ConformanceVenue, Fills, ReduceOnly};
+
+/// Paper venue with a book quoted wide enough around the scenario prices
+/// that limits rest until a trade print reaches them.
+struct PaperVenue {
+    adapter: PaperExchangeAdapter,
+}
+
+impl PaperVenue {
+    async fn new() -> Self {
+        let adapter = PaperExchangeAdapter::new("paper");
+        adapter
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                venue: "paper".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                bid: Some(90.0),
+                ask: Some(110.0),
+                last: None,
+                mark: Some(100.0),
+                ts_ms: 0,
+            }))
+            .await;
+        Self { adapter }
+    }
+}
+
+impl ConformanceVenue for PaperVenue {
+    fn adapter(&self) -> &dyn ExchangeAdapter {
+        &self.adapter
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fills: Fills::Driven,
+            short_positions: true,
+            cost_basis: true,
+            reduce_only: ReduceOnly::Checked,
+            outages: false,
+        }
+    }
+
+    fn instrument(&self) -> InstrumentRef {
+        crypto("paper", "BTC-USD", InstrumentType::Spot)
+    }
+
+    fn prices(&self) -> [f64; 2] {
+        [100.0, 105.0]
+    }
+
+    fn fill(&self, venue_order_id: &str, qty: f64) -> ExchangeResultFuture<'_, ()> {
+        let venue_order_id = venue_order_id.to_string();
+        Box::pin(async move {
+            let order = self
+                .adapter
+                .get_order(&venue_order_id)
+                .await?
+                .ok_or_else(|| {
+                    ExchangeError::new("order_not_found", venue_order_id.clone(), false)
+                })?;
+            self.adapter
+                .apply_market_data(&MarketDataUpdate::Trade(TradePrint {
+                    venue: "paper".to_string(),
+                    venue_symbol: order.instrument.venue_symbol,
+                    price: order.limit_price.unwrap_or_default(),
+                    size: Some(qty),
+                    side: None,
+                    ts_ms: 0,
+                }))
+                .await;
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn paper_adapter_conforms() {
+    crate::run(PaperVenue::new).await.assert_conforms();
+}
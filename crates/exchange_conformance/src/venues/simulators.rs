@@ -0,0 +1,125 @@
+//! The per-venue simulators: paper mode fills every order as it arrives, live
+//! mode only acknowledges orders.
+
+use exchange_coinbase_spot::{default_spot_instrument, CoinbaseSpotAdapter};
+use exchange_core::{ExchangeAdapter, InstrumentRef};
+use exchange_derivatives_paper::{default_perp_instrument, DerivativesPaperAdapter};
+use exchange_kalshi::{default_kalshi_prediction_instrument, KalshiAdapter};
+use trading_domain::ExecutionMode;
+
+use crate::{run, Capabilities, ConformanceVenue, Fills, ReduceOnly, Scenario};
+
+struct SimulatorVenue<A> {
+    adapter: A,
+    capabilities: Capabilities,
+    instrument: InstrumentRef,
+    prices: [f64; 2],
+    unsupported: InstrumentRef,
+}
+
+impl<A: ExchangeAdapter> ConformanceVenue for SimulatorVenue<A> {
+    fn adapter(&self) -> &dyn ExchangeAdapter {
+        &self.adapter
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn instrument(&self) -> InstrumentRef {
+        self.instrument.clone()
+    }
+
+    fn prices(&self) -> [f64; 2] {
+        self.prices
+    }
+
+    fn unsupported_instrument(&self) -> Option<InstrumentRef> {
+        Some(self.unsupported.clone())
+    }
+}
+
+fn capabilities(mode: ExecutionMode, short_positions: bool) -> Capabilities {
+    Capabilities {
+        fills: match mode {
+            ExecutionMode::Paper => Fills::OnArrival,
+            ExecutionMode::Live => Fills::Never,
+        },
+        short_positions,
+        cost_basis: true,
+        reduce_only: ReduceOnly::Checked,
+        outages: false,
+    }
+}
+
+fn kalshi(mode: ExecutionMode) -> SimulatorVenue<KalshiAdapter> {
+    SimulatorVenue {
+        adapter: KalshiAdapter::new(mode),
+        capabilities: capabilities(mode, true),
+        instrument: default_kalshi_prediction_instrument("KXHIGHNY-26OCT18-B70"),
+        prices: [0.42, 0.47],
+        unsupported: default_spot_instrument("BTC-USD"),
+    }
+}
+
+fn coinbase_spot(mode: ExecutionMode) -> SimulatorVenue<CoinbaseSpotAdapter> {
+    SimulatorVenue {
+        adapter: CoinbaseSpotAdapter::new(mode),
+        capabilities: capabilities(mode, false),
+        instrument: default_spot_instrument("BTC-USD"),
+        prices: [100.0, 105.0],
+        unsupported: default_perp_instrument("BTC-PERP"),
+    }
+}
+
+fn derivatives() -> SimulatorVenue<DerivativesPaperAdapter> {
+    SimulatorVenue {
+        adapter: DerivativesPaperAdapter::new(ExecutionMode::Paper),
+        capabilities: capabilities(ExecutionMode::Paper, true),
+        instrument: default_perp_instrument("BTC-PERP"),
+        prices: [100.0, 105.0],
+        unsupported: default_spot_instrument("BTC-USD"),
+    }
+}
+
+#[tokio::test]
+async fn kalshi_simulator_conforms_in_both_modes() {
+    for mode in [ExecutionMode::Paper, ExecutionMode::Live] {
+        run(|| async move { kalshi(mode) }).await.assert_conforms();
+    }
+}
+
+#[tokio::test]
+async fn coinbase_spot_simulator_conforms_in_both_modes() {
+    for mode in [ExecutionMode::Paper, ExecutionMode::Live] {
+        run(|| async move { coinbase_spot(mode) })
+            .await
+            .assert_conforms();
+    }
+}
+
+#[tokio::test]
+async fn derivatives_paper_adapter_conforms() {
+    run(|| async { derivatives() }).await.assert_conforms();
+}
+
+#[tokio::test]
+async fn capabilities_a_venue_lacks_are_reported_as_failures() {
+    // Paper-mode orders fill on arrival, so a venue claiming its fills are
+    // driven sees orders filled before it could drive them.
+    let report = run(|| async {
+        let mut venue = kalshi(ExecutionMode::Paper);
+        venue.capabilities.fills = Fills::Driven;
+        venue
+    })
+    .await;
+
+    let failed: Vec<Scenario> = report
+        .failures()
+        .into_iter()
+        .map(|(scenario, _)| scenario)
+        .collect();
+    assert!(failed.contains(&Scenario::CancelWorking), "{report}");
+    assert!(failed.contains(&Scenario::PartialFill), "{report}");
+    assert!(report.to_string().contains("cancel_working: FAILED"));
+}
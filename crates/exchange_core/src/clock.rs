@@ -0,0 +1,83 @@
+//! Wall-clock source shared by the daemon and adapters, so that TTLs, pacing
+//! and timestamps can be driven deterministically in tests and replays.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync + Debug {
+    /// Milliseconds since the Unix epoch.
+    fn now_ms(&self) -> i64;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Reads the system wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0)
+    }
+}
+
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    now_ms: AtomicI64,
+}
+
+impl SimulatedClock {
+    pub fn new(start_ms: i64) -> Self {
+        Self {
+            now_ms: AtomicI64::new(start_ms),
+        }
+    }
+
+    /// Moves the clock forward by `delta_ms` and returns the new time.
+    pub fn advance(&self, delta_ms: i64) -> i64 {
+        self.now_ms.fetch_add(delta_ms, Ordering::SeqCst) + delta_ms
+    }
+
+    /// Jumps to `now_ms`; replays use this to follow recorded timestamps.
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_ms(&self) -> i64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulated_clock_moves_only_when_advanced() {
+        let clock = Arc::new(SimulatedClock::new(1_000));
+        let shared: SharedClock = clock.clone();
+
+        assert_eq!(shared.now_ms(), 1_000);
+        assert_eq!(clock.advance(250), 1_250);
+        assert_eq!(shared.now_ms(), 1_250);
+        clock.set(5_000);
+        assert_eq!(shared.now_ms(), 5_000);
+    }
+
+    #[test]
+    fn system_clock_reads_wall_time() {
+        assert!(system_clock().now_ms() > 1_600_000_000_000);
+    }
+}
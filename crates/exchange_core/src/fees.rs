@@ -0,0 +1,285 @@
+//! Fee schedules simulated venues charge on fills.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetClass, FillReport, InstrumentRef, InstrumentType, NormalizedOrderRequest};
+
+/// Window venues use to place an account in a volume tier.
+pub const FEE_TIER_WINDOW_MS: i64 = 30 * 86_400_000;
+
+/// Whether a fill added liquidity to the book or took it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+impl Liquidity {
+    /// Value reported in `FillReport.liquidity`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Maker => "maker",
+            Self::Taker => "taker",
+        }
+    }
+}
+
+/// Maker/taker rates for accounts with at least `min_volume` of trailing
+/// notional.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FeeTier {
+    pub min_volume: f64,
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FeeSchedule {
+    /// Notional rates picked by trailing 30-day volume; tiers ascend by volume.
+    Tiered {
+        tiers: Vec<FeeTier>,
+    },
+    /// `coeff × contracts × p × (1 − p)` with `p` the price in dollars,
+    /// rounded up to the cent, as computed by `kalshi_common::FeeSchedule`.
+    Probability {
+        maker_coeff: f64,
+        taker_coeff: f64,
+    },
+    /// Per-contract charge capped at a fraction of the premium paid.
+    PerContract {
+        per_contract: f64,
+        max_premium_fraction: f64,
+    },
+    Free,
+}
+
+impl FeeSchedule {
+    /// Coinbase Advanced Trade spot schedule.
+    pub fn coinbase_advanced() -> Self {
+        let tier = |min_volume: f64, maker_bps: f64, taker_bps: f64| FeeTier {
+            min_volume,
+            maker_bps,
+            taker_bps,
+        };
+        Self::Tiered {
+            tiers: vec![
+                tier(0.0, 40.0, 60.0),
+                tier(10_000.0, 25.0, 40.0),
+                tier(50_000.0, 15.0, 25.0),
+                tier(100_000.0, 10.0, 20.0),
+                tier(1_000_000.0, 8.0, 18.0),
+                tier(15_000_000.0, 6.0, 16.0),
+                tier(75_000_000.0, 3.0, 12.0),
+                tier(250_000_000.0, 0.0, 8.0),
+                tier(400_000_000.0, 0.0, 5.0),
+            ],
+        }
+    }
+
+    /// Kalshi's general schedule; makers pay a quarter of the taker coefficient.
+    pub fn kalshi() -> Self {
+        let schedule = kalshi_common::FeeSchedule::weather();
+        Self::Probability {
+            maker_coeff: schedule.maker_coeff,
+            taker_coeff: schedule.taker_coeff,
+        }
+    }
+
+    /// Flat rates typical of perpetual and dated futures venues.
+    pub fn futures() -> Self {
+        Self::Tiered {
+            tiers: vec![FeeTier {
+                min_volume: 0.0,
+                maker_bps: 2.0,
+                taker_bps: 5.0,
+            }],
+        }
+    }
+
+    /// Listed options: a per-contract charge capped at 12.5% of premium.
+    pub fn options() -> Self {
+        Self::PerContract {
+            per_contract: 0.65,
+            max_premium_fraction: 0.125,
+        }
+    }
+
+    /// The schedule a venue would charge for `instrument`.
+    pub fn for_instrument(instrument: &InstrumentRef) -> Self {
+        match instrument.instrument_type {
+            InstrumentType::BinaryOption => Self::kalshi(),
+            _ if instrument.asset_class == AssetClass::Prediction => Self::kalshi(),
+            InstrumentType::Perpetual | InstrumentType::Future => Self::futures(),
+            InstrumentType::Option => Self::options(),
+            InstrumentType::Spot | InstrumentType::Custom => Self::coinbase_advanced(),
+        }
+    }
+
+    /// Taker fee for filling all of `req` at `price` on arrival, under
+    /// `schedule` or the instrument's venue schedule when `None`. `fills` are
+    /// the account's earlier fills, which place it in a volume tier.
+    pub fn charge(
+        schedule: Option<&Self>,
+        req: &NormalizedOrderRequest,
+        price: f64,
+        fills: &[FillReport],
+        now_ms: i64,
+    ) -> f64 {
+        schedule
+            .cloned()
+            .unwrap_or_else(|| Self::for_instrument(&req.instrument))
+            .fee(
+                Liquidity::Taker,
+                req.qty,
+                price,
+                req.instrument.contract_multiplier.unwrap_or(1.0),
+                trailing_volume(fills, now_ms),
+            )
+    }
+
+    /// Fee in quote currency for `qty` contracts at `price`, given the
+    /// account's trailing notional volume.
+    pub fn fee(
+        &self,
+        liquidity: Liquidity,
+        qty: f64,
+        price: f64,
+        contract_multiplier: f64,
+        trailing_volume: f64,
+    ) -> f64 {
+        let qty = qty.abs();
+        let fee = match self {
+            Self::Tiered { tiers } => {
+                let tier = tiers
+                    .iter()
+                    .rev()
+                    .find(|tier| trailing_volume >= tier.min_volume)
+                    .or(tiers.first());
+                let bps = tier.map_or(0.0, |tier| match liquidity {
+                    Liquidity::Maker => tier.maker_bps,
+                    Liquidity::Taker => tier.taker_bps,
+                });
+                qty * price * contract_multiplier * bps / 10_000.0
+            }
+            Self::Probability {
+                maker_coeff,
+                taker_coeff,
+            } => {
+                let schedule = kalshi_common::FeeSchedule {
+                    taker_coeff: *taker_coeff,
+                    maker_coeff: *maker_coeff,
+                };
+                // Kalshi trades whole contracts at whole-cent prices.
+                let count = qty.round() as i64;
+                let price_cents = (price.clamp(0.0, 1.0) * 100.0).round() as i64;
+                let cents = match liquidity {
+                    Liquidity::Maker => schedule.maker_fee_cents(count, price_cents),
+                    Liquidity::Taker => schedule.taker_fee_cents(count, price_cents),
+                };
+                cents as f64 / 100.0
+            }
+            Self::PerContract {
+                per_contract,
+                max_premium_fraction,
+            } => {
+                let cap = qty * price * contract_multiplier * max_premium_fraction;
+                (qty * per_contract).min(cap)
+            }
+            Self::Free => 0.0,
+        };
+        fee.max(0.0)
+    }
+}
+
+/// Notional traded in the fee tier window ending at `now_ms`.
+pub fn trailing_volume(fills: &[FillReport], now_ms: i64) -> f64 {
+    fills
+        .iter()
+        .filter(|fill| fill.ts_ms > now_ms - FEE_TIER_WINDOW_MS)
+        .map(|fill| {
+            fill.qty.abs() * fill.price * fill.instrument.contract_multiplier.unwrap_or(1.0)
+        })
+        .sum()
+}
+
+/// Fees charged on `fills`, which simulated venues debit from the USD balance.
+pub fn fees_paid(fills: &[FillReport]) -> f64 {
+    fills.iter().map(|fill| fill.fee).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(left: f64, right: f64) -> bool {
+        (left - right).abs() < 1e-9
+    }
+
+    #[test]
+    fn coinbase_tiers_follow_trailing_volume() {
+        let schedule = FeeSchedule::coinbase_advanced();
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 1.0, 1_000.0, 1.0, 0.0),
+            6.0
+        ));
+        assert!(approx(
+            schedule.fee(Liquidity::Maker, 1.0, 1_000.0, 1.0, 0.0),
+            4.0
+        ));
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 1.0, 1_000.0, 1.0, 60_000.0),
+            2.5
+        ));
+    }
+
+    #[test]
+    fn kalshi_fees_round_up_to_the_cent() {
+        let schedule = FeeSchedule::kalshi();
+        // 0.07 × 10 × 0.5 × 0.5 = $0.175 → $0.18.
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 10.0, 0.5, 1.0, 0.0),
+            0.18
+        ));
+        // 0.0175 × 100 × 0.5 × 0.5 = $0.4375 → $0.44.
+        assert!(approx(
+            schedule.fee(Liquidity::Maker, 100.0, 0.5, 1.0, 0.0),
+            0.44
+        ));
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 4.0, 0.25, 1.0, 0.0),
+            0.06
+        ));
+    }
+
+    #[test]
+    fn option_fees_are_capped_by_premium() {
+        let schedule = FeeSchedule::options();
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 2.0, 5.0, 100.0, 0.0),
+            1.3
+        ));
+        // Premium is 2 × 0.01 × 100 = $2, so the cap is $0.25.
+        assert!(approx(
+            schedule.fee(Liquidity::Taker, 2.0, 0.01, 100.0, 0.0),
+            0.25
+        ));
+        assert_eq!(
+            FeeSchedule::for_instrument(&InstrumentRef {
+                venue: "derivatives_paper".to_string(),
+                venue_symbol: "BTC-PERP".to_string(),
+                asset_class: AssetClass::Crypto,
+                instrument_type: InstrumentType::Perpetual,
+                base: None,
+                quote: None,
+                expiry_ts_ms: None,
+                strike: None,
+                option_right: None,
+                contract_multiplier: None,
+            }),
+            FeeSchedule::futures()
+        );
+    }
+}
@@ -1,10 +1,20 @@
 //! Exchange abstraction layer for normalized, venue-agnostic execution.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+mod clock;
+mod fees;
+mod positions;
+
+pub use clock::{system_clock, Clock, SharedClock, SimulatedClock, SystemClock};
+pub use fees::{fees_paid, trailing_volume, FeeSchedule, FeeTier, Liquidity, FEE_TIER_WINDOW_MS};
+pub use positions::check_reduce_only;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum AssetClass {
     Crypto,
     Equity,
@@ -15,7 +25,7 @@ pub enum AssetClass {
     Custom,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum InstrumentType {
     Spot,
     Perpetual,
@@ -25,13 +35,13 @@ pub enum InstrumentType {
     Custom,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum OptionRight {
     Call,
     Put,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct InstrumentRef {
     pub venue: String,
     pub venue_symbol: String,
@@ -45,19 +55,35 @@ pub struct InstrumentRef {
     pub contract_multiplier: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+impl InstrumentRef {
+    /// Notional in cents of `qty` contracts at `price`, scaled by the contract
+    /// multiplier (1 when unset); zero unless price and qty are positive.
+    pub fn notional_cents(&self, price: f64, qty: f64) -> i64 {
+        let multiplier = self
+            .contract_multiplier
+            .filter(|m| m.is_finite() && *m > 0.0)
+            .unwrap_or(1.0);
+        if price > 0.0 && qty > 0.0 {
+            (price * qty * multiplier * 100.0) as i64
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum OrderType {
     Limit,
     Market,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum TimeInForce {
     Gtc,
     Ioc,
@@ -65,7 +91,7 @@ pub enum TimeInForce {
     Day,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum OrderStatus {
     New,
     PartiallyFilled,
@@ -74,7 +100,7 @@ pub enum OrderStatus {
     Rejected,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NormalizedOrderRequest {
     pub venue: String,
     pub symbol: String,
@@ -85,14 +111,21 @@ pub struct NormalizedOrderRequest {
     pub side: OrderSide,
     pub order_type: OrderType,
     pub qty: f64,
+    /// Quote-currency amount a market order spends or receives, sent instead
+    /// of `qty` by venues that size orders in quote.
+    #[serde(default)]
+    pub quote_size: Option<f64>,
     pub limit_price: Option<f64>,
+    /// Trigger price that makes a limit order a stop-limit.
+    #[serde(default)]
+    pub stop_price: Option<f64>,
     pub tif: Option<TimeInForce>,
     pub post_only: bool,
     pub reduce_only: bool,
     pub requested_notional_cents: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OrderAck {
     pub venue_order_id: String,
     pub client_order_id: String,
@@ -105,7 +138,7 @@ pub struct OrderAck {
     pub ts_ms: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OrderSnapshot {
     pub venue: String,
     pub venue_order_id: String,
@@ -124,12 +157,12 @@ pub struct OrderSnapshot {
     pub simulated: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OpenOrderSnapshot {
     pub order: OrderSnapshot,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FillReport {
     pub venue: String,
     pub venue_fill_id: String,
@@ -147,7 +180,7 @@ pub struct FillReport {
     pub ts_ms: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PositionSnapshot {
     pub venue: String,
     pub instrument: InstrumentRef,
@@ -157,7 +190,7 @@ pub struct PositionSnapshot {
     pub unrealized_pnl: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BalanceSnapshot {
     pub venue: String,
     pub asset: String,
@@ -165,7 +198,99 @@ pub struct BalanceSnapshot {
     pub available: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuoteSnapshot {
+    pub venue: String,
+    pub venue_symbol: String,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub last: Option<f64>,
+    pub mark: Option<f64>,
+    pub ts_ms: i64,
+}
+
+impl QuoteSnapshot {
+    /// Price an order on `side` would most likely trade at: the ask for buys and
+    /// the bid for sells, falling back to last trade and then mark.
+    pub fn reference_price(&self, side: &OrderSide) -> Option<(f64, &'static str)> {
+        let touch = match side {
+            OrderSide::Buy => self.ask.map(|p| (p, "ask")),
+            OrderSide::Sell => self.bid.map(|p| (p, "bid")),
+        };
+        touch
+            .into_iter()
+            .chain(self.last.map(|p| (p, "last")))
+            .chain(self.mark.map(|p| (p, "mark")))
+            .find(|(price, _)| price.is_finite() && *price > 0.0)
+    }
+
+    /// The other side of a binary contract quoted on a $1 payout, e.g. NO
+    /// prices from a YES quote: buying NO sells YES, so the NO ask is one
+    /// minus the YES bid.
+    pub fn binary_complement(&self) -> QuoteSnapshot {
+        let complement = |price: Option<f64>| price.map(|p| 1.0 - p);
+        QuoteSnapshot {
+            bid: complement(self.ask),
+            ask: complement(self.bid),
+            last: complement(self.last),
+            mark: complement(self.mark),
+            ..self.clone()
+        }
+    }
+}
+
+/// Order-entry rules a venue publishes for one instrument. Increments and
+/// sizes are in the instrument's base or quote currency.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ProductInfo {
+    pub venue: String,
+    pub venue_symbol: String,
+    pub base: Option<String>,
+    pub quote: Option<String>,
+    pub base_increment: f64,
+    pub base_min_size: f64,
+    pub base_max_size: Option<f64>,
+    pub quote_increment: f64,
+    pub quote_min_size: Option<f64>,
+    pub quote_max_size: Option<f64>,
+    pub price_increment: f64,
+    pub status: String,
+    /// New orders are refused; working orders may only be canceled.
+    pub trading_disabled: bool,
+    pub cancel_only: bool,
+    pub limit_only: bool,
+    pub post_only: bool,
+}
+
+/// Trade print from a venue's public feed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TradePrint {
+    pub venue: String,
+    pub venue_symbol: String,
+    pub price: f64,
+    pub size: Option<f64>,
+    pub side: Option<OrderSide>,
+    pub ts_ms: i64,
+}
+
+/// Normalized update emitted by a venue market data stream.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum MarketDataUpdate {
+    /// Top of book; `None` fields were not carried by the venue message.
+    Quote(QuoteSnapshot),
+    Trade(TradePrint),
+    /// The stream for `venue` dropped; cached quotes should be treated as stale.
+    Disconnected {
+        venue: String,
+        reason: String,
+    },
+}
+
+/// Callback that receives updates from a market data stream. Adapters invoke it
+/// from their own background tasks, so it must not block.
+pub type MarketDataSink = Arc<dyn Fn(MarketDataUpdate) + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExchangeHealth {
     pub venue: String,
     pub healthy: bool,
@@ -174,7 +299,7 @@ pub struct ExchangeHealth {
     pub message: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExchangeError {
     pub code: String,
     pub message: String,
@@ -194,7 +319,14 @@ impl ExchangeError {
 pub trait ExchangeAdapter: Send + Sync {
     fn venue(&self) -> &'static str;
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()>;
+    /// Subscribes to top-of-book and trade updates for `venue_symbols`, delivering
+    /// them to `sink` until the adapter is dropped. Resolves once the stream is
+    /// established; adapters reconnect on their own after that.
+    fn connect_market_data(
+        &self,
+        venue_symbols: Vec<String>,
+        sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()>;
 
     fn place_order(&self, req: NormalizedOrderRequest) -> ExchangeResultFuture<'_, OrderAck>;
 
@@ -214,6 +346,16 @@ pub trait ExchangeAdapter: Send + Sync {
 
     fn sync_balances(&self) -> ExchangeResultFuture<'_, Vec<BalanceSnapshot>>;
 
+    /// Best-effort top of book for `venue_symbol`; `None` when the venue has no quote.
+    fn quote(&self, _venue_symbol: &str) -> ExchangeResultFuture<'_, Option<QuoteSnapshot>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Order-entry rules for `venue_symbol`; `None` when the venue publishes none.
+    fn product(&self, _venue_symbol: &str) -> ExchangeResultFuture<'_, Option<ProductInfo>> {
+        Box::pin(async { Ok(None) })
+    }
+
     fn health(&self) -> ExchangeValueFuture<'_, ExchangeHealth>;
 }
 
@@ -242,5 +384,41 @@ mod tests {
 
         assert_eq!(instrument.asset_class, AssetClass::Crypto);
         assert_eq!(instrument.instrument_type, InstrumentType::Spot);
+        assert_eq!(instrument.notional_cents(100.0, 0.5), 5_000);
+
+        let future = InstrumentRef {
+            contract_multiplier: Some(0.01),
+            ..instrument
+        };
+        assert_eq!(future.notional_cents(60_000.0, 2.0), 120_000);
+        assert_eq!(future.notional_cents(60_000.0, 0.0), 0);
+    }
+
+    #[test]
+    fn quote_reference_price_prefers_touch_then_last_then_mark() {
+        let mut quote = QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(99.0),
+            ask: Some(101.0),
+            last: Some(100.0),
+            mark: Some(100.5),
+            ts_ms: 0,
+        };
+
+        assert_eq!(quote.reference_price(&OrderSide::Buy), Some((101.0, "ask")));
+        assert_eq!(quote.reference_price(&OrderSide::Sell), Some((99.0, "bid")));
+
+        quote.ask = None;
+        assert_eq!(
+            quote.reference_price(&OrderSide::Buy),
+            Some((100.0, "last"))
+        );
+
+        quote.last = Some(0.0);
+        assert_eq!(
+            quote.reference_price(&OrderSide::Buy),
+            Some((100.5, "mark"))
+        );
     }
 }
@@ -0,0 +1,126 @@
+//! Position bookkeeping shared by the simulated venues, so they agree on how
+//! fills move a position and which reduce-only orders they accept.
+
+use crate::{ExchangeError, OrderSide, PositionSnapshot};
+
+impl OrderSide {
+    /// `qty` as a signed position change: positive for buys.
+    pub fn signed(&self, qty: f64) -> f64 {
+        match self {
+            OrderSide::Buy => qty,
+            OrderSide::Sell => -qty,
+        }
+    }
+}
+
+impl PositionSnapshot {
+    /// Books a fill of `qty` at `price` at average cost. Adding to a position
+    /// re-weights its basis, reducing it keeps the basis, and a fill that
+    /// flattens or flips it starts again from `price`. The mark moves to
+    /// `price`.
+    pub fn apply_fill(&mut self, side: &OrderSide, qty: f64, price: f64) {
+        let signed_delta = side.signed(qty);
+        let old_qty = self.qty;
+        let new_qty = old_qty + signed_delta;
+        let old_abs = old_qty.abs();
+        let delta_abs = signed_delta.abs();
+        let new_abs = new_qty.abs();
+        let epsilon = f64::EPSILON;
+
+        if old_abs <= epsilon {
+            self.avg_price = price;
+        } else if old_qty.is_sign_positive() == signed_delta.is_sign_positive() {
+            if new_abs > epsilon {
+                self.avg_price = ((self.avg_price * old_abs) + (price * delta_abs)) / new_abs;
+            } else {
+                self.avg_price = price;
+            }
+        } else if delta_abs > old_abs + epsilon || new_abs <= epsilon {
+            // Trade crossed through zero (or flattened), so remaining basis is this fill.
+            self.avg_price = price;
+        }
+
+        self.qty = if new_abs <= epsilon { 0.0 } else { new_qty };
+        self.mark_price = Some(price);
+    }
+}
+
+/// Accepts a reduce-only order for `qty` on `side` only when it shrinks the
+/// signed `position_qty` without flipping it.
+pub fn check_reduce_only(
+    position_qty: f64,
+    side: &OrderSide,
+    qty: f64,
+) -> Result<(), ExchangeError> {
+    let reduces = position_qty * side.signed(1.0) < 0.0;
+    if reduces && qty <= position_qty.abs() + f64::EPSILON {
+        return Ok(());
+    }
+    Err(ExchangeError::new(
+        "reduce_only_violation",
+        format!("reduce-only {side:?} of {qty} would not reduce a position of {position_qty}"),
+        false,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetClass, InstrumentRef, InstrumentType};
+
+    fn flat() -> PositionSnapshot {
+        PositionSnapshot {
+            venue: "paper".to_string(),
+            instrument: InstrumentRef {
+                venue: "paper".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                asset_class: AssetClass::Crypto,
+                instrument_type: InstrumentType::Perpetual,
+                base: Some("BTC".to_string()),
+                quote: Some("USD".to_string()),
+                expiry_ts_ms: None,
+                strike: None,
+                option_right: None,
+                contract_multiplier: None,
+            },
+            qty: 0.0,
+            avg_price: 0.0,
+            mark_price: None,
+            unrealized_pnl: None,
+        }
+    }
+
+    #[test]
+    fn fills_keep_average_cost_until_the_position_flips() {
+        let mut position = flat();
+        position.apply_fill(&OrderSide::Buy, 1.0, 100.0);
+        position.apply_fill(&OrderSide::Buy, 3.0, 120.0);
+        assert_eq!((position.qty, position.avg_price), (4.0, 115.0));
+
+        position.apply_fill(&OrderSide::Sell, 3.0, 90.0);
+        assert_eq!((position.qty, position.avg_price), (1.0, 115.0));
+
+        position.apply_fill(&OrderSide::Sell, 3.0, 80.0);
+        assert_eq!((position.qty, position.avg_price), (-2.0, 80.0));
+        assert_eq!(position.mark_price, Some(80.0));
+
+        position.apply_fill(&OrderSide::Buy, 2.0, 85.0);
+        assert_eq!((position.qty, position.avg_price), (0.0, 85.0));
+    }
+
+    #[test]
+    fn reduce_only_orders_may_not_open_grow_or_flip_a_position() {
+        assert!(check_reduce_only(2.0, &OrderSide::Sell, 2.0).is_ok());
+        assert!(check_reduce_only(-2.0, &OrderSide::Buy, 1.0).is_ok());
+
+        for (position, side, qty) in [
+            (0.0, OrderSide::Sell, 1.0),
+            (2.0, OrderSide::Buy, 1.0),
+            (2.0, OrderSide::Sell, 3.0),
+        ] {
+            let err = check_reduce_only(position, &side, qty).unwrap_err();
+            assert_eq!(err.code, "reduce_only_violation");
+            assert!(!err.retriable);
+        }
+    }
+}
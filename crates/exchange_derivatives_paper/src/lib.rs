@@ -3,10 +3,10 @@
 use std::collections::HashMap;
 
 use exchange_core::{
-    AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth,
-    ExchangeResultFuture, ExchangeValueFuture, FillReport, InstrumentRef, InstrumentType,
-    NormalizedOrderRequest, OpenOrderSnapshot, OrderAck, OrderSide, OrderSnapshot, OrderStatus,
-    PositionSnapshot,
+    check_reduce_only, fees_paid, AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError,
+    ExchangeHealth, ExchangeResultFuture, ExchangeValueFuture, FeeSchedule, FillReport,
+    InstrumentRef, InstrumentType, Liquidity, MarketDataSink, NormalizedOrderRequest,
+    OpenOrderSnapshot, OrderAck, OrderSnapshot, OrderStatus, PositionSnapshot,
 };
 use tokio::sync::RwLock;
 use trading_domain::ExecutionMode;
@@ -23,6 +23,8 @@ struct AdapterState {
 pub struct DerivativesPaperAdapter {
     mode: ExecutionMode,
     state: RwLock<AdapterState>,
+    /// Schedule for simulated fills; `None` uses `FeeSchedule::for_instrument`.
+    fees: Option<FeeSchedule>,
 }
 
 impl Default for DerivativesPaperAdapter {
@@ -42,9 +44,15 @@ impl DerivativesPaperAdapter {
         Self {
             mode,
             state: RwLock::new(AdapterState::default()),
+            fees: None,
         }
     }
 
+    pub fn with_fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
     pub fn execution_mode(&self) -> ExecutionMode {
         self.mode
     }
@@ -73,14 +81,23 @@ impl DerivativesPaperAdapter {
             ));
         }
 
-        Ok(())
-    }
+        if req.stop_price.is_some() {
+            return Err(ExchangeError::new(
+                "stop_unsupported",
+                "paper derivatives adapter does not simulate stop orders",
+                false,
+            ));
+        }
 
-    fn signed_qty(side: &OrderSide, qty: f64) -> f64 {
-        match side {
-            OrderSide::Buy => qty,
-            OrderSide::Sell => -qty,
+        if req.quote_size.is_some() {
+            return Err(ExchangeError::new(
+                "quote_size_unsupported",
+                "paper derivatives adapter orders are sized by qty",
+                false,
+            ));
         }
+
+        Ok(())
     }
 
     fn fill_price(req: &NormalizedOrderRequest) -> f64 {
@@ -93,7 +110,11 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
         "derivatives_paper"
     }
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()> {
+    fn connect_market_data(
+        &self,
+        _venue_symbols: Vec<String>,
+        _sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
         Box::pin(async { Ok(()) })
     }
 
@@ -123,6 +144,16 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
                 simulated: true,
             };
 
+            let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
+            let mut state = self.state.write().await;
+            if req.reduce_only {
+                let held = state
+                    .positions
+                    .get(&key)
+                    .map_or(0.0, |position| position.qty);
+                check_reduce_only(held, &req.side, req.qty)?;
+            }
+            let fee = FeeSchedule::charge(self.fees.as_ref(), &req, fill_price, &state.fills, now);
             let fill = FillReport {
                 venue: req.venue.clone(),
                 venue_fill_id: format!("deriv-fill-{}", Uuid::new_v4()),
@@ -133,15 +164,13 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
                 side: req.side.clone(),
                 qty: req.qty,
                 price: fill_price,
-                fee: 0.0,
+                fee,
                 fee_asset: req.instrument.quote.clone(),
-                liquidity: Some("maker".to_string()),
+                liquidity: Some(Liquidity::Taker.as_str().to_string()),
                 simulated: true,
                 ts_ms: now,
             };
 
-            let mut state = self.state.write().await;
-            let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
             let entry = state
                 .positions
                 .entry(key)
@@ -153,9 +182,7 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
                     mark_price: Some(fill_price),
                     unrealized_pnl: Some(0.0),
                 });
-            entry.qty += Self::signed_qty(&req.side, req.qty);
-            entry.avg_price = fill_price;
-            entry.mark_price = Some(fill_price);
+            entry.apply_fill(&req.side, req.qty, fill_price);
 
             state.orders.insert(venue_order_id.clone(), snapshot);
             state.fills.push(fill);
@@ -185,8 +212,14 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
                     false,
                 )
             })?;
-            order.status = OrderStatus::Canceled;
-            order.updated_at_ms = Self::now_ms();
+            // Filled and canceled orders stay as they are.
+            if matches!(
+                order.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at_ms = Self::now_ms();
+            }
             Ok(())
         })
     }
@@ -238,12 +271,13 @@ impl ExchangeAdapter for DerivativesPaperAdapter {
     }
 
     fn sync_balances(&self) -> ExchangeResultFuture<'_, Vec<BalanceSnapshot>> {
-        Box::pin(async {
+        Box::pin(async move {
+            let fees_paid = fees_paid(&self.state.read().await.fills);
             Ok(vec![BalanceSnapshot {
                 venue: "derivatives_paper".to_string(),
                 asset: "USD".to_string(),
-                total: 20_000.0,
-                available: 20_000.0,
+                total: 20_000.0 - fees_paid,
+                available: 20_000.0 - fees_paid,
             }])
         })
     }
@@ -283,6 +317,8 @@ pub fn default_perp_instrument(symbol: &str) -> InstrumentRef {
 
 #[cfg(test)]
 mod tests {
+    use exchange_core::OrderSide;
+
     use super::*;
 
     #[test]
@@ -296,4 +332,66 @@ mod tests {
         let adapter = DerivativesPaperAdapter::default();
         assert_eq!(adapter.execution_mode(), ExecutionMode::Paper);
     }
+
+    #[tokio::test]
+    async fn fills_charge_the_instrument_fee_schedule() {
+        let adapter = DerivativesPaperAdapter::default();
+        let ack = adapter
+            .place_order(NormalizedOrderRequest {
+                venue: "derivatives_paper".to_string(),
+                symbol: "BTC-USD".to_string(),
+                instrument: default_perp_instrument("BTC-USD"),
+                strategy_id: "test.strategy".to_string(),
+                client_order_id: "perp-1".to_string(),
+                intent_id: None,
+                side: OrderSide::Buy,
+                order_type: exchange_core::OrderType::Limit,
+                qty: 2.0,
+                quote_size: None,
+                limit_price: Some(100.0),
+                stop_price: None,
+                tif: None,
+                post_only: false,
+                reduce_only: false,
+                requested_notional_cents: 20_000,
+            })
+            .await
+            .expect("order fills");
+        assert_eq!(ack.status, OrderStatus::Filled);
+
+        let fills = adapter.fills_since(0, 10).await.expect("fills");
+        // Perpetual taker rate: 5 bps of $200.
+        assert!((fills[0].fee - 0.1).abs() < 1e-9);
+        assert_eq!(fills[0].liquidity.as_deref(), Some("taker"));
+        let balances = adapter.sync_balances().await.expect("balances");
+        assert!((balances[0].total - 19_999.9).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn stop_orders_are_refused() {
+        let adapter = DerivativesPaperAdapter::default();
+        let err = adapter
+            .place_order(NormalizedOrderRequest {
+                venue: "derivatives_paper".to_string(),
+                symbol: "BTC-USD".to_string(),
+                instrument: default_perp_instrument("BTC-USD"),
+                strategy_id: "test.strategy".to_string(),
+                client_order_id: "perp-stop".to_string(),
+                intent_id: None,
+                side: OrderSide::Buy,
+                order_type: exchange_core::OrderType::Limit,
+                qty: 1.0,
+                quote_size: None,
+                limit_price: Some(100.0),
+                stop_price: Some(105.0),
+                tif: None,
+                post_only: false,
+                reduce_only: false,
+                requested_notional_cents: 10_000,
+            })
+            .await
+            .expect_err("stop refused");
+        assert_eq!(err.code, "stop_unsupported");
+        assert!(adapter.fills_since(0, 10).await.expect("fills").is_empty());
+    }
 }
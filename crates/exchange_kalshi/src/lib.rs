@@ -1,17 +1,23 @@
-//! Kalshi adapter used by legacy workspace members.
+//! Kalshi adapters: `KalshiAdapter` simulates fills in memory for legacy
+//! workspace members and backtests; `KalshiLiveAdapter` trades the venue.
 
 use std::collections::HashMap;
 
 use exchange_core::{
-    AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth,
-    ExchangeResultFuture, ExchangeValueFuture, FillReport, InstrumentRef, InstrumentType,
-    NormalizedOrderRequest, OpenOrderSnapshot, OrderAck, OrderSide, OrderSnapshot, OrderStatus,
-    PositionSnapshot,
+    check_reduce_only, fees_paid, AssetClass, BalanceSnapshot, ExchangeAdapter, ExchangeError,
+    ExchangeHealth, ExchangeResultFuture, ExchangeValueFuture, FeeSchedule, FillReport,
+    InstrumentRef, InstrumentType, Liquidity, MarketDataSink, MarketDataUpdate,
+    NormalizedOrderRequest, OpenOrderSnapshot, OrderAck, OrderSnapshot, OrderStatus,
+    PositionSnapshot, QuoteSnapshot,
 };
 use tokio::sync::RwLock;
 use trading_domain::ExecutionMode;
 use uuid::Uuid;
 
+mod live;
+
+pub use live::KalshiLiveAdapter;
+
 #[derive(Debug, Default)]
 struct AdapterState {
     positions: HashMap<String, PositionSnapshot>,
@@ -23,6 +29,8 @@ struct AdapterState {
 pub struct KalshiAdapter {
     mode: ExecutionMode,
     state: RwLock<AdapterState>,
+    /// Schedule for simulated fills; `None` uses `FeeSchedule::for_instrument`.
+    fees: Option<FeeSchedule>,
 }
 
 impl KalshiAdapter {
@@ -30,14 +38,20 @@ impl KalshiAdapter {
         Self {
             mode,
             state: RwLock::new(AdapterState::default()),
+            fees: None,
         }
     }
 
+    pub fn with_fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
     fn now_ms() -> i64 {
         chrono::Utc::now().timestamp_millis()
     }
 
-    fn validate_order(req: &NormalizedOrderRequest) -> Result<(), ExchangeError> {
+    pub(crate) fn validate_order(req: &NormalizedOrderRequest) -> Result<(), ExchangeError> {
         if req.instrument.instrument_type != InstrumentType::BinaryOption
             || req.instrument.asset_class != AssetClass::Prediction
         {
@@ -56,14 +70,23 @@ impl KalshiAdapter {
             ));
         }
 
-        Ok(())
-    }
+        if req.stop_price.is_some() {
+            return Err(ExchangeError::new(
+                "stop_unsupported",
+                "kalshi does not support stop orders",
+                false,
+            ));
+        }
 
-    fn signed_qty(side: &OrderSide, qty: f64) -> f64 {
-        match side {
-            OrderSide::Buy => qty,
-            OrderSide::Sell => -qty,
+        if req.quote_size.is_some() {
+            return Err(ExchangeError::new(
+                "quote_size_unsupported",
+                "kalshi orders are sized in contracts",
+                false,
+            ));
         }
+
+        Ok(())
     }
 
     fn mode_is_live(&self) -> bool {
@@ -73,6 +96,34 @@ impl KalshiAdapter {
     fn fill_price(req: &NormalizedOrderRequest) -> f64 {
         req.limit_price.unwrap_or(0.5)
     }
+
+    /// Normalizes a `ticker` channel message (YES prices in cents) into a quote
+    /// priced in dollars per contract. Fields missing from the message stay `None`.
+    pub fn ticker_update(
+        market_ticker: &str,
+        yes_bid_cents: Option<i64>,
+        yes_ask_cents: Option<i64>,
+        last_price_cents: Option<i64>,
+        ts_ms: i64,
+    ) -> MarketDataUpdate {
+        let dollars = |cents: Option<i64>| cents.filter(|c| *c > 0).map(|c| c as f64 / 100.0);
+        let bid = dollars(yes_bid_cents);
+        let ask = dollars(yes_ask_cents);
+        let mark = match (bid, ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        };
+
+        MarketDataUpdate::Quote(QuoteSnapshot {
+            venue: "kalshi".to_string(),
+            venue_symbol: market_ticker.to_string(),
+            bid,
+            ask,
+            last: dollars(last_price_cents),
+            mark,
+            ts_ms,
+        })
+    }
 }
 
 impl ExchangeAdapter for KalshiAdapter {
@@ -80,7 +131,11 @@ impl ExchangeAdapter for KalshiAdapter {
         "kalshi"
     }
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()> {
+    fn connect_market_data(
+        &self,
+        _venue_symbols: Vec<String>,
+        _sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
         Box::pin(async { Ok(()) })
     }
 
@@ -124,9 +179,16 @@ impl ExchangeAdapter for KalshiAdapter {
                 simulated: !self.mode_is_live(),
             };
 
+            let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
             let mut state = self.state.write().await;
+            if req.reduce_only {
+                let held = state
+                    .positions
+                    .get(&key)
+                    .map_or(0.0, |position| position.qty);
+                check_reduce_only(held, &req.side, req.qty)?;
+            }
             if status == OrderStatus::Filled {
-                let key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
                 let fill_price = Self::fill_price(&req);
                 let entry = state
                     .positions
@@ -139,10 +201,10 @@ impl ExchangeAdapter for KalshiAdapter {
                         mark_price: Some(fill_price),
                         unrealized_pnl: Some(0.0),
                     });
-                entry.qty += Self::signed_qty(&req.side, req.qty);
-                entry.avg_price = fill_price;
-                entry.mark_price = Some(fill_price);
+                entry.apply_fill(&req.side, req.qty, fill_price);
 
+                let fee =
+                    FeeSchedule::charge(self.fees.as_ref(), &req, fill_price, &state.fills, now);
                 state.fills.push(FillReport {
                     venue: req.venue.clone(),
                     venue_fill_id: format!("kalshi-fill-{}", Uuid::new_v4()),
@@ -153,9 +215,9 @@ impl ExchangeAdapter for KalshiAdapter {
                     side: req.side.clone(),
                     qty: req.qty,
                     price: fill_price,
-                    fee: 0.0,
+                    fee,
                     fee_asset: Some("USD".to_string()),
-                    liquidity: Some("maker".to_string()),
+                    liquidity: Some(Liquidity::Taker.as_str().to_string()),
                     simulated: true,
                     ts_ms: now,
                 });
@@ -187,8 +249,14 @@ impl ExchangeAdapter for KalshiAdapter {
                     false,
                 )
             })?;
-            order.status = OrderStatus::Canceled;
-            order.updated_at_ms = Self::now_ms();
+            // Filled and canceled orders stay as they are.
+            if matches!(
+                order.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at_ms = Self::now_ms();
+            }
             Ok(())
         })
     }
@@ -244,7 +312,8 @@ impl ExchangeAdapter for KalshiAdapter {
             let (total, available) = if self.mode_is_live() {
                 (0.0, 0.0)
             } else {
-                (100_000.0, 100_000.0)
+                let fees_paid = fees_paid(&self.state.read().await.fills);
+                (100_000.0 - fees_paid, 100_000.0 - fees_paid)
             };
             Ok(vec![BalanceSnapshot {
                 venue: self.venue().to_string(),
@@ -0,0 +1,126 @@
+//! On-disk copy of a paper account so restarts resume the same orders,
+//! fills, positions and balances.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use exchange_core::ExchangeError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+fn ledger_error(path: &Path, action: &str, err: impl std::fmt::Display) -> ExchangeError {
+    ExchangeError::new(
+        "paper_ledger",
+        format!(
+            "failed to {} paper ledger {}: {}",
+            action,
+            path.display(),
+            err
+        ),
+        false,
+    )
+}
+
+/// Reads the ledger at `path`; `None` when no ledger has been written yet.
+pub(crate) fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, ExchangeError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(ledger_error(path, "read", err)),
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|err| ledger_error(path, "parse", err))
+}
+
+/// Replaces the ledger at `path` through a temporary file, so a crash leaves
+/// either the previous or the new ledger on disk.
+pub(crate) fn save<T: Serialize>(path: &Path, ledger: &T) -> Result<(), ExchangeError> {
+    write(path, &encode(path, ledger)?)
+}
+
+fn encode<T: Serialize>(path: &Path, ledger: &T) -> Result<Vec<u8>, ExchangeError> {
+    serde_json::to_vec(ledger).map_err(|err| ledger_error(path, "encode", err))
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<(), ExchangeError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| ledger_error(path, "create", err))?;
+    }
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".tmp.{}", std::process::id()));
+    std::fs::write(&tmp_path, bytes).map_err(|err| ledger_error(path, "write", err))?;
+    std::fs::rename(&tmp_path, path).map_err(|err| ledger_error(path, "write", err))
+}
+
+/// Outcome of the newest write that reached the blocking pool.
+#[derive(Default)]
+struct Written {
+    seq: u64,
+    error: Option<String>,
+}
+
+/// Newest write handed to the blocking pool.
+#[derive(Default)]
+struct Submitted {
+    seq: u64,
+    task: Option<JoinHandle<()>>,
+}
+
+/// Writes ledger snapshots on the blocking pool so callers never wait on the
+/// disk. Writes run one at a time and a snapshot older than the one already
+/// written is dropped, so the newest always ends up on disk.
+pub(crate) struct LedgerWriter {
+    path: PathBuf,
+    written: Arc<StdMutex<Written>>,
+    submitted: StdMutex<Submitted>,
+}
+
+impl LedgerWriter {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            written: Arc::default(),
+            submitted: StdMutex::default(),
+        }
+    }
+
+    /// Encodes `ledger` now and writes it in the background. Must be called
+    /// from within a tokio runtime.
+    pub(crate) fn submit<T: Serialize>(&self, ledger: &T) {
+        let bytes = match encode(&self.path, ledger) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.written.lock().unwrap().error = Some(err.message);
+                return;
+            }
+        };
+        let mut submitted = self.submitted.lock().unwrap();
+        submitted.seq += 1;
+        let seq = submitted.seq;
+        let path = self.path.clone();
+        let written = Arc::clone(&self.written);
+        submitted.task = Some(tokio::task::spawn_blocking(move || {
+            let mut written = written.lock().unwrap();
+            if written.seq >= seq {
+                return;
+            }
+            written.seq = seq;
+            written.error = write(&path, &bytes).err().map(|err| err.message);
+        }));
+    }
+
+    /// Why the newest write failed, if it did.
+    pub(crate) fn error(&self) -> Option<String> {
+        self.written.lock().unwrap().error.clone()
+    }
+
+    /// Waits until the newest submitted snapshot is on disk.
+    pub(crate) async fn settle(&self) {
+        let task = self.submitted.lock().unwrap().task.take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
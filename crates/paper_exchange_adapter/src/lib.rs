@@ -1,59 +1,333 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::Utc;
 use exchange_core::{
-    BalanceSnapshot, ExchangeAdapter, ExchangeError, ExchangeHealth, ExchangeResultFuture,
-    ExchangeValueFuture, FillReport, InstrumentType, NormalizedOrderRequest, OpenOrderSnapshot,
-    OptionRight, OrderAck, OrderSide, OrderSnapshot, OrderStatus, PositionSnapshot,
+    check_reduce_only, system_clock, trailing_volume, BalanceSnapshot, ExchangeAdapter,
+    ExchangeError, ExchangeHealth, ExchangeResultFuture, ExchangeValueFuture, FeeSchedule,
+    FillReport, InstrumentType, Liquidity, MarketDataSink, MarketDataUpdate,
+    NormalizedOrderRequest, OpenOrderSnapshot, OptionRight, OrderAck, OrderSide, OrderSnapshot,
+    OrderStatus, OrderType, PositionSnapshot, QuoteSnapshot, SharedClock, TimeInForce,
 };
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+mod ledger;
+mod matching;
+
+use ledger::LedgerWriter;
+pub use matching::MatchingConfig;
+use matching::{crosses, day_end_ms, Book};
+
+const QTY_EPSILON: f64 = 1e-12;
+
+/// Cash a new paper account starts with when no balances are configured.
+pub const DEFAULT_STARTING_USD: f64 = 1_000_000.0;
+
+/// An accepted order that has not finished trading.
+#[derive(Serialize, Deserialize)]
+struct WorkingOrder {
+    venue_order_id: String,
+    request: NormalizedOrderRequest,
+    /// When the order reaches the book, after the configured latency.
+    active_at_ms: i64,
+}
+
+/// The paper account. Everything but the books is the ledger persisted by
+/// `with_ledger`; books are rebuilt from market data after a restart.
+#[derive(Serialize, Deserialize)]
 struct PaperState {
     orders: HashMap<String, OrderSnapshot>,
     fills: Vec<FillReport>,
     positions: HashMap<String, PositionSnapshot>,
     balances: HashMap<String, BalanceSnapshot>,
+    /// Book per venue symbol, fed by `apply_market_data`.
+    #[serde(skip)]
+    books: HashMap<String, Book>,
+    /// Orders still in flight to the book, in arrival order.
+    pending: Vec<WorkingOrder>,
+    /// Limit orders resting on the book, in time priority.
+    resting: Vec<WorkingOrder>,
+    /// Set when the ledger changed since it was last handed to the writer.
+    /// Reads that advance the book leave it set for the next change to write.
+    #[serde(skip)]
+    dirty: bool,
 }
 
 impl PaperState {
-    fn new(venue: &str) -> Self {
-        let mut balances = HashMap::new();
-        balances.insert(
-            "USD".to_string(),
-            BalanceSnapshot {
-                venue: venue.to_string(),
-                asset: "USD".to_string(),
-                total: 1_000_000.0,
-                available: 1_000_000.0,
-            },
-        );
+    fn new(venue: &str, starting_balances: &BTreeMap<String, f64>) -> Self {
+        let balances = starting_balances
+            .iter()
+            .map(|(asset, amount)| {
+                (
+                    asset.clone(),
+                    BalanceSnapshot {
+                        venue: venue.to_string(),
+                        asset: asset.clone(),
+                        total: *amount,
+                        available: *amount,
+                    },
+                )
+            })
+            .collect();
 
         Self {
             orders: HashMap::new(),
             fills: Vec::new(),
             positions: HashMap::new(),
             balances,
+            books: HashMap::new(),
+            pending: Vec::new(),
+            resting: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    fn sorted_balances(&self) -> Vec<BalanceSnapshot> {
+        let mut balances: Vec<BalanceSnapshot> = self.balances.values().cloned().collect();
+        balances.sort_by(|left, right| left.asset.cmp(&right.asset));
+        balances
+    }
+
+    fn remaining_qty(&self, venue_order_id: &str) -> f64 {
+        self.orders
+            .get(venue_order_id)
+            .filter(|order| {
+                matches!(
+                    order.status,
+                    OrderStatus::New | OrderStatus::PartiallyFilled
+                )
+            })
+            .map(|order| (order.qty - order.filled_qty).max(0.0))
+            .unwrap_or(0.0)
+    }
+
+    fn close_order(&mut self, venue_order_id: &str, status: OrderStatus, now: i64) {
+        if let Some(order) = self.orders.get_mut(venue_order_id) {
+            order.status = status;
+            order.updated_at_ms = now;
+            self.dirty = true;
         }
     }
 }
 
+/// Interval between synthetic quotes on paper market data streams.
+const PAPER_QUOTE_INTERVAL_MS: u64 = 1_000;
+
+/// Simulated venue. Symbols with book data from `apply_market_data` are
+/// matched like a live venue: marketable orders take the touch, limits rest
+/// and fill once the book or a trade reaches them, post-only orders that would
+/// cross are rejected, and IOC/FOK/Day are honoured. Without book data,
+/// limit orders rest until the first book update, and market, IOC and FOK
+/// orders cancel unfilled as there is nothing to take.
 pub struct PaperExchangeAdapter {
     venue: String,
     state: Mutex<PaperState>,
+    /// Market data tasks hold a weak reference and stop once the adapter is dropped.
+    feed_guard: Arc<()>,
+    clock: SharedClock,
+    matching: MatchingConfig,
+    /// Balances a new or reset account starts with.
+    starting_balances: BTreeMap<String, f64>,
+    /// Persists the ledger; `None` keeps the account in memory.
+    ledger: Option<LedgerWriter>,
 }
 
 impl PaperExchangeAdapter {
     pub fn new(venue: impl Into<String>) -> Self {
+        Self::with_clock(venue, system_clock())
+    }
+
+    /// Adapter whose order, fill and quote timestamps come from `clock`.
+    pub fn with_clock(venue: impl Into<String>, clock: SharedClock) -> Self {
         let venue = venue.into();
+        let starting_balances = BTreeMap::from([("USD".to_string(), DEFAULT_STARTING_USD)]);
         Self {
-            state: Mutex::new(PaperState::new(&venue)),
+            state: Mutex::new(PaperState::new(&venue, &starting_balances)),
             venue,
+            feed_guard: Arc::new(()),
+            clock,
+            matching: MatchingConfig::default(),
+            starting_balances,
+            ledger: None,
+        }
+    }
+
+    /// Starts the account with `balances` (asset → amount) instead of
+    /// `DEFAULT_STARTING_USD`. A ledger loaded afterwards by `with_ledger`
+    /// keeps its own balances; these still apply on `reset`.
+    pub fn with_starting_balances(mut self, balances: BTreeMap<String, f64>) -> Self {
+        *self.state.get_mut() = PaperState::new(&self.venue, &balances);
+        self.starting_balances = balances;
+        self
+    }
+
+    /// Persists the account at `path` in the background after every order,
+    /// fill and balance change, resuming the ledger already there. Fails when
+    /// an existing ledger cannot be read or the first write does.
+    pub fn with_ledger(mut self, path: impl Into<PathBuf>) -> Result<Self, ExchangeError> {
+        let path = path.into();
+        if let Some(mut loaded) = ledger::load::<PaperState>(&path)? {
+            loaded.dirty = false;
+            *self.state.get_mut() = loaded;
+        } else {
+            ledger::save(&path, self.state.get_mut())?;
+            self.state.get_mut().dirty = false;
+        }
+        self.ledger = Some(LedgerWriter::new(path));
+        Ok(self)
+    }
+
+    pub fn starting_balances(&self) -> &BTreeMap<String, f64> {
+        &self.starting_balances
+    }
+
+    /// Discards every order, fill and position and restarts the account with
+    /// `balances`, or the starting balances when `None`. Books are kept.
+    pub async fn reset(
+        &self,
+        balances: Option<BTreeMap<String, f64>>,
+    ) -> Result<Vec<BalanceSnapshot>, ExchangeError> {
+        let balances = balances.unwrap_or_else(|| self.starting_balances.clone());
+        if let Some((asset, amount)) = balances
+            .iter()
+            .find(|(_, amount)| !amount.is_finite() || **amount < 0.0)
+        {
+            return Err(ExchangeError::new(
+                "invalid_amount",
+                format!("{} starting balance {} must be non-negative", asset, amount),
+                false,
+            ));
+        }
+        let mut state = self.state.lock().await;
+        let books = std::mem::take(&mut state.books);
+        *state = PaperState::new(&self.venue, &balances);
+        state.books = books;
+        self.flush(&mut state);
+        Ok(state.sorted_balances())
+    }
+
+    /// Adds `amounts` (asset → amount) to the account; negative amounts
+    /// withdraw but cannot take an asset's available balance below zero.
+    pub async fn fund(
+        &self,
+        amounts: &BTreeMap<String, f64>,
+    ) -> Result<Vec<BalanceSnapshot>, ExchangeError> {
+        let mut state = self.state.lock().await;
+        for (asset, amount) in amounts {
+            if !amount.is_finite() {
+                return Err(ExchangeError::new(
+                    "invalid_amount",
+                    format!("{} amount must be finite", asset),
+                    false,
+                ));
+            }
+            let available = state
+                .balances
+                .get(asset)
+                .map_or(0.0, |balance| balance.available);
+            if available + amount < -QTY_EPSILON {
+                return Err(ExchangeError::new(
+                    "insufficient_balance",
+                    format!(
+                        "cannot withdraw {} {} with {} available",
+                        -amount, asset, available
+                    ),
+                    false,
+                ));
+            }
+        }
+        for (asset, amount) in amounts {
+            Self::apply_balance_delta(&mut state, &self.venue, asset, *amount);
         }
+        self.flush(&mut state);
+        Ok(state.sorted_balances())
     }
 
-    fn now_ms() -> i64 {
-        Utc::now().timestamp_millis()
+    /// Hands the ledger to the background writer if it changed. A failed
+    /// write is kept for `health` and retried on the next change.
+    fn flush(&self, state: &mut PaperState) {
+        let Some(ledger) = &self.ledger else {
+            return;
+        };
+        if state.dirty {
+            ledger.submit(state);
+            state.dirty = false;
+        }
+    }
+
+    /// Writes any unsaved change and waits until the ledger is on disk, e.g.
+    /// before shutting down.
+    pub async fn settle_ledger(&self) {
+        let Some(ledger) = &self.ledger else {
+            return;
+        };
+        self.flush(&mut *self.state.lock().await);
+        ledger.settle().await;
+    }
+
+    pub fn with_matching(mut self, matching: MatchingConfig) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    pub fn matching(&self) -> &MatchingConfig {
+        &self.matching
+    }
+
+    /// Feeds a price path or recorded book data into the simulated book. Quotes
+    /// replace the symbol's book and refill touch liquidity; trades fill resting
+    /// limits at or through their price, up to the printed size.
+    pub async fn apply_market_data(&self, update: &MarketDataUpdate) {
+        let now = self.clock.now_ms();
+        let mut state = self.state.lock().await;
+        self.advance(&mut state, now);
+        match update {
+            MarketDataUpdate::Quote(quote) => {
+                self.replace_book(&mut state, quote, self.matching.touch_liquidity, now);
+            }
+            MarketDataUpdate::Trade(trade) => {
+                if let Some(book) = state.books.get_mut(&trade.venue_symbol) {
+                    book.quote.last = Some(trade.price);
+                }
+                let size = trade.size.unwrap_or(f64::INFINITY);
+                self.match_resting(
+                    &mut state,
+                    &trade.venue_symbol,
+                    Some((trade.price, size)),
+                    now,
+                );
+            }
+            MarketDataUpdate::Disconnected { .. } => {}
+        }
+        self.flush(&mut state);
+    }
+
+    /// Replaces the symbol's book with `quote`, offering `touch_size` on each
+    /// side until the next update whatever `MatchingConfig::touch_liquidity`
+    /// says; replays use it to reproduce the size a recorded order traded.
+    pub async fn apply_book(&self, quote: &QuoteSnapshot, touch_size: f64) {
+        let now = self.clock.now_ms();
+        let mut state = self.state.lock().await;
+        self.advance(&mut state, now);
+        self.replace_book(&mut state, quote, Some(touch_size), now);
+        self.flush(&mut state);
+    }
+
+    fn replace_book(
+        &self,
+        state: &mut PaperState,
+        quote: &QuoteSnapshot,
+        touch_size: Option<f64>,
+        now: i64,
+    ) {
+        state.books.insert(
+            quote.venue_symbol.clone(),
+            Book::new(quote.clone(), touch_size),
+        );
+        self.match_resting(state, &quote.venue_symbol, None, now);
     }
 
     fn deterministic_mark_price(symbol: &str) -> f64 {
@@ -64,6 +338,20 @@ impl PaperExchangeAdapter {
         (base * 100.0).round() / 100.0
     }
 
+    fn synthetic_quote(venue: &str, venue_symbol: &str, now_ms: i64) -> QuoteSnapshot {
+        let mark = Self::deterministic_mark_price(venue_symbol);
+        let half_spread = Self::slippage_bps(&InstrumentType::Spot, &None) / 10_000.0;
+        QuoteSnapshot {
+            venue: venue.to_string(),
+            venue_symbol: venue_symbol.to_string(),
+            bid: Some(mark * (1.0 - half_spread)),
+            ask: Some(mark * (1.0 + half_spread)),
+            last: Some(mark),
+            mark: Some(mark),
+            ts_ms: now_ms,
+        }
+    }
+
     fn slippage_bps(instrument_type: &InstrumentType, option_right: &Option<OptionRight>) -> f64 {
         match instrument_type {
             InstrumentType::Spot => 5.0,
@@ -106,38 +394,221 @@ impl PaperExchangeAdapter {
 
         entry.total += delta;
         entry.available += delta;
+        state.dirty = true;
     }
 
-    fn update_position_after_fill(
-        position: &mut PositionSnapshot,
-        side: &OrderSide,
+    /// Books `qty` of a working order at `fill_price`: the fill, the order's
+    /// progress, balances and the position.
+    fn book_fill(
+        &self,
+        state: &mut PaperState,
+        working: &WorkingOrder,
         qty: f64,
         fill_price: f64,
+        liquidity: Liquidity,
+        now: i64,
     ) {
-        let signed_delta = if *side == OrderSide::Buy { qty } else { -qty };
-        let old_qty = position.qty;
-        let new_qty = old_qty + signed_delta;
-        let old_abs = old_qty.abs();
-        let delta_abs = signed_delta.abs();
-        let new_abs = new_qty.abs();
-        let epsilon = f64::EPSILON;
-
-        if old_abs <= epsilon {
-            position.avg_price = fill_price;
-        } else if old_qty.is_sign_positive() == signed_delta.is_sign_positive() {
-            if new_abs > epsilon {
-                position.avg_price =
-                    ((position.avg_price * old_abs) + (fill_price * delta_abs)) / new_abs;
+        let req = &working.request;
+        let fee = self
+            .matching
+            .fees
+            .clone()
+            .unwrap_or_else(|| FeeSchedule::for_instrument(&req.instrument))
+            .fee(
+                liquidity,
+                qty,
+                fill_price,
+                req.instrument.contract_multiplier.unwrap_or(1.0),
+                trailing_volume(&state.fills, now),
+            );
+        state.fills.push(FillReport {
+            venue: self.venue.clone(),
+            venue_fill_id: format!("fill-{}", Uuid::new_v4().as_simple()),
+            venue_order_id: working.venue_order_id.clone(),
+            client_order_id: req.client_order_id.clone(),
+            strategy_id: req.strategy_id.clone(),
+            instrument: req.instrument.clone(),
+            side: req.side.clone(),
+            qty,
+            price: fill_price,
+            fee,
+            fee_asset: Some(Self::quote_asset(req)),
+            liquidity: Some(liquidity.as_str().to_string()),
+            simulated: true,
+            ts_ms: now,
+        });
+
+        if let Some(order) = state.orders.get_mut(&working.venue_order_id) {
+            let filled = order.filled_qty + qty;
+            let previous_notional = order.avg_fill_price.unwrap_or(0.0) * order.filled_qty;
+            order.avg_fill_price = Some((previous_notional + qty * fill_price) / filled);
+            order.filled_qty = filled;
+            order.status = if order.qty - filled <= QTY_EPSILON {
+                OrderStatus::Filled
             } else {
-                position.avg_price = fill_price;
+                OrderStatus::PartiallyFilled
+            };
+            order.updated_at_ms = now;
+        }
+
+        let quote = Self::quote_asset(req);
+        let base = Self::base_asset(req);
+        let notional = qty * fill_price;
+
+        match req.side {
+            OrderSide::Buy => {
+                Self::apply_balance_delta(state, &self.venue, &quote, -notional);
+                Self::apply_balance_delta(state, &self.venue, &base, qty);
+            }
+            OrderSide::Sell => {
+                Self::apply_balance_delta(state, &self.venue, &quote, notional);
+                Self::apply_balance_delta(state, &self.venue, &base, -qty);
             }
-        } else if delta_abs > old_abs + epsilon || new_abs <= epsilon {
-            // Trade crossed through zero (or flattened), so remaining basis is this fill.
-            position.avg_price = fill_price;
         }
+        Self::apply_balance_delta(state, &self.venue, &quote, -fee);
+
+        let position_key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
+        let position = state
+            .positions
+            .entry(position_key)
+            .or_insert_with(|| PositionSnapshot {
+                venue: self.venue.clone(),
+                instrument: req.instrument.clone(),
+                qty: 0.0,
+                avg_price: fill_price,
+                mark_price: Some(fill_price),
+                unrealized_pnl: Some(0.0),
+            });
 
-        position.qty = if new_abs <= epsilon { 0.0 } else { new_qty };
-        position.mark_price = Some(fill_price);
+        position.apply_fill(&req.side, qty, fill_price);
+    }
+
+    /// Delivers orders whose latency has elapsed and expires `Day` orders.
+    fn advance(&self, state: &mut PaperState, now: i64) {
+        let (due, waiting) = std::mem::take(&mut state.pending)
+            .into_iter()
+            .partition(|working: &WorkingOrder| working.active_at_ms <= now);
+        state.pending = waiting;
+        for working in due {
+            self.activate(state, working, now);
+        }
+
+        let resting = std::mem::take(&mut state.resting);
+        for working in resting {
+            let expired = working.request.tif == Some(TimeInForce::Day)
+                && now >= day_end_ms(working.active_at_ms);
+            if expired {
+                state.close_order(&working.venue_order_id, OrderStatus::Canceled, now);
+            } else {
+                state.resting.push(working);
+            }
+        }
+    }
+
+    /// An order arriving at the book: it trades what it can at the touch and
+    /// then rests, or cancels if it is a market, IOC or FOK order.
+    fn activate(&self, state: &mut PaperState, working: WorkingOrder, now: i64) {
+        let req = &working.request;
+        let remaining = state.remaining_qty(&working.venue_order_id);
+        if remaining <= QTY_EPSILON {
+            return;
+        }
+        let Some(book) = state.books.get(&req.instrument.venue_symbol) else {
+            let takes_only = req.order_type == OrderType::Market
+                || matches!(req.tif, Some(TimeInForce::Ioc | TimeInForce::Fok));
+            if takes_only {
+                // Nothing to take yet.
+                state.close_order(&working.venue_order_id, OrderStatus::Canceled, now);
+            } else {
+                // Nothing to cross yet; wait for the first book update.
+                state.resting.push(working);
+            }
+            return;
+        };
+
+        let marketable = book
+            .touch(&req.side)
+            .filter(|(price, _)| crosses(&req.side, req.limit_price, *price));
+        if req.post_only && marketable.is_some() {
+            state.close_order(&working.venue_order_id, OrderStatus::Rejected, now);
+            return;
+        }
+
+        let is_market = req.order_type == OrderType::Market;
+        let tif = req.tif.clone().unwrap_or(if is_market {
+            TimeInForce::Ioc
+        } else {
+            TimeInForce::Gtc
+        });
+        if let Some((price, available)) = marketable {
+            let fill_or_kill_short = tif == TimeInForce::Fok && available + QTY_EPSILON < remaining;
+            let qty = remaining.min(available);
+            if !fill_or_kill_short {
+                if let Some(book) = state.books.get_mut(&req.instrument.venue_symbol) {
+                    book.take(&req.side, qty);
+                }
+                self.book_fill(state, &working, qty, price, Liquidity::Taker, now);
+            }
+        }
+
+        if state.remaining_qty(&working.venue_order_id) > QTY_EPSILON {
+            if is_market || matches!(tif, TimeInForce::Ioc | TimeInForce::Fok) {
+                state.close_order(&working.venue_order_id, OrderStatus::Canceled, now);
+            } else {
+                state.resting.push(working);
+            }
+        }
+    }
+
+    /// Fills resting limits on `venue_symbol` the book (or a trade at `price`
+    /// for `size`) has reached, in time priority, at their limit price.
+    fn match_resting(
+        &self,
+        state: &mut PaperState,
+        venue_symbol: &str,
+        trade: Option<(f64, f64)>,
+        now: i64,
+    ) {
+        let mut trade_size = trade.map(|(_, size)| size);
+        let resting = std::mem::take(&mut state.resting);
+        for working in resting {
+            let req = &working.request;
+            if req.instrument.venue_symbol != venue_symbol {
+                state.resting.push(working);
+                continue;
+            }
+            let remaining = state.remaining_qty(&working.venue_order_id);
+            if remaining <= QTY_EPSILON {
+                continue;
+            }
+            let reached = match trade {
+                Some((price, _)) => Some((price, trade_size.unwrap_or(0.0))),
+                None => state
+                    .books
+                    .get(venue_symbol)
+                    .and_then(|book| book.touch(&req.side)),
+            }
+            .filter(|(price, size)| {
+                *size > QTY_EPSILON && crosses(&req.side, req.limit_price, *price)
+            });
+
+            if let Some((price, available)) = reached {
+                let qty = remaining.min(available);
+                match trade_size.as_mut() {
+                    Some(size) => *size -= qty,
+                    None => {
+                        if let Some(book) = state.books.get_mut(venue_symbol) {
+                            book.take(&req.side, qty);
+                        }
+                    }
+                }
+                let fill_price = req.limit_price.unwrap_or(price);
+                self.book_fill(state, &working, qty, fill_price, Liquidity::Maker, now);
+            }
+            if state.remaining_qty(&working.venue_order_id) > QTY_EPSILON {
+                state.resting.push(working);
+            }
+        }
     }
 }
 
@@ -146,24 +617,67 @@ impl ExchangeAdapter for PaperExchangeAdapter {
         "paper"
     }
 
-    fn connect_market_data(&self) -> ExchangeResultFuture<'_, ()> {
-        Box::pin(async { Ok(()) })
+    fn connect_market_data(
+        &self,
+        venue_symbols: Vec<String>,
+        sink: MarketDataSink,
+    ) -> ExchangeResultFuture<'_, ()> {
+        Box::pin(async move {
+            let venue = self.venue.clone();
+            let guard = Arc::downgrade(&self.feed_guard);
+            let clock = self.clock.clone();
+            tokio::spawn(async move {
+                let mut ticker =
+                    tokio::time::interval(Duration::from_millis(PAPER_QUOTE_INTERVAL_MS));
+                loop {
+                    ticker.tick().await;
+                    if guard.upgrade().is_none() {
+                        break;
+                    }
+                    for venue_symbol in &venue_symbols {
+                        sink(MarketDataUpdate::Quote(Self::synthetic_quote(
+                            &venue,
+                            venue_symbol,
+                            clock.now_ms(),
+                        )));
+                    }
+                }
+            });
+            Ok(())
+        })
     }
 
     fn place_order(&self, req: NormalizedOrderRequest) -> ExchangeResultFuture<'_, OrderAck> {
         Box::pin(async move {
-            let mark = req
-                .limit_price
-                .unwrap_or_else(|| Self::deterministic_mark_price(&req.instrument.venue_symbol));
-            let slippage = Self::slippage_bps(
-                &req.instrument.instrument_type,
-                &req.instrument.option_right,
-            ) / 10_000.0;
-            let fill_price = match req.side {
-                OrderSide::Buy => mark * (1.0 + slippage),
-                OrderSide::Sell => mark * (1.0 - slippage),
-            };
-            let now = Self::now_ms();
+            if !(req.qty.is_finite() && req.qty > 0.0) {
+                return Err(ExchangeError::new(
+                    "invalid_quantity",
+                    "order qty must be > 0",
+                    false,
+                ));
+            }
+            if req.post_only && (req.order_type == OrderType::Market || req.limit_price.is_none()) {
+                return Err(ExchangeError::new(
+                    "invalid_order",
+                    "post-only orders need a limit price",
+                    false,
+                ));
+            }
+            if req.stop_price.is_some() {
+                return Err(ExchangeError::new(
+                    "stop_unsupported",
+                    "paper adapter does not simulate stop orders",
+                    false,
+                ));
+            }
+            if req.quote_size.is_some() {
+                return Err(ExchangeError::new(
+                    "quote_size_unsupported",
+                    "paper orders are sized by qty",
+                    false,
+                ));
+            }
+            let now = self.clock.now_ms();
             let venue_order_id = format!("paper-{}", Uuid::new_v4().as_simple());
 
             let order = OrderSnapshot {
@@ -174,76 +688,58 @@ impl ExchangeAdapter for PaperExchangeAdapter {
                 instrument: req.instrument.clone(),
                 side: req.side.clone(),
                 order_type: req.order_type.clone(),
-                status: OrderStatus::Filled,
+                status: OrderStatus::New,
                 qty: req.qty,
-                filled_qty: req.qty,
+                filled_qty: 0.0,
                 limit_price: req.limit_price,
-                avg_fill_price: Some(fill_price),
+                avg_fill_price: None,
                 created_at_ms: now,
                 updated_at_ms: now,
                 simulated: true,
             };
 
-            let fill = FillReport {
-                venue: self.venue.clone(),
-                venue_fill_id: format!("fill-{}", Uuid::new_v4().as_simple()),
-                venue_order_id: venue_order_id.clone(),
-                client_order_id: req.client_order_id.clone(),
-                strategy_id: req.strategy_id.clone(),
-                instrument: req.instrument.clone(),
-                side: req.side.clone(),
-                qty: req.qty,
-                price: fill_price,
-                fee: (req.qty * fill_price * 0.0005).max(0.0),
-                fee_asset: Some(Self::quote_asset(&req)),
-                liquidity: Some("taker".to_string()),
-                simulated: true,
-                ts_ms: now,
-            };
-
             let mut state = self.state.lock().await;
+            self.advance(&mut state, now);
+            if req.reduce_only {
+                let position_key =
+                    format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
+                let held = state
+                    .positions
+                    .get(&position_key)
+                    .map_or(0.0, |position| position.qty);
+                check_reduce_only(held, &req.side, req.qty)?;
+            }
             state.orders.insert(venue_order_id.clone(), order);
-            state.fills.push(fill);
-
-            let quote = Self::quote_asset(&req);
-            let base = Self::base_asset(&req);
-            let notional = req.qty * fill_price;
+            state.dirty = true;
+            state.pending.push(WorkingOrder {
+                venue_order_id: venue_order_id.clone(),
+                request: req.clone(),
+                active_at_ms: now + self.matching.latency_ms.max(0),
+            });
+            self.advance(&mut state, now);
 
-            match req.side {
-                OrderSide::Buy => {
-                    Self::apply_balance_delta(&mut state, &self.venue, &quote, -notional);
-                    Self::apply_balance_delta(&mut state, &self.venue, &base, req.qty);
-                }
-                OrderSide::Sell => {
-                    Self::apply_balance_delta(&mut state, &self.venue, &quote, notional);
-                    Self::apply_balance_delta(&mut state, &self.venue, &base, -req.qty);
-                }
+            let order = state.orders[&venue_order_id].clone();
+            if order.status == OrderStatus::Rejected {
+                state.orders.remove(&venue_order_id);
+                self.flush(&mut state);
+                return Err(ExchangeError::new(
+                    "post_only_would_cross",
+                    format!(
+                        "post-only {:?} at {:?} would cross the {} book",
+                        req.side, req.limit_price, req.instrument.venue_symbol
+                    ),
+                    false,
+                ));
             }
-
-            let position_key = format!("{}:{}", req.instrument.venue, req.instrument.venue_symbol);
-            let position =
-                state
-                    .positions
-                    .entry(position_key)
-                    .or_insert_with(|| PositionSnapshot {
-                        venue: self.venue.clone(),
-                        instrument: req.instrument.clone(),
-                        qty: 0.0,
-                        avg_price: fill_price,
-                        mark_price: Some(fill_price),
-                        unrealized_pnl: Some(0.0),
-                    });
-
-            Self::update_position_after_fill(position, &req.side, req.qty, fill_price);
-
+            self.flush(&mut state);
 
             Ok(OrderAck {
                 venue_order_id,
                 client_order_id: req.client_order_id,
                 accepted: true,
-                status: OrderStatus::Filled,
-                filled_qty: req.qty,
-                avg_fill_price: Some(fill_price),
+                status: order.status,
+                filled_qty: order.filled_qty,
+                avg_fill_price: order.avg_fill_price,
                 simulated: true,
                 reason: None,
                 ts_ms: now,
@@ -254,7 +750,9 @@ impl ExchangeAdapter for PaperExchangeAdapter {
     fn cancel_order(&self, venue_order_id: &str) -> ExchangeResultFuture<'_, ()> {
         let venue_order_id = venue_order_id.to_string();
         Box::pin(async move {
+            let now = self.clock.now_ms();
             let mut state = self.state.lock().await;
+            self.advance(&mut state, now);
             let order = state.orders.get_mut(&venue_order_id).ok_or_else(|| {
                 ExchangeError::new(
                     "order_not_found",
@@ -263,24 +761,38 @@ impl ExchangeAdapter for PaperExchangeAdapter {
                 )
             })?;
 
-            if matches!(order.status, OrderStatus::Filled | OrderStatus::Rejected) {
-                return Ok(());
+            if !matches!(
+                order.status,
+                OrderStatus::Filled | OrderStatus::Rejected | OrderStatus::Canceled
+            ) {
+                order.status = OrderStatus::Canceled;
+                order.updated_at_ms = now;
+                state.dirty = true;
+                state
+                    .pending
+                    .retain(|working| working.venue_order_id != venue_order_id);
+                state
+                    .resting
+                    .retain(|working| working.venue_order_id != venue_order_id);
             }
-
-            order.status = OrderStatus::Canceled;
-            order.updated_at_ms = Self::now_ms();
+            self.flush(&mut state);
             Ok(())
         })
     }
 
     fn get_order(&self, venue_order_id: &str) -> ExchangeResultFuture<'_, Option<OrderSnapshot>> {
         let venue_order_id = venue_order_id.to_string();
-        Box::pin(async move { Ok(self.state.lock().await.orders.get(&venue_order_id).cloned()) })
+        Box::pin(async move {
+            let mut state = self.state.lock().await;
+            self.advance(&mut state, self.clock.now_ms());
+            Ok(state.orders.get(&venue_order_id).cloned())
+        })
     }
 
     fn open_orders(&self) -> ExchangeResultFuture<'_, Vec<OpenOrderSnapshot>> {
         Box::pin(async move {
-            let state = self.state.lock().await;
+            let mut state = self.state.lock().await;
+            self.advance(&mut state, self.clock.now_ms());
             Ok(state
                 .orders
                 .values()
@@ -302,7 +814,8 @@ impl ExchangeAdapter for PaperExchangeAdapter {
         limit: usize,
     ) -> ExchangeResultFuture<'_, Vec<FillReport>> {
         Box::pin(async move {
-            let state = self.state.lock().await;
+            let mut state = self.state.lock().await;
+            self.advance(&mut state, self.clock.now_ms());
             let mut fills: Vec<FillReport> = state
                 .fills
                 .iter()
@@ -319,26 +832,44 @@ impl ExchangeAdapter for PaperExchangeAdapter {
 
     fn sync_positions(&self) -> ExchangeResultFuture<'_, Vec<PositionSnapshot>> {
         Box::pin(async move {
-            let state = self.state.lock().await;
+            let mut state = self.state.lock().await;
+            self.advance(&mut state, self.clock.now_ms());
             Ok(state.positions.values().cloned().collect())
         })
     }
 
     fn sync_balances(&self) -> ExchangeResultFuture<'_, Vec<BalanceSnapshot>> {
         Box::pin(async move {
-            let state = self.state.lock().await;
+            let mut state = self.state.lock().await;
+            self.advance(&mut state, self.clock.now_ms());
             Ok(state.balances.values().cloned().collect())
         })
     }
 
+    fn quote(&self, venue_symbol: &str) -> ExchangeResultFuture<'_, Option<QuoteSnapshot>> {
+        let venue_symbol = venue_symbol.to_string();
+        Box::pin(async move {
+            let state = self.state.lock().await;
+            if let Some(book) = state.books.get(&venue_symbol) {
+                return Ok(Some(book.quote.clone()));
+            }
+            Ok(Some(Self::synthetic_quote(
+                &self.venue,
+                &venue_symbol,
+                self.clock.now_ms(),
+            )))
+        })
+    }
+
     fn health(&self) -> ExchangeValueFuture<'_, ExchangeHealth> {
         Box::pin(async move {
+            let ledger_error = self.ledger.as_ref().and_then(LedgerWriter::error);
             ExchangeHealth {
                 venue: self.venue.clone(),
-                healthy: true,
+                healthy: ledger_error.is_none(),
                 connected_market_data: true,
                 connected_trading: true,
-                message: Some("paper adapter ready".to_string()),
+                message: Some(ledger_error.unwrap_or_else(|| "paper adapter ready".to_string())),
             }
         })
     }
@@ -380,7 +911,9 @@ mod tests {
             side,
             order_type: OrderType::Limit,
             qty,
+            quote_size: None,
             limit_price: Some(limit_price),
+            stop_price: None,
             tif: Some(TimeInForce::Gtc),
             post_only: false,
             reduce_only: false,
@@ -391,6 +924,7 @@ mod tests {
     #[tokio::test]
     async fn fills_spot_order_and_updates_balances() {
         let adapter = PaperExchangeAdapter::new("paper");
+        adapter.apply_market_data(&book_quote(99.0, 100.0, 0)).await;
         let order = spot_order("client-1", OrderSide::Buy, 1.0, 100.0);
 
         let ack = adapter.place_order(order).await.expect("order should fill");
@@ -401,13 +935,39 @@ mod tests {
         assert!(balances.iter().any(|b| b.asset == "BTC" && b.total > 0.0));
     }
 
+    #[tokio::test]
+    async fn stop_and_quote_sized_orders_are_refused() {
+        let adapter = PaperExchangeAdapter::new("paper");
+        adapter.apply_market_data(&book_quote(99.0, 100.0, 0)).await;
+
+        let mut stop = spot_order("client-stop", OrderSide::Buy, 1.0, 100.0);
+        stop.stop_price = Some(101.0);
+        let err = adapter.place_order(stop).await.expect_err("stop refused");
+        assert_eq!(err.code, "stop_unsupported");
+
+        let mut quote_sized = spot_order("client-quote", OrderSide::Buy, 0.001, 100.0);
+        quote_sized.order_type = OrderType::Market;
+        quote_sized.limit_price = None;
+        quote_sized.quote_size = Some(500.0);
+        let err = adapter
+            .place_order(quote_sized)
+            .await
+            .expect_err("quote size refused");
+        assert_eq!(err.code, "quote_size_unsupported");
+        assert!(adapter.open_orders().await.expect("orders").is_empty());
+    }
+
     #[tokio::test]
     async fn sell_reduction_keeps_existing_long_basis() {
         let adapter = PaperExchangeAdapter::new("paper");
+        adapter.apply_market_data(&book_quote(99.0, 100.0, 0)).await;
         adapter
             .place_order(spot_order("client-1", OrderSide::Buy, 2.0, 100.0))
             .await
             .expect("buy should fill");
+        adapter
+            .apply_market_data(&book_quote(120.0, 121.0, 0))
+            .await;
         adapter
             .place_order(spot_order("client-2", OrderSide::Sell, 1.0, 120.0))
             .await
@@ -419,16 +979,20 @@ mod tests {
             .find(|p| p.instrument.venue_symbol == "BTC-USD")
             .expect("position should exist");
         assert!((position.qty - 1.0).abs() < 1e-9);
-        assert!((position.avg_price - 100.05).abs() < 1e-9);
+        assert!((position.avg_price - 100.0).abs() < 1e-9);
     }
 
     #[tokio::test]
     async fn side_flip_resets_basis_to_flip_fill_price() {
         let adapter = PaperExchangeAdapter::new("paper");
+        adapter.apply_market_data(&book_quote(99.0, 100.0, 0)).await;
         adapter
             .place_order(spot_order("client-1", OrderSide::Buy, 1.0, 100.0))
             .await
             .expect("buy should fill");
+        adapter
+            .apply_market_data(&book_quote(120.0, 121.0, 0))
+            .await;
         adapter
             .place_order(spot_order("client-2", OrderSide::Sell, 2.0, 120.0))
             .await
@@ -440,16 +1004,22 @@ mod tests {
             .find(|p| p.instrument.venue_symbol == "BTC-USD")
             .expect("position should exist");
         assert!((position.qty + 1.0).abs() < 1e-9);
-        assert!((position.avg_price - 119.94).abs() < 1e-9);
+        assert!((position.avg_price - 120.0).abs() < 1e-9);
     }
 
     #[tokio::test]
     async fn short_addition_recomputes_weighted_basis() {
         let adapter = PaperExchangeAdapter::new("paper");
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 0))
+            .await;
         adapter
             .place_order(spot_order("client-1", OrderSide::Sell, 1.0, 100.0))
             .await
             .expect("sell should fill");
+        adapter
+            .apply_market_data(&book_quote(110.0, 111.0, 0))
+            .await;
         adapter
             .place_order(spot_order("client-2", OrderSide::Sell, 1.0, 110.0))
             .await
@@ -461,6 +1031,405 @@ mod tests {
             .find(|p| p.instrument.venue_symbol == "BTC-USD")
             .expect("position should exist");
         assert!((position.qty + 2.0).abs() < 1e-9);
-        assert!((position.avg_price - 104.9475).abs() < 1e-9);
+        assert!((position.avg_price - 105.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn market_data_stream_emits_synthetic_quotes() {
+        let adapter = PaperExchangeAdapter::new("paper");
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_received = Arc::clone(&received);
+        let sink: MarketDataSink = Arc::new(move |update| {
+            sink_received.lock().expect("sink lock").push(update);
+        });
+
+        adapter
+            .connect_market_data(vec!["BTC-USD".to_string()], sink)
+            .await
+            .expect("paper stream should connect");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let received = received.lock().expect("sink lock");
+        match received.first() {
+            Some(MarketDataUpdate::Quote(quote)) => {
+                assert_eq!(quote.venue_symbol, "BTC-USD");
+                assert!(quote.bid < quote.ask);
+            }
+            other => panic!("expected a quote, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn timestamps_follow_the_injected_clock() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(5_000));
+        let adapter = PaperExchangeAdapter::with_clock("paper", clock.clone());
+        adapter
+            .apply_market_data(&book_quote(99.0, 100.0, 5_000))
+            .await;
+
+        let ack = adapter
+            .place_order(spot_order("client-clock", OrderSide::Buy, 1.0, 100.0))
+            .await
+            .expect("order should fill");
+        clock.advance(1_500);
+        // Symbols without book data are quoted synthetically at the clock's time.
+        let quote = adapter
+            .quote("ETH-USD")
+            .await
+            .expect("quote")
+            .expect("paper always quotes");
+
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .expect("lookup")
+            .expect("order exists");
+        assert_eq!(order.created_at_ms, 5_000);
+        let fills = adapter.fills_since(0, 10).await.expect("fills");
+        assert_eq!(fills[0].ts_ms, 5_000);
+        assert_eq!(quote.ts_ms, 6_500);
+    }
+
+    fn book_quote(bid: f64, ask: f64, ts_ms: i64) -> MarketDataUpdate {
+        MarketDataUpdate::Quote(QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(bid),
+            ask: Some(ask),
+            last: None,
+            mark: None,
+            ts_ms,
+        })
+    }
+
+    fn matching_adapter(clock: SharedClock, matching: MatchingConfig) -> PaperExchangeAdapter {
+        PaperExchangeAdapter::with_clock("paper", clock).with_matching(matching)
+    }
+
+    #[tokio::test]
+    async fn resting_limit_fills_partially_as_the_book_reaches_it() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(0));
+        let adapter = matching_adapter(
+            clock.clone(),
+            MatchingConfig {
+                touch_liquidity: Some(0.6),
+                ..MatchingConfig::default()
+            },
+        );
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 0))
+            .await;
+
+        let ack = adapter
+            .place_order(spot_order("rest-1", OrderSide::Buy, 1.0, 99.0))
+            .await
+            .expect("order accepted");
+        assert_eq!(ack.status, OrderStatus::New);
+        assert_eq!(adapter.open_orders().await.expect("open").len(), 1);
+
+        adapter
+            .apply_market_data(&book_quote(98.0, 98.5, 1_000))
+            .await;
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert!((order.filled_qty - 0.6).abs() < 1e-9);
+
+        adapter
+            .apply_market_data(&book_quote(98.0, 98.5, 2_000))
+            .await;
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.avg_fill_price, Some(99.0));
+
+        let fills = adapter.fills_since(0, 10).await.expect("fills");
+        assert_eq!(fills.len(), 2);
+        assert!(fills
+            .iter()
+            .all(|fill| fill.liquidity.as_deref() == Some("maker") && fill.price == 99.0));
+        // Coinbase's entry maker tier: 40 bps of each fill, paid from USD.
+        assert!((fills[0].fee - 0.6 * 99.0 * 0.004).abs() < 1e-9);
+        let balances = adapter.sync_balances().await.expect("balances");
+        let usd = balances.iter().find(|b| b.asset == "USD").expect("usd");
+        assert!((usd.total - (1_000_000.0 - 99.0 - 99.0 * 0.004)).abs() < 1e-6);
+        assert!(adapter.open_orders().await.expect("open").is_empty());
+    }
+
+    #[tokio::test]
+    async fn marketable_orders_take_the_touch_and_honour_time_in_force() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(0));
+        let adapter = matching_adapter(
+            clock.clone(),
+            MatchingConfig {
+                touch_liquidity: Some(1.0),
+                ..MatchingConfig::default()
+            },
+        );
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 0))
+            .await;
+
+        let mut post_only = spot_order("post-1", OrderSide::Buy, 1.0, 102.0);
+        post_only.post_only = true;
+        let err = adapter.place_order(post_only).await.unwrap_err();
+        assert_eq!(err.code, "post_only_would_cross");
+
+        let mut fok = spot_order("fok-1", OrderSide::Buy, 2.0, 102.0);
+        fok.tif = Some(TimeInForce::Fok);
+        let ack = adapter.place_order(fok).await.expect("fok accepted");
+        assert_eq!((ack.status, ack.filled_qty), (OrderStatus::Canceled, 0.0));
+
+        let mut ioc = spot_order("ioc-1", OrderSide::Buy, 2.0, 102.0);
+        ioc.tif = Some(TimeInForce::Ioc);
+        let ack = adapter.place_order(ioc).await.expect("ioc accepted");
+        assert_eq!(ack.status, OrderStatus::Canceled);
+        assert_eq!(ack.filled_qty, 1.0);
+        assert_eq!(ack.avg_fill_price, Some(101.0));
+
+        // The touch is used up until the next update, so a GTC buy rests.
+        let ack = adapter
+            .place_order(spot_order("gtc-1", OrderSide::Buy, 1.0, 102.0))
+            .await
+            .expect("gtc accepted");
+        assert_eq!(ack.status, OrderStatus::New);
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 1_000))
+            .await;
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.avg_fill_price, Some(102.0));
+    }
+
+    #[tokio::test]
+    async fn latency_delays_orders_reaching_the_book() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(0));
+        let adapter = matching_adapter(
+            clock.clone(),
+            MatchingConfig {
+                latency_ms: 250,
+                ..MatchingConfig::default()
+            },
+        );
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 0))
+            .await;
+
+        let ack = adapter
+            .place_order(spot_order("late-1", OrderSide::Buy, 1.0, 101.0))
+            .await
+            .expect("accepted");
+        assert_eq!(ack.status, OrderStatus::New);
+        let canceled = adapter
+            .place_order(spot_order("late-2", OrderSide::Buy, 1.0, 101.0))
+            .await
+            .expect("accepted");
+        adapter
+            .cancel_order(&canceled.venue_order_id)
+            .await
+            .expect("cancel in flight");
+
+        // The book moves away before the order arrives.
+        clock.set(100);
+        adapter
+            .apply_market_data(&book_quote(101.5, 102.0, 100))
+            .await;
+        clock.set(250);
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+        assert!(adapter.fills_since(0, 10).await.unwrap().is_empty());
+
+        // A print through the limit fills the resting order as maker.
+        let trade = MarketDataUpdate::Trade(exchange_core::TradePrint {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            price: 100.5,
+            size: Some(5.0),
+            side: Some(OrderSide::Sell),
+            ts_ms: 300,
+        });
+        clock.set(300);
+        adapter.apply_market_data(&trade).await;
+        let fills = adapter.fills_since(0, 10).await.unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].price, fills[0].ts_ms), (101.0, 300));
+        let canceled = adapter
+            .get_order(&canceled.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(canceled.status, OrderStatus::Canceled);
+    }
+
+    #[tokio::test]
+    async fn orders_without_book_data_never_fill_before_the_book_reaches_them() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(1_000));
+        let adapter = matching_adapter(clock.clone(), MatchingConfig::default());
+
+        let ack = adapter
+            .place_order(spot_order("bookless-1", OrderSide::Buy, 1.0, 100.0))
+            .await
+            .expect("accepted");
+        assert_eq!((ack.status, ack.filled_qty), (OrderStatus::New, 0.0));
+        let mut ioc = spot_order("bookless-ioc", OrderSide::Buy, 1.0, 100.0);
+        ioc.tif = Some(TimeInForce::Ioc);
+        let ack = adapter.place_order(ioc).await.expect("accepted");
+        assert_eq!((ack.status, ack.filled_qty), (OrderStatus::Canceled, 0.0));
+        // No synthetic mark to trade against: market orders cancel unfilled.
+        let mut market = spot_order("bookless-market", OrderSide::Sell, 1.0, 100.0);
+        market.order_type = OrderType::Market;
+        market.limit_price = None;
+        let ack = adapter.place_order(market).await.expect("accepted");
+        assert_eq!((ack.status, ack.filled_qty), (OrderStatus::Canceled, 0.0));
+
+        adapter
+            .apply_market_data(&book_quote(98.0, 99.0, 2_000))
+            .await;
+        let fills = adapter.fills_since(0, 10).await.expect("fills");
+        assert_eq!(fills.len(), 1);
+        // Filled at its limit, never through it.
+        assert_eq!((fills[0].qty, fills[0].price), (1.0, 100.0));
+    }
+
+    #[tokio::test]
+    async fn day_orders_expire_at_the_end_of_the_utc_day() {
+        let clock = Arc::new(exchange_core::SimulatedClock::new(1_000));
+        let adapter = matching_adapter(clock.clone(), MatchingConfig::default());
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 1_000))
+            .await;
+
+        let mut order = spot_order("day-1", OrderSide::Sell, 1.0, 110.0);
+        order.tif = Some(TimeInForce::Day);
+        let ack = adapter.place_order(order).await.expect("accepted");
+        assert_eq!(adapter.open_orders().await.unwrap().len(), 1);
+
+        clock.set(86_400_000);
+        assert!(adapter.open_orders().await.unwrap().is_empty());
+        let order = adapter
+            .get_order(&ack.venue_order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.status, OrderStatus::Canceled);
+    }
+
+    fn ledger_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("paper-ledger-{}", Uuid::new_v4().as_simple()))
+            .join(format!("{name}.json"))
+    }
+
+    #[tokio::test]
+    async fn ledger_resumes_orders_fills_and_balances_after_restart() {
+        let path = ledger_path("resume");
+        let clock = Arc::new(exchange_core::SimulatedClock::new(1_000));
+        let balances = BTreeMap::from([("USD".to_string(), 5_000.0), ("BTC".to_string(), 1.0)]);
+        let adapter = matching_adapter(clock.clone(), MatchingConfig::default())
+            .with_starting_balances(balances.clone())
+            .with_ledger(&path)
+            .expect("new ledger");
+        adapter
+            .apply_market_data(&book_quote(100.0, 101.0, 1_000))
+            .await;
+        adapter
+            .place_order(spot_order("fill-1", OrderSide::Buy, 1.0, 101.0))
+            .await
+            .expect("filled");
+        let resting = adapter
+            .place_order(spot_order("rest-1", OrderSide::Buy, 1.0, 95.0))
+            .await
+            .expect("rests");
+        let before = adapter.sync_balances().await.unwrap();
+        adapter.settle_ledger().await;
+        drop(adapter);
+        let written = std::fs::read_to_string(&path).expect("ledger written");
+        assert!(!written.contains('\n'), "ledger is written compactly");
+
+        let restarted = matching_adapter(clock, MatchingConfig::default())
+            .with_starting_balances(balances)
+            .with_ledger(&path)
+            .expect("existing ledger");
+        assert_eq!(restarted.fills_since(0, 10).await.unwrap().len(), 1);
+        let open = restarted.open_orders().await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].order.venue_order_id, resting.venue_order_id);
+        let usd = |balances: &[BalanceSnapshot]| {
+            balances
+                .iter()
+                .find(|balance| balance.asset == "USD")
+                .map(|balance| balance.total)
+        };
+        let after = restarted.sync_balances().await.unwrap();
+        assert_eq!(usd(&after), usd(&before));
+        assert!(usd(&after).unwrap() < 5_000.0 - 101.0 + 1e-9);
+
+        // The resting order still trades once the book reaches it.
+        restarted
+            .apply_market_data(&book_quote(94.0, 95.0, 2_000))
+            .await;
+        assert!(restarted.open_orders().await.unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn reset_and_fund_rewrite_the_account() {
+        let path = ledger_path("reset");
+        let adapter = PaperExchangeAdapter::new("paper")
+            .with_starting_balances(BTreeMap::from([("USD".to_string(), 1_000.0)]))
+            .with_ledger(&path)
+            .expect("new ledger");
+        adapter
+            .place_order(spot_order("client-1", OrderSide::Buy, 1.0, 100.0))
+            .await
+            .expect("filled");
+
+        let balances = adapter.reset(None).await.expect("reset");
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].total, 1_000.0);
+        assert!(adapter.fills_since(0, 10).await.unwrap().is_empty());
+        assert!(adapter.sync_positions().await.unwrap().is_empty());
+
+        let balances = adapter
+            .fund(&BTreeMap::from([
+                ("USD".to_string(), 500.0),
+                ("ETH".to_string(), 2.0),
+            ]))
+            .await
+            .expect("funded");
+        let total = |asset: &str| {
+            balances
+                .iter()
+                .find(|balance| balance.asset == asset)
+                .map(|balance| balance.total)
+        };
+        assert_eq!(total("USD"), Some(1_500.0));
+        assert_eq!(total("ETH"), Some(2.0));
+        let overdraw = adapter
+            .fund(&BTreeMap::from([("ETH".to_string(), -3.0)]))
+            .await
+            .expect_err("cannot withdraw more than available");
+        assert_eq!(overdraw.code, "insufficient_balance");
+        adapter.settle_ledger().await;
+
+        let reloaded = PaperExchangeAdapter::new("paper")
+            .with_ledger(&path)
+            .expect("existing ledger");
+        assert_eq!(reloaded.sync_balances().await.unwrap().len(), 2);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
     }
 }
@@ -0,0 +1,114 @@
+//! Top-of-book simulation for paper orders: the book each symbol trades
+//! against and the knobs that make paper fills resemble a live venue.
+
+use exchange_core::{FeeSchedule, OrderSide, QuoteSnapshot};
+
+const DAY_MS: i64 = 86_400_000;
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// How paper orders reach and trade against the book.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchingConfig {
+    /// Delay between accepting an order and it reaching the book.
+    pub latency_ms: i64,
+    /// Quantity available at the touch on each side per book update; `None`
+    /// lets any size fill at the touch.
+    pub touch_liquidity: Option<f64>,
+    /// Schedule charged on every fill; `None` charges each instrument's
+    /// venue schedule (`FeeSchedule::for_instrument`).
+    pub fees: Option<FeeSchedule>,
+}
+
+/// Latest quote for a symbol and the touch size left until the next update.
+#[derive(Debug, Clone)]
+pub(crate) struct Book {
+    pub(crate) quote: QuoteSnapshot,
+    bid_size: f64,
+    ask_size: f64,
+}
+
+impl Book {
+    pub(crate) fn new(quote: QuoteSnapshot, touch_liquidity: Option<f64>) -> Self {
+        let size = touch_liquidity.unwrap_or(f64::INFINITY).max(0.0);
+        Self {
+            quote,
+            bid_size: size,
+            ask_size: size,
+        }
+    }
+
+    /// Price and size an incoming order on `side` can take: the ask for buys,
+    /// the bid for sells.
+    pub(crate) fn touch(&self, side: &OrderSide) -> Option<(f64, f64)> {
+        let (price, size) = match side {
+            OrderSide::Buy => (self.quote.ask, self.ask_size),
+            OrderSide::Sell => (self.quote.bid, self.bid_size),
+        };
+        price
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .filter(|_| size > PRICE_EPSILON)
+            .map(|price| (price, size))
+    }
+
+    pub(crate) fn take(&mut self, side: &OrderSide, qty: f64) {
+        let size = match side {
+            OrderSide::Buy => &mut self.ask_size,
+            OrderSide::Sell => &mut self.bid_size,
+        };
+        *size = (*size - qty).max(0.0);
+    }
+}
+
+/// Whether a `side` order limited at `limit` trades at `price`; orders
+/// without a limit take any price.
+pub(crate) fn crosses(side: &OrderSide, limit: Option<f64>, price: f64) -> bool {
+    match (side, limit) {
+        (_, None) => true,
+        (OrderSide::Buy, Some(limit)) => price <= limit + PRICE_EPSILON,
+        (OrderSide::Sell, Some(limit)) => price >= limit - PRICE_EPSILON,
+    }
+}
+
+/// First millisecond of the UTC day after `ts_ms`, when `Day` orders expire.
+pub(crate) fn day_end_ms(ts_ms: i64) -> i64 {
+    (ts_ms.div_euclid(DAY_MS) + 1) * DAY_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(bid: f64, ask: f64) -> QuoteSnapshot {
+        QuoteSnapshot {
+            venue: "paper".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(bid),
+            ask: Some(ask),
+            last: None,
+            mark: None,
+            ts_ms: 0,
+        }
+    }
+
+    #[test]
+    fn touch_size_is_consumed_until_the_next_update() {
+        let mut book = Book::new(quote(99.0, 101.0), Some(2.0));
+        assert_eq!(book.touch(&OrderSide::Buy), Some((101.0, 2.0)));
+
+        book.take(&OrderSide::Buy, 1.5);
+        assert_eq!(book.touch(&OrderSide::Buy), Some((101.0, 0.5)));
+        book.take(&OrderSide::Buy, 1.0);
+        assert_eq!(book.touch(&OrderSide::Buy), None);
+        assert_eq!(book.touch(&OrderSide::Sell), Some((99.0, 2.0)));
+    }
+
+    #[test]
+    fn limits_cross_at_or_through_their_price() {
+        assert!(crosses(&OrderSide::Buy, Some(100.0), 100.0));
+        assert!(!crosses(&OrderSide::Buy, Some(100.0), 100.5));
+        assert!(crosses(&OrderSide::Sell, Some(100.0), 100.5));
+        assert!(crosses(&OrderSide::Sell, None, 1.0));
+        assert_eq!(day_end_ms(1), 86_400_000);
+        assert_eq!(day_end_ms(86_400_000), 172_800_000);
+    }
+}
@@ -257,6 +257,7 @@ impl HardSafetyCage {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -1,8 +1,13 @@
 //! Strategy abstraction for regime-aware signal generation.
 
 use exchange_core::NormalizedOrderRequest;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+mod regime;
+
+pub use regime::{RegimeConfig, RegimeEngine, RegimeUpdate};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum StrategyFamily {
     Arbitrage,
@@ -14,7 +19,7 @@ pub enum StrategyFamily {
     Custom,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum MarketRegime {
     Trending,
     MeanReverting,
@@ -25,7 +30,7 @@ pub enum MarketRegime {
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct RegimeContext {
     pub venue: String,
     pub symbol: String,
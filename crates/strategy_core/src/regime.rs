@@ -0,0 +1,435 @@
+//! Regime engine: derives `RegimeContext` per instrument from quote and trade
+//! streams and classifies `MarketRegime` with hysteresis.
+
+use std::collections::{HashMap, VecDeque};
+
+use exchange_core::{MarketDataUpdate, OrderSide, QuoteSnapshot, TradePrint};
+use serde::{Deserialize, Serialize};
+
+use crate::{MarketRegime, RegimeContext};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegimeConfig {
+    /// Window for `momentum_lookback_return`.
+    pub momentum_lookback_ms: i64,
+    /// Window for `realized_volatility` and the return autocorrelation.
+    pub volatility_lookback_ms: i64,
+    /// Window of trade flow behind `order_book_imbalance`.
+    pub imbalance_lookback_ms: i64,
+    /// Price samples needed before an instrument leaves `Unknown`.
+    pub min_samples: usize,
+    /// Absolute lookback return that marks a trend.
+    pub trending_return: f64,
+    /// Standard deviation of per-update log returns above which the market is
+    /// `HighVolatility`, and below which it is `LowVolatility`.
+    pub high_volatility: f64,
+    pub low_volatility: f64,
+    /// Lag-1 return autocorrelation at or below which the market is `MeanReverting`.
+    pub mean_reversion_autocorrelation: f64,
+    /// Fraction by which the current regime's threshold is relaxed before the
+    /// market is considered to have left it.
+    pub hysteresis: f64,
+    /// Consecutive classifications a new regime needs before it replaces the current one.
+    pub confirm_samples: u32,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            momentum_lookback_ms: 300_000,
+            volatility_lookback_ms: 900_000,
+            imbalance_lookback_ms: 60_000,
+            min_samples: 5,
+            trending_return: 0.004,
+            high_volatility: 0.004,
+            low_volatility: 0.0002,
+            mean_reversion_autocorrelation: -0.3,
+            hysteresis: 0.2,
+            confirm_samples: 3,
+        }
+    }
+}
+
+/// Context after an observation; `previous` is set when the regime just changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegimeUpdate {
+    pub context: RegimeContext,
+    pub previous: Option<MarketRegime>,
+}
+
+#[derive(Debug)]
+struct InstrumentRegime {
+    prices: VecDeque<(i64, f64)>,
+    /// Signed trade size: positive for buyer-initiated prints.
+    flow: VecDeque<(i64, f64)>,
+    spread_bps: f64,
+    quoted: bool,
+    regime: MarketRegime,
+    pending: Option<(MarketRegime, u32)>,
+    context: RegimeContext,
+}
+
+#[derive(Debug, Default)]
+pub struct RegimeEngine {
+    config: RegimeConfig,
+    instruments: HashMap<(String, String), InstrumentRegime>,
+}
+
+impl RegimeEngine {
+    pub fn new(config: RegimeConfig) -> Self {
+        Self {
+            config,
+            instruments: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RegimeConfig {
+        &self.config
+    }
+
+    /// Feeds any stream update; disconnects are ignored.
+    pub fn apply(&mut self, update: &MarketDataUpdate) -> Option<RegimeUpdate> {
+        match update {
+            MarketDataUpdate::Quote(quote) => self.observe_quote(quote),
+            MarketDataUpdate::Trade(trade) => Some(self.observe_trade(trade)),
+            MarketDataUpdate::Disconnected { .. } => None,
+        }
+    }
+
+    /// Samples the quote's mid (falling back to last, then mark). Returns
+    /// `None` when the quote carries no usable price.
+    pub fn observe_quote(&mut self, quote: &QuoteSnapshot) -> Option<RegimeUpdate> {
+        let price = match (quote.bid, quote.ask) {
+            (Some(bid), Some(ask)) if bid > 0.0 && ask >= bid => Some((bid + ask) / 2.0),
+            _ => quote.last.or(quote.mark),
+        }
+        .filter(|price| price.is_finite() && *price > 0.0)?;
+
+        let instrument = self.instrument(&quote.venue, &quote.venue_symbol, quote.ts_ms);
+        instrument.quoted = true;
+        instrument.spread_bps = match (quote.bid, quote.ask) {
+            (Some(bid), Some(ask)) if ask >= bid => (ask - bid) / price * 10_000.0,
+            _ => 0.0,
+        };
+        instrument.prices.push_back((quote.ts_ms, price));
+        Some(self.classify(&quote.venue, &quote.venue_symbol, quote.ts_ms))
+    }
+
+    /// Adds the print to trade flow. Prices from trades are only sampled until
+    /// the instrument has a quote, so books and prints are not mixed.
+    pub fn observe_trade(&mut self, trade: &TradePrint) -> RegimeUpdate {
+        let instrument = self.instrument(&trade.venue, &trade.venue_symbol, trade.ts_ms);
+        if let Some(side) = &trade.side {
+            let size = trade.size.unwrap_or(1.0).abs();
+            let signed = if *side == OrderSide::Buy { size } else { -size };
+            instrument.flow.push_back((trade.ts_ms, signed));
+        }
+        if !instrument.quoted && trade.price.is_finite() && trade.price > 0.0 {
+            instrument.prices.push_back((trade.ts_ms, trade.price));
+        }
+        self.classify(&trade.venue, &trade.venue_symbol, trade.ts_ms)
+    }
+
+    pub fn context(&self, venue: &str, venue_symbol: &str) -> Option<&RegimeContext> {
+        self.instruments
+            .get(&(venue.to_string(), venue_symbol.to_string()))
+            .map(|instrument| &instrument.context)
+    }
+
+    /// Latest context of every instrument, ordered by venue and symbol.
+    pub fn contexts(&self) -> Vec<RegimeContext> {
+        let mut contexts: Vec<RegimeContext> = self
+            .instruments
+            .values()
+            .map(|instrument| instrument.context.clone())
+            .collect();
+        contexts.sort_by(|a, b| (&a.venue, &a.symbol).cmp(&(&b.venue, &b.symbol)));
+        contexts
+    }
+
+    fn instrument(&mut self, venue: &str, venue_symbol: &str, ts_ms: i64) -> &mut InstrumentRegime {
+        self.instruments
+            .entry((venue.to_string(), venue_symbol.to_string()))
+            .or_insert_with(|| InstrumentRegime {
+                prices: VecDeque::new(),
+                flow: VecDeque::new(),
+                spread_bps: 0.0,
+                quoted: false,
+                regime: MarketRegime::Unknown,
+                pending: None,
+                context: RegimeContext {
+                    venue: venue.to_string(),
+                    symbol: venue_symbol.to_string(),
+                    regime: MarketRegime::Unknown,
+                    spread_bps: 0.0,
+                    realized_volatility: 0.0,
+                    momentum_lookback_return: 0.0,
+                    order_book_imbalance: 0.0,
+                    ts_ms,
+                },
+            })
+    }
+
+    fn classify(&mut self, venue: &str, venue_symbol: &str, now_ms: i64) -> RegimeUpdate {
+        let config = &self.config;
+        let instrument = self
+            .instruments
+            .get_mut(&(venue.to_string(), venue_symbol.to_string()))
+            .expect("instrument registered before classification");
+
+        let horizon = config
+            .momentum_lookback_ms
+            .max(config.volatility_lookback_ms);
+        // Keep one sample older than the horizon so the lookback return spans it.
+        while instrument.prices.len() > 1 && now_ms - instrument.prices[1].0 >= horizon {
+            instrument.prices.pop_front();
+        }
+        while instrument
+            .flow
+            .front()
+            .is_some_and(|(ts_ms, _)| now_ms - ts_ms > config.imbalance_lookback_ms)
+        {
+            instrument.flow.pop_front();
+        }
+
+        let latest = instrument.prices.back().map(|(_, price)| *price);
+        let anchor = instrument
+            .prices
+            .iter()
+            .rev()
+            .find(|(ts_ms, _)| now_ms - ts_ms >= config.momentum_lookback_ms)
+            .or(instrument.prices.front())
+            .map(|(_, price)| *price);
+        let momentum = match (latest, anchor) {
+            (Some(latest), Some(anchor)) => latest / anchor - 1.0,
+            _ => 0.0,
+        };
+
+        let returns: Vec<f64> = instrument
+            .prices
+            .iter()
+            .zip(instrument.prices.iter().skip(1))
+            .filter(|(_, (ts_ms, _))| now_ms - ts_ms <= config.volatility_lookback_ms)
+            .map(|((_, previous), (_, next))| (next / previous).ln())
+            .collect();
+        let (volatility, autocorrelation) = return_statistics(&returns);
+
+        let (buys, total) = instrument
+            .flow
+            .iter()
+            .fold((0.0, 0.0), |(net, total), (_, signed)| {
+                (net + signed, total + signed.abs())
+            });
+        let imbalance = if total > 0.0 { buys / total } else { 0.0 };
+
+        let previous = instrument.regime.clone();
+        if instrument.prices.len() >= config.min_samples.max(2) {
+            let observed = observe_regime(
+                config,
+                &instrument.regime,
+                volatility,
+                momentum,
+                autocorrelation,
+            );
+            if observed == instrument.regime {
+                instrument.pending = None;
+            } else if instrument.regime == MarketRegime::Unknown {
+                instrument.regime = observed;
+                instrument.pending = None;
+            } else {
+                let seen = match &instrument.pending {
+                    Some((pending, seen)) if *pending == observed => seen + 1,
+                    _ => 1,
+                };
+                if seen >= config.confirm_samples {
+                    instrument.regime = observed;
+                    instrument.pending = None;
+                } else {
+                    instrument.pending = Some((observed, seen));
+                }
+            }
+        }
+
+        instrument.context = RegimeContext {
+            venue: venue.to_string(),
+            symbol: venue_symbol.to_string(),
+            regime: instrument.regime.clone(),
+            spread_bps: instrument.spread_bps,
+            realized_volatility: volatility,
+            momentum_lookback_return: momentum,
+            order_book_imbalance: imbalance,
+            ts_ms: now_ms,
+        };
+        RegimeUpdate {
+            context: instrument.context.clone(),
+            previous: (previous != instrument.regime).then_some(previous),
+        }
+    }
+}
+
+/// Population standard deviation and lag-1 autocorrelation of `returns`.
+fn return_statistics(returns: &[f64]) -> (f64, f64) {
+    if returns.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / returns.len() as f64;
+    let covariance = returns
+        .iter()
+        .zip(returns.iter().skip(1))
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum::<f64>()
+        / returns.len() as f64;
+    let autocorrelation = if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    };
+    (variance.sqrt(), autocorrelation)
+}
+
+/// Classifies one observation. The thresholds of `current` are relaxed by
+/// `hysteresis`, so a regime is entered on a clear signal and only left once
+/// the signal has faded past that margin.
+fn observe_regime(
+    config: &RegimeConfig,
+    current: &MarketRegime,
+    volatility: f64,
+    momentum: f64,
+    autocorrelation: f64,
+) -> MarketRegime {
+    let relax = |regime: MarketRegime| {
+        if *current == regime {
+            1.0 - config.hysteresis
+        } else {
+            1.0
+        }
+    };
+
+    if volatility > config.high_volatility * relax(MarketRegime::HighVolatility) {
+        MarketRegime::HighVolatility
+    } else if momentum.abs() > config.trending_return * relax(MarketRegime::Trending) {
+        MarketRegime::Trending
+    } else if autocorrelation
+        <= config.mean_reversion_autocorrelation * relax(MarketRegime::MeanReverting)
+    {
+        MarketRegime::MeanReverting
+    } else if volatility < config.low_volatility / relax(MarketRegime::LowVolatility) {
+        MarketRegime::LowVolatility
+    } else {
+        MarketRegime::RangeBound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(ts_ms: i64, mid: f64) -> QuoteSnapshot {
+        QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(mid - 0.05),
+            ask: Some(mid + 0.05),
+            last: None,
+            mark: None,
+            ts_ms,
+        }
+    }
+
+    fn config() -> RegimeConfig {
+        RegimeConfig {
+            momentum_lookback_ms: 5_000,
+            volatility_lookback_ms: 5_000,
+            imbalance_lookback_ms: 5_000,
+            min_samples: 3,
+            confirm_samples: 2,
+            ..RegimeConfig::default()
+        }
+    }
+
+    #[test]
+    fn derives_context_fields_from_quotes_and_trades() {
+        let mut engine = RegimeEngine::new(config());
+        let update = engine.observe_quote(&quote(0, 100.0)).unwrap();
+        assert_eq!(update.context.regime, MarketRegime::Unknown);
+        assert!((update.context.spread_bps - 10.0).abs() < 1e-9);
+
+        engine.observe_quote(&quote(1_000, 101.0));
+        let trade = |side, size, ts_ms| TradePrint {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            price: 101.0,
+            size: Some(size),
+            side: Some(side),
+            ts_ms,
+        };
+        engine.observe_trade(&trade(OrderSide::Buy, 3.0, 1_500));
+        engine.observe_trade(&trade(OrderSide::Sell, 1.0, 1_600));
+        let update = engine.observe_quote(&quote(2_000, 102.0)).unwrap();
+
+        assert_eq!(update.context.regime, MarketRegime::Trending);
+        assert_eq!(update.previous, Some(MarketRegime::Unknown));
+        assert!((update.context.momentum_lookback_return - 0.02).abs() < 1e-9);
+        assert!((update.context.order_book_imbalance - 0.5).abs() < 1e-9);
+        assert!(update.context.realized_volatility > 0.0);
+        assert_eq!(
+            engine.context("coinbase_at", "BTC-USD").unwrap().regime,
+            MarketRegime::Trending
+        );
+    }
+
+    #[test]
+    fn regime_changes_need_confirmation_and_fade_past_hysteresis() {
+        let mut engine = RegimeEngine::new(RegimeConfig {
+            trending_return: 0.01,
+            high_volatility: 1.0,
+            mean_reversion_autocorrelation: -2.0,
+            low_volatility: 0.0,
+            ..config()
+        });
+        for (ts_ms, mid) in [(0, 100.0), (1_000, 100.5), (2_000, 101.5)] {
+            engine.observe_quote(&quote(ts_ms, mid));
+        }
+        assert_eq!(
+            engine.contexts()[0].regime,
+            MarketRegime::Trending,
+            "1.5% over the window is a trend"
+        );
+
+        // 0.9% is below the entry threshold but inside the 20% hysteresis band.
+        let update = engine.observe_quote(&quote(7_000, 102.4)).unwrap();
+        assert!((update.context.momentum_lookback_return - 0.009).abs() < 1e-3);
+        assert_eq!(update.context.regime, MarketRegime::Trending);
+
+        // Flat prices. The window first holds too few samples to reclassify,
+        // then the first range-bound reading is only pending.
+        for ts_ms in [12_000, 13_000] {
+            let update = engine.observe_quote(&quote(ts_ms, 102.4)).unwrap();
+            assert_eq!(update.context.regime, MarketRegime::Trending);
+            assert_eq!(update.previous, None);
+        }
+        let update = engine.observe_quote(&quote(14_000, 102.4)).unwrap();
+        assert_eq!(update.context.regime, MarketRegime::RangeBound);
+        assert_eq!(update.previous, Some(MarketRegime::Trending));
+    }
+
+    #[test]
+    fn alternating_returns_read_as_mean_reverting() {
+        let mut engine = RegimeEngine::new(RegimeConfig {
+            trending_return: 1.0,
+            high_volatility: 1.0,
+            ..config()
+        });
+        let mut update = None;
+        for step in 0..8 {
+            let mid = if step % 2 == 0 { 100.0 } else { 100.3 };
+            update = engine.observe_quote(&quote(step * 500, mid));
+        }
+        assert_eq!(update.unwrap().context.regime, MarketRegime::MeanReverting);
+    }
+}
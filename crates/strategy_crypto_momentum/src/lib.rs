@@ -72,7 +72,9 @@ impl StrategyPlugin for CryptoMomentumStrategy {
             side,
             order_type: OrderType::Market,
             qty: 0.001,
+            quote_size: None,
             limit_price: None,
+            stop_price: None,
             tif: None,
             post_only: false,
             reduce_only,
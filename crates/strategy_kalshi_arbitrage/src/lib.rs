@@ -66,7 +66,9 @@ impl StrategyPlugin for KalshiArbitrageStrategy {
             side,
             order_type: OrderType::Limit,
             qty: 1.0,
+            quote_size: None,
             limit_price: Some(0.50),
+            stop_price: None,
             tif: Some(TimeInForce::Fok),
             post_only: false,
             reduce_only,
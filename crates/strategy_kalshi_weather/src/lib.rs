@@ -56,7 +56,9 @@ impl StrategyPlugin for KalshiWeatherStrategy {
             side: OrderSide::Buy,
             order_type: OrderType::Limit,
             qty: 1.0,
+            quote_size: None,
             limit_price: Some(0.45),
+            stop_price: None,
             tif: Some(TimeInForce::Ioc),
             post_only: false,
             reduce_only: false,
@@ -0,0 +1,93 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use trading_protocol::ErrorCode;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The daemon socket could not be reached.
+    Connect {
+        socket_path: PathBuf,
+        source: std::io::Error,
+    },
+    Io(std::io::Error),
+    /// No response arrived within the configured request timeout.
+    Timeout(Duration),
+    /// The daemon closed the connection before answering.
+    Closed,
+    Decode(serde_json::Error),
+    /// The daemon answered with `ok: false`.
+    Daemon {
+        code: ErrorCode,
+        message: String,
+        payload: serde_json::Value,
+    },
+    /// The response was missing a field the typed method expects.
+    UnexpectedResponse(String),
+}
+
+impl ClientError {
+    /// Typed daemon error code, when the daemon rejected the request.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            ClientError::Daemon { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Failures after which a fresh connection may succeed.
+    pub(crate) fn is_connection_lost(&self) -> bool {
+        matches!(self, ClientError::Io(_) | ClientError::Closed)
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Connect {
+                socket_path,
+                source,
+            } => write!(
+                f,
+                "failed to connect to daemon socket {}: {}",
+                socket_path.display(),
+                source
+            ),
+            ClientError::Io(err) => write!(f, "daemon connection failed: {}", err),
+            ClientError::Timeout(timeout) => {
+                write!(f, "no response from daemon within {:?}", timeout)
+            }
+            ClientError::Closed => write!(f, "daemon closed the connection"),
+            ClientError::Decode(err) => write!(f, "invalid daemon response: {}", err),
+            ClientError::Daemon { code, message, .. } => {
+                write!(f, "daemon error ({}): {}", code.as_str(), message)
+            }
+            ClientError::UnexpectedResponse(message) => {
+                write!(f, "unexpected daemon response: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Connect { source, .. } | ClientError::Io(source) => Some(source),
+            ClientError::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(err: serde_json::Error) -> Self {
+        ClientError::Decode(err)
+    }
+}
@@ -0,0 +1,100 @@
+//! `Control.SubscribeEvents` stream with automatic resubscription.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use trading_protocol::{ControlCommand, Envelope, Event, EventSubscribePayload, EVENT_KIND};
+
+use crate::{check_ok, connect, exchange, ClientConfig, ClientError, Connection, Result};
+
+const EVENT_BUFFER: usize = 256;
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Daemon events in emission order.
+///
+/// When the connection drops the stream yields one [`ClientError::Closed`]
+/// and resubscribes with exponential backoff; events emitted in between are
+/// missed. Undecodable events are yielded as errors without ending the
+/// stream. Dropping the stream closes the subscription.
+pub struct EventStream {
+    events: mpsc::Receiver<Result<Event>>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+pub(crate) async fn subscribe(config: ClientConfig, replay_recent: bool) -> Result<EventStream> {
+    let connection = open_subscription(&config, replay_recent).await?;
+    let (sender, events) = mpsc::channel(EVENT_BUFFER);
+    let task = tokio::spawn(forward_events(config, connection, sender));
+    Ok(EventStream { events, task })
+}
+
+async fn open_subscription(config: &ClientConfig, replay_recent: bool) -> Result<Connection> {
+    let mut connection = connect(config).await?;
+    let request = Envelope::new(
+        ControlCommand::SubscribeEvents.as_kind(),
+        serde_json::to_value(EventSubscribePayload { replay_recent })?,
+    );
+    let response = exchange(&mut connection, &request, config.request_timeout).await?;
+    check_ok(response.payload)?;
+    Ok(connection)
+}
+
+fn decode_event(frame: &[u8]) -> Option<Result<Event>> {
+    let envelope: Envelope = match serde_json::from_slice(frame) {
+        Ok(envelope) => envelope,
+        Err(err) => return Some(Err(err.into())),
+    };
+    (envelope.kind == EVENT_KIND)
+        .then(|| serde_json::from_value(envelope.payload).map_err(ClientError::from))
+}
+
+async fn forward_events(
+    config: ClientConfig,
+    mut connection: Connection,
+    sender: mpsc::Sender<Result<Event>>,
+) {
+    loop {
+        while let Some(Ok(frame)) = connection.next().await {
+            if let Some(event) = decode_event(&frame) {
+                if sender.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+        if sender.send(Err(ClientError::Closed)).await.is_err() {
+            return;
+        }
+
+        // Resubscribe without replay so consumers do not see events twice.
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        connection = loop {
+            tokio::select! {
+                _ = sender.closed() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            match open_subscription(&config, false).await {
+                Ok(connection) => break connection,
+                Err(_) => delay = (delay * 2).min(MAX_RECONNECT_DELAY),
+            }
+        };
+    }
+}
@@ -0,0 +1,812 @@
+//! Async client for the trading daemon's UDS protocol: pooled connections,
+//! request timeouts, typed methods per command and the daemon event stream.
+
+mod error;
+mod events;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use exchange_core::OrderSnapshot;
+use futures::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::net::UnixStream;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tokio_util::codec::Framed;
+use tracing::Instrument;
+use trading_protocol::{
+    create_codec, CandidatePromotePayload, CandidateUploadPayload, CapabilitiesPayload,
+    CapabilitiesRequestPayload, ControlCommand, EngineCommand, EngineExportSnapshotPayload,
+    EngineMode, EngineModePayload, EngineReplayPayload, EngineReplayResultPayload, Envelope,
+    ErrorCode, ExecutionCancelPayload, ExecutionCommand, ExecutionFillsPayload,
+    ExecutionFillsResultPayload, ExecutionGetPayload, ExecutionOpenOrdersPayload,
+    ExecutionPlacePayload, ExecutionPlaceResultPayload, HelloPayload, HelloResultPayload,
+    JsonCodec, MarketDataCommand, MarketDataProductPayload, MarketDataProductResultPayload,
+    MarketDataQuotePayload, MarketDataRegimePayload, MarketDataRegimeResultPayload,
+    MarketDataSubscribePayload, MarketDataSubscribeResultPayload, MarketQuotePayload,
+    PortfolioBalancesPayload, PortfolioCommand, PortfolioPaperAccountPayload,
+    PortfolioPaperAccountResultPayload, PortfolioPnlPayload, PortfolioPnlResultPayload,
+    PortfolioPositionsPayload, PortfolioSummaryPayload, RiskCommand, RiskOverridePayload,
+    StrategyCommand, StrategyIdPayload, StrategySummaryPayload, DEFAULT_SOCKET_PATH,
+};
+
+pub use error::ClientError;
+pub use events::EventStream;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+type Connection = Framed<UnixStream, JsonCodec>;
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub socket_path: PathBuf,
+    /// Upper bound for connecting and for each request/response exchange.
+    pub request_timeout: Duration,
+    /// Maximum concurrent connections; idle ones are kept for reuse.
+    pub pool_size: usize,
+}
+
+impl ClientConfig {
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            pool_size: DEFAULT_POOL_SIZE,
+        }
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_pool_size(mut self, pool_size: usize) -> Self {
+        self.pool_size = pool_size.max(1);
+        self
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_SOCKET_PATH)
+    }
+}
+
+struct Pool {
+    config: ClientConfig,
+    idle: Mutex<Vec<Connection>>,
+    permits: Semaphore,
+}
+
+/// Cheap to clone; clones share the connection pool.
+#[derive(Clone)]
+pub struct TradingClient {
+    pool: Arc<Pool>,
+}
+
+impl TradingClient {
+    /// Creates a client without connecting; connections are opened on demand.
+    pub fn new(config: ClientConfig) -> Self {
+        let permits = Semaphore::new(config.pool_size.max(1));
+        Self {
+            pool: Arc::new(Pool {
+                config,
+                idle: Mutex::new(Vec::new()),
+                permits,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.pool.config
+    }
+
+    /// Sends `kind` with `payload` and returns the daemon's response envelope,
+    /// including `ok: false` responses.
+    pub async fn send(&self, kind: &str, payload: serde_json::Value) -> Result<Envelope> {
+        self.send_envelope(Envelope::new(kind, payload)).await
+    }
+
+    /// Sends a prepared request, e.g. one carrying a `correlation_id`.
+    ///
+    /// Each request runs in a `trading_client.request` span whose trace
+    /// context is sent as `traceparent` (unless already set), so daemon spans
+    /// join the caller's trace when spans are exported.
+    ///
+    /// A pooled connection the daemon closed while idle (e.g. after a restart)
+    /// is replaced when writing the request to it fails, and the request is
+    /// sent once more on a fresh connection. Once a request has been written it
+    /// is never resent: the daemon may have executed it, so losing the
+    /// connection while awaiting the response is returned as an error.
+    pub async fn send_envelope(&self, mut request: Envelope) -> Result<Envelope> {
+        let span = tracing::info_span!(
+            "trading_client.request",
+            kind = %request.kind,
+            request_id = %request.id,
+        );
+        if request.traceparent.is_none() {
+            request.traceparent = span.in_scope(trading_telemetry::current_traceparent);
+        }
+        self.exchange_pooled(&request).instrument(span).await
+    }
+
+    async fn exchange_pooled(&self, request: &Envelope) -> Result<Envelope> {
+        let timeout = self.pool.config.request_timeout;
+        let _permit = self
+            .pool
+            .permits
+            .acquire()
+            .await
+            .expect("pool semaphore is never closed");
+
+        let deadline = Deadline::after(timeout);
+        let idle = self.pool.idle.lock().expect("pool lock poisoned").pop();
+        let mut connection = match idle {
+            Some(mut connection) => match send_request(&mut connection, request, deadline).await {
+                Ok(()) => connection,
+                // The daemon never read the request, so resending it cannot
+                // duplicate a command.
+                Err(err) if err.is_connection_lost() => {
+                    let mut connection = connect(&self.pool.config).await?;
+                    send_request(&mut connection, request, deadline).await?;
+                    connection
+                }
+                Err(err) => return Err(err),
+            },
+            None => {
+                let mut connection = connect(&self.pool.config).await?;
+                send_request(&mut connection, request, deadline).await?;
+                connection
+            }
+        };
+        let result = read_response(&mut connection, request, deadline).await;
+
+        // Connections that failed or timed out may still carry a late response.
+        if result.is_ok() {
+            self.pool
+                .idle
+                .lock()
+                .expect("pool lock poisoned")
+                .push(connection);
+        }
+        result
+    }
+
+    /// Sends a command and returns the response payload, turning `ok: false`
+    /// responses into [`ClientError::Daemon`].
+    pub async fn request(
+        &self,
+        kind: &str,
+        payload: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self.send(kind, payload).await?;
+        check_ok(response.payload)
+    }
+
+    async fn request_with<P: Serialize>(
+        &self,
+        kind: &str,
+        payload: &P,
+    ) -> Result<serde_json::Value> {
+        self.request(kind, serde_json::to_value(payload)?).await
+    }
+
+    async fn request_empty(&self, kind: &str) -> Result<serde_json::Value> {
+        self.request(kind, serde_json::json!({})).await
+    }
+
+    /// Subscribes to daemon events (`Control.SubscribeEvents`) on a dedicated
+    /// connection outside the pool. With `replay_recent`, the daemon first
+    /// sends its buffer of recent events.
+    pub async fn subscribe_events(&self, replay_recent: bool) -> Result<EventStream> {
+        events::subscribe(self.pool.config.clone(), replay_recent).await
+    }
+
+    pub async fn ping(&self) -> Result<serde_json::Value> {
+        self.request_empty(ControlCommand::Ping.as_kind()).await
+    }
+
+    /// `Engine.Status` document.
+    pub async fn status(&self) -> Result<serde_json::Value> {
+        self.request_empty(EngineCommand::Status.as_kind()).await
+    }
+
+    pub async fn capabilities(&self, include_schemas: bool) -> Result<CapabilitiesPayload> {
+        let payload = self
+            .request_with(
+                ControlCommand::Capabilities.as_kind(),
+                &CapabilitiesRequestPayload { include_schemas },
+            )
+            .await?;
+        field(payload, "capabilities")
+    }
+
+    pub async fn hello(&self, hello: &HelloPayload) -> Result<HelloResultPayload> {
+        let payload = self
+            .request_with(ControlCommand::Hello.as_kind(), hello)
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn start(&self) -> Result<serde_json::Value> {
+        self.request_empty(ControlCommand::Start.as_kind()).await
+    }
+
+    pub async fn stop(&self) -> Result<serde_json::Value> {
+        self.request_empty(ControlCommand::Stop.as_kind()).await
+    }
+
+    pub async fn pause(&self) -> Result<serde_json::Value> {
+        self.request_empty(EngineCommand::Pause.as_kind()).await
+    }
+
+    pub async fn resume(&self) -> Result<serde_json::Value> {
+        self.request_empty(EngineCommand::Resume.as_kind()).await
+    }
+
+    pub async fn kill_switch(&self) -> Result<serde_json::Value> {
+        self.request_empty(EngineCommand::KillSwitch.as_kind())
+            .await
+    }
+
+    pub async fn get_mode(&self) -> Result<EngineMode> {
+        let payload = self.request_empty(EngineCommand::GetMode.as_kind()).await?;
+        field::<EngineModePayload>(payload, "mode").map(|mode| mode.mode)
+    }
+
+    pub async fn set_mode(&self, mode: EngineMode) -> Result<EngineMode> {
+        let payload = self
+            .request_with(
+                EngineCommand::SetMode.as_kind(),
+                &EngineModePayload { mode },
+            )
+            .await?;
+        field::<EngineModePayload>(payload, "mode").map(|mode| mode.mode)
+    }
+
+    /// Inline snapshot bundle, or the written file's details when `file_name`
+    /// is set.
+    pub async fn export_snapshot(&self, file_name: Option<String>) -> Result<serde_json::Value> {
+        let payload = self
+            .request_with(
+                EngineCommand::ExportSnapshot.as_kind(),
+                &EngineExportSnapshotPayload { file_name },
+            )
+            .await?;
+        field(payload, "result")
+    }
+
+    /// Replays the daemon's journals for the window through a sandboxed
+    /// paper engine and reports divergences from the recorded outcomes.
+    pub async fn replay(
+        &self,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<EngineReplayResultPayload> {
+        let payload = self
+            .request_with(
+                EngineCommand::Replay.as_kind(),
+                &EngineReplayPayload { from_ms, to_ms },
+            )
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn strategies(&self) -> Result<Vec<StrategySummaryPayload>> {
+        let payload = self.request_empty(StrategyCommand::List.as_kind()).await?;
+        field(payload, "strategies")
+    }
+
+    pub async fn enable_strategy(&self, strategy_id: &str) -> Result<StrategySummaryPayload> {
+        let payload = self
+            .request_with(
+                StrategyCommand::Enable.as_kind(),
+                &StrategyIdPayload {
+                    strategy_id: strategy_id.to_string(),
+                },
+            )
+            .await?;
+        field(payload, "strategy")
+    }
+
+    pub async fn disable_strategy(&self, strategy_id: &str) -> Result<StrategySummaryPayload> {
+        let payload = self
+            .request_with(
+                StrategyCommand::Disable.as_kind(),
+                &StrategyIdPayload {
+                    strategy_id: strategy_id.to_string(),
+                },
+            )
+            .await?;
+        field(payload, "strategy")
+    }
+
+    pub async fn upload_candidate(
+        &self,
+        candidate: &CandidateUploadPayload,
+    ) -> Result<serde_json::Value> {
+        self.request_with(StrategyCommand::UploadCandidate.as_kind(), candidate)
+            .await
+    }
+
+    pub async fn promote_candidate(
+        &self,
+        promotion: &CandidatePromotePayload,
+    ) -> Result<serde_json::Value> {
+        self.request_with(StrategyCommand::PromoteCandidate.as_kind(), promotion)
+            .await
+    }
+
+    pub async fn risk_status(&self) -> Result<serde_json::Value> {
+        self.request_empty(RiskCommand::Status.as_kind()).await
+    }
+
+    pub async fn risk_override(&self, action: &RiskOverridePayload) -> Result<serde_json::Value> {
+        self.request_with(RiskCommand::Override.as_kind(), action)
+            .await
+    }
+
+    /// Places an order. Retrying with the same `client_order_id` is safe: the
+    /// daemon replays the original result.
+    pub async fn place(
+        &self,
+        order: &ExecutionPlacePayload,
+    ) -> Result<ExecutionPlaceResultPayload> {
+        let payload = self
+            .request_with(ExecutionCommand::Place.as_kind(), order)
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn cancel(&self, venue_order_id: &str) -> Result<serde_json::Value> {
+        self.request_with(
+            ExecutionCommand::Cancel.as_kind(),
+            &ExecutionCancelPayload {
+                venue_order_id: venue_order_id.to_string(),
+            },
+        )
+        .await
+    }
+
+    pub async fn get_order(&self, venue_order_id: &str) -> Result<Option<OrderSnapshot>> {
+        let payload = self
+            .request_with(
+                ExecutionCommand::Get.as_kind(),
+                &ExecutionGetPayload {
+                    venue_order_id: venue_order_id.to_string(),
+                },
+            )
+            .await?;
+        field(payload, "order")
+    }
+
+    pub async fn open_orders(&self) -> Result<ExecutionOpenOrdersPayload> {
+        let payload = self
+            .request_empty(ExecutionCommand::OpenOrders.as_kind())
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn fills(
+        &self,
+        filter: &ExecutionFillsPayload,
+    ) -> Result<ExecutionFillsResultPayload> {
+        let payload = self
+            .request_with(ExecutionCommand::Fills.as_kind(), filter)
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn positions(&self) -> Result<PortfolioPositionsPayload> {
+        let payload = self
+            .request_empty(PortfolioCommand::Positions.as_kind())
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn balances(&self) -> Result<PortfolioBalancesPayload> {
+        let payload = self
+            .request_empty(PortfolioCommand::Balances.as_kind())
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn exposure(&self) -> Result<PortfolioSummaryPayload> {
+        let payload = self
+            .request_empty(PortfolioCommand::Exposure.as_kind())
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn pnl(&self, filter: &PortfolioPnlPayload) -> Result<PortfolioPnlResultPayload> {
+        let payload = self
+            .request_with(PortfolioCommand::Pnl.as_kind(), filter)
+            .await?;
+        field(payload, "result")
+    }
+
+    /// Resets or funds the daemon's paper account.
+    pub async fn paper_account(
+        &self,
+        request: &PortfolioPaperAccountPayload,
+    ) -> Result<PortfolioPaperAccountResultPayload> {
+        let payload = self
+            .request_with(PortfolioCommand::PaperAccount.as_kind(), request)
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn quote(&self, venue: &str, venue_symbol: &str) -> Result<MarketQuotePayload> {
+        let payload = self
+            .request_with(
+                MarketDataCommand::Quote.as_kind(),
+                &MarketDataQuotePayload {
+                    venue: venue.to_string(),
+                    venue_symbol: venue_symbol.to_string(),
+                },
+            )
+            .await?;
+        field(payload, "result")
+    }
+
+    /// The venue's order-entry rules (increments, size limits, trading status)
+    /// for one instrument.
+    pub async fn product(
+        &self,
+        venue: &str,
+        venue_symbol: &str,
+    ) -> Result<MarketDataProductResultPayload> {
+        let payload = self
+            .request_with(
+                MarketDataCommand::Product.as_kind(),
+                &MarketDataProductPayload {
+                    venue: venue.to_string(),
+                    venue_symbol: venue_symbol.to_string(),
+                },
+            )
+            .await?;
+        field(payload, "result")
+    }
+
+    /// Current regime contexts, optionally narrowed to one venue or symbol.
+    pub async fn regimes(
+        &self,
+        venue: Option<&str>,
+        venue_symbol: Option<&str>,
+    ) -> Result<MarketDataRegimeResultPayload> {
+        let payload = self
+            .request_with(
+                MarketDataCommand::Regime.as_kind(),
+                &MarketDataRegimePayload {
+                    venue: venue.map(str::to_string),
+                    venue_symbol: venue_symbol.map(str::to_string),
+                },
+            )
+            .await?;
+        field(payload, "result")
+    }
+
+    pub async fn subscribe_market_data(
+        &self,
+        venue: &str,
+        venue_symbols: &[String],
+    ) -> Result<MarketDataSubscribeResultPayload> {
+        let payload = self
+            .request_with(
+                MarketDataCommand::Subscribe.as_kind(),
+                &MarketDataSubscribePayload {
+                    venue: venue.to_string(),
+                    venue_symbols: venue_symbols.to_vec(),
+                },
+            )
+            .await?;
+        field(payload, "result")
+    }
+}
+
+async fn connect(config: &ClientConfig) -> Result<Connection> {
+    let connect_error = |source| ClientError::Connect {
+        socket_path: config.socket_path.clone(),
+        source,
+    };
+    let stream = tokio::time::timeout(
+        config.request_timeout,
+        UnixStream::connect(&config.socket_path),
+    )
+    .await
+    .map_err(|_| ClientError::Timeout(config.request_timeout))?
+    .map_err(connect_error)?;
+    Ok(Framed::new(stream, create_codec()))
+}
+
+/// Writes `request` and waits for the response carrying the same envelope id.
+async fn exchange(
+    connection: &mut Connection,
+    request: &Envelope,
+    timeout: Duration,
+) -> Result<Envelope> {
+    let deadline = Deadline::after(timeout);
+    send_request(connection, request, deadline).await?;
+    read_response(connection, request, deadline).await
+}
+
+#[derive(Clone, Copy)]
+struct Deadline {
+    at: Instant,
+    timeout: Duration,
+}
+
+impl Deadline {
+    fn after(timeout: Duration) -> Self {
+        Self {
+            at: Instant::now() + timeout,
+            timeout,
+        }
+    }
+
+    async fn run<T>(self, operation: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        tokio::time::timeout_at(self.at, operation)
+            .await
+            .map_err(|_| ClientError::Timeout(self.timeout))?
+    }
+}
+
+/// Writes `request`. An error here means the daemon cannot have read the
+/// request, e.g. because it closed the connection.
+async fn send_request(
+    connection: &mut Connection,
+    request: &Envelope,
+    deadline: Deadline,
+) -> Result<()> {
+    let frame = Bytes::from(serde_json::to_vec(request)?);
+    deadline
+        .run(async { Ok(connection.send(frame).await?) })
+        .await
+}
+
+async fn read_response(
+    connection: &mut Connection,
+    request: &Envelope,
+    deadline: Deadline,
+) -> Result<Envelope> {
+    deadline
+        .run(async {
+            loop {
+                let frame = connection.next().await.ok_or(ClientError::Closed)??;
+                let response: Envelope = serde_json::from_slice(&frame)?;
+                if response.id == request.id {
+                    return Ok(response);
+                }
+            }
+        })
+        .await
+}
+
+fn check_ok(payload: serde_json::Value) -> Result<serde_json::Value> {
+    if payload.get("ok").and_then(serde_json::Value::as_bool) == Some(true) {
+        return Ok(payload);
+    }
+    let code = payload
+        .get("code")
+        .and_then(|code| serde_json::from_value(code.clone()).ok())
+        .unwrap_or(ErrorCode::Unknown);
+    let message = payload
+        .get("error")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("request failed")
+        .to_string();
+    Err(ClientError::Daemon {
+        code,
+        message,
+        payload,
+    })
+}
+
+fn field<T: DeserializeOwned>(mut payload: serde_json::Value, name: &str) -> Result<T> {
+    let value = payload
+        .get_mut(name)
+        .map(serde_json::Value::take)
+        .ok_or_else(|| ClientError::UnexpectedResponse(format!("missing `{}`", name)))?;
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::StreamExt;
+    use serde_json::json;
+    use tokio::net::UnixListener;
+    use trading_protocol::{Event, EVENT_KIND};
+
+    use super::*;
+
+    enum Reply {
+        Respond(serde_json::Value),
+        /// Answer, then close the connection.
+        RespondAndClose(serde_json::Value),
+        Ignore,
+        /// Close the connection without answering.
+        Close,
+        /// Acknowledge, then push these events.
+        Events(Vec<serde_json::Value>),
+    }
+
+    fn socket_path(label: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "trading-client-{}-{}.sock",
+            std::process::id(),
+            label
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Serves `reply` for every request; returns the count of accepted connections.
+    fn mock_daemon<F>(path: &PathBuf, reply: F) -> Arc<AtomicUsize>
+    where
+        F: Fn(&Envelope) -> Reply + Send + Sync + 'static,
+    {
+        let listener = UnixListener::bind(path).expect("bind mock socket");
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let reply = Arc::new(reply);
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let reply = reply.clone();
+                tokio::spawn(async move {
+                    let mut framed = Framed::new(stream, create_codec());
+                    while let Some(Ok(frame)) = framed.next().await {
+                        let request: Envelope = serde_json::from_slice(&frame).expect("request");
+                        let send = |payload| {
+                            Bytes::from(
+                                serde_json::to_vec(&Envelope::response_to(&request, payload))
+                                    .expect("encode"),
+                            )
+                        };
+                        match reply(&request) {
+                            Reply::Respond(payload) => {
+                                framed.send(send(payload)).await.expect("respond");
+                            }
+                            Reply::RespondAndClose(payload) => {
+                                framed.send(send(payload)).await.expect("respond");
+                                return;
+                            }
+                            Reply::Ignore => {}
+                            Reply::Close => return,
+                            Reply::Events(events) => {
+                                framed.send(send(json!({"ok": true}))).await.expect("ack");
+                                for event in events {
+                                    let envelope = Envelope::new(EVENT_KIND, event);
+                                    let bytes = serde_json::to_vec(&envelope).expect("encode");
+                                    framed.send(Bytes::from(bytes)).await.expect("event");
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        accepted
+    }
+
+    #[tokio::test]
+    async fn typed_methods_decode_results_over_pooled_connections() {
+        let path = socket_path("typed");
+        let accepted = mock_daemon(&path, |request| {
+            assert_eq!(request.kind, PortfolioCommand::Positions.as_kind());
+            Reply::Respond(json!({"ok": true, "result": {"positions": []}}))
+        });
+        let client = TradingClient::new(ClientConfig::new(&path));
+
+        for _ in 0..3 {
+            let positions = client.positions().await.expect("positions");
+            assert!(positions.positions.is_empty());
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn daemon_errors_keep_their_code() {
+        let path = socket_path("errors");
+        mock_daemon(&path, |_| {
+            Reply::Respond(json!({"ok": false, "code": "risk_denied", "error": "too large"}))
+        });
+        let client = TradingClient::new(ClientConfig::new(&path));
+
+        let err = client.ping().await.expect_err("rejected");
+        assert_eq!(err.code(), Some(ErrorCode::RiskDenied));
+        assert_eq!(err.to_string(), "daemon error (risk_denied): too large");
+
+        let raw = client
+            .send(ControlCommand::Ping.as_kind(), json!({}))
+            .await
+            .expect("raw response");
+        assert_eq!(raw.payload["ok"], false);
+    }
+
+    #[tokio::test]
+    async fn closed_idle_connections_are_replaced() {
+        let path = socket_path("reconnect");
+        let accepted = mock_daemon(&path, |_| Reply::RespondAndClose(json!({"ok": true})));
+        let client = TradingClient::new(ClientConfig::new(&path));
+
+        client.ping().await.expect("first ping");
+        client.ping().await.expect("second ping after reconnect");
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn requests_the_daemon_read_are_not_resent() {
+        let path = socket_path("no-resend");
+        let places = Arc::new(AtomicUsize::new(0));
+        let seen = places.clone();
+        mock_daemon(&path, move |request| {
+            if request.kind == ControlCommand::Ping.as_kind() {
+                return Reply::Respond(json!({"ok": true}));
+            }
+            seen.fetch_add(1, Ordering::SeqCst);
+            Reply::Close
+        });
+        let client = TradingClient::new(ClientConfig::new(&path));
+
+        client.ping().await.expect("ping pools the connection");
+        let err = client
+            .send(ExecutionCommand::Place.as_kind(), json!({}))
+            .await
+            .expect_err("connection lost after the daemon read the order");
+        assert!(matches!(err, ClientError::Closed));
+        assert_eq!(places.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn requests_time_out() {
+        let path = socket_path("timeout");
+        mock_daemon(&path, |_| Reply::Ignore);
+        let client = TradingClient::new(
+            ClientConfig::new(&path).with_request_timeout(Duration::from_millis(50)),
+        );
+
+        let err = client.ping().await.expect_err("timed out");
+        assert!(matches!(err, ClientError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn missing_socket_is_a_connect_error() {
+        let client = TradingClient::new(ClientConfig::new(socket_path("missing")));
+        let err = client.ping().await.expect_err("no daemon");
+        assert!(matches!(err, ClientError::Connect { .. }));
+    }
+
+    #[tokio::test]
+    async fn event_stream_yields_pushed_events() {
+        let path = socket_path("events");
+        mock_daemon(&path, |request| {
+            assert_eq!(request.kind, ControlCommand::SubscribeEvents.as_kind());
+            assert_eq!(request.payload["replay_recent"], true);
+            Reply::Events(vec![
+                json!({"Event.Alert": {"level": "info", "message": "one"}}),
+                json!({"Event.EngineState": {"running": true, "risk_tripped": false}}),
+            ])
+        });
+        let client = TradingClient::new(ClientConfig::new(&path));
+
+        let mut events = client.subscribe_events(true).await.expect("subscribed");
+        match events.next().await {
+            Some(Ok(Event::Alert { message, .. })) => assert_eq!(message, "one"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(matches!(
+            events.next().await,
+            Some(Ok(Event::EngineState { running: true, .. }))
+        ));
+    }
+}
@@ -1,52 +1,132 @@
+mod market_data;
+mod pacing;
+mod pnl;
+mod replay;
+mod shutdown;
+
 use anyhow::{Context, Result};
 use bytes::Bytes;
-use coinbase_at_adapter::CoinbaseAdvancedTradeAdapter;
+use coinbase_at_adapter::{CoinbaseAdvancedTradeAdapter, UserUpdate};
 use exchange_core::{
-    BalanceSnapshot, ExchangeAdapter, FillReport, InstrumentType, NormalizedOrderRequest,
-    OpenOrderSnapshot, OrderAck, OrderSnapshot, OrderStatus, PositionSnapshot,
+    system_clock, BalanceSnapshot, Clock, ExchangeAdapter, ExchangeError, FillReport,
+    InstrumentType, MarketDataSink, MarketDataUpdate, NormalizedOrderRequest, OpenOrderSnapshot,
+    OptionRight, OrderAck, OrderSide, OrderSnapshot, OrderStatus, OrderType, PositionSnapshot,
+    QuoteSnapshot, SharedClock, SimulatedClock, SystemClock,
 };
+use exchange_kalshi::KalshiLiveAdapter;
 use fs2::FileExt;
 use futures::{SinkExt, StreamExt};
 use risk_core::{HardSafetyCage, HardSafetyPolicy, PromotionRequest, RiskDecision, RiskSnapshot};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, ErrorKind, Write};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use strategy_core::StrategyFamily;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use strategy_core::{RegimeConfig, RegimeUpdate, StrategyFamily};
 use tokio::net::{UnixListener, UnixStream};
 use tokio::signal;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::time::sleep;
 use tokio_util::codec::Framed;
-use tracing::{error, info, warn};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, info_span, warn, Instrument};
 use trading_protocol::{
-    create_codec, CandidatePromotePayload, CandidateUploadPayload, CapabilitiesPayload,
-    ControlCommand, DaemonBuildPayload, EngineCommand, EngineMode, EngineModePayload,
-    EngineStatePayload, Envelope, Event, ExecutionCommand, ExecutionFillsPayload,
-    ExecutionFillsResultPayload, ExecutionGetPayload, ExecutionOpenOrdersPayload,
-    ExecutionPlacePayload, ExecutionPlaceResultPayload, PortfolioBalancesPayload, PortfolioCommand,
-    PortfolioPositionsPayload, PortfolioSummaryPayload, RequestKind, RiskCommand,
-    RiskLimitsPayload, RiskOverridePayload, RiskStatePayload, RoutingCountersPayload,
-    ScopedKillSwitchesPayload, StrategyCommand, StrategySummaryPayload, DEFAULT_SOCKET_PATH,
-    PROTOCOL_VERSION, STATUS_SCHEMA_VERSION,
+    command_schemas, create_codec, negotiate_protocol_version, protocol_schemas,
+    CandidatePromotePayload, CandidatePromoteResultPayload, CandidateReplayPayload,
+    CandidateSummaryPayload, CandidateUploadPayload, CandidateUploadResultPayload,
+    CapabilitiesPayload, CapabilitiesRequestPayload, CapabilitiesResultPayload, ControlCommand,
+    DaemonBuildPayload, EngineCommand, EngineExportSnapshotPayload,
+    EngineExportSnapshotResultPayload, EngineMode, EngineModePayload, EngineModeResultPayload,
+    EngineReplayPayload, EngineReplayResultPayload, EngineStatePayload, EngineStatusPayload,
+    Envelope, ErrorCode, ErrorPayload, Event, EventSubscribePayload, EventSubscribeResultPayload,
+    ExecutionCancelResultPayload, ExecutionCommand, ExecutionFillsPayload,
+    ExecutionFillsResultPayload, ExecutionGetPayload, ExecutionGetResultPayload,
+    ExecutionOpenOrdersPayload, ExecutionPlacePayload, ExecutionPlaceResultPayload, HelloPayload,
+    HelloResultPayload, JsonCodec, MarketDataCommand, MarketDataProductPayload,
+    MarketDataProductResultPayload, MarketDataQuotePayload, MarketDataRegimePayload,
+    MarketDataRegimeResultPayload, MarketDataSubscribePayload, MarketDataSubscribeResultPayload,
+    OrderNotionalPayload, PaperAccountAction, PingResultPayload, PortfolioBalancesPayload,
+    PortfolioCommand, PortfolioPaperAccountPayload, PortfolioPaperAccountResultPayload,
+    PortfolioPnlPayload, PortfolioPositionsPayload, PortfolioSummaryPayload,
+    ProtocolSchemasPayload, ReplayDivergencePayload, RequestKind, RiskCommand, RiskLimitsPayload,
+    RiskOverridePayload, RiskOverrideResultPayload, RiskStatePayload, RiskStatusPayload,
+    RiskStatusResultPayload, RoutingCountersPayload, ScopedKillSwitchesPayload, StrategyCommand,
+    StrategyIdPayload, StrategyListResultPayload, StrategyResultPayload, StrategySummaryPayload,
+    DEFAULT_SOCKET_PATH, EVENT_KIND, MAX_FRAME_LENGTH, PROTOCOL_VERSION, STATUS_SCHEMA_VERSION,
+    SUPPORTED_PROTOCOL_VERSIONS,
 };
 use uuid::Uuid;
 
-use paper_exchange_adapter::PaperExchangeAdapter;
+use market_data::{MarketDataCache, DEFAULT_QUOTE_STALE_AFTER_MS};
+use pacing::{
+    order_is_urgent, OrderPacer, PacingDecision, VenueRateLimit, DEFAULT_PACING_MAX_QUEUE_DEPTH,
+    DEFAULT_PACING_MAX_WAIT_MS,
+};
+use paper_exchange_adapter::{MatchingConfig, PaperExchangeAdapter, DEFAULT_STARTING_USD};
+use replay::{
+    divergence, RecordedCancel, RecordedOrder, RecordedOutcome, RecordedRiskAction, ReplayStep,
+};
+use shutdown::{paper_flatten_orders, ShutdownPolicy, DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_MS};
 
 const DEFAULT_LOCK_PATH: &str = "/var/run/openclaw/trading.lock";
+/// Divergences listed in a replay report; the rest are only counted.
+const MAX_REPLAY_DIVERGENCES: usize = 200;
+/// Journal window replayed when a strategy candidate is uploaded.
+const CANDIDATE_REPLAY_WINDOW_MS: i64 = 24 * 60 * 60 * 1_000;
 const DEFAULT_DATA_DIR: &str = "/var/lib/openclaw/trading";
 const DEFAULT_CANDIDATE_TTL_MS: i64 = 0;
 const MAX_RECENT_EVENTS: usize = 128;
+/// Events buffered per subscriber before a slow event stream starts dropping.
+const EVENT_STREAM_CAPACITY: usize = 256;
 const PORTFOLIO_SYNC_INTERVAL_SECS: u64 = 15;
 const OPEN_ORDER_RECONCILE_INTERVAL_SECS: u64 = 20;
+const ORDER_RATE_WINDOW_MS: i64 = 60_000;
+/// Longest real-time sleep between pacing re-checks, so a queued order notices
+/// a simulated clock being advanced.
+const PACING_RECHECK_MS: i64 = 50;
+/// Date suffix of journal file names.
+const JOURNAL_DATE_FORMAT: &str = "%Y-%m-%d";
+const NOTIONAL_MISMATCH_TOLERANCE_BPS: i64 = 500;
+const ENGINE_SNAPSHOT_SCHEMA_VERSION: u8 = 3;
+const SNAPSHOT_EXPORT_FORMAT: &str = "openclaw.trading.engine_snapshot";
+/// Headroom left for the response envelope when an export is returned inline.
+const INLINE_EXPORT_FRAME_HEADROOM: usize = 4 * 1024;
+
+/// Optional behaviours advertised in `Control.Hello`.
+const DAEMON_FEATURES: &[&str] = &[
+    "error_codes",
+    "event_stream",
+    "graceful_shutdown",
+    "journal_replay",
+    "market_data",
+    "market_regime",
+    "order_pacing",
+    "portfolio_pnl",
+    "snapshot_export",
+];
+
+/// Journal appends run on the blocking pool; tracked so shutdown can wait for them.
+static JOURNAL_WRITES: LazyLock<TaskTracker> = LazyLock::new(TaskTracker::new);
+
+tokio::task_local! {
+    /// Correlation id of the request (or shutdown) being handled by this task.
+    static CORRELATION_ID: String;
+}
+
+fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+fn adapter_span(operation: &'static str, venue: &str) -> tracing::Span {
+    info_span!("adapter", operation, venue)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StrategyCandidate {
@@ -124,6 +204,7 @@ struct EngineState {
     mode: EngineMode,
     strategies: HashMap<String, StrategyState>,
     recent_events: VecDeque<serde_json::Value>,
+    events: broadcast::Sender<serde_json::Value>,
     risk_snapshot: RiskSnapshot,
     safety_policy: HardSafetyPolicy,
     data_dir: String,
@@ -139,6 +220,13 @@ struct EngineState {
     portfolio_positions: Vec<PositionSnapshot>,
     portfolio_balances: Vec<BalanceSnapshot>,
     last_orders_reset_ms: i64,
+    pacer: OrderPacer,
+    order_notional_cents: HashMap<String, i64>,
+    market_data: MarketDataCache,
+    paper_only: bool,
+    clock: SharedClock,
+    /// Off for replay sandboxes, which must not append to the daemon's journals.
+    journaling: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,6 +246,20 @@ struct EngineStateSnapshot {
     fills: Vec<FillReport>,
     processed_intents: Vec<String>,
     risk_snapshot: RiskSnapshot,
+    #[serde(default)]
+    order_notional_cents: HashMap<String, i64>,
+    /// Pins the engine to paper mode; set when importing with live routing disabled.
+    #[serde(default)]
+    paper_only: bool,
+}
+
+/// Portable copy of an engine snapshot produced by `Engine.ExportSnapshot`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EngineSnapshotExport {
+    format: String,
+    exported_at_ms: i64,
+    daemon_build: DaemonBuildPayload,
+    snapshot: EngineStateSnapshot,
 }
 
 #[derive(Debug)]
@@ -170,28 +272,49 @@ struct PromotionSuccess {
     phase: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct StrategyIdPayload {
-    strategy_id: String,
-}
-
 type DynAdapter = Arc<dyn ExchangeAdapter>;
 
 #[derive(Clone)]
 struct AdapterRegistry {
     coinbase: Option<DynAdapter>,
-    paper: DynAdapter,
+    kalshi: Option<DynAdapter>,
+    /// Concrete so streamed market data can drive its simulated book.
+    paper: Arc<PaperExchangeAdapter>,
+}
+
+impl AdapterRegistry {
+    /// Adapters trading real venues.
+    fn live(&self) -> impl Iterator<Item = &DynAdapter> {
+        self.coinbase.iter().chain(self.kalshi.iter())
+    }
+
+    fn live_available(&self) -> bool {
+        self.live().next().is_some()
+    }
 }
 
 #[derive(Clone)]
 struct DaemonContext {
     state: Arc<Mutex<EngineState>>,
     adapters: Arc<AdapterRegistry>,
+    market_data_sink: MarketDataSink,
+    /// Time source for the engine state, the paper adapter and background tasks.
+    clock: SharedClock,
+    /// Cancelled once a shutdown signal arrives; connections stop reading requests.
+    shutdown: CancellationToken,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
+    let _telemetry = trading_telemetry::init("trading_daemon", true);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import-snapshot") {
+        return import_snapshot_command(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("replay") {
+        return replay_command(&args[1..]).await;
+    }
 
     let socket_path = socket_path_from_env();
     let lock_path = lock_path_from_env();
@@ -199,6 +322,10 @@ async fn main() -> Result<()> {
     let state_path = state_path_from_env(&data_dir);
     let candidate_ttl_ms = candidate_ttl_ms_from_env();
     let default_mode = mode_from_env();
+    let pacer = pacer_from_env();
+    let market_data = MarketDataCache::new(quote_stale_after_ms_from_env())
+        .with_regime_config(regime_config_from_env());
+    let shutdown_policy = shutdown_policy_from_env();
 
     info!("Starting trading daemon");
     info!("Socket path: {}", socket_path);
@@ -212,39 +339,61 @@ async fn main() -> Result<()> {
     let _lock_file = acquire_single_instance_lock(&lock_path)?;
     let listener = bind_listener(&socket_path)?;
 
-    let adapters = Arc::new(build_adapters());
-    let state = Arc::new(Mutex::new(initial_engine_state(
+    let clock = system_clock();
+    let (mut adapters, coinbase_user_channel) = build_adapters(&clock, &data_dir);
+    let initial_state = initial_engine_state(
         data_dir.clone(),
         state_path,
         candidate_ttl_ms,
         default_mode,
-        adapters.coinbase.is_some(),
-    )));
+        pacer,
+        market_data,
+        adapters.live_available(),
+        Arc::clone(&clock),
+    );
+    if initial_state.paper_only && adapters.live_available() {
+        adapters.coinbase = None;
+        adapters.kalshi = None;
+        warn!("Engine state is paper-only (imported snapshot); live adapters disabled");
+    }
+    let adapters = Arc::new(adapters);
+    let state = Arc::new(Mutex::new(initial_state));
 
-    cleanup_old_journals(&data_dir, 7);
+    cleanup_old_journals(&data_dir, 7, clock.now_ms());
     recover_from_journals(&state).await;
 
+    let (market_data_tx, market_data_rx) = mpsc::unbounded_channel();
     let context = DaemonContext {
         state: Arc::clone(&state),
         adapters: Arc::clone(&adapters),
+        market_data_sink: Arc::new(move |update| {
+            let _ = market_data_tx.send(update);
+        }),
+        clock,
+        shutdown: CancellationToken::new(),
     };
 
     spawn_background_reconcilers(context.clone());
+    spawn_market_data(context.clone(), market_data_rx);
+    if let Some(coinbase) = coinbase_user_channel.filter(|_| adapters.coinbase.is_some()) {
+        spawn_coinbase_user_channel(context.clone(), coinbase);
+    }
 
-    let terminate = signal::ctrl_c();
+    let terminate = shutdown_signal();
     tokio::pin!(terminate);
+    let connections = TaskTracker::new();
 
     loop {
         tokio::select! {
-             _ = &mut terminate => {
-                info!("Shutdown signal received");
+             signal_name = &mut terminate => {
+                info!("Shutdown signal received ({})", signal_name);
                 break;
             }
             res = listener.accept() => {
                 match res {
                      Ok((stream, _addr)) => {
                         let context = context.clone();
-                        tokio::spawn(async move {
+                        connections.spawn(async move {
                             handle_connection(stream, context).await;
                         });
                      }
@@ -256,6 +405,15 @@ async fn main() -> Result<()> {
         }
     }
 
+    drop(listener);
+    CORRELATION_ID
+        .scope(
+            format!("shutdown-{}", context.clock.now_ms()),
+            shutdown_daemon(&context, &connections, shutdown_policy),
+        )
+        .instrument(info_span!("shutdown"))
+        .await;
+
     if Path::new(&socket_path).exists() {
         if let Err(err) = std::fs::remove_file(&socket_path) {
             error!("Failed to clean up socket {}: {:?}", socket_path, err);
@@ -266,29 +424,297 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn build_adapters() -> AdapterRegistry {
-    let paper: DynAdapter = Arc::new(PaperExchangeAdapter::new("paper"));
+/// Resolves on SIGINT or SIGTERM (sent by Docker and systemd on stop).
+async fn shutdown_signal() -> &'static str {
+    let mut sigterm = match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(err) => {
+            warn!("Failed to install SIGTERM handler: {:?}", err);
+            let _ = signal::ctrl_c().await;
+            return "SIGINT";
+        }
+    };
+    tokio::select! {
+        _ = signal::ctrl_c() => "SIGINT",
+        _ = sigterm.recv() => "SIGTERM",
+    }
+}
+
+/// Shutdown sequence run after the listener is closed: refuse new orders,
+/// drain in-flight requests, apply the cancel/flatten policy, wait for journal
+/// and paper ledger writes and persist a final snapshot.
+async fn shutdown_daemon(
+    context: &DaemonContext,
+    connections: &TaskTracker,
+    policy: ShutdownPolicy,
+) {
+    let drain_timeout = Duration::from_millis(policy.drain_timeout_ms);
+    context.shutdown.cancel();
+    {
+        let mut state = context.state.lock().await;
+        push_event(
+            &mut state,
+            Event::Alert {
+                level: "info".to_string(),
+                message: format!(
+                    "daemon shutting down (cancel_open_orders={}, flatten_paper_positions={})",
+                    policy.cancel_open_orders, policy.flatten_paper_positions
+                ),
+            },
+        );
+    }
+
+    connections.close();
+    if tokio::time::timeout(drain_timeout, connections.wait())
+        .await
+        .is_err()
+    {
+        warn!(
+            "{} connection(s) still busy after {}ms; continuing shutdown",
+            connections.len(),
+            policy.drain_timeout_ms
+        );
+    }
+
+    if policy.cancel_open_orders {
+        cancel_open_orders(context).await;
+    }
+    if policy.flatten_paper_positions {
+        flatten_paper_positions(context).await;
+    }
+
+    JOURNAL_WRITES.close();
+    if tokio::time::timeout(drain_timeout, JOURNAL_WRITES.wait())
+        .await
+        .is_err()
+    {
+        warn!(
+            "{} journal write(s) still pending after {}ms",
+            JOURNAL_WRITES.len(),
+            policy.drain_timeout_ms
+        );
+    }
+    if tokio::time::timeout(drain_timeout, context.adapters.paper.settle_ledger())
+        .await
+        .is_err()
+    {
+        warn!(
+            "paper ledger write still pending after {}ms",
+            policy.drain_timeout_ms
+        );
+    }
+
+    let state = context.state.lock().await;
+    persist_engine_state(&state);
+    info!("Final engine snapshot written to {}", state.state_path);
+}
+
+async fn cancel_open_orders(context: &DaemonContext) {
+    let open_orders: Vec<OrderSnapshot> = {
+        let state = context.state.lock().await;
+        state
+            .orders
+            .values()
+            .filter(|o| matches!(o.status, OrderStatus::New | OrderStatus::PartiallyFilled))
+            .cloned()
+            .collect()
+    };
+
+    for order in open_orders {
+        let Some(adapter) = cancel_adapter_for(Some(&order), &context.adapters) else {
+            warn!(
+                "Cannot cancel {} on shutdown: {} adapter unavailable",
+                order.venue_order_id, order.venue
+            );
+            continue;
+        };
+        if let Err(err) = adapter
+            .cancel_order(&order.venue_order_id)
+            .instrument(adapter_span("cancel_order", adapter.venue()))
+            .await
+        {
+            warn!(
+                "Failed to cancel {} on shutdown: {}",
+                order.venue_order_id, err.message
+            );
+            continue;
+        }
+
+        let mut state = context.state.lock().await;
+        record_order_canceled(&mut state, &order.venue_order_id);
+        push_event(
+            &mut state,
+            Event::Execution {
+                venue: order.venue.clone(),
+                strategy_id: order.strategy_id.clone(),
+                symbol: order.instrument.venue_symbol.clone(),
+                action: "cancel".to_string(),
+                status: "shutdown".to_string(),
+                latency_ms: 0,
+                simulated: order.simulated,
+                venue_order_id: Some(order.venue_order_id.clone()),
+                correlation_id: current_correlation_id(),
+            },
+        );
+        info!("Canceled {} on shutdown", order.venue_order_id);
+    }
+}
+
+/// Closes simulated positions on the paper adapter. These reduce-only orders
+/// skip pacing and the safety cage since they can only shrink exposure.
+async fn flatten_paper_positions(context: &DaemonContext) {
+    let orders = {
+        let state = context.state.lock().await;
+        paper_flatten_orders(&state.fills, state.clock.now_ms())
+    };
+
+    for order in orders {
+        let adapter = &context.adapters.paper;
+        let ack = match adapter
+            .place_order(order.clone())
+            .instrument(adapter_span("place_order", adapter.venue()))
+            .await
+        {
+            Ok(ack) => ack,
+            Err(err) => {
+                warn!(
+                    "Failed to flatten {} {} on shutdown: {}",
+                    order.strategy_id, order.symbol, err.message
+                );
+                continue;
+            }
+        };
+        let order_snapshot = match adapter
+            .get_order(&ack.venue_order_id)
+            .instrument(adapter_span("get_order", adapter.venue()))
+            .await
+        {
+            Ok(Some(snapshot)) => snapshot,
+            _ => synthetic_order_from_ack(&order, &ack),
+        };
+        let mut maybe_fill = maybe_fill_from_ack(&order_snapshot, &ack);
+        if let Some(fill) = maybe_fill.as_mut() {
+            (fill.fee, fill.liquidity) = venue_fill_costs(adapter.as_ref(), &order_snapshot).await;
+        }
+
+        let mut state = context.state.lock().await;
+        record_flatten_order(&mut state, &order, &order_snapshot, maybe_fill.as_ref());
+        push_event(
+            &mut state,
+            Event::Execution {
+                venue: order.venue.clone(),
+                strategy_id: order.strategy_id.clone(),
+                symbol: order.symbol.clone(),
+                action: "flatten".to_string(),
+                status: "shutdown".to_string(),
+                latency_ms: 0,
+                simulated: true,
+                venue_order_id: Some(ack.venue_order_id.clone()),
+                correlation_id: current_correlation_id(),
+            },
+        );
+
+        write_journal_entry(
+            &state,
+            "orders",
+            &json!({
+                "ts_ms": state.clock.now_ms(),
+                "intent_id": order.intent_id,
+                "routed_to": "paper",
+                "outcome": "flatten",
+                "request": order,
+                "order": order_snapshot,
+            }),
+        );
+        if let Some(fill) = &maybe_fill {
+            write_journal_entry(
+                &state,
+                "fills",
+                &json!({
+                    "ts_ms": state.clock.now_ms(),
+                    "intent_id": order.intent_id,
+                    "fill": fill,
+                }),
+            );
+        }
+        info!(
+            "Flattened {} {} {} on shutdown",
+            order.strategy_id, order.symbol, order.qty
+        );
+    }
+}
+
+/// Adapter registry, plus the concrete Coinbase adapter whose user channel
+/// streams order updates.
+fn build_adapters(
+    clock: &SharedClock,
+    data_dir: &str,
+) -> (AdapterRegistry, Option<CoinbaseAdvancedTradeAdapter>) {
+    let paper = Arc::new(build_paper_adapter(clock, data_dir));
+
+    let kalshi = if KalshiLiveAdapter::credentials_present() {
+        match KalshiLiveAdapter::from_env() {
+            Ok(adapter) => Some(Arc::new(adapter) as DynAdapter),
+            Err(err) => {
+                warn!("Kalshi adapter not initialized: {}", err.message);
+                None
+            }
+        }
+    } else {
+        warn!("KALSHI_API_KEY or KALSHI_SECRET_KEY missing; live prediction-market execution unavailable");
+        None
+    };
 
     let coinbase = if CoinbaseAdvancedTradeAdapter::credentials_present() {
         match CoinbaseAdvancedTradeAdapter::from_env() {
-            Ok(adapter) => Some(Arc::new(adapter) as DynAdapter),
+            Ok(adapter) => Some(adapter),
             Err(err) => {
                 warn!("Coinbase adapter not initialized: {}", err.message);
                 None
             }
         }
     } else {
-        warn!("COINBASE_BEARER_TOKEN missing; live spot execution unavailable");
+        warn!(
+            "COINBASE_API_KEY_FILE and COINBASE_BEARER_TOKEN missing; live spot execution unavailable"
+        );
         None
     };
 
-    AdapterRegistry { coinbase, paper }
+    (
+        AdapterRegistry {
+            coinbase: coinbase
+                .clone()
+                .map(|adapter| Arc::new(adapter) as DynAdapter),
+            kalshi,
+            paper,
+        },
+        coinbase,
+    )
 }
 
-fn now_ms() -> i64 {
-    match SystemTime::now().duration_since(UNIX_EPOCH) {
-        Ok(dur) => dur.as_millis() as i64,
-        Err(_) => 0,
+/// Paper adapter whose ledger survives restarts; falls back to an in-memory
+/// account when the ledger cannot be read or written.
+fn build_paper_adapter(clock: &SharedClock, data_dir: &str) -> PaperExchangeAdapter {
+    let matching = paper_matching_from_env();
+    let starting_balances = paper_balances_from_env();
+    let adapter = || {
+        PaperExchangeAdapter::with_clock("paper", Arc::clone(clock))
+            .with_matching(matching.clone())
+            .with_starting_balances(starting_balances.clone())
+    };
+    let ledger_path = paper_ledger_path_from_env(data_dir);
+    match adapter().with_ledger(&ledger_path) {
+        Ok(adapter) => {
+            info!("Paper ledger: {}", ledger_path);
+            adapter
+        }
+        Err(err) => {
+            warn!(
+                "{}; paper account will not persist across restarts",
+                err.message
+            );
+            adapter()
+        }
     }
 }
 
@@ -309,6 +735,41 @@ fn state_path_from_env(data_dir: &str) -> String {
         .unwrap_or_else(|_| format!("{}/state/engine-state.json", data_dir))
 }
 
+fn paper_ledger_path_from_env(data_dir: &str) -> String {
+    std::env::var("TRADING_PAPER_LEDGER_PATH")
+        .unwrap_or_else(|_| format!("{}/state/paper-ledger.json", data_dir))
+}
+
+/// Starting paper balances from `TRADING_PAPER_BALANCES`, e.g.
+/// `USD=250000,BTC=2`.
+fn paper_balances_from_env() -> BTreeMap<String, f64> {
+    let default = || BTreeMap::from([("USD".to_string(), DEFAULT_STARTING_USD)]);
+    let Ok(raw) = std::env::var("TRADING_PAPER_BALANCES") else {
+        return default();
+    };
+    let parsed: Option<BTreeMap<String, f64>> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (asset, amount) = entry.split_once('=')?;
+            let asset = asset.trim().to_ascii_uppercase();
+            let amount = amount.trim().parse::<f64>().ok()?;
+            (!asset.is_empty() && amount.is_finite() && amount >= 0.0).then_some((asset, amount))
+        })
+        .collect();
+    match parsed {
+        Some(balances) if !balances.is_empty() => balances,
+        _ => {
+            warn!(
+                "Invalid TRADING_PAPER_BALANCES='{}'; defaulting to USD={}",
+                raw, DEFAULT_STARTING_USD
+            );
+            default()
+        }
+    }
+}
+
 fn mode_from_env() -> EngineMode {
     match std::env::var("TRADING_ENGINE_MODE") {
         Ok(mode) => match mode.trim().to_ascii_lowercase().as_str() {
@@ -343,6 +804,196 @@ fn candidate_ttl_ms_from_env() -> i64 {
     }
 }
 
+fn flag_from_env(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" | "" => false,
+            _ => {
+                warn!("Invalid {}='{}'; treating as disabled", name, value);
+                false
+            }
+        },
+        Err(_) => false,
+    }
+}
+
+fn pacing_queue_enabled_from_env() -> bool {
+    flag_from_env("TRADING_PACING_QUEUE")
+}
+
+fn shutdown_policy_from_env() -> ShutdownPolicy {
+    let drain_timeout_ms = match std::env::var("TRADING_SHUTDOWN_DRAIN_TIMEOUT_MS") {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                warn!(
+                    "Invalid TRADING_SHUTDOWN_DRAIN_TIMEOUT_MS='{}'; defaulting to {}",
+                    value, DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_MS
+                );
+                DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_MS
+            }
+        },
+        Err(_) => DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_MS,
+    };
+    ShutdownPolicy {
+        cancel_open_orders: flag_from_env("TRADING_SHUTDOWN_CANCEL_OPEN_ORDERS"),
+        flatten_paper_positions: flag_from_env("TRADING_SHUTDOWN_FLATTEN_PAPER"),
+        drain_timeout_ms,
+    }
+}
+
+fn pacing_max_wait_ms_from_env() -> u64 {
+    match std::env::var("TRADING_PACING_MAX_WAIT_MS") {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                warn!(
+                    "Invalid TRADING_PACING_MAX_WAIT_MS='{}'; defaulting to {}",
+                    value, DEFAULT_PACING_MAX_WAIT_MS
+                );
+                DEFAULT_PACING_MAX_WAIT_MS
+            }
+        },
+        Err(_) => DEFAULT_PACING_MAX_WAIT_MS,
+    }
+}
+
+fn pacing_max_queue_depth_from_env() -> usize {
+    match std::env::var("TRADING_PACING_MAX_QUEUE_DEPTH") {
+        Ok(value) => match value.parse::<usize>() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                warn!(
+                    "Invalid TRADING_PACING_MAX_QUEUE_DEPTH='{}'; defaulting to {}",
+                    value, DEFAULT_PACING_MAX_QUEUE_DEPTH
+                );
+                DEFAULT_PACING_MAX_QUEUE_DEPTH
+            }
+        },
+        Err(_) => DEFAULT_PACING_MAX_QUEUE_DEPTH,
+    }
+}
+
+fn venue_rate_limits_from_env() -> HashMap<String, VenueRateLimit> {
+    let mut limits = pacing::default_venue_rate_limits();
+    if let Ok(raw) = std::env::var("TRADING_VENUE_RATE_LIMITS") {
+        match pacing::parse_venue_rate_limits(&raw) {
+            Ok(overrides) => limits.extend(overrides),
+            Err(err) => warn!(
+                "Invalid TRADING_VENUE_RATE_LIMITS='{}' ({}); using published defaults",
+                raw, err
+            ),
+        }
+    }
+    limits
+}
+
+fn quote_stale_after_ms_from_env() -> i64 {
+    match std::env::var("TRADING_QUOTE_STALE_MS") {
+        Ok(value) => match value.parse::<i64>() {
+            Ok(parsed) if parsed >= 0 => parsed,
+            _ => {
+                warn!(
+                    "Invalid TRADING_QUOTE_STALE_MS='{}'; defaulting to {}",
+                    value, DEFAULT_QUOTE_STALE_AFTER_MS
+                );
+                DEFAULT_QUOTE_STALE_AFTER_MS
+            }
+        },
+        Err(_) => DEFAULT_QUOTE_STALE_AFTER_MS,
+    }
+}
+
+fn regime_lookback_ms_from_env(name: &str, default_ms: i64) -> i64 {
+    match std::env::var(name) {
+        Ok(value) => match value.parse::<i64>() {
+            Ok(parsed) if parsed > 0 => parsed,
+            _ => {
+                warn!("Invalid {}='{}'; defaulting to {}", name, value, default_ms);
+                default_ms
+            }
+        },
+        Err(_) => default_ms,
+    }
+}
+
+fn regime_config_from_env() -> RegimeConfig {
+    let defaults = RegimeConfig::default();
+    RegimeConfig {
+        momentum_lookback_ms: regime_lookback_ms_from_env(
+            "TRADING_REGIME_MOMENTUM_LOOKBACK_MS",
+            defaults.momentum_lookback_ms,
+        ),
+        volatility_lookback_ms: regime_lookback_ms_from_env(
+            "TRADING_REGIME_VOLATILITY_LOOKBACK_MS",
+            defaults.volatility_lookback_ms,
+        ),
+        imbalance_lookback_ms: regime_lookback_ms_from_env(
+            "TRADING_REGIME_IMBALANCE_LOOKBACK_MS",
+            defaults.imbalance_lookback_ms,
+        ),
+        ..defaults
+    }
+}
+
+fn paper_matching_from_env() -> MatchingConfig {
+    let defaults = MatchingConfig::default();
+    let latency_ms = match std::env::var("TRADING_PAPER_LATENCY_MS") {
+        Ok(value) => match value.parse::<i64>() {
+            Ok(parsed) if parsed >= 0 => parsed,
+            _ => {
+                warn!(
+                    "Invalid TRADING_PAPER_LATENCY_MS='{}'; defaulting to {}",
+                    value, defaults.latency_ms
+                );
+                defaults.latency_ms
+            }
+        },
+        Err(_) => defaults.latency_ms,
+    };
+    let touch_liquidity = match std::env::var("TRADING_PAPER_TOUCH_LIQUIDITY") {
+        Ok(value) => match value.parse::<f64>() {
+            Ok(parsed) if parsed.is_finite() && parsed > 0.0 => Some(parsed),
+            _ => {
+                warn!(
+                    "Invalid TRADING_PAPER_TOUCH_LIQUIDITY='{}'; touch size is unlimited",
+                    value
+                );
+                None
+            }
+        },
+        Err(_) => defaults.touch_liquidity,
+    };
+    MatchingConfig {
+        latency_ms,
+        touch_liquidity,
+        ..defaults
+    }
+}
+
+fn market_data_symbols_from_env() -> BTreeMap<String, Vec<String>> {
+    match std::env::var("TRADING_MARKET_DATA_SYMBOLS") {
+        Ok(raw) => market_data::parse_market_data_symbols(&raw).unwrap_or_else(|err| {
+            warn!(
+                "Invalid TRADING_MARKET_DATA_SYMBOLS='{}' ({}); no startup subscriptions",
+                raw, err
+            );
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+fn pacer_from_env() -> OrderPacer {
+    OrderPacer::new(
+        venue_rate_limits_from_env(),
+        pacing_queue_enabled_from_env(),
+        pacing_max_wait_ms_from_env(),
+        pacing_max_queue_depth_from_env(),
+    )
+}
+
 fn ensure_socket_parent_dir(socket_path: &str) -> Result<()> {
     let parent = PathBuf::from(socket_path)
         .parent()
@@ -390,14 +1041,18 @@ fn bind_listener(socket_path: &str) -> Result<UnixListener> {
     Ok(listener)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn initial_engine_state(
     data_dir: String,
     state_path: String,
     candidate_ttl_ms: i64,
     mode: EngineMode,
-    coinbase_available: bool,
+    pacer: OrderPacer,
+    market_data: MarketDataCache,
+    live_available: bool,
+    clock: SharedClock,
 ) -> EngineState {
-    let now = now_ms();
+    let now = clock.now_ms();
     let mut state = EngineState {
         running: false,
         paused: false,
@@ -408,6 +1063,7 @@ fn initial_engine_state(
         mode,
         strategies: default_strategies(),
         recent_events: VecDeque::new(),
+        events: broadcast::channel(EVENT_STREAM_CAPACITY).0,
         risk_snapshot: RiskSnapshot::default(),
         safety_policy: HardSafetyPolicy::default(),
         data_dir,
@@ -423,6 +1079,12 @@ fn initial_engine_state(
         portfolio_positions: Vec::new(),
         portfolio_balances: Vec::new(),
         last_orders_reset_ms: now,
+        pacer,
+        order_notional_cents: HashMap::new(),
+        market_data,
+        paper_only: false,
+        clock,
+        journaling: true,
     };
 
     match load_engine_snapshot(&state.state_path) {
@@ -439,7 +1101,7 @@ fn initial_engine_state(
         }
     }
 
-    if state.mode != EngineMode::Paper && !coinbase_available {
+    if state.mode != EngineMode::Paper && !live_available {
         state.running = false;
         state.paused = true;
         state.risk_tripped = true;
@@ -447,7 +1109,7 @@ fn initial_engine_state(
             &mut state,
             Event::Alert {
                 level: "critical".to_string(),
-                message: "auto/hitl live mode requested but Coinbase credentials unavailable; engine remains non-running"
+                message: "auto/hitl live mode requested but no live venue credentials (Coinbase or Kalshi) available; engine remains non-running"
                     .to_string(),
             },
         );
@@ -505,8 +1167,8 @@ fn strategy_snapshot_from_state(state: &EngineState) -> EngineStateSnapshot {
     strategies.sort_by(|a, b| a.id.cmp(&b.id));
 
     EngineStateSnapshot {
-        schema_version: 2,
-        saved_at_ms: now_ms(),
+        schema_version: ENGINE_SNAPSHOT_SCHEMA_VERSION,
+        saved_at_ms: state.clock.now_ms(),
         mode: state.mode,
         kill_switch_engaged: state.kill_switch_engaged,
         paused: state.paused,
@@ -520,6 +1182,8 @@ fn strategy_snapshot_from_state(state: &EngineState) -> EngineStateSnapshot {
         fills: state.fills.clone(),
         processed_intents: state.processed_intents.iter().cloned().collect(),
         risk_snapshot: state.risk_snapshot.clone(),
+        order_notional_cents: state.order_notional_cents.clone(),
+        paper_only: state.paper_only,
     }
 }
 
@@ -538,50 +1202,205 @@ fn load_engine_snapshot(path: &str) -> Result<Option<EngineStateSnapshot>> {
 }
 
 fn save_engine_snapshot(path: &str, snapshot: &EngineStateSnapshot) -> Result<()> {
+    write_json_atomically(path, snapshot, "engine snapshot").map(|_| ())
+}
+
+/// Writes `value` as pretty JSON via a temporary file and rename; returns the
+/// number of bytes written.
+fn write_json_atomically<T: Serialize>(path: &str, value: &T, what: &str) -> Result<usize> {
     if let Some(parent) = Path::new(path).parent() {
         std::fs::create_dir_all(parent).with_context(|| {
             format!(
-                "Failed to create parent directory for engine snapshot {}",
+                "Failed to create parent directory for {} {}",
+                what,
                 parent.display()
             )
         })?;
     }
 
     let tmp_path = format!("{}.tmp.{}", path, std::process::id());
-    let bytes = serde_json::to_vec_pretty(snapshot).context("Failed to encode engine snapshot")?;
+    let bytes =
+        serde_json::to_vec_pretty(value).with_context(|| format!("Failed to encode {}", what))?;
+    let len = bytes.len();
     std::fs::write(&tmp_path, bytes)
-        .with_context(|| format!("Failed to write temporary engine snapshot {}", tmp_path))?;
-    std::fs::rename(&tmp_path, path).with_context(|| {
-        format!(
-            "Failed to move temporary engine snapshot {} to {}",
-            tmp_path, path
-        )
-    })?;
-    Ok(())
+        .with_context(|| format!("Failed to write temporary {} {}", what, tmp_path))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to move temporary {} {} to {}", what, tmp_path, path))?;
+    Ok(len)
 }
 
-fn apply_engine_snapshot(state: &mut EngineState, snapshot: EngineStateSnapshot) {
-    for persisted in snapshot.strategies {
-        let Some(strategy) = state.strategies.get_mut(&persisted.id) else {
-            warn!(
-                "Ignoring persisted strategy '{}' because it is not in defaults",
-                persisted.id
-            );
-            continue;
-        };
+fn snapshot_export_from_state(state: &EngineState) -> EngineSnapshotExport {
+    EngineSnapshotExport {
+        format: SNAPSHOT_EXPORT_FORMAT.to_string(),
+        exported_at_ms: state.clock.now_ms(),
+        daemon_build: daemon_build_payload(),
+        snapshot: strategy_snapshot_from_state(state),
+    }
+}
 
-        if strategy.family != persisted.family {
-            warn!(
-                "Ignoring persisted strategy '{}' due to family mismatch ({:?} != {:?})",
-                persisted.id, persisted.family, strategy.family
-            );
-            continue;
+/// Export file names are confined to `<data_dir>/exports`.
+fn export_path(data_dir: &str, file_name: &str) -> std::result::Result<PathBuf, String> {
+    let valid = !file_name.is_empty()
+        && !file_name.starts_with('.')
+        && file_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(format!(
+            "invalid export file_name '{}': use letters, digits, '.', '_' or '-'",
+            file_name
+        ));
+    }
+    Ok(Path::new(data_dir).join("exports").join(file_name))
+}
+
+/// Accepts either an `Engine.ExportSnapshot` bundle or a raw state file.
+fn decode_snapshot_import(raw: &[u8]) -> Result<EngineStateSnapshot> {
+    let value: serde_json::Value =
+        serde_json::from_slice(raw).context("Snapshot import is not valid JSON")?;
+    let snapshot: EngineStateSnapshot = if value.get("snapshot").is_some() {
+        let export: EngineSnapshotExport =
+            serde_json::from_value(value).context("Failed to decode snapshot export")?;
+        if export.format != SNAPSHOT_EXPORT_FORMAT {
+            anyhow::bail!("Unsupported snapshot export format '{}'", export.format);
         }
+        export.snapshot
+    } else {
+        serde_json::from_value(value).context("Failed to decode engine snapshot")?
+    };
 
-        strategy.enabled = persisted.enabled;
-        strategy.source = persisted.source;
-        strategy.version = persisted.version.max(1);
-        strategy.canary_deployment = persisted.canary_deployment;
+    if snapshot.schema_version > ENGINE_SNAPSHOT_SCHEMA_VERSION {
+        anyhow::bail!(
+            "Snapshot schema_version {} is newer than supported {}",
+            snapshot.schema_version,
+            ENGINE_SNAPSHOT_SCHEMA_VERSION
+        );
+    }
+    Ok(snapshot)
+}
+
+/// Pins an imported snapshot to paper so no order can reach a live venue.
+fn disable_live_routing(snapshot: &mut EngineStateSnapshot) {
+    snapshot.mode = EngineMode::Paper;
+    snapshot.paper_only = true;
+}
+
+/// `trading_daemon import-snapshot <file> [--paper] [--force]`: installs an
+/// exported snapshot as this host's engine state while the daemon is stopped.
+/// `trading_daemon replay [--from-ms <ms>] [--to-ms <ms>]` replays the
+/// journals under the data dir, prints the report and fails on divergences.
+/// Safe to run next to a live daemon: the sandbox never writes journals.
+async fn replay_command(args: &[String]) -> Result<()> {
+    let mut from_ms = 0;
+    let mut to_ms = SystemClock.now_ms();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let bound = match flag.as_str() {
+            "--from-ms" => &mut from_ms,
+            "--to-ms" => &mut to_ms,
+            other => anyhow::bail!("Unknown replay argument {}", other),
+        };
+        *bound = args
+            .next()
+            .with_context(|| format!("{} requires a value", flag))?
+            .parse()
+            .with_context(|| format!("{} must be a timestamp in milliseconds", flag))?;
+    }
+    if from_ms > to_ms {
+        anyhow::bail!("--from-ms must not be after --to-ms");
+    }
+
+    let data_dir = data_dir_from_env();
+    let report = replay_journals(&data_dir, HardSafetyPolicy::default(), from_ms, to_ms).await;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.passed {
+        anyhow::bail!(
+            "journal replay found {} divergences",
+            report.divergence_count
+        );
+    }
+    Ok(())
+}
+
+fn import_snapshot_command(args: &[String]) -> Result<()> {
+    let mut source = None;
+    let mut paper = false;
+    let mut force = false;
+    for arg in args {
+        match arg.as_str() {
+            "--paper" => paper = true,
+            "--force" => force = true,
+            flag if flag.starts_with("--") => {
+                anyhow::bail!("Unknown import-snapshot flag {}", flag)
+            }
+            path if source.is_none() => source = Some(path.to_string()),
+            extra => anyhow::bail!("Unexpected import-snapshot argument {}", extra),
+        }
+    }
+    let source =
+        source.context("usage: trading_daemon import-snapshot <file> [--paper] [--force]")?;
+
+    let data_dir = data_dir_from_env();
+    let state_path = state_path_from_env(&data_dir);
+    let lock_path = lock_path_from_env();
+    let _lock_file = acquire_single_instance_lock(&lock_path)
+        .context("Stop the daemon before importing a snapshot")?;
+
+    let raw = std::fs::read(&source)
+        .with_context(|| format!("Failed to read snapshot import {}", source))?;
+    let mut snapshot = decode_snapshot_import(&raw)?;
+    if paper {
+        disable_live_routing(&mut snapshot);
+    }
+
+    if Path::new(&state_path).exists() {
+        if !force {
+            anyhow::bail!(
+                "Refusing to replace existing engine state {} without --force",
+                state_path
+            );
+        }
+        let backup_path = format!("{}.bak.{}", state_path, SystemClock.now_ms());
+        std::fs::copy(&state_path, &backup_path)
+            .with_context(|| format!("Failed to back up {} to {}", state_path, backup_path))?;
+        info!("Backed up existing engine state to {}", backup_path);
+    }
+
+    save_engine_snapshot(&state_path, &snapshot)?;
+    info!(
+        "Imported snapshot {} into {} (mode={}, paper_only={}, orders={}, fills={})",
+        source,
+        state_path,
+        snapshot.mode.as_str(),
+        snapshot.paper_only,
+        snapshot.orders.len(),
+        snapshot.fills.len()
+    );
+    Ok(())
+}
+
+fn apply_engine_snapshot(state: &mut EngineState, snapshot: EngineStateSnapshot) {
+    for persisted in snapshot.strategies {
+        let Some(strategy) = state.strategies.get_mut(&persisted.id) else {
+            warn!(
+                "Ignoring persisted strategy '{}' because it is not in defaults",
+                persisted.id
+            );
+            continue;
+        };
+
+        if strategy.family != persisted.family {
+            warn!(
+                "Ignoring persisted strategy '{}' due to family mismatch ({:?} != {:?})",
+                persisted.id, persisted.family, strategy.family
+            );
+            continue;
+        }
+
+        strategy.enabled = persisted.enabled;
+        strategy.source = persisted.source;
+        strategy.version = persisted.version.max(1);
+        strategy.canary_deployment = persisted.canary_deployment;
         strategy.canary_notional_cents = persisted.canary_notional_cents.max(0);
         strategy.active_code_hash = persisted.active_code_hash;
         strategy.candidate = persisted.candidate;
@@ -603,6 +1422,16 @@ fn apply_engine_snapshot(state: &mut EngineState, snapshot: EngineStateSnapshot)
     state.fills = snapshot.fills;
     state.processed_intents = snapshot.processed_intents.into_iter().collect();
     state.risk_snapshot = snapshot.risk_snapshot;
+    state.order_notional_cents = snapshot.order_notional_cents;
+    // Snapshots written before settled orders were pruned still list them.
+    let tracked: Vec<String> = state.order_notional_cents.keys().cloned().collect();
+    for venue_order_id in tracked {
+        release_settled_notional(state, &venue_order_id);
+    }
+    state.paper_only = snapshot.paper_only;
+    if state.paper_only {
+        state.mode = EngineMode::Paper;
+    }
     sync_scoped_kills_into_snapshot(state);
 }
 
@@ -616,16 +1445,28 @@ fn persist_engine_state(state: &EngineState) {
     }
 }
 
-fn journal_path(data_dir: &str, stream: &str) -> PathBuf {
-    let date_key = chrono::Utc::now().format("%Y-%m-%d").to_string();
+/// Journal file for `stream` on the UTC day of `now_ms` (engine clock time).
+fn journal_path(data_dir: &str, stream: &str, now_ms: i64) -> PathBuf {
+    let date_key = journal_date(now_ms).format(JOURNAL_DATE_FORMAT).to_string();
     Path::new(data_dir)
         .join("journal")
         .join(format!("{}-{}.jsonl", stream, date_key))
 }
 
-fn write_journal_entry(data_dir: &str, stream: &str, entry: &serde_json::Value) {
-    let path = journal_path(data_dir, stream);
-    let line = match serde_json::to_string(entry) {
+fn write_journal_entry(state: &EngineState, stream: &str, entry: &serde_json::Value) {
+    if !state.journaling {
+        return;
+    }
+    let path = journal_path(&state.data_dir, stream, state.clock.now_ms());
+    let encoded = match current_correlation_id() {
+        Some(correlation_id) if entry.is_object() && entry.get("correlation_id").is_none() => {
+            let mut entry = entry.clone();
+            entry["correlation_id"] = json!(correlation_id);
+            serde_json::to_string(&entry)
+        }
+        _ => serde_json::to_string(entry),
+    };
+    let line = match encoded {
         Ok(line) => line,
         Err(err) => {
             warn!("failed to serialize journal entry: {}", err);
@@ -634,7 +1475,7 @@ fn write_journal_entry(data_dir: &str, stream: &str, entry: &serde_json::Value)
     };
 
     // Offload blocking file I/O to the blocking thread pool
-    tokio::task::spawn_blocking(move || {
+    JOURNAL_WRITES.spawn_blocking(move || {
         if let Some(parent) = path.parent() {
             if let Err(err) = std::fs::create_dir_all(parent) {
                 warn!("failed to create journal dir {}: {}", parent.display(), err);
@@ -714,7 +1555,7 @@ async fn recover_from_journals(state: &Arc<Mutex<EngineState>>) {
         let mut paths: Vec<PathBuf> = entries
             .filter_map(|e| e.ok())
             .map(|e| e.path())
-            .filter(|p| p.extension().map_or(false, |ext| ext == "jsonl"))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
             .collect();
         paths.sort();
 
@@ -743,11 +1584,19 @@ async fn recover_from_journals(state: &Arc<Mutex<EngineState>>) {
     }
 }
 
-fn cleanup_old_journals(data_dir: &str, max_age_days: u64) {
+fn journal_date(now_ms: i64) -> chrono::NaiveDate {
+    chrono::DateTime::from_timestamp_millis(now_ms)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Removes journal files dated more than `max_age_days` before the UTC day of
+/// `now_ms` (engine clock time), going by the date in their names.
+fn cleanup_old_journals(data_dir: &str, max_age_days: u64, now_ms: i64) {
     let journal_dir = Path::new(data_dir).join("journal");
-    let cutoff = SystemTime::now()
-        .checked_sub(Duration::from_secs(max_age_days * 86400))
-        .unwrap_or(UNIX_EPOCH);
+    let cutoff = journal_date(now_ms)
+        .checked_sub_days(chrono::Days::new(max_age_days))
+        .unwrap_or(chrono::NaiveDate::MIN);
 
     let entries = match std::fs::read_dir(&journal_dir) {
         Ok(entries) => entries,
@@ -756,14 +1605,18 @@ fn cleanup_old_journals(data_dir: &str, max_age_days: u64) {
 
     for entry in entries.filter_map(|e| e.ok()) {
         let path = entry.path();
-        if path.extension().map_or(true, |ext| ext != "jsonl") {
+        if path.extension().is_none_or(|ext| ext != "jsonl") {
             continue;
         }
-        let modified = match entry.metadata().and_then(|m| m.modified()) {
-            Ok(t) => t,
-            Err(_) => continue,
+        let dated = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.len().checked_sub(10).map(|at| &stem[at..]))
+            .and_then(|key| chrono::NaiveDate::parse_from_str(key, JOURNAL_DATE_FORMAT).ok());
+        let Some(date) = dated else {
+            continue;
         };
-        if modified < cutoff {
+        if date < cutoff {
             info!("Removing old journal file: {}", path.display());
             if let Err(err) = std::fs::remove_file(&path) {
                 warn!("Failed to remove old journal {}: {}", path.display(), err);
@@ -772,6 +1625,345 @@ fn cleanup_old_journals(data_dir: &str, max_age_days: u64) {
     }
 }
 
+/// Replays the journaled orders and risk actions in `[from_ms, to_ms]` through
+/// a sandboxed paper engine, using the same safety cage and state transitions
+/// as live request handling, and reports where the outcomes differ from the
+/// journal. After a divergence the sandbox follows the recorded outcome so
+/// one difference does not cascade into the rest of the report.
+async fn replay_journals(
+    data_dir: &str,
+    safety_policy: HardSafetyPolicy,
+    from_ms: i64,
+    to_ms: i64,
+) -> EngineReplayResultPayload {
+    let journal = replay::load_journal_window(&Path::new(data_dir).join("journal"), from_ms, to_ms);
+    let clock = Arc::new(SimulatedClock::new(from_ms));
+    // An empty state path loads no snapshot: the sandbox starts from a fresh engine.
+    let mut sandbox = initial_engine_state(
+        data_dir.to_string(),
+        String::new(),
+        0,
+        EngineMode::Paper,
+        OrderPacer::new(
+            pacing::default_venue_rate_limits(),
+            false,
+            DEFAULT_PACING_MAX_WAIT_MS,
+            DEFAULT_PACING_MAX_QUEUE_DEPTH,
+        ),
+        MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+        false,
+        clock.clone(),
+    );
+    sandbox.journaling = false;
+    sandbox.safety_policy = safety_policy;
+    let paper = PaperExchangeAdapter::with_clock("paper", clock.clone());
+
+    let mut recorded_fills = journal.fills;
+    let mut divergences = Vec::new();
+    // Sandbox orders get their own venue order ids; cancels name the recorded ones.
+    let mut replayed_order_ids: HashMap<String, String> = HashMap::new();
+    let (mut orders, mut risk_actions) = (0, 0);
+    for step in journal.steps {
+        clock.set(step.ts_ms());
+        match step {
+            ReplayStep::Order(recorded) => {
+                orders += 1;
+                let recorded_id = recorded
+                    .order
+                    .as_ref()
+                    .map(|order| order.venue_order_id.clone());
+                let replayed_id = replay_order(
+                    &mut sandbox,
+                    &paper,
+                    *recorded,
+                    &mut recorded_fills,
+                    &mut divergences,
+                )
+                .await;
+                if let (Some(recorded_id), Some(replayed_id)) = (recorded_id, replayed_id) {
+                    replayed_order_ids.insert(recorded_id, replayed_id);
+                }
+            }
+            ReplayStep::Cancel(recorded) => {
+                replay_cancel(&mut sandbox, &paper, &replayed_order_ids, recorded).await;
+            }
+            ReplayStep::Risk(recorded) => {
+                risk_actions += 1;
+                replay_risk_action(&mut sandbox, recorded, &mut divergences);
+            }
+        }
+    }
+    for (venue_order_id, fills) in recorded_fills {
+        for (ts_ms, _) in fills {
+            divergences.push(divergence(
+                ts_ms,
+                "fills",
+                &venue_order_id,
+                "unmatched_fill",
+                "fill",
+                "no journaled order in window",
+            ));
+        }
+    }
+    divergences.sort_by_key(|divergence| divergence.ts_ms);
+    let divergence_count = divergences.len();
+    divergences.truncate(MAX_REPLAY_DIVERGENCES);
+
+    EngineReplayResultPayload {
+        from_ms,
+        to_ms,
+        orders,
+        fills: journal.fill_count,
+        risk_actions,
+        accepted: sandbox.execution_stats.accepted,
+        rejected: sandbox.execution_stats.rejected,
+        passed: divergence_count == 0,
+        divergence_count,
+        divergences,
+        risk_state: risk_state_payload(&sandbox),
+    }
+}
+
+/// Places `order` on the sandbox paper adapter the way live routing does.
+///
+/// The journals carry no market data, so the order meets a book offering the
+/// quantity its recorded fills traded at their weighted price, or one with no
+/// touch when it did not fill, and trades or rests as it did when it was
+/// recorded.
+async fn replay_paper_order(
+    paper: &PaperExchangeAdapter,
+    order: &NormalizedOrderRequest,
+    recorded_fill: Option<&FillReport>,
+    ts_ms: i64,
+) -> std::result::Result<(OrderSnapshot, Option<FillReport>), ExchangeError> {
+    let touch = recorded_fill.map(|fill| fill.price);
+    let (bid, ask) = match order.side {
+        OrderSide::Buy => (None, touch),
+        OrderSide::Sell => (touch, None),
+    };
+    let quote = QuoteSnapshot {
+        venue: paper.venue().to_string(),
+        venue_symbol: order.instrument.venue_symbol.clone(),
+        bid,
+        ask,
+        last: None,
+        mark: None,
+        ts_ms,
+    };
+    let touch_size = recorded_fill.map_or(0.0, |fill| fill.qty);
+    paper.apply_book(&quote, touch_size).await;
+    let ack = paper.place_order(order.clone()).await?;
+    let snapshot = match paper.get_order(&ack.venue_order_id).await {
+        Ok(Some(snapshot)) => snapshot,
+        _ => synthetic_order_from_ack(order, &ack),
+    };
+    let fill = maybe_fill_from_ack(&snapshot, &ack);
+    Ok((snapshot, fill))
+}
+
+async fn replay_order(
+    sandbox: &mut EngineState,
+    paper: &PaperExchangeAdapter,
+    recorded: RecordedOrder,
+    recorded_fills: &mut HashMap<String, Vec<(i64, FillReport)>>,
+    divergences: &mut Vec<ReplayDivergencePayload>,
+) -> Option<String> {
+    let ts_ms = recorded.ts_ms;
+    let key = recorded.intent_id.as_str();
+    let request = &recorded.request;
+    // Fills booked after placement (reconciled partials, later trades) are
+    // compared together with the first.
+    let recorded_fill = recorded
+        .order
+        .as_ref()
+        .and_then(|order| recorded_fills.remove(&order.venue_order_id))
+        .and_then(replay::combined_fill);
+
+    if recorded.outcome != RecordedOutcome::Flatten {
+        let notional_cents = recorded
+            .notional_cents
+            .unwrap_or_else(|| assess_order_notional(request, None).used_notional_cents);
+        roll_order_rate_window(sandbox, ts_ms);
+        let decision = evaluate_order_risk(
+            request,
+            notional_cents,
+            &sandbox.risk_snapshot,
+            sandbox.safety_policy.clone(),
+        );
+        match (&recorded.outcome, decision) {
+            (RecordedOutcome::RiskDenied { reason }, decision) => {
+                if matches!(decision, RiskDecision::Allow) {
+                    divergences.push(divergence(
+                        ts_ms,
+                        "orders",
+                        key,
+                        "risk_decision",
+                        format!("denied: {}", reason),
+                        "allowed",
+                    ));
+                }
+                sandbox.execution_stats.rejected =
+                    sandbox.execution_stats.rejected.saturating_add(1);
+                return None;
+            }
+            (_, RiskDecision::Deny { reason }) => divergences.push(divergence(
+                ts_ms,
+                "orders",
+                key,
+                "risk_decision",
+                "accepted",
+                format!("denied: {}", reason),
+            )),
+            (_, RiskDecision::Allow) => {}
+        }
+        if sandbox.processed_intents.contains(key) {
+            divergences.push(divergence(
+                ts_ms,
+                "orders",
+                key,
+                "duplicate_intent",
+                "placed",
+                "intent already processed",
+            ));
+        }
+
+        let live_route = recorded.routed_to != "paper";
+        let (snapshot, fill) = if live_route {
+            // Venue fills cannot be simulated; book what the venue reported.
+            match &recorded.order {
+                Some(order) => (order.clone(), recorded_fill),
+                None => return None,
+            }
+        } else {
+            match replay_paper_order(paper, request, recorded_fill.as_ref(), ts_ms).await {
+                Ok((snapshot, fill)) => {
+                    if let Some((recorded, replayed)) =
+                        replay::fill_mismatch(recorded_fill.as_ref(), fill.as_ref())
+                    {
+                        divergences
+                            .push(divergence(ts_ms, "fills", key, "fill", recorded, replayed));
+                    }
+                    (snapshot, fill)
+                }
+                Err(err) => {
+                    divergences.push(divergence(
+                        ts_ms,
+                        "orders",
+                        key,
+                        "fill",
+                        "accepted",
+                        format!("paper adapter rejected: {}", err.message),
+                    ));
+                    return None;
+                }
+            }
+        };
+        record_accepted_order(
+            sandbox,
+            request,
+            key,
+            notional_cents,
+            live_route,
+            &snapshot,
+            fill.as_ref(),
+        );
+        return Some(snapshot.venue_order_id);
+    }
+
+    match replay_paper_order(paper, request, recorded_fill.as_ref(), ts_ms).await {
+        Ok((snapshot, fill)) => {
+            if let Some((recorded, replayed)) =
+                replay::fill_mismatch(recorded_fill.as_ref(), fill.as_ref())
+            {
+                divergences.push(divergence(ts_ms, "fills", key, "fill", recorded, replayed));
+            }
+            record_flatten_order(sandbox, request, &snapshot, fill.as_ref());
+            Some(snapshot.venue_order_id)
+        }
+        Err(err) => {
+            divergences.push(divergence(
+                ts_ms,
+                "orders",
+                key,
+                "fill",
+                "flattened",
+                format!("paper adapter rejected: {}", err.message),
+            ));
+            None
+        }
+    }
+}
+
+/// Cancels the sandbox order standing in for a recorded one, releasing its
+/// notional. Orders placed before the window hold nothing in the sandbox.
+async fn replay_cancel(
+    sandbox: &mut EngineState,
+    paper: &PaperExchangeAdapter,
+    replayed_order_ids: &HashMap<String, String>,
+    recorded: RecordedCancel,
+) {
+    let Some(venue_order_id) = replayed_order_ids.get(&recorded.venue_order_id) else {
+        return;
+    };
+    let Some(order) = sandbox.orders.get(venue_order_id) else {
+        return;
+    };
+    if !matches!(
+        order.status,
+        OrderStatus::New | OrderStatus::PartiallyFilled
+    ) {
+        return;
+    }
+    // Live-routed orders were booked as the venue reported them and never
+    // rested on the sandbox adapter.
+    if order.simulated {
+        if let Err(err) = paper.cancel_order(venue_order_id).await {
+            warn!(
+                "Replay could not cancel {} on the sandbox: {}",
+                venue_order_id, err.message
+            );
+        }
+    }
+    record_order_canceled(sandbox, venue_order_id);
+}
+
+fn replay_risk_action(
+    sandbox: &mut EngineState,
+    recorded: RecordedRiskAction,
+    divergences: &mut Vec<ReplayDivergencePayload>,
+) {
+    if let Err(err) = apply_risk_override(
+        sandbox,
+        &recorded.action,
+        recorded.venue.as_deref(),
+        recorded.strategy_id.as_deref(),
+    ) {
+        divergences.push(divergence(
+            recorded.ts_ms,
+            "risk",
+            &recorded.action,
+            "risk_state",
+            "applied",
+            format!("rejected: {}", err.error),
+        ));
+        return;
+    }
+    if let Some(recorded_state) = &recorded.state {
+        if let Some((recorded_kills, replayed_kills)) =
+            replay::risk_state_mismatch(recorded_state, &risk_state_payload(sandbox))
+        {
+            divergences.push(divergence(
+                recorded.ts_ms,
+                "risk",
+                &recorded.action,
+                "risk_state",
+                recorded_kills,
+                replayed_kills,
+            ));
+        }
+    }
+}
+
 fn sync_scoped_kills_into_snapshot(state: &mut EngineState) {
     state.risk_snapshot.scoped_kill_venues = state.scoped_kill_venues.clone();
     state.risk_snapshot.scoped_kill_strategies = state.scoped_kill_strategies.clone();
@@ -785,22 +1977,22 @@ fn set_engine_runtime_state(state: &mut EngineState, running: bool, paused: bool
 
 fn apply_start(state: &mut EngineState) {
     set_engine_runtime_state(state, true, false);
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn apply_stop(state: &mut EngineState) {
     set_engine_runtime_state(state, false, false);
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn apply_pause(state: &mut EngineState) {
     set_engine_runtime_state(state, false, true);
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn apply_resume(state: &mut EngineState) {
     set_engine_runtime_state(state, true, false);
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn apply_kill_switch(state: &mut EngineState) {
@@ -808,14 +2000,14 @@ fn apply_kill_switch(state: &mut EngineState) {
     state.risk_tripped = true;
     set_engine_runtime_state(state, false, true);
     state.risk_snapshot.kill_switch_engaged = true;
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn apply_reset_kill_switch(state: &mut EngineState) {
     state.kill_switch_engaged = false;
     state.risk_snapshot.kill_switch_engaged = false;
     set_engine_runtime_state(state, false, true);
-    state.last_command_at_ms = now_ms();
+    state.last_command_at_ms = state.clock.now_ms();
 }
 
 fn promote_candidate_locked(
@@ -916,20 +2108,328 @@ fn route_is_live(
     order: &NormalizedOrderRequest,
     adapters: &AdapterRegistry,
 ) -> bool {
-    mode != EngineMode::Paper
-        && order.venue == "coinbase_at"
-        && order.instrument.instrument_type == InstrumentType::Spot
-        && adapters.coinbase.is_some()
+    mode != EngineMode::Paper && live_adapter_for(order, adapters).is_some()
+}
+
+/// Live adapter that trades `order`: Coinbase for `coinbase_at` spot orders,
+/// Kalshi for `kalshi` binary options. `None` when the order has no live
+/// venue or its adapter is not configured.
+fn live_adapter_for(
+    order: &NormalizedOrderRequest,
+    adapters: &AdapterRegistry,
+) -> Option<(DynAdapter, &'static str)> {
+    match (order.venue.as_str(), &order.instrument.instrument_type) {
+        ("coinbase_at", InstrumentType::Spot) => adapters
+            .coinbase
+            .clone()
+            .map(|adapter| (adapter, "coinbase_at")),
+        ("kalshi", InstrumentType::BinaryOption) => {
+            adapters.kalshi.clone().map(|adapter| (adapter, "kalshi"))
+        }
+        _ => None,
+    }
+}
+
+/// Resets the cage's per-minute order counter once its window has elapsed and
+/// returns how long until the window next resets when it is saturated.
+fn roll_order_rate_window(state: &mut EngineState, now: i64) -> i64 {
+    if now.saturating_sub(state.last_orders_reset_ms) >= ORDER_RATE_WINDOW_MS {
+        state.risk_snapshot.orders_last_minute = 0;
+        state.last_orders_reset_ms = now;
+    }
+    if state.risk_snapshot.orders_last_minute >= state.safety_policy.max_orders_per_minute {
+        state
+            .last_orders_reset_ms
+            .saturating_add(ORDER_RATE_WINDOW_MS)
+            .saturating_sub(now)
+    } else {
+        0
+    }
+}
+
+/// Runs an order through the hard safety cage; shared by order placement and
+/// journal replay.
+fn evaluate_order_risk(
+    order: &NormalizedOrderRequest,
+    notional_cents: i64,
+    risk_snapshot: &RiskSnapshot,
+    safety_policy: HardSafetyPolicy,
+) -> RiskDecision {
+    let cage = HardSafetyCage::new(safety_policy);
+    let risk_span = info_span!(
+        "risk.evaluate",
+        strategy_id = %order.strategy_id,
+        venue = %order.venue,
+        notional_cents,
+        allowed = tracing::field::Empty,
+    );
+    let risk_decision = risk_span.in_scope(|| {
+        cage.evaluate_order_with_scope(
+            &order.strategy_id,
+            &order.venue,
+            &order.instrument.asset_class,
+            notional_cents,
+            risk_snapshot,
+        )
+    });
+    risk_span.record(
+        "allowed",
+        !matches!(risk_decision, RiskDecision::Deny { .. }),
+    );
+    risk_decision
+}
+
+/// Books an order the cage admitted and the venue accepted: counters, risk
+/// exposure, the order and its fill.
+fn record_accepted_order(
+    state: &mut EngineState,
+    order: &NormalizedOrderRequest,
+    intent_id: &str,
+    notional_cents: i64,
+    live_route: bool,
+    order_snapshot: &OrderSnapshot,
+    fill: Option<&FillReport>,
+) {
+    state.execution_stats.accepted = state.execution_stats.accepted.saturating_add(1);
+    if fill.is_some() {
+        state.execution_stats.fills = state.execution_stats.fills.saturating_add(1);
+    }
+
+    if live_route {
+        state.routing_counters.live_count = state.routing_counters.live_count.saturating_add(1);
+    } else {
+        state.routing_counters.paper_count = state.routing_counters.paper_count.saturating_add(1);
+    }
+
+    state.risk_snapshot.total_notional_cents = state
+        .risk_snapshot
+        .total_notional_cents
+        .saturating_add(notional_cents);
+    state.risk_snapshot.orders_last_minute =
+        state.risk_snapshot.orders_last_minute.saturating_add(1);
+    *state
+        .risk_snapshot
+        .strategy_canary_notional
+        .entry(order.strategy_id.clone())
+        .or_insert(0) += notional_cents;
+    *state
+        .risk_snapshot
+        .venue_notional
+        .entry(order.venue.clone())
+        .or_insert(0) += notional_cents;
+    *state
+        .risk_snapshot
+        .asset_class_notional
+        .entry(order.instrument.asset_class.clone())
+        .or_insert(0) += notional_cents;
+
+    state.orders.insert(
+        order_snapshot.venue_order_id.clone(),
+        order_snapshot.clone(),
+    );
+    state
+        .order_notional_cents
+        .insert(order_snapshot.venue_order_id.clone(), notional_cents);
+    release_settled_notional(state, &order_snapshot.venue_order_id);
+    if let Some(fill) = fill {
+        state.fills.push(fill.clone());
+    }
+    state.processed_intents.insert(intent_id.to_string());
+    state.last_command_at_ms = state.clock.now_ms();
+}
+
+/// Stops tracking the notional of a known order that can no longer be
+/// canceled; only working orders need it, to release it on cancel.
+fn release_settled_notional(state: &mut EngineState, venue_order_id: &str) {
+    let settled = state.orders.get(venue_order_id).is_some_and(|order| {
+        !matches!(
+            order.status,
+            OrderStatus::New | OrderStatus::PartiallyFilled
+        )
+    });
+    if settled {
+        state.order_notional_cents.remove(venue_order_id);
+    }
+}
+
+/// Books a shutdown flatten order; these do not count toward risk exposure.
+fn record_flatten_order(
+    state: &mut EngineState,
+    order: &NormalizedOrderRequest,
+    order_snapshot: &OrderSnapshot,
+    fill: Option<&FillReport>,
+) {
+    state.execution_stats.accepted = state.execution_stats.accepted.saturating_add(1);
+    state.routing_counters.paper_count = state.routing_counters.paper_count.saturating_add(1);
+    state.orders.insert(
+        order_snapshot.venue_order_id.clone(),
+        order_snapshot.clone(),
+    );
+    if let Some(fill) = fill {
+        state.execution_stats.fills = state.execution_stats.fills.saturating_add(1);
+        state.fills.push(fill.clone());
+    }
+    if let Some(intent_id) = &order.intent_id {
+        state.processed_intents.insert(intent_id.clone());
+    }
+}
+
+/// Waits for venue and global order capacity. Urgent orders (and every order
+/// when the queue is disabled) are admitted or rejected immediately; others
+/// wait until capacity frees up or their deadline passes. Returns the risk
+/// snapshot and policy observed at admission.
+async fn await_order_pacing(
+    context: &DaemonContext,
+    venue: &str,
+    urgent: bool,
+    max_queue_wait_ms: Option<u64>,
+) -> std::result::Result<(RiskSnapshot, HardSafetyPolicy), String> {
+    let mut queued = false;
+    let mut deadline_ms = None;
+
+    let result = loop {
+        let wait_ms = {
+            let mut state = context.state.lock().await;
+            let now = state.clock.now_ms();
+            let deadline = *deadline_ms.get_or_insert_with(|| {
+                let max_wait = max_queue_wait_ms.unwrap_or(state.pacer.max_wait_ms());
+                now.saturating_add(i64::try_from(max_wait).unwrap_or(i64::MAX))
+            });
+            let global_wait_ms = roll_order_rate_window(&mut state, now);
+
+            match state
+                .pacer
+                .poll(venue, urgent, global_wait_ms, now, deadline)
+            {
+                PacingDecision::Admit => {
+                    break Ok((state.risk_snapshot.clone(), state.safety_policy.clone()));
+                }
+                PacingDecision::Reject { reason } => break Err(reason),
+                PacingDecision::Wait { wait_ms } => {
+                    if !queued {
+                        if let Err(reason) = state.pacer.enqueue(venue) {
+                            break Err(reason);
+                        }
+                        queued = true;
+                    }
+                    wait_ms
+                }
+            }
+        };
+
+        // Re-check against the engine clock rather than sleeping out the wait
+        // in real time, which a simulated clock does not follow.
+        let recheck_ms = wait_ms.clamp(1, PACING_RECHECK_MS) as u64;
+        sleep(Duration::from_millis(recheck_ms)).await;
+    };
+
+    if queued {
+        context.state.lock().await.pacer.dequeue(venue);
+    }
+    result
+}
+
+/// Adapter whose quotes price an order: the live adapter configured for the
+/// order's venue. `None` otherwise; the paper adapter's quotes are synthetic
+/// and must not size risk.
+fn quote_adapter_for(
+    order: &NormalizedOrderRequest,
+    adapters: &AdapterRegistry,
+) -> Option<DynAdapter> {
+    adapters
+        .live()
+        .find(|adapter| adapter.venue() == order.venue)
+        .cloned()
 }
 
-fn compute_requested_notional_cents(order: &NormalizedOrderRequest) -> i64 {
-    if order.requested_notional_cents > 0 {
-        return order.requested_notional_cents;
+/// Prefers a fresh streamed quote and falls back to polling the venue. `None`
+/// when the order's venue has no live adapter, so risk uses the requested and
+/// limit notional alone.
+async fn fetch_reference_quote(
+    order: &NormalizedOrderRequest,
+    context: &DaemonContext,
+) -> Option<QuoteSnapshot> {
+    let adapter = quote_adapter_for(order, &context.adapters)?;
+    let venue_symbol = &order.instrument.venue_symbol;
+    {
+        let state = context.state.lock().await;
+        if let Some(quote) =
+            state
+                .market_data
+                .fresh_quote(adapter.venue(), venue_symbol, state.clock.now_ms())
+        {
+            return Some(quote);
+        }
+    }
+
+    match adapter
+        .quote(venue_symbol)
+        .instrument(adapter_span("quote", adapter.venue()))
+        .await
+    {
+        Ok(Some(quote)) => {
+            let mut state = context.state.lock().await;
+            let now = state.clock.now_ms();
+            state.market_data.record_snapshot(quote.clone(), now);
+            Some(quote)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            warn!(
+                "quote lookup failed for {}:{}: {}",
+                order.venue, order.instrument.venue_symbol, err.message
+            );
+            None
+        }
     }
+}
 
-    match order.limit_price {
-        Some(price) if price > 0.0 && order.qty > 0.0 => (price * order.qty * 100.0) as i64,
+/// Derives the order's notional from the quote (and limit price for limit
+/// orders, or the quote-currency amount for quote-sized orders), cross-checks
+/// it against the caller-supplied notional and uses the larger of the two for
+/// risk.
+fn assess_order_notional(
+    order: &NormalizedOrderRequest,
+    quote: Option<&QuoteSnapshot>,
+) -> OrderNotionalPayload {
+    // Binary quotes are YES-side; NO contracts trade at the complement.
+    let no_side = order.instrument.instrument_type == InstrumentType::BinaryOption
+        && order.instrument.option_right == Some(OptionRight::Put);
+    let quote = quote.map(|q| {
+        if no_side {
+            q.binary_complement()
+        } else {
+            q.clone()
+        }
+    });
+    let reference = quote.and_then(|q| q.reference_price(&order.side));
+    let market_cents = reference
+        .map(|(price, _)| order.instrument.notional_cents(price, order.qty))
+        .unwrap_or(0);
+    let limit_cents = match (&order.order_type, order.limit_price) {
+        (OrderType::Limit, Some(price)) => order.instrument.notional_cents(price, order.qty),
         _ => 0,
+    };
+    // Venues spend the whole quote amount whatever `qty` says.
+    let quote_size_cents = order
+        .quote_size
+        .filter(|size| size.is_finite() && *size > 0.0)
+        .map_or(0, |size| (size * 100.0) as i64);
+
+    let requested = order.requested_notional_cents.max(0);
+    let derived = market_cents.max(limit_cents).max(quote_size_cents);
+    let understated = requested > 0
+        && derived > 0
+        && (requested as i128) * 10_000
+            < (derived as i128) * (10_000 - NOTIONAL_MISMATCH_TOLERANCE_BPS as i128);
+
+    OrderNotionalPayload {
+        requested_notional_cents: requested,
+        derived_notional_cents: derived,
+        used_notional_cents: requested.max(derived),
+        reference_price: reference.map(|(price, _)| price),
+        price_source: reference.map(|(_, source)| source.to_string()),
+        understated,
     }
 }
 
@@ -947,6 +2447,92 @@ fn ack_from_order(order: &OrderSnapshot) -> OrderAck {
     }
 }
 
+/// Adapter that owns `order`; `None` when it is a live venue's order (Coinbase
+/// spot, Kalshi binary) and that venue's adapter is unavailable.
+fn cancel_adapter_for(
+    order: Option<&OrderSnapshot>,
+    adapters: &AdapterRegistry,
+) -> Option<DynAdapter> {
+    match order
+        .filter(|o| !o.simulated)
+        .map(|o| (o.venue.as_str(), &o.instrument.instrument_type))
+    {
+        Some(("coinbase_at", InstrumentType::Spot)) => adapters.coinbase.clone(),
+        Some(("kalshi", InstrumentType::BinaryOption)) => adapters.kalshi.clone(),
+        _ => Some(adapters.paper.clone()),
+    }
+}
+
+/// Marks an order canceled, releases the notional it held against risk limits
+/// and journals the cancel so recovery and replay see it.
+fn record_order_canceled(state: &mut EngineState, venue_order_id: &str) {
+    // Extract info and update order status first, then adjust risk counters
+    let cancel_info = if let Some(existing) = state.orders.get_mut(venue_order_id) {
+        existing.status = OrderStatus::Canceled;
+        existing.updated_at_ms = state.clock.now_ms();
+
+        let notional = existing
+            .limit_price
+            .map(|p| (p * existing.qty * 100.0) as i64)
+            .unwrap_or(0);
+        Some((
+            notional,
+            existing.venue.clone(),
+            existing.instrument.asset_class.clone(),
+            existing.strategy_id.clone(),
+        ))
+    } else {
+        None
+    };
+
+    let tracked_notional = state.order_notional_cents.remove(venue_order_id);
+
+    // Decrement risk notional counters so cancelled orders free capacity
+    if let Some((notional, venue, asset_class, strategy_id)) = cancel_info {
+        let notional = tracked_notional.unwrap_or(notional);
+        if notional > 0 {
+            state.risk_snapshot.total_notional_cents = state
+                .risk_snapshot
+                .total_notional_cents
+                .saturating_sub(notional);
+            if let Some(v) = state.risk_snapshot.venue_notional.get_mut(&venue) {
+                *v = v.saturating_sub(notional);
+            }
+            if let Some(v) = state
+                .risk_snapshot
+                .asset_class_notional
+                .get_mut(&asset_class)
+            {
+                *v = v.saturating_sub(notional);
+            }
+            if let Some(v) = state
+                .risk_snapshot
+                .strategy_canary_notional
+                .get_mut(&strategy_id)
+            {
+                *v = v.saturating_sub(notional);
+            }
+        }
+    }
+    state.execution_stats.canceled = state.execution_stats.canceled.saturating_add(1);
+    if let Some(order) = state.orders.get(venue_order_id) {
+        journal_order_status(state, "canceled", order);
+    }
+}
+
+/// Journals a status change to an order the engine already tracks.
+fn journal_order_status(state: &EngineState, outcome: &str, order: &OrderSnapshot) {
+    write_journal_entry(
+        state,
+        "orders",
+        &json!({
+            "ts_ms": state.clock.now_ms(),
+            "outcome": outcome,
+            "order": order,
+        }),
+    );
+}
+
 fn synthetic_order_from_ack(order_req: &NormalizedOrderRequest, ack: &OrderAck) -> OrderSnapshot {
     OrderSnapshot {
         venue: order_req.venue.clone(),
@@ -967,6 +2553,23 @@ fn synthetic_order_from_ack(order_req: &NormalizedOrderRequest, ack: &OrderAck)
     }
 }
 
+/// Fee and liquidity a simulated venue charged on an order's fills so far.
+async fn venue_fill_costs(
+    adapter: &dyn ExchangeAdapter,
+    order: &OrderSnapshot,
+) -> (f64, Option<String>) {
+    let fills = adapter
+        .fills_since(order.created_at_ms, usize::MAX)
+        .await
+        .unwrap_or_default();
+    fills
+        .iter()
+        .filter(|fill| fill.venue_order_id == order.venue_order_id)
+        .fold((0.0, None), |(fee, liquidity), fill| {
+            (fee + fill.fee, liquidity.or_else(|| fill.liquidity.clone()))
+        })
+}
+
 fn maybe_fill_from_ack(order: &OrderSnapshot, ack: &OrderAck) -> Option<FillReport> {
     if ack.filled_qty <= 0.0 {
         return None;
@@ -996,26 +2599,36 @@ async fn fetch_portfolio(
     let mut positions = Vec::new();
     let mut balances = Vec::new();
 
-    if let Some(coinbase) = &adapters.coinbase {
-        match coinbase.sync_positions().await {
+    for adapter in adapters.live() {
+        match adapter.sync_positions().await {
             Ok(mut p) => positions.append(&mut p),
-            Err(err) => warn!("coinbase sync_positions failed: {}", err.message),
+            Err(err) => warn!("{} sync_positions failed: {}", adapter.venue(), err.message),
         }
-        match coinbase.sync_balances().await {
+        match adapter.sync_balances().await {
             Ok(mut b) => balances.append(&mut b),
-            Err(err) => warn!("coinbase sync_balances failed: {}", err.message),
+            Err(err) => warn!("{} sync_balances failed: {}", adapter.venue(), err.message),
         }
     }
 
-    match adapters.paper.sync_positions().await {
-        Ok(mut p) => positions.append(&mut p),
-        Err(err) => warn!("paper sync_positions failed: {}", err.message),
-    }
-    match adapters.paper.sync_balances().await {
-        Ok(mut b) => balances.append(&mut b),
-        Err(err) => warn!("paper sync_balances failed: {}", err.message),
-    }
+    let (mut paper_positions, mut paper_balances) = fetch_paper_portfolio(&adapters.paper).await;
+    positions.append(&mut paper_positions);
+    balances.append(&mut paper_balances);
+
+    (positions, balances)
+}
 
+/// Positions and balances of the paper account alone; no venue calls.
+async fn fetch_paper_portfolio(
+    paper: &PaperExchangeAdapter,
+) -> (Vec<PositionSnapshot>, Vec<BalanceSnapshot>) {
+    let positions = paper.sync_positions().await.unwrap_or_else(|err| {
+        warn!("paper sync_positions failed: {}", err.message);
+        Vec::new()
+    });
+    let balances = paper.sync_balances().await.unwrap_or_else(|err| {
+        warn!("paper sync_balances failed: {}", err.message);
+        Vec::new()
+    });
     (positions, balances)
 }
 
@@ -1042,23 +2655,36 @@ fn spawn_background_reconcilers(context: DaemonContext) {
     tokio::spawn(async move {
         loop {
             let mut open_orders = Vec::<OpenOrderSnapshot>::new();
-            if let Some(coinbase) = &context.adapters.coinbase {
-                match coinbase.open_orders().await {
+            for adapter in context.adapters.live() {
+                match adapter.open_orders().await {
                     Ok(mut items) => open_orders.append(&mut items),
-                    Err(err) => warn!("coinbase open_orders failed: {}", err.message),
+                    Err(err) => warn!("{} open_orders failed: {}", adapter.venue(), err.message),
                 }
             }
             match context.adapters.paper.open_orders().await {
                 Ok(mut items) => open_orders.append(&mut items),
                 Err(err) => warn!("paper open_orders failed: {}", err.message),
             }
+            reconcile_paper_orders(&context).await;
+            reconcile_kalshi_orders(&context).await;
 
             {
                 let mut state = context.state.lock().await;
                 for snapshot in open_orders {
-                    state
-                        .orders
-                        .insert(snapshot.order.venue_order_id.clone(), snapshot.order);
+                    let mut order = snapshot.order;
+                    let known = state.orders.get(&order.venue_order_id);
+                    // Polled orders were refreshed above, with the fills
+                    // booked in between; a snapshot taken earlier is stale.
+                    if known.is_some_and(is_polled_order) {
+                        continue;
+                    }
+                    // Venues that cannot attribute an order keep the known strategy.
+                    if order.strategy_id.is_empty() {
+                        if let Some(known) = known {
+                            order.strategy_id = known.strategy_id.clone();
+                        }
+                    }
+                    state.orders.insert(order.venue_order_id.clone(), order);
                 }
             }
 
@@ -1067,10 +2693,351 @@ fn spawn_background_reconcilers(context: DaemonContext) {
     });
 }
 
+/// Orders whose fills and cancels are booked by polling their venue: paper
+/// orders and Kalshi live orders, which have no user channel.
+fn is_polled_order(order: &OrderSnapshot) -> bool {
+    order.simulated || order.venue == "kalshi"
+}
+
+/// Books fills the paper venue made after placement, when resting limits were
+/// reached by later book data, releases the notional of orders the venue
+/// canceled and refreshes those orders' snapshots.
+async fn reconcile_paper_orders(context: &DaemonContext) {
+    let paper = context.adapters.paper.as_ref();
+    reconcile_working_orders(context, paper, |order| order.simulated).await;
+}
+
+/// Books fills and cancels of Kalshi live orders that happened on the venue.
+async fn reconcile_kalshi_orders(context: &DaemonContext) {
+    let Some(kalshi) = context.adapters.kalshi.clone() else {
+        return;
+    };
+    reconcile_working_orders(context, kalshi.as_ref(), |order| {
+        !order.simulated && order.venue == "kalshi"
+    })
+    .await;
+}
+
+/// Polls `adapter` for the working orders `owns` selects and books what
+/// changed since the engine last saw them: new fills, and cancels, which
+/// release the order's notional as client cancels do.
+async fn reconcile_working_orders(
+    context: &DaemonContext,
+    adapter: &dyn ExchangeAdapter,
+    owns: impl Fn(&OrderSnapshot) -> bool,
+) {
+    let working: Vec<(String, f64)> = {
+        let state = context.state.lock().await;
+        state
+            .orders
+            .values()
+            .filter(|order| {
+                owns(order)
+                    && matches!(
+                        order.status,
+                        OrderStatus::New | OrderStatus::PartiallyFilled
+                    )
+            })
+            .map(|order| (order.venue_order_id.clone(), order.filled_qty))
+            .collect()
+    };
+
+    for (venue_order_id, known_filled_qty) in working {
+        let mut latest = match adapter.get_order(&venue_order_id).await {
+            Ok(Some(latest)) => latest,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!(
+                    "{} get_order {} failed: {}",
+                    adapter.venue(),
+                    venue_order_id,
+                    err.message
+                );
+                continue;
+            }
+        };
+        // Only orders that traded need their venue fills looked up.
+        let (venue_fee, liquidity) = if latest.filled_qty - known_filled_qty > 1e-12 {
+            venue_fill_costs(adapter, &latest).await
+        } else {
+            (0.0, None)
+        };
+        let mut state = context.state.lock().await;
+        let Some(known) = state.orders.get(&venue_order_id).cloned() else {
+            continue;
+        };
+        if latest.strategy_id.is_empty() {
+            latest.strategy_id = known.strategy_id.clone();
+        }
+        let filled = latest.filled_qty - known.filled_qty;
+        if filled > 1e-12 {
+            let notional = latest.avg_fill_price.unwrap_or(0.0) * latest.filled_qty
+                - known.avg_fill_price.unwrap_or(0.0) * known.filled_qty;
+            let recorded_fee: f64 = state
+                .fills
+                .iter()
+                .filter(|fill| fill.venue_order_id == venue_order_id)
+                .map(|fill| fill.fee)
+                .sum();
+            let fill = FillReport {
+                venue: latest.venue.clone(),
+                venue_fill_id: format!("fill-{}", Uuid::new_v4().as_simple()),
+                venue_order_id: latest.venue_order_id.clone(),
+                client_order_id: latest.client_order_id.clone(),
+                strategy_id: latest.strategy_id.clone(),
+                instrument: latest.instrument.clone(),
+                side: latest.side.clone(),
+                qty: filled,
+                price: notional / filled,
+                fee: (venue_fee - recorded_fee).max(0.0),
+                fee_asset: latest.instrument.quote.clone(),
+                liquidity,
+                simulated: latest.simulated,
+                ts_ms: latest.updated_at_ms,
+            };
+            record_venue_fill(&mut state, fill);
+        }
+        // Venue-driven cancels (IOC and market remainders, FOK kills, Day
+        // expiry, cancels made on the venue) release the notional the order
+        // held, as client cancels do.
+        let was_working = matches!(
+            known.status,
+            OrderStatus::New | OrderStatus::PartiallyFilled
+        );
+        let changed = filled > 1e-12 || latest.status != known.status;
+        if changed {
+            state.orders.insert(venue_order_id.clone(), latest.clone());
+        }
+        if was_working && latest.status == OrderStatus::Canceled {
+            record_order_canceled(&mut state, &venue_order_id);
+        } else if changed {
+            journal_order_status(&state, "updated", &latest);
+            release_settled_notional(&mut state, &venue_order_id);
+        }
+    }
+}
+
+/// Books a fill the venue reported after placement into engine state and
+/// the `fills` journal.
+fn record_venue_fill(state: &mut EngineState, fill: FillReport) {
+    state.execution_stats.fills = state.execution_stats.fills.saturating_add(1);
+    write_journal_entry(
+        state,
+        "fills",
+        &json!({
+            "ts_ms": state.clock.now_ms(),
+            "fill": fill,
+        }),
+    );
+    let venue_order_id = fill.venue_order_id.clone();
+    state.fills.push(fill);
+    release_settled_notional(state, &venue_order_id);
+}
+
+/// Streams Coinbase order changes and fills into engine state as the venue
+/// reports them; the open-order reconciler remains the fallback when the
+/// user channel is unavailable.
+fn spawn_coinbase_user_channel(context: DaemonContext, coinbase: CoinbaseAdvancedTradeAdapter) {
+    let mut updates = coinbase.subscribe_user_updates();
+    tokio::spawn(async move {
+        if let Err(err) = coinbase.connect_user_channel().await {
+            warn!("Coinbase user channel unavailable: {}", err.message);
+            return;
+        }
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    let mut state = context.state.lock().await;
+                    apply_coinbase_user_update(&mut state, update);
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Coinbase user channel dropped {} updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+fn apply_coinbase_user_update(state: &mut EngineState, update: UserUpdate) {
+    match update {
+        UserUpdate::Order(mut order) => {
+            let known = state.orders.get(&order.venue_order_id).cloned();
+            // Orders placed outside the engine are picked up by the
+            // open-order reconciler while they are working.
+            let Some(known) = known else {
+                return;
+            };
+            if order.strategy_id.is_empty() {
+                order.strategy_id = known.strategy_id.clone();
+            }
+            let was_working = matches!(
+                known.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            );
+            let changed = order.status != known.status || order.filled_qty != known.filled_qty;
+            state
+                .orders
+                .insert(order.venue_order_id.clone(), order.clone());
+            if was_working && order.status == OrderStatus::Canceled {
+                record_order_canceled(state, &order.venue_order_id);
+            } else if changed {
+                journal_order_status(state, "updated", &order);
+                release_settled_notional(state, &order.venue_order_id);
+            }
+        }
+        UserUpdate::Fill(mut fill) => {
+            if state
+                .fills
+                .iter()
+                .any(|booked| booked.venue_fill_id == fill.venue_fill_id)
+            {
+                return;
+            }
+            let Some(known) = state.orders.get(&fill.venue_order_id) else {
+                return;
+            };
+            if fill.strategy_id.is_empty() {
+                fill.strategy_id = known.strategy_id.clone();
+            }
+            record_venue_fill(state, fill);
+        }
+        UserUpdate::Disconnected { reason } => {
+            warn!("Coinbase user channel disconnected: {}", reason);
+            push_event(
+                state,
+                Event::Alert {
+                    level: "warning".to_string(),
+                    message: format!("coinbase user channel disconnected: {reason}"),
+                },
+            );
+        }
+    }
+}
+
+/// Adapter serving market data for `venue`, if one is configured.
+fn market_data_adapter_for(venue: &str, adapters: &AdapterRegistry) -> Option<DynAdapter> {
+    match adapters.live().find(|adapter| adapter.venue() == venue) {
+        Some(adapter) => Some(adapter.clone()),
+        None if venue == adapters.paper.venue() => Some(adapters.paper.clone()),
+        None => None,
+    }
+}
+
+async fn subscribe_market_data(
+    context: &DaemonContext,
+    venue: &str,
+    venue_symbols: &[String],
+) -> std::result::Result<MarketDataSubscribeResultPayload, ErrorPayload> {
+    let adapter = market_data_adapter_for(venue, &context.adapters).ok_or_else(|| {
+        ErrorPayload::new(
+            ErrorCode::VenueUnavailable,
+            format!("no market data adapter for venue '{}'", venue),
+        )
+    })?;
+    let (subscribed, already_subscribed) = context
+        .state
+        .lock()
+        .await
+        .market_data
+        .claim_subscriptions(venue, venue_symbols);
+
+    if !subscribed.is_empty() {
+        if let Err(err) = adapter
+            .connect_market_data(subscribed.clone(), Arc::clone(&context.market_data_sink))
+            .await
+        {
+            let mut state = context.state.lock().await;
+            state.market_data.release_subscriptions(venue, &subscribed);
+            return Err(ErrorPayload::new(
+                ErrorCode::VenueError,
+                format!("{} market data subscribe failed: {}", venue, err.message),
+            ));
+        }
+        info!(
+            "Subscribed to {} market data for {}",
+            venue,
+            subscribed.join(",")
+        );
+    }
+
+    Ok(MarketDataSubscribeResultPayload {
+        venue: venue.to_string(),
+        subscribed,
+        already_subscribed,
+    })
+}
+
+/// Applies stream updates to the quote cache and opens the startup
+/// subscriptions from `TRADING_MARKET_DATA_SYMBOLS`.
+fn spawn_market_data(
+    context: DaemonContext,
+    mut updates: mpsc::UnboundedReceiver<MarketDataUpdate>,
+) {
+    let state = Arc::clone(&context.state);
+    let paper = Arc::clone(&context.adapters.paper);
+    tokio::spawn(async move {
+        while let Some(update) = updates.recv().await {
+            let mut batch = vec![update];
+            while let Ok(update) = updates.try_recv() {
+                batch.push(update);
+            }
+            for update in &batch {
+                paper.apply_market_data(update).await;
+            }
+            let mut state = state.lock().await;
+            for update in batch {
+                apply_market_data_update(&mut state, update);
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        for (venue, venue_symbols) in market_data_symbols_from_env() {
+            if let Err(err) = subscribe_market_data(&context, &venue, &venue_symbols).await {
+                warn!("Startup market data subscription failed: {}", err.error);
+            }
+        }
+    });
+}
+
+/// Caches a streamed update and publishes disconnects and regime changes.
+fn apply_market_data_update(state: &mut EngineState, update: MarketDataUpdate) {
+    if let MarketDataUpdate::Disconnected { venue, reason } = &update {
+        warn!("{} market data disconnected: {}", venue, reason);
+        let event = Event::MarketData {
+            venue: venue.clone(),
+            status: "disconnected".to_string(),
+            message: Some(reason.clone()),
+        };
+        push_event(state, event);
+    }
+    let now = state.clock.now_ms();
+    if let Some(RegimeUpdate {
+        context,
+        previous: Some(previous),
+    }) = state.market_data.apply(update, now)
+    {
+        info!(
+            "{}:{} regime {:?} -> {:?}",
+            context.venue, context.symbol, previous, context.regime
+        );
+        push_event(state, Event::Regime { context, previous });
+    }
+}
+
 async fn handle_connection(stream: UnixStream, context: DaemonContext) {
     let mut framed = Framed::new(stream, create_codec());
 
-    while let Some(result) = framed.next().await {
+    loop {
+        // Requests already read are answered; no new ones are read once shutdown starts.
+        let result = tokio::select! {
+            _ = context.shutdown.cancelled() => break,
+            next = framed.next() => match next {
+                Some(result) => result,
+                None => break,
+            },
+        };
         match result {
             Ok(bytes) => {
                 let envelope: Envelope = match serde_json::from_slice(&bytes) {
@@ -1082,6 +3049,10 @@ async fn handle_connection(stream: UnixStream, context: DaemonContext) {
                 };
 
                 info!("Received request: {}", envelope.kind);
+                if envelope.kind == ControlCommand::SubscribeEvents.as_kind() {
+                    stream_events(framed, &envelope, &context).await;
+                    return;
+                }
                 let response = process_request(&envelope, &context).await;
                 let response_bytes = match serde_json::to_vec(&response) {
                     Ok(bytes) => bytes,
@@ -1104,8 +3075,134 @@ async fn handle_connection(stream: UnixStream, context: DaemonContext) {
     }
 }
 
+async fn send_envelope(
+    framed: &mut Framed<UnixStream, JsonCodec>,
+    envelope: &Envelope,
+) -> Result<()> {
+    let bytes = serde_json::to_vec(envelope)?;
+    framed.send(Bytes::from(bytes)).await?;
+    Ok(())
+}
+
+/// Serves a `Control.SubscribeEvents` connection: acknowledges the request,
+/// optionally replays recent events, then forwards every new event as an
+/// `Engine.Event` frame until the client hangs up or the daemon shuts down.
+async fn stream_events(
+    mut framed: Framed<UnixStream, JsonCodec>,
+    request: &Envelope,
+    context: &DaemonContext,
+) {
+    let payload: EventSubscribePayload = match parse_payload(&request.payload) {
+        Ok(p) => p,
+        Err(err) => {
+            let response = error_response(request, ErrorCode::InvalidPayload, err);
+            let _ = send_envelope(&mut framed, &response).await;
+            return;
+        }
+    };
+
+    let (mut events, replay) = {
+        let state = context.state.lock().await;
+        let replay: Vec<serde_json::Value> = if payload.replay_recent {
+            state.recent_events.iter().cloned().collect()
+        } else {
+            Vec::new()
+        };
+        (state.events.subscribe(), replay)
+    };
+
+    let response = fields_response(
+        request,
+        EventSubscribeResultPayload {
+            replayed: replay.len(),
+        },
+    );
+    if send_envelope(&mut framed, &response).await.is_err() {
+        return;
+    }
+    for event in replay {
+        if send_envelope(&mut framed, &Envelope::new(EVENT_KIND, event))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    loop {
+        let event = tokio::select! {
+            _ = context.shutdown.cancelled() => break,
+            // Only read to notice the client hanging up; other frames are ignored.
+            next = framed.next() => match next {
+                Some(Ok(_)) => continue,
+                _ => break,
+            },
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Event subscriber lagged; dropped {} events", skipped);
+                    json!(Event::Alert {
+                        level: "warning".to_string(),
+                        message: format!("event stream lagged; {} events dropped", skipped),
+                    })
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if send_envelope(&mut framed, &Envelope::new(EVENT_KIND, event))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Runs a request inside a span (a child of the caller's `traceparent`, when
+/// sent) with its correlation id in scope for journal records and events.
 async fn process_request(request: &Envelope, context: &DaemonContext) -> Envelope {
-    match RequestKind::from_kind(request.kind.as_str()) {
+    let correlation_id = request.effective_correlation_id();
+    let span = info_span!(
+        "request",
+        kind = %request.kind,
+        request_id = %request.id,
+        correlation_id = %correlation_id,
+        ok = tracing::field::Empty,
+    );
+    if let Some(traceparent) = &request.traceparent {
+        trading_telemetry::set_remote_parent(&span, traceparent);
+    }
+
+    let response = CORRELATION_ID
+        .scope(correlation_id, dispatch_request(request, context))
+        .instrument(span.clone())
+        .await;
+    span.record(
+        "ok",
+        response
+            .payload
+            .get("ok")
+            .and_then(serde_json::Value::as_bool)
+            == Some(true),
+    );
+    response
+}
+
+async fn dispatch_request(request: &Envelope, context: &DaemonContext) -> Envelope {
+    let kind = RequestKind::from_kind(request.kind.as_str());
+    // Hello is exempt so clients on another version can still negotiate.
+    if !SUPPORTED_PROTOCOL_VERSIONS.contains(&request.v)
+        && kind != Some(RequestKind::Control(ControlCommand::Hello))
+    {
+        return error_response_with(
+            request,
+            ErrorCode::UnsupportedProtocol,
+            format!("protocol version {} is not supported", request.v),
+            json!({"supported_protocol_versions": SUPPORTED_PROTOCOL_VERSIONS}),
+        );
+    }
+
+    match kind {
         Some(RequestKind::Control(command)) => {
             process_control_request(request, context, command).await
         }
@@ -1122,12 +3219,13 @@ async fn process_request(request: &Envelope, context: &DaemonContext) -> Envelop
         Some(RequestKind::Portfolio(command)) => {
             process_portfolio_request(request, context, command).await
         }
-        None => Envelope::response_to(
+        Some(RequestKind::MarketData(command)) => {
+            process_market_data_request(request, context, command).await
+        }
+        None => error_response(
             request,
-            json!({
-                "ok": false,
-                "error": format!("Unsupported command kind '{}'", request.kind)
-            }),
+            ErrorCode::UnsupportedCommand,
+            format!("Unsupported command kind '{}'", request.kind),
         ),
     }
 }
@@ -1138,42 +3236,68 @@ async fn process_control_request(
     command: ControlCommand,
 ) -> Envelope {
     match command {
-        ControlCommand::Ping => Envelope::response_to(
-            request,
-            json!({
-                "ok": true,
-                "connected": true,
-            }),
-        ),
+        ControlCommand::Ping => fields_response(request, PingResultPayload { connected: true }),
         ControlCommand::Status => {
             let state = context.state.lock().await;
             status_response(request, &state)
         }
-        ControlCommand::Capabilities => Envelope::response_to(
+        ControlCommand::Capabilities => {
+            let payload: CapabilitiesRequestPayload = match parse_payload(&request.payload) {
+                Ok(p) => p,
+                Err(err) => {
+                    return error_response(request, ErrorCode::InvalidPayload, err);
+                }
+            };
+            fields_response(
+                request,
+                CapabilitiesResultPayload {
+                    capabilities: capabilities_payload(payload.include_schemas),
+                },
+            )
+        }
+        ControlCommand::Hello => {
+            let payload: HelloPayload = match parse_payload(&request.payload) {
+                Ok(p) => p,
+                Err(err) => {
+                    return error_response(request, ErrorCode::InvalidPayload, err);
+                }
+            };
+            match hello_result(&payload, request.v) {
+                Ok(result) => {
+                    info!(
+                        "Hello from {} (protocol v{})",
+                        payload.client_name.as_deref().unwrap_or("unnamed client"),
+                        result.protocol_version
+                    );
+                    Envelope::response_to(request, json!({"ok": true, "result": result}))
+                }
+                Err(err) => error_response_with(
+                    request,
+                    err.code,
+                    err.error,
+                    json!({"supported_protocol_versions": SUPPORTED_PROTOCOL_VERSIONS}),
+                ),
+            }
+        }
+        ControlCommand::SubscribeEvents => error_response(
             request,
-            json!({
-                "ok": true,
-                "capabilities": capabilities_payload(),
-            }),
+            ErrorCode::UnsupportedCommand,
+            "event subscriptions are only served on a client connection",
         ),
         ControlCommand::Start => {
             let mut state = context.state.lock().await;
             if state.kill_switch_engaged {
-                return Envelope::response_to(
+                return error_response(
                     request,
-                    json!({
-                        "ok": false,
-                        "error": "Kill switch engaged; cannot start until operator reset"
-                    }),
+                    ErrorCode::KillSwitchEngaged,
+                    "Kill switch engaged; cannot start until operator reset",
                 );
             }
-            if state.mode != EngineMode::Paper && context.adapters.coinbase.is_none() {
-                return Envelope::response_to(
+            if state.mode != EngineMode::Paper && !context.adapters.live_available() {
+                return error_response(
                     request,
-                    json!({
-                        "ok": false,
-                        "error": "Coinbase credentials unavailable for live mode"
-                    }),
+                    ErrorCode::VenueUnavailable,
+                    "no live venue credentials (Coinbase or Kalshi) available for live mode",
                 );
             }
 
@@ -1215,21 +3339,17 @@ async fn process_engine_request(
         EngineCommand::Resume => {
             let mut state = context.state.lock().await;
             if state.kill_switch_engaged {
-                return Envelope::response_to(
+                return error_response(
                     request,
-                    json!({
-                        "ok": false,
-                        "error": "Kill switch engaged; cannot resume"
-                    }),
+                    ErrorCode::KillSwitchEngaged,
+                    "Kill switch engaged; cannot resume",
                 );
             }
-            if state.mode != EngineMode::Paper && context.adapters.coinbase.is_none() {
-                return Envelope::response_to(
+            if state.mode != EngineMode::Paper && !context.adapters.live_available() {
+                return error_response(
                     request,
-                    json!({
-                        "ok": false,
-                        "error": "Coinbase credentials unavailable for live mode"
-                    }),
+                    ErrorCode::VenueUnavailable,
+                    "no live venue credentials (Coinbase or Kalshi) available for live mode",
                 );
             }
             apply_resume(&mut state);
@@ -1257,35 +3377,40 @@ async fn process_engine_request(
         }
         EngineCommand::GetMode => {
             let state = context.state.lock().await;
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({
-                    "ok": true,
-                    "mode": EngineModePayload { mode: state.mode },
-                }),
+                EngineModeResultPayload {
+                    mode: EngineModePayload { mode: state.mode },
+                },
             )
         }
         EngineCommand::SetMode => {
             let payload: EngineModePayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
-            if payload.mode != EngineMode::Paper && context.adapters.coinbase.is_none() {
-                return Envelope::response_to(
+            if payload.mode != EngineMode::Paper && context.state.lock().await.paper_only {
+                return error_response(
                     request,
-                    json!({
-                        "ok": false,
-                        "error": "Coinbase credentials unavailable for live mode"
-                    }),
+                    ErrorCode::ModeNotAllowed,
+                    "engine is paper-only (imported snapshot); live modes are disabled",
+                );
+            }
+
+            if payload.mode != EngineMode::Paper && !context.adapters.live_available() {
+                return error_response(
+                    request,
+                    ErrorCode::VenueUnavailable,
+                    "no live venue credentials (Coinbase or Kalshi) available for live mode",
                 );
             }
 
             let mut state = context.state.lock().await;
             state.mode = payload.mode;
-            state.last_command_at_ms = now_ms();
+            state.last_command_at_ms = state.clock.now_ms();
             push_event(
                 &mut state,
                 Event::Alert {
@@ -1295,55 +3420,136 @@ async fn process_engine_request(
             );
             persist_engine_state(&state);
 
-            Envelope::response_to(
-                request,
-                json!({
-                    "ok": true,
-                    "mode": EngineModePayload { mode: state.mode },
-                }),
-            )
-        }
-    }
-}
-
-async fn process_strategy_request(
-    request: &Envelope,
-    context: &DaemonContext,
-    command: StrategyCommand,
-) -> Envelope {
-    match command {
-        StrategyCommand::List => {
-            let state = context.state.lock().await;
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({
-                    "ok": true,
-                    "strategies": strategy_summaries(&state),
-                }),
+                EngineModeResultPayload {
+                    mode: EngineModePayload { mode: state.mode },
+                },
             )
         }
-        StrategyCommand::Enable | StrategyCommand::Disable => {
-            let payload: StrategyIdPayload = match parse_payload(&request.payload) {
+        EngineCommand::ExportSnapshot => {
+            let payload: EngineExportSnapshotPayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
-            let mut state = context.state.lock().await;
-            let enabled = matches!(command, StrategyCommand::Enable);
-            let (summary, event) = {
-                let strategy = match state.strategies.get_mut(&payload.strategy_id) {
-                    Some(strategy) => strategy,
-                    None => {
-                        return Envelope::response_to(
-                            request,
-                            json!({
-                                "ok": false,
-                                "error": format!("Unknown strategy '{}'", payload.strategy_id)
-                            }),
-                        );
-                    }
+            let (export, data_dir) = {
+                let state = context.state.lock().await;
+                (snapshot_export_from_state(&state), state.data_dir.clone())
+            };
+
+            let Some(file_name) = payload.file_name else {
+                let inline = json!({"ok": true, "result": export});
+                let encoded_len = serde_json::to_vec(&inline).map(|b| b.len()).unwrap_or(0);
+                if encoded_len + INLINE_EXPORT_FRAME_HEADROOM > MAX_FRAME_LENGTH {
+                    return error_response(
+                        request,
+                        ErrorCode::InvalidPayload,
+                        format!(
+                            "snapshot export is {} bytes, too large to return inline; pass file_name to write it under the daemon data dir",
+                            encoded_len
+                        ),
+                    );
+                }
+                return Envelope::response_to(request, inline);
+            };
+
+            let path = match export_path(&data_dir, &file_name) {
+                Ok(path) => path,
+                Err(err) => {
+                    return error_response(request, ErrorCode::InvalidPayload, err);
+                }
+            };
+            let path = path.to_string_lossy().to_string();
+            match write_json_atomically(&path, &export, "snapshot export") {
+                Ok(bytes) => {
+                    let mut state = context.state.lock().await;
+                    push_event(
+                        &mut state,
+                        Event::Alert {
+                            level: "info".to_string(),
+                            message: format!("engine snapshot exported to {}", path),
+                        },
+                    );
+                    let result = EngineExportSnapshotResultPayload::Written {
+                        path,
+                        bytes,
+                        exported_at_ms: export.exported_at_ms,
+                    };
+                    Envelope::response_to(request, json!({"ok": true, "result": result}))
+                }
+                Err(err) => error_response(request, ErrorCode::Internal, format!("{:#}", err)),
+            }
+        }
+        EngineCommand::Replay => {
+            let payload: EngineReplayPayload = match parse_payload(&request.payload) {
+                Ok(p) => p,
+                Err(err) => {
+                    return error_response(request, ErrorCode::InvalidPayload, err);
+                }
+            };
+
+            let (data_dir, safety_policy, now) = {
+                let state = context.state.lock().await;
+                (
+                    state.data_dir.clone(),
+                    state.safety_policy.clone(),
+                    state.clock.now_ms(),
+                )
+            };
+            let from_ms = payload.from_ms.unwrap_or(0);
+            let to_ms = payload.to_ms.unwrap_or(now);
+            if from_ms > to_ms {
+                return error_response(
+                    request,
+                    ErrorCode::InvalidPayload,
+                    "from_ms must not be after to_ms",
+                );
+            }
+
+            let result = replay_journals(&data_dir, safety_policy, from_ms, to_ms).await;
+            Envelope::response_to(request, json!({"ok": true, "result": result}))
+        }
+    }
+}
+
+async fn process_strategy_request(
+    request: &Envelope,
+    context: &DaemonContext,
+    command: StrategyCommand,
+) -> Envelope {
+    match command {
+        StrategyCommand::List => {
+            let state = context.state.lock().await;
+            fields_response(
+                request,
+                StrategyListResultPayload {
+                    strategies: strategy_summaries(&state),
+                },
+            )
+        }
+        StrategyCommand::Enable | StrategyCommand::Disable => {
+            let payload: StrategyIdPayload = match parse_payload(&request.payload) {
+                Ok(p) => p,
+                Err(err) => {
+                    return error_response(request, ErrorCode::InvalidPayload, err);
+                }
+            };
+
+            let mut state = context.state.lock().await;
+            let enabled = matches!(command, StrategyCommand::Enable);
+            let (summary, event) = {
+                let strategy = match state.strategies.get_mut(&payload.strategy_id) {
+                    Some(strategy) => strategy,
+                    None => {
+                        return error_response(
+                            request,
+                            ErrorCode::NotFound,
+                            format!("Unknown strategy '{}'", payload.strategy_id),
+                        );
+                    }
                 };
 
                 strategy.enabled = enabled;
@@ -1357,37 +3563,49 @@ async fn process_strategy_request(
                 (strategy.summary(), event)
             };
 
-            state.last_command_at_ms = now_ms();
+            state.last_command_at_ms = state.clock.now_ms();
             push_event(&mut state, event);
             persist_engine_state(&state);
 
-            Envelope::response_to(
-                request,
-                json!({
-                    "ok": true,
-                    "strategy": summary,
-                }),
-            )
+            fields_response(request, StrategyResultPayload { strategy: summary })
         }
         StrategyCommand::UploadCandidate => {
             let payload: CandidateUploadPayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
+            // The uploader's replay claim only counts when the engine's own
+            // replay of the recent journals agrees.
+            let (data_dir, safety_policy, now) = {
+                let state = context.state.lock().await;
+                (
+                    state.data_dir.clone(),
+                    state.safety_policy.clone(),
+                    state.clock.now_ms(),
+                )
+            };
+            let replay = replay_journals(
+                &data_dir,
+                safety_policy,
+                now.saturating_sub(CANDIDATE_REPLAY_WINDOW_MS),
+                now,
+            )
+            .await;
+
             let mut state = context.state.lock().await;
             let candidate = StrategyCandidate {
                 source: payload.source.clone(),
                 code_hash: payload.code_hash.clone(),
                 requested_canary_notional_cents: payload.requested_canary_notional_cents,
                 compile_passed: payload.compile_passed,
-                replay_passed: payload.replay_passed,
+                replay_passed: payload.replay_passed && replay.passed,
                 paper_passed: payload.paper_passed,
                 latency_passed: payload.latency_passed,
                 risk_passed: payload.risk_passed,
-                uploaded_at_ms: now_ms(),
+                uploaded_at_ms: state.clock.now_ms(),
             };
 
             let candidate_ok = candidate.all_gates_passed();
@@ -1395,12 +3613,10 @@ async fn process_strategy_request(
                 let strategy = match state.strategies.get_mut(&payload.strategy_id) {
                     Some(strategy) => strategy,
                     None => {
-                        return Envelope::response_to(
+                        return error_response(
                             request,
-                            json!({
-                                "ok": false,
-                                "error": format!("Unknown strategy '{}'", payload.strategy_id)
-                            }),
+                            ErrorCode::NotFound,
+                            format!("Unknown strategy '{}'", payload.strategy_id),
                         );
                     }
                 };
@@ -1413,6 +3629,11 @@ async fn process_strategy_request(
                     passed: candidate_ok,
                     reason: if candidate_ok {
                         None
+                    } else if !replay.passed {
+                        Some(format!(
+                            "journal replay found {} divergences",
+                            replay.divergence_count
+                        ))
                     } else {
                         Some("candidate uploaded but at least one gate failed".to_string())
                     },
@@ -1420,35 +3641,42 @@ async fn process_strategy_request(
                 (strategy.id.clone(), event)
             };
 
-            state.last_command_at_ms = now_ms();
+            state.last_command_at_ms = state.clock.now_ms();
             push_event(&mut state, event);
             persist_engine_state(&state);
 
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({
-                    "ok": true,
-                    "strategy_id": strategy_id,
-                    "candidate": {
-                        "source": candidate.source,
-                        "code_hash": candidate.code_hash,
-                        "requested_canary_notional_cents": candidate.requested_canary_notional_cents,
-                        "all_gates_passed": candidate_ok,
-                        "uploaded_at_ms": candidate.uploaded_at_ms,
-                    }
-                }),
+                CandidateUploadResultPayload {
+                    strategy_id,
+                    candidate: CandidateSummaryPayload {
+                        source: candidate.source,
+                        code_hash: candidate.code_hash,
+                        requested_canary_notional_cents: candidate.requested_canary_notional_cents,
+                        all_gates_passed: candidate_ok,
+                        uploaded_at_ms: candidate.uploaded_at_ms,
+                    },
+                    replay: CandidateReplayPayload {
+                        passed: replay.passed,
+                        from_ms: replay.from_ms,
+                        to_ms: replay.to_ms,
+                        orders: replay.orders,
+                        divergence_count: replay.divergence_count,
+                    },
+                },
             )
         }
         StrategyCommand::PromoteCandidate => {
             let payload: CandidatePromotePayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
             let mut state = context.state.lock().await;
-            match promote_candidate_locked(&mut state, &payload, now_ms()) {
+            let now = state.clock.now_ms();
+            match promote_candidate_locked(&mut state, &payload, now) {
                 Ok(success) => {
                     push_event(
                         &mut state,
@@ -1461,14 +3689,13 @@ async fn process_strategy_request(
                     );
                     persist_engine_state(&state);
 
-                    Envelope::response_to(
+                    fields_response(
                         request,
-                        json!({
-                            "ok": true,
-                            "strategy": success.summary,
-                            "previous_version": success.previous_version,
-                            "hard_safety_floor": "enforced",
-                        }),
+                        CandidatePromoteResultPayload {
+                            strategy: success.summary,
+                            previous_version: success.previous_version,
+                            hard_safety_floor: "enforced".to_string(),
+                        },
                     )
                 }
                 Err(reason) => {
@@ -1490,13 +3717,11 @@ async fn process_strategy_request(
                         },
                     );
 
-                    Envelope::response_to(
+                    error_response_with(
                         request,
-                        json!({
-                            "ok": false,
-                            "error": reason,
-                            "hard_safety_floor": "enforced",
-                        }),
+                        ErrorCode::PromotionRejected,
+                        reason,
+                        json!({"hard_safety_floor": "enforced"}),
                     )
                 }
             }
@@ -1504,6 +3729,137 @@ async fn process_strategy_request(
     }
 }
 
+/// Applies a `Risk.Override` action; shared by the request handler and
+/// journal replay.
+fn apply_risk_override(
+    state: &mut EngineState,
+    action: &str,
+    venue: Option<&str>,
+    strategy_id: Option<&str>,
+) -> std::result::Result<(), ErrorPayload> {
+    match action {
+        "clear_runtime_counters" => {
+            state.risk_snapshot.orders_last_minute = 0;
+            state.last_command_at_ms = state.clock.now_ms();
+        }
+        "reset_kill_switch" | "reset_global" => {
+            apply_reset_kill_switch(state);
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "important".to_string(),
+                    reason: "manual_kill_switch_reset".to_string(),
+                    kill_switch_engaged: false,
+                },
+            );
+        }
+        "kill_global" => {
+            apply_kill_switch(state);
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "critical".to_string(),
+                    reason: "manual_global_kill".to_string(),
+                    kill_switch_engaged: true,
+                },
+            );
+        }
+        "kill_venue" => {
+            let venue = match venue {
+                Some(v) if !v.trim().is_empty() => v.to_string(),
+                _ => {
+                    return Err(ErrorPayload::new(
+                        ErrorCode::InvalidPayload,
+                        "venue required for kill_venue",
+                    ));
+                }
+            };
+            state.scoped_kill_venues.insert(venue.clone());
+            let kill_switch_engaged = state.kill_switch_engaged;
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "critical".to_string(),
+                    reason: format!("venue kill engaged: {}", venue),
+                    kill_switch_engaged,
+                },
+            );
+        }
+        "reset_venue" => {
+            let venue = match venue {
+                Some(v) if !v.trim().is_empty() => v.to_string(),
+                _ => {
+                    return Err(ErrorPayload::new(
+                        ErrorCode::InvalidPayload,
+                        "venue required for reset_venue",
+                    ));
+                }
+            };
+            state.scoped_kill_venues.remove(&venue);
+            let kill_switch_engaged = state.kill_switch_engaged;
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "important".to_string(),
+                    reason: format!("venue kill reset: {}", venue),
+                    kill_switch_engaged,
+                },
+            );
+        }
+        "kill_strategy" => {
+            let strategy_id = match strategy_id {
+                Some(v) if !v.trim().is_empty() => v.to_string(),
+                _ => {
+                    return Err(ErrorPayload::new(
+                        ErrorCode::InvalidPayload,
+                        "strategy_id required for kill_strategy",
+                    ));
+                }
+            };
+            state.scoped_kill_strategies.insert(strategy_id.clone());
+            let kill_switch_engaged = state.kill_switch_engaged;
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "critical".to_string(),
+                    reason: format!("strategy kill engaged: {}", strategy_id),
+                    kill_switch_engaged,
+                },
+            );
+        }
+        "reset_strategy" => {
+            let strategy_id = match strategy_id {
+                Some(v) if !v.trim().is_empty() => v.to_string(),
+                _ => {
+                    return Err(ErrorPayload::new(
+                        ErrorCode::InvalidPayload,
+                        "strategy_id required for reset_strategy",
+                    ));
+                }
+            };
+            state.scoped_kill_strategies.remove(&strategy_id);
+            let kill_switch_engaged = state.kill_switch_engaged;
+            push_event(
+                state,
+                Event::RiskAlert {
+                    level: "important".to_string(),
+                    reason: format!("strategy kill reset: {}", strategy_id),
+                    kill_switch_engaged,
+                },
+            );
+        }
+        _ => {
+            return Err(ErrorPayload::new(
+                ErrorCode::RiskDenied,
+                "hard safety floor cannot be overridden",
+            ))
+        }
+    }
+
+    sync_scoped_kills_into_snapshot(state);
+    Ok(())
+}
+
 async fn process_risk_request(
     request: &Envelope,
     context: &DaemonContext,
@@ -1512,174 +3868,60 @@ async fn process_risk_request(
     match command {
         RiskCommand::Status => {
             let state = context.state.lock().await;
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({
-                    "ok": true,
-                    "safety_floor": "hard_cage",
-                    "limits": risk_limits_payload(&state),
-                    "state": risk_state_payload(&state),
-                }),
+                RiskStatusResultPayload {
+                    safety_floor: "hard_cage".to_string(),
+                    limits: risk_limits_payload(&state),
+                    state: risk_state_payload(&state),
+                },
             )
         }
         RiskCommand::Override => {
             let payload: RiskOverridePayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
             let action = payload.action.clone();
-            let venue_for_log = payload.venue.clone();
-            let strategy_for_log = payload.strategy_id.clone();
 
             let mut state = context.state.lock().await;
-            match action.as_str() {
-                "clear_runtime_counters" => {
-                    state.risk_snapshot.orders_last_minute = 0;
-                    state.last_command_at_ms = now_ms();
-                }
-                "reset_kill_switch" | "reset_global" => {
-                    apply_reset_kill_switch(&mut state);
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "important".to_string(),
-                            reason: "manual_kill_switch_reset".to_string(),
-                            kill_switch_engaged: false,
-                        },
-                    );
-                }
-                "kill_global" => {
-                    apply_kill_switch(&mut state);
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "critical".to_string(),
-                            reason: "manual_global_kill".to_string(),
-                            kill_switch_engaged: true,
-                        },
-                    );
-                }
-                "kill_venue" => {
-                    let venue = match payload.venue.as_ref() {
-                        Some(v) if !v.trim().is_empty() => v.clone(),
-                        _ => {
-                            return Envelope::response_to(
-                                request,
-                                json!({ "ok": false, "error": "venue required for kill_venue" }),
-                            );
-                        }
-                    };
-                    state.scoped_kill_venues.insert(venue.clone());
-                    let kill_switch_engaged = state.kill_switch_engaged;
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "critical".to_string(),
-                            reason: format!("venue kill engaged: {}", venue),
-                            kill_switch_engaged,
-                        },
-                    );
-                }
-                "reset_venue" => {
-                    let venue = match payload.venue.as_ref() {
-                        Some(v) if !v.trim().is_empty() => v.clone(),
-                        _ => {
-                            return Envelope::response_to(
-                                request,
-                                json!({ "ok": false, "error": "venue required for reset_venue" }),
-                            );
-                        }
-                    };
-                    state.scoped_kill_venues.remove(&venue);
-                    let kill_switch_engaged = state.kill_switch_engaged;
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "important".to_string(),
-                            reason: format!("venue kill reset: {}", venue),
-                            kill_switch_engaged,
-                        },
-                    );
-                }
-                "kill_strategy" => {
-                    let strategy_id = match payload.strategy_id.as_ref() {
-                        Some(v) if !v.trim().is_empty() => v.clone(),
-                        _ => {
-                            return Envelope::response_to(
-                                request,
-                                json!({ "ok": false, "error": "strategy_id required for kill_strategy" }),
-                            );
-                        }
-                    };
-                    state.scoped_kill_strategies.insert(strategy_id.clone());
-                    let kill_switch_engaged = state.kill_switch_engaged;
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "critical".to_string(),
-                            reason: format!("strategy kill engaged: {}", strategy_id),
-                            kill_switch_engaged,
-                        },
-                    );
-                }
-                "reset_strategy" => {
-                    let strategy_id = match payload.strategy_id.as_ref() {
-                        Some(v) if !v.trim().is_empty() => v.clone(),
-                        _ => {
-                            return Envelope::response_to(
-                                request,
-                                json!({ "ok": false, "error": "strategy_id required for reset_strategy" }),
-                            );
-                        }
-                    };
-                    state.scoped_kill_strategies.remove(&strategy_id);
-                    let kill_switch_engaged = state.kill_switch_engaged;
-                    push_event(
-                        &mut state,
-                        Event::RiskAlert {
-                            level: "important".to_string(),
-                            reason: format!("strategy kill reset: {}", strategy_id),
-                            kill_switch_engaged,
-                        },
-                    );
-                }
-                _ => {
-                    return Envelope::response_to(
-                        request,
-                        json!({
-                            "ok": false,
-                            "error": "hard safety floor cannot be overridden",
-                            "action": action,
-                        }),
-                    )
-                }
+            if let Err(err) = apply_risk_override(
+                &mut state,
+                &action,
+                payload.venue.as_deref(),
+                payload.strategy_id.as_deref(),
+            ) {
+                return error_response_with(
+                    request,
+                    err.code,
+                    err.error,
+                    json!({"action": action}),
+                );
             }
-
-            sync_scoped_kills_into_snapshot(&mut state);
             persist_engine_state(&state);
 
             write_journal_entry(
-                &state.data_dir,
+                &state,
                 "risk",
                 &json!({
-                    "ts_ms": now_ms(),
+                    "ts_ms": state.clock.now_ms(),
                     "action": action,
-                    "venue": venue_for_log,
-                    "strategy_id": strategy_for_log,
+                    "venue": payload.venue,
+                    "strategy_id": payload.strategy_id,
                     "state": risk_state_payload(&state),
                 }),
             );
 
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({
-                    "ok": true,
-                    "action": action,
-                    "state": risk_state_payload(&state),
-                    "note": "Engine remains PAUSED after kill switch reset. Use 'resume' to start.",
-                }),
+                RiskOverrideResultPayload {
+                    action,
+                    state: risk_state_payload(&state),
+                    note: "Engine remains PAUSED after kill switch reset. Use 'resume' to start."
+                        .to_string(),
+                },
             )
         }
     }
@@ -1695,7 +3937,7 @@ async fn process_execution_request(
             let payload: ExecutionPlacePayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
@@ -1731,28 +3973,28 @@ async fn process_execution_request(
                 }
             }
 
-            let (mode, running, paused, requested_notional_cents, risk_snapshot, safety_policy) = {
-                let mut state = context.state.lock().await;
-                // Auto-reset the per-minute order rate counter every 60 seconds
-                let now = now_ms();
-                if now.saturating_sub(state.last_orders_reset_ms) >= 60_000 {
-                    state.risk_snapshot.orders_last_minute = 0;
-                    state.last_orders_reset_ms = now;
-                }
-                (
-                    state.mode,
-                    state.running,
-                    state.paused,
-                    compute_requested_notional_cents(&order),
-                    state.risk_snapshot.clone(),
-                    state.safety_policy.clone(),
-                )
+            if context.shutdown.is_cancelled() {
+                return error_response(
+                    request,
+                    ErrorCode::ShuttingDown,
+                    "daemon is shutting down; new orders are not accepted",
+                );
+            }
+
+            let quote = fetch_reference_quote(&order, context).await;
+            let notional = assess_order_notional(&order, quote.as_ref());
+            let requested_notional_cents = notional.used_notional_cents;
+
+            let (mode, running, paused) = {
+                let state = context.state.lock().await;
+                (state.mode, state.running, state.paused)
             };
 
             if !running || paused {
-                return Envelope::response_to(
+                return error_response(
                     request,
-                    json!({"ok": false, "error": "engine is not running"}),
+                    ErrorCode::EngineNotRunning,
+                    "engine is not running",
                 );
             }
 
@@ -1762,28 +4004,76 @@ async fn process_execution_request(
                 .map(|token| token.trim().is_empty())
                 .unwrap_or(true);
             if mode == EngineMode::HitlLive && missing_approval {
-                return Envelope::response_to(
+                return error_response(
                     request,
-                    json!({"ok": false, "error": "approval_token required in hitl_live mode"}),
+                    ErrorCode::ApprovalRequired,
+                    "approval_token required in hitl_live mode",
                 );
             }
 
             if requested_notional_cents <= 0 {
-                return Envelope::response_to(
+                return error_response_with(
                     request,
-                    json!({"ok": false, "error": "requested_notional_cents must be positive"}),
+                    ErrorCode::InvalidPayload,
+                    "order notional unavailable: no reference price and requested_notional_cents not positive",
+                    json!({"notional": notional}),
+                );
+            }
+
+            if notional.understated {
+                let mut state = context.state.lock().await;
+                let kill_switch_engaged = state.kill_switch_engaged;
+                push_event(
+                    &mut state,
+                    Event::RiskAlert {
+                        level: "warning".to_string(),
+                        reason: format!(
+                            "requested notional {} understates derived {} for {}:{}; using derived",
+                            notional.requested_notional_cents,
+                            notional.derived_notional_cents,
+                            order.venue,
+                            order.symbol
+                        ),
+                        kill_switch_engaged,
+                    },
                 );
             }
 
-            let cage = HardSafetyCage::new(safety_policy);
-            let venue_scope = &order.venue;
-            let asset_scope = &order.instrument.asset_class;
-            let risk_decision = cage.evaluate_order_with_scope(
-                &order.strategy_id,
-                venue_scope,
-                asset_scope,
+            let urgent = order_is_urgent(payload.priority, order.tif.as_ref());
+            let (risk_snapshot, safety_policy) =
+                match await_order_pacing(context, &order.venue, urgent, payload.max_queue_wait_ms)
+                    .await
+                {
+                    Ok(admitted) => admitted,
+                    Err(reason) => {
+                        let mut state = context.state.lock().await;
+                        state.execution_stats.rejected =
+                            state.execution_stats.rejected.saturating_add(1);
+                        let kill_switch_engaged = state.kill_switch_engaged;
+                        push_event(
+                            &mut state,
+                            Event::RiskAlert {
+                                level: "warning".to_string(),
+                                reason: format!("execution paced out: {}", reason),
+                                kill_switch_engaged,
+                            },
+                        );
+                        persist_engine_state(&state);
+
+                        return error_response_with(
+                            request,
+                            ErrorCode::PacingRejected,
+                            reason,
+                            json!({"pacing": "rejected"}),
+                        );
+                    }
+                };
+
+            let risk_decision = evaluate_order_risk(
+                &order,
                 requested_notional_cents,
                 &risk_snapshot,
+                safety_policy,
             );
 
             if let RiskDecision::Deny { reason } = risk_decision {
@@ -1798,30 +4088,52 @@ async fn process_execution_request(
                         kill_switch_engaged,
                     },
                 );
+                write_journal_entry(
+                    &state,
+                    "orders",
+                    &json!({
+                        "ts_ms": state.clock.now_ms(),
+                        "intent_id": intent_id,
+                        "outcome": "risk_denied",
+                        "reason": reason,
+                        "request": order,
+                        "notional_cents": requested_notional_cents,
+                    }),
+                );
                 persist_engine_state(&state);
 
-                return Envelope::response_to(
+                return error_response_with(
                     request,
-                    json!({"ok": false, "error": reason, "hard_safety_floor": "enforced"}),
+                    ErrorCode::RiskDenied,
+                    reason,
+                    json!({"hard_safety_floor": "enforced"}),
                 );
             }
 
             let live_route = route_is_live(mode, &order, &context.adapters);
             let (adapter, routed_to) = if live_route {
-                match &context.adapters.coinbase {
-                    Some(adapter) => (adapter.clone(), "coinbase_at"),
+                match live_adapter_for(&order, &context.adapters) {
+                    Some(route) => route,
                     None => {
-                        return Envelope::response_to(
+                        return error_response(
                             request,
-                            json!({"ok": false, "error": "live route requested but coinbase adapter unavailable"}),
+                            ErrorCode::VenueUnavailable,
+                            format!(
+                                "live route requested but {} adapter unavailable",
+                                order.venue
+                            ),
                         );
                     }
                 }
             } else {
-                (context.adapters.paper.clone(), "paper")
+                (context.adapters.paper.clone() as DynAdapter, "paper")
             };
 
-            let ack = match adapter.place_order(order.clone()).await {
+            let ack = match adapter
+                .place_order(order.clone())
+                .instrument(adapter_span("place_order", routed_to))
+                .await
+            {
                 Ok(ack) => ack,
                 Err(err) => {
                     let mut state = context.state.lock().await;
@@ -1838,69 +4150,40 @@ async fn process_execution_request(
                             latency_ms: 0,
                             simulated: routed_to == "paper",
                             venue_order_id: None,
+                            correlation_id: current_correlation_id(),
                         },
                     );
                     persist_engine_state(&state);
 
-                    return Envelope::response_to(
-                        request,
-                        json!({"ok": false, "error": err.message, "code": err.code}),
-                    );
+                    return venue_error_response(request, err);
                 }
             };
 
-            let order_snapshot = match adapter.get_order(&ack.venue_order_id).await {
+            let order_snapshot = match adapter
+                .get_order(&ack.venue_order_id)
+                .instrument(adapter_span("get_order", routed_to))
+                .await
+            {
                 Ok(Some(snapshot)) => snapshot,
                 Ok(None) => synthetic_order_from_ack(&order, &ack),
                 Err(_) => synthetic_order_from_ack(&order, &ack),
             };
-            let maybe_fill = maybe_fill_from_ack(&order_snapshot, &ack);
-
-            let mut state = context.state.lock().await;
-            state.execution_stats.accepted = state.execution_stats.accepted.saturating_add(1);
-            if maybe_fill.is_some() {
-                state.execution_stats.fills = state.execution_stats.fills.saturating_add(1);
+            let mut maybe_fill = maybe_fill_from_ack(&order_snapshot, &ack);
+            if let Some(fill) = maybe_fill.as_mut().filter(|_| ack.simulated) {
+                (fill.fee, fill.liquidity) =
+                    venue_fill_costs(adapter.as_ref(), &order_snapshot).await;
             }
 
-            if live_route {
-                state.routing_counters.live_count =
-                    state.routing_counters.live_count.saturating_add(1);
-            } else {
-                state.routing_counters.paper_count =
-                    state.routing_counters.paper_count.saturating_add(1);
-            }
-
-            state.risk_snapshot.total_notional_cents = state
-                .risk_snapshot
-                .total_notional_cents
-                .saturating_add(requested_notional_cents);
-            state.risk_snapshot.orders_last_minute =
-                state.risk_snapshot.orders_last_minute.saturating_add(1);
-            *state
-                .risk_snapshot
-                .strategy_canary_notional
-                .entry(order.strategy_id.clone())
-                .or_insert(0) += requested_notional_cents;
-            *state
-                .risk_snapshot
-                .venue_notional
-                .entry(order.venue.clone())
-                .or_insert(0) += requested_notional_cents;
-            *state
-                .risk_snapshot
-                .asset_class_notional
-                .entry(order.instrument.asset_class.clone())
-                .or_insert(0) += requested_notional_cents;
-
-            state.orders.insert(
-                order_snapshot.venue_order_id.clone(),
-                order_snapshot.clone(),
+            let mut state = context.state.lock().await;
+            record_accepted_order(
+                &mut state,
+                &order,
+                &intent_id,
+                requested_notional_cents,
+                live_route,
+                &order_snapshot,
+                maybe_fill.as_ref(),
             );
-            if let Some(fill) = &maybe_fill {
-                state.fills.push(fill.clone());
-            }
-            state.processed_intents.insert(intent_id.clone());
-            state.last_command_at_ms = now_ms();
 
             push_event(
                 &mut state,
@@ -1913,25 +4196,29 @@ async fn process_execution_request(
                     latency_ms: 0,
                     simulated: ack.simulated,
                     venue_order_id: Some(ack.venue_order_id.clone()),
+                    correlation_id: current_correlation_id(),
                 },
             );
 
             write_journal_entry(
-                &state.data_dir,
+                &state,
                 "orders",
                 &json!({
-                    "ts_ms": now_ms(),
+                    "ts_ms": state.clock.now_ms(),
                     "intent_id": intent_id,
                     "routed_to": routed_to,
+                    "outcome": "accepted",
+                    "request": order,
+                    "notional_cents": requested_notional_cents,
                     "order": order_snapshot,
                 }),
             );
             if let Some(fill) = &maybe_fill {
                 write_journal_entry(
-                    &state.data_dir,
+                    &state,
                     "fills",
                     &json!({
-                        "ts_ms": now_ms(),
+                        "ts_ms": state.clock.now_ms(),
                         "intent_id": order.intent_id,
                         "fill": fill,
                     }),
@@ -1945,6 +4232,7 @@ async fn process_execution_request(
                 json!({
                     "ok": true,
                     "routed_to": routed_to,
+                    "notional": notional,
                     "result": ExecutionPlaceResultPayload {
                         ack,
                         order: Some(order_snapshot),
@@ -1958,7 +4246,7 @@ async fn process_execution_request(
                 match parse_payload(&request.payload) {
                     Ok(p) => p,
                     Err(err) => {
-                        return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                        return error_response(request, ErrorCode::InvalidPayload, err);
                     }
                 };
 
@@ -1967,95 +4255,49 @@ async fn process_execution_request(
                 state.orders.get(&payload.venue_order_id).cloned()
             };
 
-            let adapter = if order.as_ref().is_some_and(|o| {
-                o.venue == "coinbase_at" && o.instrument.instrument_type == InstrumentType::Spot
-            }) {
-                match &context.adapters.coinbase {
-                    Some(adapter) => adapter.clone(),
-                    None => {
-                        return Envelope::response_to(
-                            request,
-                            json!({"ok": false, "error": "coinbase adapter unavailable"}),
-                        )
-                    }
-                }
-            } else {
-                context.adapters.paper.clone()
-            };
-
-            if let Err(err) = adapter.cancel_order(&payload.venue_order_id).await {
-                return Envelope::response_to(
+            let Some(adapter) = cancel_adapter_for(order.as_ref(), &context.adapters) else {
+                let venue = order.as_ref().map_or("venue", |order| order.venue.as_str());
+                return error_response(
                     request,
-                    json!({"ok": false, "error": err.message, "code": err.code}),
+                    ErrorCode::VenueUnavailable,
+                    format!("{} adapter unavailable", venue),
                 );
-            }
-
-            let mut state = context.state.lock().await;
-            // Extract info and update order status first, then adjust risk counters
-            let cancel_info = if let Some(existing) = state.orders.get_mut(&payload.venue_order_id) {
-                existing.status = OrderStatus::Canceled;
-                existing.updated_at_ms = now_ms();
-
-                let notional = existing.limit_price
-                    .map(|p| (p * existing.qty * 100.0) as i64)
-                    .unwrap_or(0);
-                Some((
-                    notional,
-                    existing.venue.clone(),
-                    existing.instrument.asset_class.clone(),
-                    existing.strategy_id.clone(),
-                ))
-            } else {
-                None
             };
 
-            // Decrement risk notional counters so cancelled orders free capacity
-            if let Some((notional, venue, asset_class, strategy_id)) = cancel_info {
-                if notional > 0 {
-                    state.risk_snapshot.total_notional_cents = state
-                        .risk_snapshot
-                        .total_notional_cents
-                        .saturating_sub(notional);
-                    if let Some(v) = state.risk_snapshot.venue_notional.get_mut(&venue) {
-                        *v = v.saturating_sub(notional);
-                    }
-                    if let Some(v) = state.risk_snapshot.asset_class_notional.get_mut(&asset_class) {
-                        *v = v.saturating_sub(notional);
-                    }
-                    if let Some(v) = state.risk_snapshot.strategy_canary_notional.get_mut(&strategy_id) {
-                        *v = v.saturating_sub(notional);
-                    }
-                }
+            if let Err(err) = adapter
+                .cancel_order(&payload.venue_order_id)
+                .instrument(adapter_span("cancel_order", adapter.venue()))
+                .await
+            {
+                return venue_error_response(request, err);
             }
-            state.execution_stats.canceled = state.execution_stats.canceled.saturating_add(1);
+
+            let mut state = context.state.lock().await;
+            record_order_canceled(&mut state, &payload.venue_order_id);
             persist_engine_state(&state);
 
-            Envelope::response_to(
+            fields_response(
                 request,
-                json!({"ok": true, "venue_order_id": payload.venue_order_id}),
+                ExecutionCancelResultPayload {
+                    venue_order_id: payload.venue_order_id,
+                },
             )
         }
         ExecutionCommand::Get => {
             let payload: ExecutionGetPayload = match parse_payload(&request.payload) {
                 Ok(p) => p,
                 Err(err) => {
-                    return Envelope::response_to(request, json!({"ok": false, "error": err}));
+                    return error_response(request, ErrorCode::InvalidPayload, err);
                 }
             };
 
             let state = context.state.lock().await;
             let order = state.orders.get(&payload.venue_order_id).cloned();
-            Envelope::response_to(
-                request,
-                json!({
-                    "ok": true,
-                    "order": order,
-                }),
-            )
+            fields_response(request, ExecutionGetResultPayload { order })
         }
         ExecutionCommand::OpenOrders => {
             let mut orders = Vec::new();
-            if let Some(adapter) = &context.adapters.coinbase {
+            for adapter in context.adapters.live() {
                 if let Ok(mut o) = adapter.open_orders().await {
                     orders.append(&mut o);
                 }
@@ -2152,7 +4394,325 @@ async fn process_portfolio_request(
                 }),
             )
         }
+        PortfolioCommand::Pnl => {
+            let filter: PortfolioPnlPayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+
+            let state = context.state.lock().await;
+            let now = state.clock.now_ms();
+            let result = pnl::compute_pnl(
+                &state.fills,
+                &filter,
+                |venue, venue_symbol| pnl_mark(&state, venue, venue_symbol, now),
+                now,
+            );
+            Envelope::response_to(request, json!({"ok": true, "result": result}))
+        }
+        PortfolioCommand::PaperAccount => {
+            let payload: PortfolioPaperAccountPayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+
+            // Held across the adapter call so order reconciliation cannot book
+            // fills from the account being discarded.
+            let mut state = context.state.lock().await;
+            let outcome = match payload.action {
+                PaperAccountAction::Reset => {
+                    let balances = (!payload.balances.is_empty()).then_some(payload.balances);
+                    context.adapters.paper.reset(balances).await
+                }
+                PaperAccountAction::Fund if payload.balances.is_empty() => {
+                    return error_response(
+                        request,
+                        ErrorCode::InvalidPayload,
+                        "fund needs at least one asset amount",
+                    );
+                }
+                PaperAccountAction::Fund => context.adapters.paper.fund(&payload.balances).await,
+            };
+            let balances = match outcome {
+                Ok(balances) => balances,
+                Err(err) => {
+                    return error_response_with(
+                        request,
+                        ErrorCode::InvalidPayload,
+                        err.message,
+                        json!({"venue_code": err.code}),
+                    )
+                }
+            };
+            let (cleared_orders, cleared_fills) = match payload.action {
+                PaperAccountAction::Reset => clear_simulated_activity(&mut state),
+                PaperAccountAction::Fund => (0, 0),
+            };
+
+            // Only the paper account changed, and live venues are not called
+            // while the engine lock is held; their entries are kept.
+            let paper = &context.adapters.paper;
+            let (positions, portfolio_balances) = fetch_paper_portfolio(paper).await;
+            state
+                .portfolio_positions
+                .retain(|position| position.venue != paper.venue());
+            state.portfolio_positions.extend(positions);
+            state
+                .portfolio_balances
+                .retain(|balance| balance.venue != paper.venue());
+            state.portfolio_balances.extend(portfolio_balances);
+            let message = match payload.action {
+                PaperAccountAction::Reset => format!(
+                    "paper account reset; cleared {} orders and {} fills",
+                    cleared_orders, cleared_fills
+                ),
+                PaperAccountAction::Fund => "paper account funded".to_string(),
+            };
+            info!("{}", message);
+            push_event(
+                &mut state,
+                Event::Alert {
+                    level: "info".to_string(),
+                    message,
+                },
+            );
+            let event = Event::PortfolioSync {
+                positions: state.portfolio_positions.len(),
+                balances: state.portfolio_balances.len(),
+            };
+            push_event(&mut state, event);
+            persist_engine_state(&state);
+
+            Envelope::response_to(
+                request,
+                json!({
+                    "ok": true,
+                    "result": PortfolioPaperAccountResultPayload {
+                        action: payload.action,
+                        balances,
+                        cleared_orders,
+                        cleared_fills,
+                    },
+                }),
+            )
+        }
+    }
+}
+
+/// Drops simulated orders and fills after the paper account is reset, so
+/// engine state matches the fresh ledger. Open orders release their notional.
+fn clear_simulated_activity(state: &mut EngineState) -> (usize, usize) {
+    let simulated: Vec<(String, bool)> = state
+        .orders
+        .values()
+        .filter(|order| order.simulated)
+        .map(|order| {
+            let open = matches!(
+                order.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            );
+            (order.venue_order_id.clone(), open)
+        })
+        .collect();
+    for (venue_order_id, open) in &simulated {
+        if *open {
+            record_order_canceled(state, venue_order_id);
+        }
+        state.orders.remove(venue_order_id);
+        state.order_notional_cents.remove(venue_order_id);
+    }
+    let fills_before = state.fills.len();
+    state.fills.retain(|fill| !fill.simulated);
+    (simulated.len(), fills_before - state.fills.len())
+}
+
+async fn process_market_data_request(
+    request: &Envelope,
+    context: &DaemonContext,
+    command: MarketDataCommand,
+) -> Envelope {
+    match command {
+        MarketDataCommand::Quote => {
+            let payload: MarketDataQuotePayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+
+            let cached = context.state.lock().await.market_data.quote(
+                &payload.venue,
+                &payload.venue_symbol,
+                context.clock.now_ms(),
+            );
+            if let Some(quote) = cached.as_ref().filter(|quote| !quote.stale) {
+                return Envelope::response_to(request, json!({"ok": true, "result": quote}));
+            }
+
+            let polled = match market_data_adapter_for(&payload.venue, &context.adapters) {
+                Some(adapter) => adapter
+                    .quote(&payload.venue_symbol)
+                    .await
+                    .map_err(|err| ErrorPayload::new(ErrorCode::VenueError, err.message)),
+                None => Err(ErrorPayload::new(
+                    ErrorCode::VenueUnavailable,
+                    format!("no market data adapter for venue '{}'", payload.venue),
+                )),
+            };
+            let result = match polled {
+                Ok(Some(quote)) => {
+                    let mut state = context.state.lock().await;
+                    let now = state.clock.now_ms();
+                    state.market_data.record_snapshot(quote, now);
+                    state
+                        .market_data
+                        .quote(&payload.venue, &payload.venue_symbol, now)
+                        .ok_or_else(|| {
+                            ErrorPayload::new(ErrorCode::Internal, "quote cache update failed")
+                        })
+                }
+                Ok(None) => Err(ErrorPayload::new(
+                    ErrorCode::NotFound,
+                    format!("no quote for {}:{}", payload.venue, payload.venue_symbol),
+                )),
+                Err(err) => Err(err),
+            };
+
+            match (result, cached) {
+                (Ok(quote), _) | (Err(_), Some(quote)) => {
+                    Envelope::response_to(request, json!({"ok": true, "result": quote}))
+                }
+                (Err(err), None) => error_response(request, err.code, err.error),
+            }
+        }
+        MarketDataCommand::Subscribe => {
+            let payload: MarketDataSubscribePayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+            if payload.venue_symbols.is_empty() {
+                return error_response(
+                    request,
+                    ErrorCode::InvalidPayload,
+                    "venue_symbols must not be empty",
+                );
+            }
+
+            match subscribe_market_data(context, &payload.venue, &payload.venue_symbols).await {
+                Ok(result) => Envelope::response_to(request, json!({"ok": true, "result": result})),
+                Err(err) => error_response(request, err.code, err.error),
+            }
+        }
+        MarketDataCommand::Regime => {
+            let payload: MarketDataRegimePayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+            let contexts = context
+                .state
+                .lock()
+                .await
+                .market_data
+                .regimes(payload.venue.as_deref(), payload.venue_symbol.as_deref());
+            let result = MarketDataRegimeResultPayload { contexts };
+            Envelope::response_to(request, json!({"ok": true, "result": result}))
+        }
+        MarketDataCommand::Product => {
+            let payload: MarketDataProductPayload = match parse_payload(&request.payload) {
+                Ok(payload) => payload,
+                Err(err) => return error_response(request, ErrorCode::InvalidPayload, err),
+            };
+            let Some(adapter) = market_data_adapter_for(&payload.venue, &context.adapters) else {
+                return error_response(
+                    request,
+                    ErrorCode::VenueUnavailable,
+                    format!("no market data adapter for venue '{}'", payload.venue),
+                );
+            };
+            match adapter.product(&payload.venue_symbol).await {
+                Ok(Some(product)) => {
+                    let result = MarketDataProductResultPayload { product };
+                    Envelope::response_to(request, json!({"ok": true, "result": result}))
+                }
+                Ok(None) => error_response(
+                    request,
+                    ErrorCode::NotFound,
+                    format!(
+                        "no product metadata for {}:{}",
+                        payload.venue, payload.venue_symbol
+                    ),
+                ),
+                Err(err) => error_response(request, ErrorCode::VenueError, err.message),
+            }
+        }
+    }
+}
+
+/// Mark for PnL: a fresh cached quote, then the reconciled position mark.
+fn pnl_mark(
+    state: &EngineState,
+    venue: &str,
+    venue_symbol: &str,
+    now: i64,
+) -> Option<(f64, &'static str)> {
+    let quoted = state
+        .market_data
+        .fresh_quote(venue, venue_symbol, now)
+        .and_then(|quote| {
+            let mid = match (quote.bid, quote.ask) {
+                (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+                _ => None,
+            };
+            quote.mark.or(mid).or(quote.last)
+        })
+        .map(|price| (price, "quote"));
+    quoted
+        .or_else(|| {
+            state
+                .portfolio_positions
+                .iter()
+                .find(|p| p.venue == venue && p.instrument.venue_symbol == venue_symbol)
+                .and_then(|p| p.mark_price)
+                .map(|price| (price, "position"))
+        })
+        .filter(|(price, _)| price.is_finite() && *price > 0.0)
+}
+
+/// Success response carrying `fields` at the top level next to `ok`.
+fn fields_response<T: Serialize>(request: &Envelope, fields: T) -> Envelope {
+    let mut body = json!(fields);
+    if let Some(body) = body.as_object_mut() {
+        body.insert("ok".to_string(), json!(true));
+    }
+    Envelope::response_to(request, body)
+}
+
+fn error_response(request: &Envelope, code: ErrorCode, message: impl Into<String>) -> Envelope {
+    error_response_with(request, code, message, json!({}))
+}
+
+/// Error response carrying extra context fields from the `context` object.
+fn error_response_with(
+    request: &Envelope,
+    code: ErrorCode,
+    message: impl Into<String>,
+    context: serde_json::Value,
+) -> Envelope {
+    let mut body = json!(ErrorPayload::new(code, message));
+    if let (Some(body), serde_json::Value::Object(context)) = (body.as_object_mut(), context) {
+        for (key, value) in context {
+            body.entry(key).or_insert(value);
+        }
     }
+    Envelope::response_to(request, body)
+}
+
+/// Adapter failures keep the venue's own code under `venue_code`.
+fn venue_error_response(request: &Envelope, err: ExchangeError) -> Envelope {
+    error_response_with(
+        request,
+        ErrorCode::VenueError,
+        err.message,
+        json!({"venue_code": err.code}),
+    )
 }
 
 fn parse_payload<T>(payload: &serde_json::Value) -> std::result::Result<T, String>
@@ -2252,6 +4812,7 @@ fn engine_state_payload(state: &EngineState) -> EngineStatePayload {
         strategies_total: state.strategies.len(),
         strategies_enabled,
         mode: state.mode,
+        paper_only: state.paper_only,
         routing_counters: RoutingCountersPayload {
             live_count: state.routing_counters.live_count,
             paper_count: state.routing_counters.paper_count,
@@ -2290,12 +4851,16 @@ fn command_kinds_supported() -> Vec<String> {
         ControlCommand::Status.as_kind().to_string(),
         ControlCommand::Ping.as_kind().to_string(),
         ControlCommand::Capabilities.as_kind().to_string(),
+        ControlCommand::Hello.as_kind().to_string(),
+        ControlCommand::SubscribeEvents.as_kind().to_string(),
         EngineCommand::Status.as_kind().to_string(),
         EngineCommand::Pause.as_kind().to_string(),
         EngineCommand::Resume.as_kind().to_string(),
         EngineCommand::KillSwitch.as_kind().to_string(),
         EngineCommand::GetMode.as_kind().to_string(),
         EngineCommand::SetMode.as_kind().to_string(),
+        EngineCommand::ExportSnapshot.as_kind().to_string(),
+        EngineCommand::Replay.as_kind().to_string(),
         StrategyCommand::List.as_kind().to_string(),
         StrategyCommand::Enable.as_kind().to_string(),
         StrategyCommand::Disable.as_kind().to_string(),
@@ -2311,36 +4876,83 @@ fn command_kinds_supported() -> Vec<String> {
         PortfolioCommand::Positions.as_kind().to_string(),
         PortfolioCommand::Balances.as_kind().to_string(),
         PortfolioCommand::Exposure.as_kind().to_string(),
+        PortfolioCommand::Pnl.as_kind().to_string(),
+        PortfolioCommand::PaperAccount.as_kind().to_string(),
+        MarketDataCommand::Quote.as_kind().to_string(),
+        MarketDataCommand::Subscribe.as_kind().to_string(),
+        MarketDataCommand::Regime.as_kind().to_string(),
+        MarketDataCommand::Product.as_kind().to_string(),
     ]
 }
 
-fn capabilities_payload() -> CapabilitiesPayload {
+/// Negotiates a `Control.Hello`; clients that list no versions are assumed to
+/// speak the version of their request envelope.
+fn hello_result(
+    payload: &HelloPayload,
+    envelope_version: u8,
+) -> std::result::Result<HelloResultPayload, ErrorPayload> {
+    let client_versions = if payload.protocol_versions.is_empty() {
+        vec![envelope_version]
+    } else {
+        payload.protocol_versions.clone()
+    };
+    let protocol_version = negotiate_protocol_version(&client_versions).ok_or_else(|| {
+        ErrorPayload::new(
+            ErrorCode::UnsupportedProtocol,
+            format!(
+                "no common protocol version (client {:?}, daemon {:?})",
+                client_versions, SUPPORTED_PROTOCOL_VERSIONS
+            ),
+        )
+    })?;
+
+    Ok(HelloResultPayload {
+        protocol_version,
+        supported_protocol_versions: SUPPORTED_PROTOCOL_VERSIONS.to_vec(),
+        status_schema_version: STATUS_SCHEMA_VERSION,
+        features: DAEMON_FEATURES.iter().map(|f| f.to_string()).collect(),
+        unsupported_features: payload
+            .features
+            .iter()
+            .filter(|f| !DAEMON_FEATURES.contains(&f.as_str()))
+            .cloned()
+            .collect(),
+        daemon_build: daemon_build_payload(),
+    })
+}
+
+fn capabilities_payload(include_schemas: bool) -> CapabilitiesPayload {
     CapabilitiesPayload {
         protocol_version: PROTOCOL_VERSION,
         status_schema_version: STATUS_SCHEMA_VERSION,
         command_kinds_supported: command_kinds_supported(),
         daemon_build: daemon_build_payload(),
+        schemas: include_schemas.then(|| ProtocolSchemasPayload {
+            commands: command_schemas(),
+            types: protocol_schemas(),
+        }),
     }
 }
 
 fn status_response(request: &Envelope, state: &EngineState) -> Envelope {
-    let recent_events: Vec<_> = state.recent_events.iter().cloned().collect();
-    Envelope::response_to(
+    let now = state.clock.now_ms();
+    fields_response(
         request,
-        json!({
-            "ok": true,
-            "protocol_version": PROTOCOL_VERSION,
-            "status_schema_version": STATUS_SCHEMA_VERSION,
-            "daemon_build": daemon_build_payload(),
-            "state": engine_state_payload(state),
-            "risk": {
-                "limits": risk_limits_payload(state),
-                "state": risk_state_payload(state),
+        EngineStatusPayload {
+            protocol_version: PROTOCOL_VERSION,
+            status_schema_version: STATUS_SCHEMA_VERSION,
+            daemon_build: daemon_build_payload(),
+            state: engine_state_payload(state),
+            risk: RiskStatusPayload {
+                limits: risk_limits_payload(state),
+                state: risk_state_payload(state),
             },
-            "portfolio_summary": portfolio_summary_payload(state),
-            "strategies": strategy_summaries(state),
-            "recent_events": recent_events,
-        }),
+            portfolio_summary: portfolio_summary_payload(state),
+            pacing: state.pacer.status(now),
+            market_data: state.market_data.status(now),
+            strategies: strategy_summaries(state),
+            recent_events: state.recent_events.iter().cloned().collect(),
+        },
     )
 }
 
@@ -2353,16 +4965,18 @@ fn push_event(state: &mut EngineState, event: Event) {
         }
     };
 
+    // Sending fails only when nobody is subscribed.
+    let _ = state.events.send(value.clone());
     state.recent_events.push_back(value.clone());
     while state.recent_events.len() > MAX_RECENT_EVENTS {
         state.recent_events.pop_front();
     }
 
     write_journal_entry(
-        &state.data_dir,
+        state,
         "events",
         &json!({
-            "ts_ms": now_ms(),
+            "ts_ms": state.clock.now_ms(),
             "event": value,
         }),
     );
@@ -2370,6 +4984,8 @@ fn push_event(state: &mut EngineState, event: Event) {
 
 #[cfg(test)]
 mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
     use super::*;
 
     fn make_candidate(code_hash: &str, uploaded_at_ms: i64) -> StrategyCandidate {
@@ -2399,6 +5015,15 @@ mod tests {
         }
     }
 
+    fn test_pacer() -> OrderPacer {
+        OrderPacer::new(
+            pacing::default_venue_rate_limits(),
+            false,
+            DEFAULT_PACING_MAX_WAIT_MS,
+            DEFAULT_PACING_MAX_QUEUE_DEPTH,
+        )
+    }
+
     fn unique_state_path(label: &str) -> String {
         let nanos = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -2420,7 +5045,10 @@ mod tests {
             state_path,
             0,
             EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
             true,
+            system_clock(),
         );
         let strategy_id = "kalshi.arbitrage";
 
@@ -2464,7 +5092,10 @@ mod tests {
             state_path,
             100,
             EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
             true,
+            system_clock(),
         );
         let strategy_id = "kalshi.market_making";
 
@@ -2480,14 +5111,161 @@ mod tests {
         assert!(error.contains("candidate expired"));
     }
 
-    #[test]
-    fn route_is_live_only_for_spot_coinbase_and_live_mode() {
-        let adapters = AdapterRegistry {
-            coinbase: Some(Arc::new(PaperExchangeAdapter::new("coinbase_at")) as DynAdapter),
-            paper: Arc::new(PaperExchangeAdapter::new("paper")),
+    #[tokio::test]
+    async fn simulated_clock_drives_candidate_ttl_and_status() {
+        let clock = Arc::new(SimulatedClock::new(1_000_000));
+        let state = initial_engine_state(
+            unique_state_path("simulated-clock").replace(".json", ""),
+            unique_state_path("simulated-clock"),
+            60_000,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        assert_eq!(state.started_at_ms, 1_000_000);
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+
+        let upload = Envelope::new(
+            StrategyCommand::UploadCandidate.as_kind(),
+            json!({
+                "strategy_id": "core.momentum",
+                "source": "agent",
+                "code_hash": "hash-clock",
+                "requested_canary_notional_cents": 500,
+                "compile_passed": true,
+                "replay_passed": true,
+                "paper_passed": true,
+                "latency_passed": true,
+                "risk_passed": true,
+            }),
+        );
+        let response = process_request(&upload, &context).await;
+        assert_eq!(response.payload["candidate"]["uploaded_at_ms"], 1_000_000);
+        assert_eq!(response.payload["replay"]["passed"], true);
+
+        clock.advance(60_001);
+        let promote = Envelope::new(
+            StrategyCommand::PromoteCandidate.as_kind(),
+            serde_json::to_value(promote_payload("core.momentum", "hash-clock", 500))
+                .expect("payload encodes"),
+        );
+        let response = process_request(&promote, &context).await;
+        assert_eq!(response.payload["ok"], false);
+        assert!(response.payload["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("age 60001ms exceeds ttl 60000ms")));
+
+        let state = context.state.lock().await;
+        assert_eq!(state.last_command_at_ms, 1_000_000);
+        let export = snapshot_export_from_state(&state);
+        assert_eq!(export.exported_at_ms, 1_060_001);
+    }
+
+    #[tokio::test]
+    async fn queued_orders_wake_when_the_simulated_clock_advances() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let pacer = OrderPacer::new(
+            [(
+                "coinbase_at".to_string(),
+                pacing::VenueRateLimit::new(1, 0.01),
+            )]
+            .into_iter()
+            .collect(),
+            true,
+            1_000_000,
+            4,
+        );
+        let state = initial_engine_state(
+            unique_state_path("simulated-pacing").replace(".json", ""),
+            unique_state_path("simulated-pacing"),
+            0,
+            EngineMode::Paper,
+            pacer,
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
         };
 
-        let order = NormalizedOrderRequest {
+        await_order_pacing(&context, "coinbase_at", false, None)
+            .await
+            .expect("first order admitted");
+        let queued = tokio::spawn({
+            let context = context.clone();
+            async move { await_order_pacing(&context, "coinbase_at", false, None).await }
+        });
+        sleep(Duration::from_millis(20)).await;
+        assert!(!queued.is_finished());
+
+        // The next token is 100s of engine time away, none of it real time.
+        clock.advance(100_000);
+        let admitted = tokio::time::timeout(Duration::from_secs(2), queued)
+            .await
+            .expect("woken by the engine clock")
+            .expect("pacing task");
+        assert!(admitted.is_ok());
+    }
+
+    #[tokio::test]
+    async fn journals_are_dated_and_retained_by_the_engine_clock() {
+        let data_dir = unique_state_path("journal-dates").replace(".json", "");
+        let day_ms = 86_400_000;
+        // 2026-01-10T00:00:00Z.
+        let clock = Arc::new(SimulatedClock::new(1_768_003_200_000));
+        let state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("journal-dates"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        write_journal_entry(&state, "orders", &json!({"ts_ms": clock.now_ms()}));
+        clock.advance(5 * day_ms);
+        write_journal_entry(&state, "orders", &json!({"ts_ms": clock.now_ms()}));
+        let journal_dir = Path::new(&data_dir).join("journal");
+        let old = journal_dir.join("orders-2026-01-10.jsonl");
+        let recent = journal_dir.join("orders-2026-01-15.jsonl");
+        for _ in 0..100 {
+            if old.exists() && recent.exists() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(old.exists() && recent.exists());
+
+        cleanup_old_journals(&data_dir, 7, clock.now_ms() + 5 * day_ms);
+        assert!(!old.exists());
+        assert!(recent.exists());
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    fn coinbase_spot_order() -> NormalizedOrderRequest {
+        NormalizedOrderRequest {
             venue: "coinbase_at".to_string(),
             symbol: "BTC-USD".to_string(),
             instrument: exchange_core::InstrumentRef {
@@ -2508,14 +5286,1371 @@ mod tests {
             side: exchange_core::OrderSide::Buy,
             order_type: exchange_core::OrderType::Limit,
             qty: 1.0,
+            quote_size: None,
             limit_price: Some(100.0),
+            stop_price: None,
             tif: Some(exchange_core::TimeInForce::Gtc),
             post_only: false,
             reduce_only: false,
             requested_notional_cents: 10_000,
+        }
+    }
+
+    fn btc_quote() -> QuoteSnapshot {
+        QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid: Some(119.0),
+            ask: Some(120.0),
+            last: Some(119.5),
+            mark: Some(119.5),
+            ts_ms: 0,
+        }
+    }
+
+    fn kalshi_binary_order(qty: f64, limit_price: f64) -> NormalizedOrderRequest {
+        NormalizedOrderRequest {
+            venue: "kalshi".to_string(),
+            symbol: "KXHIGHNY-26OCT18-B70".to_string(),
+            instrument: exchange_kalshi::default_kalshi_prediction_instrument(
+                "KXHIGHNY-26OCT18-B70",
+            ),
+            strategy_id: "kalshi.arbitrage".to_string(),
+            limit_price: Some(limit_price),
+            qty,
+            requested_notional_cents: (qty * limit_price * 100.0) as i64,
+            ..coinbase_spot_order()
+        }
+    }
+
+    #[test]
+    fn route_is_live_only_for_spot_coinbase_and_live_mode() {
+        let adapters = AdapterRegistry {
+            coinbase: Some(Arc::new(PaperExchangeAdapter::new("coinbase_at")) as DynAdapter),
+            kalshi: None,
+            paper: Arc::new(PaperExchangeAdapter::new("paper")),
         };
 
+        let order = coinbase_spot_order();
+
         assert!(route_is_live(EngineMode::AutoLive, &order, &adapters));
         assert!(!route_is_live(EngineMode::Paper, &order, &adapters));
+        // Kalshi orders stay on paper until a Kalshi adapter is configured.
+        assert!(!route_is_live(
+            EngineMode::AutoLive,
+            &kalshi_binary_order(1.0, 0.5),
+            &adapters
+        ));
+    }
+
+    #[tokio::test]
+    async fn kalshi_binary_orders_route_live_through_the_hard_safety_cage() {
+        let data_dir = unique_state_path("kalshi-live").replace(".json", "");
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("kalshi-live"),
+            0,
+            EngineMode::AutoLive,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            true,
+            system_clock(),
+        );
+        state.running = true;
+        let kalshi = Arc::new(exchange_kalshi::KalshiAdapter::new(
+            trading_domain::ExecutionMode::Live,
+        ));
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: Some(kalshi.clone() as DynAdapter),
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        let place = |order: NormalizedOrderRequest| {
+            Envelope::new(
+                ExecutionCommand::Place.as_kind(),
+                serde_json::to_value(ExecutionPlacePayload {
+                    order,
+                    approval_token: None,
+                    priority: None,
+                    max_queue_wait_ms: None,
+                })
+                .expect("payload encodes"),
+            )
+        };
+
+        let response = process_request(&place(kalshi_binary_order(10.0, 0.42)), &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        assert_eq!(response.payload["routed_to"], "kalshi");
+        assert_eq!(response.payload["result"]["ack"]["simulated"], false);
+
+        // Above the default strategy canary limit: the cage denies it before
+        // the venue sees it.
+        let mut oversized = kalshi_binary_order(100.0, 0.5);
+        oversized.intent_id = Some("i2".to_string());
+        let response = process_request(&place(oversized), &context).await;
+        assert_eq!(response.payload["ok"], false);
+        assert_eq!(response.payload["code"], "risk_denied");
+
+        assert_eq!(kalshi.open_orders().await.expect("orders").len(), 1);
+        let state = context.state.lock().await;
+        assert_eq!(state.routing_counters.live_count, 1);
+        assert_eq!(state.risk_snapshot.venue_notional.get("kalshi"), Some(&420));
+        drop(state);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn kalshi_orders_canceled_on_the_venue_release_their_notional() {
+        let data_dir = unique_state_path("kalshi-reconcile").replace(".json", "");
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("kalshi-reconcile"),
+            0,
+            EngineMode::AutoLive,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            true,
+            system_clock(),
+        );
+        state.running = true;
+        let kalshi = Arc::new(exchange_kalshi::KalshiAdapter::new(
+            trading_domain::ExecutionMode::Live,
+        ));
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: Some(kalshi.clone() as DynAdapter),
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        let request = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(ExecutionPlacePayload {
+                order: kalshi_binary_order(10.0, 0.42),
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            })
+            .expect("payload encodes"),
+        );
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        let venue_order_id = response.payload["result"]["ack"]["venue_order_id"]
+            .as_str()
+            .expect("venue order id")
+            .to_string();
+
+        // Nothing changed on the venue: the order keeps its notional.
+        reconcile_kalshi_orders(&context).await;
+        let state = context.state.lock().await;
+        assert_eq!(state.risk_snapshot.venue_notional.get("kalshi"), Some(&420));
+        drop(state);
+
+        kalshi
+            .cancel_order(&venue_order_id)
+            .await
+            .expect("canceled");
+        reconcile_kalshi_orders(&context).await;
+
+        let state = context.state.lock().await;
+        assert_eq!(state.orders[&venue_order_id].status, OrderStatus::Canceled);
+        assert_eq!(state.risk_snapshot.venue_notional.get("kalshi"), Some(&0));
+        assert_eq!(state.risk_snapshot.total_notional_cents, 0);
+        assert!(state.fills.is_empty());
+        drop(state);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn market_order_notional_is_derived_from_quote() {
+        let mut order = coinbase_spot_order();
+        order.order_type = exchange_core::OrderType::Market;
+        order.limit_price = None;
+        order.requested_notional_cents = 0;
+
+        let notional = assess_order_notional(&order, Some(&btc_quote()));
+        assert_eq!(notional.derived_notional_cents, 12_000);
+        assert_eq!(notional.used_notional_cents, 12_000);
+        assert_eq!(notional.price_source.as_deref(), Some("ask"));
+        assert!(!notional.understated);
+
+        let without_quote = assess_order_notional(&order, None);
+        assert_eq!(without_quote.used_notional_cents, 0);
+    }
+
+    #[test]
+    fn understated_requested_notional_is_flagged_and_overridden() {
+        let mut order = coinbase_spot_order();
+        order.requested_notional_cents = 100;
+
+        let notional = assess_order_notional(&order, Some(&btc_quote()));
+        assert!(notional.understated);
+        assert_eq!(notional.used_notional_cents, 12_000);
+    }
+
+    #[tokio::test]
+    async fn quote_sized_orders_are_risk_checked_on_their_quote_amount() {
+        let mut order = coinbase_spot_order();
+        order.order_type = exchange_core::OrderType::Market;
+        order.limit_price = None;
+        order.qty = 0.0001;
+        order.quote_size = Some(100_000.0);
+        order.requested_notional_cents = 1;
+
+        let notional = assess_order_notional(&order, Some(&btc_quote()));
+        assert_eq!(notional.derived_notional_cents, 10_000_000);
+        assert_eq!(notional.used_notional_cents, 10_000_000);
+        assert!(notional.understated);
+
+        let data_dir = unique_state_path("quote-size").replace(".json", "");
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("quote-size"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        let place = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(ExecutionPlacePayload {
+                order,
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            })
+            .expect("payload encodes"),
+        );
+        let response = process_request(&place, &context).await;
+        assert_eq!(response.payload["ok"], false, "{}", response.payload);
+        assert_eq!(response.payload["code"], "risk_denied");
+        assert!(context.state.lock().await.orders.is_empty());
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn limit_order_uses_larger_of_limit_and_market_notional() {
+        let mut order = coinbase_spot_order();
+        order.limit_price = Some(130.0);
+        order.requested_notional_cents = 0;
+
+        let notional = assess_order_notional(&order, Some(&btc_quote()));
+        assert_eq!(notional.derived_notional_cents, 13_000);
+        assert_eq!(notional.price_source.as_deref(), Some("ask"));
+    }
+
+    #[test]
+    fn no_contracts_are_priced_from_the_complement_of_the_yes_quote() {
+        let yes_quote = QuoteSnapshot {
+            venue: "kalshi".to_string(),
+            venue_symbol: "KXHIGHNY-26OCT18-B70".to_string(),
+            bid: Some(0.91),
+            ask: Some(0.93),
+            last: Some(0.92),
+            mark: None,
+            ts_ms: 0,
+        };
+        let mut no_buy = kalshi_binary_order(10.0, 0.10);
+        no_buy.instrument.option_right = Some(OptionRight::Put);
+
+        let notional = assess_order_notional(&no_buy, Some(&yes_quote));
+        let no_ask = notional.reference_price.expect("NO ask");
+        assert!((no_ask - 0.09).abs() < 1e-9, "{no_ask}");
+        assert_eq!(notional.price_source.as_deref(), Some("ask"));
+        assert_eq!(notional.derived_notional_cents, 100);
+        assert_eq!(notional.used_notional_cents, 100);
+        assert!(!notional.understated);
+
+        let yes_buy = kalshi_binary_order(10.0, 0.10);
+        let notional = assess_order_notional(&yes_buy, Some(&yes_quote));
+        assert_eq!(notional.reference_price, Some(0.93));
+        assert!(notional.understated);
+    }
+
+    #[test]
+    fn synthetic_paper_quotes_never_price_orders() {
+        let adapters = AdapterRegistry {
+            coinbase: None,
+            kalshi: None,
+            paper: Arc::new(PaperExchangeAdapter::new("paper")),
+        };
+        let mut paper_order = coinbase_spot_order();
+        paper_order.venue = "paper".to_string();
+        paper_order.instrument.venue = "paper".to_string();
+        assert!(quote_adapter_for(&paper_order, &adapters).is_none());
+        assert!(quote_adapter_for(&kalshi_binary_order(1.0, 0.5), &adapters).is_none());
+
+        let notional = assess_order_notional(&kalshi_binary_order(10.0, 0.08), None);
+        assert_eq!(notional.used_notional_cents, 80);
+        assert!(!notional.understated);
+    }
+
+    #[test]
+    fn paper_import_round_trip_pins_engine_to_paper() {
+        let mut source = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("export-source"),
+            0,
+            EngineMode::AutoLive,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            true,
+            system_clock(),
+        );
+        source.mode = EngineMode::AutoLive;
+        source.kill_switch_engaged = true;
+        source
+            .order_notional_cents
+            .insert("venue-order-1".to_string(), 12_000);
+
+        let export = snapshot_export_from_state(&source);
+        let raw = serde_json::to_vec(&export).expect("export encodes");
+        let mut snapshot = decode_snapshot_import(&raw).expect("export decodes");
+        assert_eq!(snapshot.mode, EngineMode::AutoLive);
+        disable_live_routing(&mut snapshot);
+
+        let mut target = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("export-target"),
+            0,
+            EngineMode::AutoLive,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            true,
+            system_clock(),
+        );
+        apply_engine_snapshot(&mut target, snapshot);
+
+        assert_eq!(target.mode, EngineMode::Paper);
+        assert!(target.paper_only);
+        assert!(target.kill_switch_engaged);
+        assert_eq!(
+            target.order_notional_cents.get("venue-order-1"),
+            Some(&12_000)
+        );
+        assert!(engine_state_payload(&target).paper_only);
+    }
+
+    #[test]
+    fn snapshot_import_accepts_raw_state_and_rejects_unknown_inputs() {
+        let state = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("import-raw"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            true,
+            system_clock(),
+        );
+        let mut snapshot = strategy_snapshot_from_state(&state);
+        let raw = serde_json::to_vec(&snapshot).expect("snapshot encodes");
+        assert!(decode_snapshot_import(&raw).is_ok());
+
+        snapshot.schema_version = ENGINE_SNAPSHOT_SCHEMA_VERSION + 1;
+        let raw = serde_json::to_vec(&snapshot).expect("snapshot encodes");
+        assert!(decode_snapshot_import(&raw).is_err());
+
+        let mut export = snapshot_export_from_state(&state);
+        export.format = "something.else".to_string();
+        let raw = serde_json::to_vec(&export).expect("export encodes");
+        assert!(decode_snapshot_import(&raw).is_err());
+    }
+
+    #[test]
+    fn export_path_stays_inside_exports_dir() {
+        let path = export_path("/data", "incident-42.json").expect("valid name");
+        assert_eq!(path, Path::new("/data/exports/incident-42.json"));
+        assert!(export_path("/data", "../state/engine-state.json").is_err());
+        assert!(export_path("/data", ".hidden").is_err());
+        assert!(export_path("/data", "").is_err());
+    }
+
+    #[test]
+    fn hello_negotiates_version_and_reports_unknown_features() {
+        let payload = HelloPayload {
+            client_name: Some("test".to_string()),
+            protocol_versions: vec![1, 7],
+            status_schema_version: Some(STATUS_SCHEMA_VERSION),
+            features: vec!["market_data".to_string(), "teleport".to_string()],
+        };
+        let result = hello_result(&payload, 7).expect("common version");
+        assert_eq!(result.protocol_version, 1);
+        assert_eq!(result.unsupported_features, vec!["teleport"]);
+        assert!(result.features.iter().any(|f| f == "error_codes"));
+
+        let err = hello_result(&HelloPayload::default(), 7).expect_err("no common version");
+        assert_eq!(err.code, ErrorCode::UnsupportedProtocol);
+        assert!(hello_result(&HelloPayload::default(), PROTOCOL_VERSION).is_ok());
+    }
+
+    #[test]
+    fn error_responses_carry_code_and_context() {
+        let request = Envelope::new("Execution.Place", json!({}));
+        let response = error_response_with(
+            &request,
+            ErrorCode::PacingRejected,
+            "venue bucket empty",
+            json!({"pacing": "rejected", "code": "ignored"}),
+        );
+        assert_eq!(
+            response.payload,
+            json!({
+                "ok": false,
+                "code": "pacing_rejected",
+                "error": "venue bucket empty",
+                "pacing": "rejected",
+            })
+        );
+    }
+
+    #[test]
+    fn every_supported_command_has_a_schema_entry() {
+        let documented: HashSet<String> = command_schemas().into_iter().map(|c| c.kind).collect();
+        for kind in command_kinds_supported() {
+            assert!(
+                documented.contains(&kind),
+                "{} missing from command_schemas",
+                kind
+            );
+        }
+
+        let capabilities = capabilities_payload(true);
+        let encoded = serde_json::to_vec(&capabilities).expect("capabilities encode");
+        assert!(encoded.len() + INLINE_EXPORT_FRAME_HEADROOM < MAX_FRAME_LENGTH);
+        assert!(capabilities_payload(false).schemas.is_none());
+    }
+
+    #[tokio::test]
+    async fn correlation_id_reaches_journal_records() {
+        let data_dir = unique_state_path("correlation").replace(".json", "");
+        let state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("correlation"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        let request = Envelope::new(
+            RiskCommand::Override.as_kind(),
+            json!({"action": "kill_venue", "venue": "paper"}),
+        )
+        .with_correlation_id("corr-test-1");
+
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["ok"], true);
+        assert_eq!(response.correlation_id.as_deref(), Some("corr-test-1"));
+
+        let journal = journal_path(&data_dir, "risk", system_clock().now_ms());
+        let mut record = None;
+        for _ in 0..100 {
+            if let Ok(contents) = std::fs::read_to_string(&journal) {
+                record = contents
+                    .lines()
+                    .next()
+                    .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok());
+                if record.is_some() {
+                    break;
+                }
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        let record = record.expect("risk journal record");
+        assert_eq!(record["correlation_id"], "corr-test-1");
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn replay_reproduces_journaled_orders_and_flags_divergences() {
+        let data_dir = unique_state_path("replay").replace(".json", "");
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("replay"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        context
+            .adapters
+            .paper
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                bid: Some(99.0),
+                ask: Some(100.0),
+                ..btc_quote()
+            }))
+            .await;
+
+        // Small enough to stay under the default strategy canary limit.
+        for (index, qty) in [0.01, 0.02].into_iter().enumerate() {
+            let mut order = coinbase_spot_order();
+            order.client_order_id = format!("replay-{}", index);
+            order.intent_id = Some(format!("replay-intent-{}", index));
+            order.qty = qty;
+            order.requested_notional_cents = 500;
+            let payload = ExecutionPlacePayload {
+                order,
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            };
+            let request = Envelope::new(
+                ExecutionCommand::Place.as_kind(),
+                serde_json::to_value(payload).expect("payload encodes"),
+            );
+            let response = process_request(&request, &context).await;
+            assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        }
+
+        let orders_journal = journal_path(&data_dir, "orders", context.clock.now_ms());
+        let fills_journal = journal_path(&data_dir, "fills", context.clock.now_ms());
+        let read_lines = |path: &PathBuf| -> Vec<serde_json::Value> {
+            std::fs::read_to_string(path)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        };
+        for _ in 0..100 {
+            if read_lines(&orders_journal).len() == 2 && read_lines(&fills_journal).len() == 2 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let report = replay_journals(&data_dir, HardSafetyPolicy::default(), 0, i64::MAX).await;
+        assert!(report.passed, "{:?}", report.divergences);
+        assert_eq!((report.orders, report.fills, report.accepted), (2, 2, 2));
+        assert_eq!(
+            report.risk_state.total_notional_cents,
+            context
+                .state
+                .lock()
+                .await
+                .risk_snapshot
+                .total_notional_cents
+        );
+
+        // Claim the second order was denied and add a fill nobody placed.
+        let mut orders = read_lines(&orders_journal);
+        orders[1]["outcome"] = json!("risk_denied");
+        orders[1]["reason"] = json!("tampered");
+        let mut fills = read_lines(&fills_journal);
+        let mut ghost = fills[0].clone();
+        ghost["fill"]["venue_order_id"] = json!("ghost-order");
+        fills.push(ghost);
+        for (path, lines) in [(&orders_journal, &orders), (&fills_journal, &fills)] {
+            let contents: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+            std::fs::write(path, contents).expect("rewrite journal");
+        }
+
+        let report = replay_journals(&data_dir, HardSafetyPolicy::default(), 0, i64::MAX).await;
+        let mut kinds: Vec<&str> = report
+            .divergences
+            .iter()
+            .map(|divergence| divergence.kind.as_str())
+            .collect();
+        kinds.sort();
+        assert_eq!(kinds, vec!["risk_decision", "unmatched_fill"]);
+        assert!(!report.passed);
+        assert_eq!((report.accepted, report.rejected), (1, 1));
+        // The sandbox never appends to the journals it replays.
+        assert_eq!(read_lines(&orders_journal).len(), 2);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn replay_compares_every_recorded_fill_of_an_order() {
+        let data_dir = unique_state_path("replay-fills").replace(".json", "");
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("replay-fills"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::new("paper")),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: system_clock(),
+            shutdown: CancellationToken::new(),
+        };
+        context
+            .adapters
+            .paper
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                bid: Some(99.0),
+                ask: Some(100.0),
+                ..btc_quote()
+            }))
+            .await;
+        let mut order = coinbase_spot_order();
+        order.qty = 0.02;
+        order.requested_notional_cents = 200;
+        let place = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(ExecutionPlacePayload {
+                order,
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            })
+            .expect("payload encodes"),
+        );
+        let response = process_request(&place, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+
+        let fills_journal = journal_path(&data_dir, "fills", context.clock.now_ms());
+        let mut recorded = None;
+        for _ in 0..100 {
+            recorded = std::fs::read_to_string(&fills_journal)
+                .ok()
+                .and_then(|contents| contents.lines().next().map(str::to_string))
+                .and_then(|line| serde_json::from_str::<serde_json::Value>(&line).ok());
+            if recorded.is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        let recorded = recorded.expect("fill journaled");
+        // Record the order as two fills, at 99 and at `second_price`.
+        let split = |second_price: f64| {
+            let mut first = recorded.clone();
+            first["fill"]["qty"] = json!(0.01);
+            first["fill"]["price"] = json!(99.0);
+            let mut second = first.clone();
+            second["fill"]["venue_fill_id"] = json!("second-fill");
+            second["fill"]["price"] = json!(second_price);
+            std::fs::write(&fills_journal, format!("{}\n{}\n", first, second))
+                .expect("rewrite journal");
+        };
+
+        // Weighted at 100, within the limit: the sandbox fills all 0.02 there.
+        split(101.0);
+        let report = replay_journals(&data_dir, HardSafetyPolicy::default(), 0, i64::MAX).await;
+        assert!(report.passed, "{:?}", report.divergences);
+        assert_eq!(report.fills, 2);
+
+        // Weighted at 101, through the buy limit of 100.
+        split(103.0);
+        let report = replay_journals(&data_dir, HardSafetyPolicy::default(), 0, i64::MAX).await;
+        let kinds: Vec<&str> = report
+            .divergences
+            .iter()
+            .map(|divergence| divergence.kind.as_str())
+            .collect();
+        assert_eq!(kinds, vec!["fill"]);
+        assert_eq!(report.divergences[0].recorded, "Buy 0.02 @ 101");
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn replay_releases_the_notional_of_journaled_cancels() {
+        let data_dir = unique_state_path("replay-cancel").replace(".json", "");
+        let clock = Arc::new(SimulatedClock::new(1_768_003_200_000));
+        let mut state = initial_engine_state(
+            data_dir.clone(),
+            unique_state_path("replay-cancel"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+        context
+            .adapters
+            .paper
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                bid: Some(99.0),
+                ask: Some(100.0),
+                ..btc_quote()
+            }))
+            .await;
+
+        // Each resting bid holds most of the default strategy canary limit,
+        // so the second only fits once the first is canceled.
+        let place = |index: usize| {
+            let mut order = coinbase_spot_order();
+            order.client_order_id = format!("replay-cancel-{}", index);
+            order.intent_id = Some(format!("replay-cancel-intent-{}", index));
+            order.qty = 0.2;
+            order.limit_price = Some(90.0);
+            order.requested_notional_cents = 1_800;
+            let payload = ExecutionPlacePayload {
+                order,
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            };
+            Envelope::new(
+                ExecutionCommand::Place.as_kind(),
+                serde_json::to_value(payload).expect("payload encodes"),
+            )
+        };
+        let response = process_request(&place(0), &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        let venue_order_id = response.payload["result"]["ack"]["venue_order_id"]
+            .as_str()
+            .expect("venue order id")
+            .to_string();
+        let cancel = Envelope::new(
+            ExecutionCommand::Cancel.as_kind(),
+            json!({"venue_order_id": venue_order_id}),
+        );
+        clock.advance(1_000);
+        let response = process_request(&cancel, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        clock.advance(1_000);
+        let response = process_request(&place(1), &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+
+        let orders_journal = journal_path(&data_dir, "orders", context.clock.now_ms());
+        let read_lines = || -> Vec<serde_json::Value> {
+            std::fs::read_to_string(&orders_journal)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        };
+        for _ in 0..100 {
+            if read_lines().len() == 3 {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        let canceled = read_lines()
+            .into_iter()
+            .find(|line| line["outcome"] == "canceled")
+            .expect("cancel journaled");
+        assert_eq!(canceled["order"]["venue_order_id"], venue_order_id.as_str());
+
+        let report = replay_journals(&data_dir, HardSafetyPolicy::default(), 0, i64::MAX).await;
+        assert!(report.passed, "{:?}", report.divergences);
+        assert_eq!((report.orders, report.accepted), (2, 2));
+        assert_eq!(
+            report.risk_state.total_notional_cents,
+            context
+                .state
+                .lock()
+                .await
+                .risk_snapshot
+                .total_notional_cents
+        );
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn pushed_events_reach_subscribers() {
+        let mut state = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("event-stream"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        let mut events = state.events.subscribe();
+
+        push_event(
+            &mut state,
+            Event::Alert {
+                level: "info".to_string(),
+                message: "hello".to_string(),
+            },
+        );
+
+        let event = events.try_recv().expect("event broadcast");
+        assert_eq!(event["Event.Alert"]["message"], "hello");
+        assert_eq!(state.recent_events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn regime_changes_are_published_and_queryable() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let state = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("regime"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS).with_regime_config(RegimeConfig {
+                min_samples: 2,
+                ..RegimeConfig::default()
+            }),
+            false,
+            clock.clone(),
+        );
+        let mut events = state.events.subscribe();
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+
+        {
+            let mut state = context.state.lock().await;
+            for (ts_ms, mid) in [(0, 100.0), (1_000, 101.0)] {
+                clock.set(ts_ms);
+                let quote = QuoteSnapshot {
+                    venue: "coinbase_at".to_string(),
+                    venue_symbol: "BTC-USD".to_string(),
+                    bid: Some(mid - 0.01),
+                    ask: Some(mid + 0.01),
+                    last: None,
+                    mark: None,
+                    ts_ms,
+                };
+                apply_market_data_update(&mut state, MarketDataUpdate::Quote(quote));
+            }
+        }
+
+        let event = events.try_recv().expect("regime event");
+        assert_eq!(event["Event.Regime"]["previous"], "Unknown");
+        assert_eq!(event["Event.Regime"]["context"]["regime"], "Trending");
+        assert!(events.try_recv().is_err(), "only changes are published");
+
+        let request = Envelope::new(
+            MarketDataCommand::Regime.as_kind(),
+            json!({"venue": "coinbase_at"}),
+        );
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["ok"], true);
+        let contexts = &response.payload["result"]["contexts"];
+        assert_eq!(contexts.as_array().map(Vec::len), Some(1));
+        assert_eq!(contexts[0]["symbol"], "BTC-USD");
+        assert_eq!(contexts[0]["ts_ms"], 1_000);
+    }
+
+    #[tokio::test]
+    async fn product_metadata_is_served_by_the_venue_adapter() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(initial_engine_state(
+                std::env::temp_dir().to_string_lossy().to_string(),
+                unique_state_path("product"),
+                0,
+                EngineMode::Paper,
+                test_pacer(),
+                MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+                false,
+                clock.clone(),
+            ))),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock,
+            shutdown: CancellationToken::new(),
+        };
+        let product = |venue: &str| {
+            Envelope::new(
+                MarketDataCommand::Product.as_kind(),
+                json!({"venue": venue, "venue_symbol": "BTC-USD"}),
+            )
+        };
+
+        let response = process_request(&product("paper"), &context).await;
+        assert_eq!(response.payload["ok"], false);
+        assert_eq!(response.payload["code"], "not_found");
+        let response = process_request(&product("coinbase_at"), &context).await;
+        assert_eq!(response.payload["code"], "venue_unavailable");
+    }
+
+    #[tokio::test]
+    async fn coinbase_user_updates_book_fills_once_and_release_canceled_orders() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let data_dir = std::env::temp_dir().join(format!("cb-user-{}", Uuid::new_v4()));
+        let mut state = initial_engine_state(
+            data_dir.to_string_lossy().to_string(),
+            unique_state_path("cb-user"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        let request = coinbase_spot_order();
+        let ack = OrderAck {
+            venue_order_id: "ord-1".to_string(),
+            client_order_id: request.client_order_id.clone(),
+            accepted: true,
+            status: OrderStatus::New,
+            filled_qty: 0.0,
+            avg_fill_price: None,
+            simulated: false,
+            reason: None,
+            ts_ms: 0,
+        };
+        let placed = synthetic_order_from_ack(&request, &ack);
+        state.orders.insert("ord-1".to_string(), placed.clone());
+        state
+            .order_notional_cents
+            .insert("ord-1".to_string(), 5_000);
+        state.risk_snapshot.total_notional_cents = 5_000;
+
+        let fill = FillReport {
+            venue: "coinbase_at".to_string(),
+            venue_fill_id: "ord-1:0.4".to_string(),
+            venue_order_id: "ord-1".to_string(),
+            client_order_id: placed.client_order_id.clone(),
+            strategy_id: String::new(),
+            instrument: placed.instrument.clone(),
+            side: placed.side.clone(),
+            qty: 0.4,
+            price: 100.0,
+            fee: 0.1,
+            fee_asset: Some("USD".to_string()),
+            liquidity: None,
+            simulated: false,
+            ts_ms: 1_000,
+        };
+        apply_coinbase_user_update(&mut state, UserUpdate::Fill(fill.clone()));
+        apply_coinbase_user_update(&mut state, UserUpdate::Fill(fill));
+        assert_eq!(state.fills.len(), 1, "resynced fills are booked once");
+        assert_eq!(state.fills[0].strategy_id, placed.strategy_id);
+        assert_eq!(state.execution_stats.fills, 1);
+
+        let canceled = OrderSnapshot {
+            strategy_id: String::new(),
+            status: OrderStatus::Canceled,
+            filled_qty: 0.4,
+            ..placed.clone()
+        };
+        apply_coinbase_user_update(&mut state, UserUpdate::Order(canceled));
+        let order = &state.orders["ord-1"];
+        assert_eq!(order.status, OrderStatus::Canceled);
+        assert_eq!(order.strategy_id, placed.strategy_id);
+        assert_eq!(state.risk_snapshot.total_notional_cents, 0);
+        assert_eq!(state.execution_stats.canceled, 1);
+
+        let unknown = OrderSnapshot {
+            venue_order_id: "ord-2".to_string(),
+            ..placed
+        };
+        apply_coinbase_user_update(&mut state, UserUpdate::Order(unknown));
+        assert!(!state.orders.contains_key("ord-2"));
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn resting_paper_fills_are_reconciled_into_engine_state() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let data_dir = std::env::temp_dir().join(format!("paper-rest-{}", Uuid::new_v4()));
+        let mut state = initial_engine_state(
+            data_dir.to_string_lossy().to_string(),
+            unique_state_path("paper-rest"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+        let paper = Arc::clone(&context.adapters.paper);
+        paper
+            .apply_market_data(&MarketDataUpdate::Quote(btc_quote()))
+            .await;
+
+        let mut order = coinbase_spot_order();
+        order.qty = 0.01;
+        order.requested_notional_cents = 500;
+        let payload = ExecutionPlacePayload {
+            order,
+            approval_token: None,
+            priority: None,
+            max_queue_wait_ms: None,
+        };
+        let request = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(payload).expect("payload encodes"),
+        );
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        {
+            let state = context.state.lock().await;
+            assert!(state.fills.is_empty());
+            assert_eq!(state.order_notional_cents.len(), 1);
+        }
+
+        clock.set(1_000);
+        paper
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                bid: Some(99.0),
+                ask: Some(99.5),
+                ts_ms: 1_000,
+                ..btc_quote()
+            }))
+            .await;
+        reconcile_paper_orders(&context).await;
+
+        let state = context.state.lock().await;
+        assert_eq!(state.fills.len(), 1);
+        assert_eq!((state.fills[0].qty, state.fills[0].price), (0.01, 100.0));
+        assert_eq!(state.fills[0].ts_ms, 1_000);
+        // The paper venue charged Coinbase's entry maker rate on the resting fill.
+        assert!((state.fills[0].fee - 0.004).abs() < 1e-12);
+        assert_eq!(state.fills[0].liquidity.as_deref(), Some("maker"));
+        assert_eq!(state.execution_stats.fills, 1);
+        let order = state.orders.values().next().expect("order booked");
+        assert_eq!(order.status, OrderStatus::Filled);
+        // Filled orders keep their exposure but are no longer tracked for release.
+        assert!(state.order_notional_cents.is_empty());
+        assert_eq!(state.risk_snapshot.total_notional_cents, 500);
+        drop(state);
+
+        let mut order = coinbase_spot_order();
+        order.client_order_id = "taker-1".to_string();
+        order.qty = 0.01;
+        order.requested_notional_cents = 500;
+        let payload = ExecutionPlacePayload {
+            order,
+            approval_token: None,
+            priority: None,
+            max_queue_wait_ms: None,
+        };
+        let request = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(payload).expect("payload encodes"),
+        );
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["result"]["ack"]["status"], "Filled");
+        let state = context.state.lock().await;
+        assert!(state.order_notional_cents.is_empty());
+        assert_eq!(state.risk_snapshot.total_notional_cents, 1_000);
+        drop(state);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn expired_paper_orders_release_their_notional() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let data_dir = std::env::temp_dir().join(format!("paper-expiry-{}", Uuid::new_v4()));
+        let mut state = initial_engine_state(
+            data_dir.to_string_lossy().to_string(),
+            unique_state_path("paper-expiry"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+        context
+            .adapters
+            .paper
+            .apply_market_data(&MarketDataUpdate::Quote(btc_quote()))
+            .await;
+
+        let mut order = coinbase_spot_order();
+        order.qty = 0.01;
+        order.limit_price = Some(90.0);
+        order.requested_notional_cents = 500;
+        order.tif = Some(exchange_core::TimeInForce::Day);
+        let payload = ExecutionPlacePayload {
+            order,
+            approval_token: None,
+            priority: None,
+            max_queue_wait_ms: None,
+        };
+        let request = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(payload).expect("payload encodes"),
+        );
+        let response = process_request(&request, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        let state = context.state.lock().await;
+        assert_eq!(state.risk_snapshot.total_notional_cents, 500);
+        drop(state);
+
+        clock.set(86_400_000);
+        reconcile_paper_orders(&context).await;
+
+        let state = context.state.lock().await;
+        let order = state.orders.values().next().expect("order booked");
+        assert_eq!(order.status, OrderStatus::Canceled);
+        assert_eq!(state.risk_snapshot.total_notional_cents, 0);
+        assert!(state.order_notional_cents.is_empty());
+        drop(state);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn paper_account_reset_clears_simulated_activity_and_fund_adds_balances() {
+        let clock = Arc::new(SimulatedClock::new(0));
+        let data_dir = std::env::temp_dir().join(format!("paper-account-{}", Uuid::new_v4()));
+        let mut state = initial_engine_state(
+            data_dir.to_string_lossy().to_string(),
+            unique_state_path("paper-account"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            clock.clone(),
+        );
+        state.running = true;
+        let context = DaemonContext {
+            state: Arc::new(Mutex::new(state)),
+            adapters: Arc::new(AdapterRegistry {
+                coinbase: None,
+                kalshi: None,
+                paper: Arc::new(PaperExchangeAdapter::with_clock("paper", clock.clone())),
+            }),
+            market_data_sink: Arc::new(|_| {}),
+            clock: clock.clone(),
+            shutdown: CancellationToken::new(),
+        };
+        context
+            .adapters
+            .paper
+            .apply_market_data(&MarketDataUpdate::Quote(QuoteSnapshot {
+                bid: Some(99.0),
+                ask: Some(100.0),
+                ..btc_quote()
+            }))
+            .await;
+        let mut order = coinbase_spot_order();
+        order.qty = 0.01;
+        order.requested_notional_cents = 100;
+        let place = Envelope::new(
+            ExecutionCommand::Place.as_kind(),
+            serde_json::to_value(ExecutionPlacePayload {
+                order,
+                approval_token: None,
+                priority: None,
+                max_queue_wait_ms: None,
+            })
+            .expect("payload encodes"),
+        );
+        let response = process_request(&place, &context).await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        assert_eq!(context.state.lock().await.fills.len(), 1);
+
+        let paper_account = |action: PaperAccountAction, balances: &[(&str, f64)]| {
+            Envelope::new(
+                PortfolioCommand::PaperAccount.as_kind(),
+                serde_json::to_value(PortfolioPaperAccountPayload {
+                    action,
+                    balances: balances
+                        .iter()
+                        .map(|(asset, amount)| (asset.to_string(), *amount))
+                        .collect(),
+                })
+                .expect("payload encodes"),
+            )
+        };
+        let response = process_request(
+            &paper_account(PaperAccountAction::Reset, &[("USD", 5_000.0)]),
+            &context,
+        )
+        .await;
+        assert_eq!(response.payload["ok"], true, "{}", response.payload);
+        let result: PortfolioPaperAccountResultPayload =
+            serde_json::from_value(response.payload["result"].clone()).expect("result decodes");
+        assert_eq!((result.cleared_orders, result.cleared_fills), (1, 1));
+        assert_eq!(result.balances.len(), 1);
+        assert_eq!(result.balances[0].total, 5_000.0);
+        {
+            let state = context.state.lock().await;
+            assert!(state.fills.is_empty());
+            assert!(state.orders.is_empty());
+            assert!(state.portfolio_positions.is_empty());
+        }
+
+        // Live venues are not refetched; their last synced entries stay.
+        context
+            .state
+            .lock()
+            .await
+            .portfolio_balances
+            .push(BalanceSnapshot {
+                venue: "coinbase_at".to_string(),
+                asset: "USD".to_string(),
+                total: 250.0,
+                available: 250.0,
+            });
+        let response = process_request(
+            &paper_account(PaperAccountAction::Fund, &[("BTC", 0.5)]),
+            &context,
+        )
+        .await;
+        let result: PortfolioPaperAccountResultPayload =
+            serde_json::from_value(response.payload["result"].clone()).expect("result decodes");
+        assert!(result
+            .balances
+            .iter()
+            .any(|balance| balance.asset == "BTC" && balance.total == 0.5));
+        {
+            let state = context.state.lock().await;
+            let venues: Vec<(&str, &str)> = state
+                .portfolio_balances
+                .iter()
+                .map(|balance| (balance.venue.as_str(), balance.asset.as_str()))
+                .collect();
+            assert!(venues.contains(&("coinbase_at", "USD")), "{:?}", venues);
+            assert!(venues.contains(&("paper", "BTC")), "{:?}", venues);
+        }
+
+        let response =
+            process_request(&paper_account(PaperAccountAction::Fund, &[]), &context).await;
+        assert_eq!(response.payload["ok"], false);
+        assert_eq!(response.payload["code"], "invalid_payload");
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn canceling_an_order_releases_its_tracked_notional() {
+        let mut state = initial_engine_state(
+            std::env::temp_dir().to_string_lossy().to_string(),
+            unique_state_path("cancel-release"),
+            0,
+            EngineMode::Paper,
+            test_pacer(),
+            MarketDataCache::new(DEFAULT_QUOTE_STALE_AFTER_MS),
+            false,
+            system_clock(),
+        );
+        state.journaling = false;
+        let request = coinbase_spot_order();
+        let ack = OrderAck {
+            venue_order_id: "venue-order-1".to_string(),
+            client_order_id: request.client_order_id.clone(),
+            accepted: true,
+            status: OrderStatus::New,
+            filled_qty: 0.0,
+            avg_fill_price: None,
+            simulated: false,
+            reason: None,
+            ts_ms: 0,
+        };
+        state.orders.insert(
+            ack.venue_order_id.clone(),
+            synthetic_order_from_ack(&request, &ack),
+        );
+        state
+            .order_notional_cents
+            .insert(ack.venue_order_id.clone(), 12_000);
+        state.risk_snapshot.total_notional_cents = 15_000;
+        state
+            .risk_snapshot
+            .venue_notional
+            .insert("coinbase_at".to_string(), 12_000);
+
+        record_order_canceled(&mut state, "venue-order-1");
+
+        assert_eq!(state.orders["venue-order-1"].status, OrderStatus::Canceled);
+        assert_eq!(state.risk_snapshot.total_notional_cents, 3_000);
+        assert_eq!(state.risk_snapshot.venue_notional["coinbase_at"], 0);
+        assert!(state.order_notional_cents.is_empty());
+        assert_eq!(state.execution_stats.canceled, 1);
     }
 }
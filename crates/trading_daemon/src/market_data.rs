@@ -0,0 +1,410 @@
+//! Market data cache: per-instrument top of book and last trade fed by venue
+//! streams, with staleness tracking for clients, risk and strategies. Streamed
+//! updates also drive the regime engine that classifies each instrument.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use exchange_core::{MarketDataUpdate, QuoteSnapshot, TradePrint};
+use strategy_core::{RegimeConfig, RegimeContext, RegimeEngine, RegimeUpdate};
+use trading_protocol::{MarketDataStatusPayload, MarketQuotePayload};
+
+pub const DEFAULT_QUOTE_STALE_AFTER_MS: i64 = 5_000;
+
+#[derive(Debug, Clone)]
+struct CachedInstrument {
+    quote: QuoteSnapshot,
+    last_trade: Option<TradePrint>,
+    received_at_ms: i64,
+    /// `stream` for subscription updates, `snapshot` for polled quotes.
+    source: &'static str,
+}
+
+#[derive(Debug)]
+pub struct MarketDataCache {
+    stale_after_ms: i64,
+    instruments: HashMap<(String, String), CachedInstrument>,
+    subscriptions: HashMap<String, BTreeSet<String>>,
+    disconnected_venues: HashSet<String>,
+    regimes: RegimeEngine,
+}
+
+impl MarketDataCache {
+    pub fn new(stale_after_ms: i64) -> Self {
+        Self {
+            stale_after_ms: stale_after_ms.max(0),
+            instruments: HashMap::new(),
+            subscriptions: HashMap::new(),
+            disconnected_venues: HashSet::new(),
+            regimes: RegimeEngine::default(),
+        }
+    }
+
+    pub fn with_regime_config(mut self, config: RegimeConfig) -> Self {
+        self.regimes = RegimeEngine::new(config);
+        self
+    }
+
+    /// Applies a stream update. Quotes carry the venue's whole top of book, so a
+    /// side missing from the update is empty and is cleared; a missing last
+    /// price keeps the previous one (level2 updates carry none). Returns the
+    /// instrument's refreshed regime context.
+    pub fn apply(&mut self, update: MarketDataUpdate, now_ms: i64) -> Option<RegimeUpdate> {
+        match update {
+            MarketDataUpdate::Quote(quote) => {
+                self.disconnected_venues.remove(&quote.venue);
+                let key = (quote.venue.clone(), quote.venue_symbol.clone());
+                self.merge_quote(quote, now_ms, "stream");
+                let merged = &self.instruments.get(&key)?.quote;
+                self.regimes.observe_quote(merged)
+            }
+            MarketDataUpdate::Trade(trade) => {
+                self.disconnected_venues.remove(&trade.venue);
+                let key = (trade.venue.clone(), trade.venue_symbol.clone());
+                let entry = self
+                    .instruments
+                    .entry(key)
+                    .or_insert_with(|| CachedInstrument {
+                        quote: QuoteSnapshot {
+                            venue: trade.venue.clone(),
+                            venue_symbol: trade.venue_symbol.clone(),
+                            bid: None,
+                            ask: None,
+                            last: None,
+                            mark: None,
+                            ts_ms: trade.ts_ms,
+                        },
+                        last_trade: None,
+                        received_at_ms: now_ms,
+                        source: "stream",
+                    });
+                entry.quote.last = Some(trade.price);
+                entry.received_at_ms = now_ms;
+                entry.source = "stream";
+                let update = self.regimes.observe_trade(&trade);
+                entry.last_trade = Some(trade);
+                Some(update)
+            }
+            MarketDataUpdate::Disconnected { venue, .. } => {
+                self.disconnected_venues.insert(venue);
+                None
+            }
+        }
+    }
+
+    /// Latest regime contexts, optionally narrowed to a venue and symbol.
+    pub fn regimes(&self, venue: Option<&str>, venue_symbol: Option<&str>) -> Vec<RegimeContext> {
+        self.regimes
+            .contexts()
+            .into_iter()
+            .filter(|context| venue.is_none_or(|venue| context.venue == venue))
+            .filter(|context| venue_symbol.is_none_or(|symbol| context.symbol == symbol))
+            .collect()
+    }
+
+    /// Records a polled quote, e.g. a REST snapshot fetched on a cache miss.
+    pub fn record_snapshot(&mut self, quote: QuoteSnapshot, now_ms: i64) {
+        self.merge_quote(quote, now_ms, "snapshot");
+    }
+
+    fn merge_quote(&mut self, quote: QuoteSnapshot, now_ms: i64, source: &'static str) {
+        let key = (quote.venue.clone(), quote.venue_symbol.clone());
+        match self.instruments.get_mut(&key) {
+            Some(entry) => {
+                let merged = &mut entry.quote;
+                merged.bid = quote.bid;
+                merged.ask = quote.ask;
+                merged.last = quote.last.or(merged.last);
+                merged.mark = quote.mark;
+                merged.ts_ms = quote.ts_ms;
+                entry.received_at_ms = now_ms;
+                entry.source = source;
+            }
+            None => {
+                self.instruments.insert(
+                    key,
+                    CachedInstrument {
+                        quote,
+                        last_trade: None,
+                        received_at_ms: now_ms,
+                        source,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Streamed entries go stale as soon as their venue's stream drops; polled
+    /// snapshots only age out.
+    fn is_stale(&self, entry: &CachedInstrument, now_ms: i64) -> bool {
+        (entry.source == "stream" && self.disconnected_venues.contains(&entry.quote.venue))
+            || now_ms - entry.received_at_ms > self.stale_after_ms
+    }
+
+    pub fn quote(
+        &self,
+        venue: &str,
+        venue_symbol: &str,
+        now_ms: i64,
+    ) -> Option<MarketQuotePayload> {
+        let entry = self
+            .instruments
+            .get(&(venue.to_string(), venue_symbol.to_string()))?;
+        Some(MarketQuotePayload {
+            venue: entry.quote.venue.clone(),
+            venue_symbol: entry.quote.venue_symbol.clone(),
+            bid: entry.quote.bid,
+            ask: entry.quote.ask,
+            last: entry.quote.last,
+            mark: entry.quote.mark,
+            last_trade: entry.last_trade.clone(),
+            ts_ms: entry.quote.ts_ms,
+            age_ms: (now_ms - entry.received_at_ms).max(0),
+            stale: self.is_stale(entry, now_ms),
+            source: entry.source.to_string(),
+        })
+    }
+
+    /// Cached quote for internal consumers; `None` when missing or stale.
+    pub fn fresh_quote(
+        &self,
+        venue: &str,
+        venue_symbol: &str,
+        now_ms: i64,
+    ) -> Option<QuoteSnapshot> {
+        self.instruments
+            .get(&(venue.to_string(), venue_symbol.to_string()))
+            .filter(|entry| !self.is_stale(entry, now_ms))
+            .map(|entry| entry.quote.clone())
+    }
+
+    /// Splits `venue_symbols` into those not yet subscribed on `venue` (which are
+    /// recorded as subscribed) and those already streaming.
+    pub fn claim_subscriptions(
+        &mut self,
+        venue: &str,
+        venue_symbols: &[String],
+    ) -> (Vec<String>, Vec<String>) {
+        let subscribed = self.subscriptions.entry(venue.to_string()).or_default();
+        let mut claimed = Vec::new();
+        let mut existing = Vec::new();
+        for symbol in venue_symbols {
+            if subscribed.insert(symbol.clone()) {
+                claimed.push(symbol.clone());
+            } else if !existing.contains(symbol) {
+                existing.push(symbol.clone());
+            }
+        }
+        (claimed, existing)
+    }
+
+    /// Reverts a claim whose stream failed to connect.
+    pub fn release_subscriptions(&mut self, venue: &str, venue_symbols: &[String]) {
+        if let Some(subscribed) = self.subscriptions.get_mut(venue) {
+            for symbol in venue_symbols {
+                subscribed.remove(symbol);
+            }
+        }
+    }
+
+    pub fn status(&self, now_ms: i64) -> MarketDataStatusPayload {
+        let mut disconnected_venues: Vec<String> =
+            self.disconnected_venues.iter().cloned().collect();
+        disconnected_venues.sort();
+
+        MarketDataStatusPayload {
+            stale_after_ms: self.stale_after_ms,
+            instruments: self.instruments.len(),
+            stale_instruments: self
+                .instruments
+                .values()
+                .filter(|entry| self.is_stale(entry, now_ms))
+                .count(),
+            subscriptions: self
+                .subscriptions
+                .iter()
+                .filter(|(_, symbols)| !symbols.is_empty())
+                .map(|(venue, symbols)| (venue.clone(), symbols.iter().cloned().collect()))
+                .collect::<BTreeMap<_, _>>(),
+            disconnected_venues,
+        }
+    }
+}
+
+/// Parses `venue:symbol` pairs separated by commas, for example
+/// `coinbase_at:BTC-USD,coinbase_at:ETH-USD`, grouped by venue.
+pub fn parse_market_data_symbols(raw: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
+    let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (venue, symbol) = item
+            .split_once(':')
+            .map(|(venue, symbol)| (venue.trim(), symbol.trim()))
+            .filter(|(venue, symbol)| !venue.is_empty() && !symbol.is_empty())
+            .ok_or_else(|| format!("expected venue:symbol, got '{}'", item))?;
+        grouped
+            .entry(venue.to_string())
+            .or_default()
+            .push(symbol.to_string());
+    }
+    Ok(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use exchange_core::OrderSide;
+    use strategy_core::MarketRegime;
+
+    use super::*;
+
+    fn quote(bid: Option<f64>, ask: Option<f64>, last: Option<f64>, ts_ms: i64) -> QuoteSnapshot {
+        QuoteSnapshot {
+            venue: "coinbase_at".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            bid,
+            ask,
+            last,
+            mark: None,
+            ts_ms,
+        }
+    }
+
+    #[test]
+    fn updates_replace_the_book_and_keep_the_last_price() {
+        let mut cache = MarketDataCache::new(1_000);
+        cache.apply(
+            MarketDataUpdate::Quote(quote(Some(100.0), Some(101.0), Some(100.4), 10)),
+            10,
+        );
+        // The bids were pulled: the stale 100.0 bid must not survive.
+        cache.apply(
+            MarketDataUpdate::Quote(quote(None, Some(100.5), None, 20)),
+            20,
+        );
+
+        let cached = cache.quote("coinbase_at", "BTC-USD", 25).expect("quote");
+        assert_eq!(cached.bid, None);
+        assert_eq!(cached.ask, Some(100.5));
+        assert_eq!(cached.last, Some(100.4));
+        assert_eq!(cached.ts_ms, 20);
+        assert_eq!(cached.age_ms, 5);
+        assert!(!cached.stale);
+    }
+
+    #[test]
+    fn trades_update_last_and_quotes_go_stale() {
+        let mut cache = MarketDataCache::new(1_000);
+        cache.apply(
+            MarketDataUpdate::Trade(TradePrint {
+                venue: "coinbase_at".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                price: 99.5,
+                size: Some(0.1),
+                side: Some(OrderSide::Buy),
+                ts_ms: 0,
+            }),
+            0,
+        );
+
+        let cached = cache.quote("coinbase_at", "BTC-USD", 500).expect("quote");
+        assert_eq!(cached.last, Some(99.5));
+        assert_eq!(cached.last_trade.map(|t| t.price), Some(99.5));
+        assert!(cache.fresh_quote("coinbase_at", "BTC-USD", 500).is_some());
+
+        assert!(cache.quote("coinbase_at", "BTC-USD", 1_001).unwrap().stale);
+        assert!(cache.fresh_quote("coinbase_at", "BTC-USD", 1_001).is_none());
+        assert_eq!(cache.status(1_001).stale_instruments, 1);
+    }
+
+    #[test]
+    fn disconnect_marks_venue_stale_until_next_update() {
+        let mut cache = MarketDataCache::new(1_000);
+        cache.apply(
+            MarketDataUpdate::Quote(quote(Some(100.0), Some(101.0), None, 0)),
+            0,
+        );
+        cache.apply(
+            MarketDataUpdate::Disconnected {
+                venue: "coinbase_at".to_string(),
+                reason: "closed".to_string(),
+            },
+            1,
+        );
+        assert!(cache.fresh_quote("coinbase_at", "BTC-USD", 2).is_none());
+        assert_eq!(cache.status(2).disconnected_venues, vec!["coinbase_at"]);
+
+        cache.apply(
+            MarketDataUpdate::Quote(quote(Some(100.0), Some(101.0), None, 3)),
+            3,
+        );
+        assert!(cache.fresh_quote("coinbase_at", "BTC-USD", 4).is_some());
+    }
+
+    #[test]
+    fn merged_stream_quotes_drive_regime_classification() {
+        let mut cache = MarketDataCache::new(1_000).with_regime_config(RegimeConfig {
+            min_samples: 2,
+            ..RegimeConfig::default()
+        });
+        let first = cache
+            .apply(
+                MarketDataUpdate::Quote(quote(Some(100.0), Some(100.2), None, 0)),
+                0,
+            )
+            .expect("context");
+        assert_eq!(first.context.regime, MarketRegime::Unknown);
+
+        let second = cache
+            .apply(
+                MarketDataUpdate::Quote(quote(Some(100.0), Some(102.0), None, 1_000)),
+                1_000,
+            )
+            .expect("context");
+        assert_eq!(second.context.regime, MarketRegime::Trending);
+        assert_eq!(second.previous, Some(MarketRegime::Unknown));
+        assert!((second.context.spread_bps - 198.0).abs() < 1.0);
+
+        assert_eq!(cache.regimes(Some("coinbase_at"), Some("BTC-USD")).len(), 1);
+        assert!(cache.regimes(Some("kalshi"), None).is_empty());
+        assert!(cache
+            .apply(
+                MarketDataUpdate::Disconnected {
+                    venue: "coinbase_at".to_string(),
+                    reason: "eof".to_string(),
+                },
+                2_000,
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn subscriptions_are_claimed_once() {
+        let mut cache = MarketDataCache::new(1_000);
+        let symbols = vec!["BTC-USD".to_string(), "ETH-USD".to_string()];
+        let (claimed, existing) = cache.claim_subscriptions("coinbase_at", &symbols);
+        assert_eq!(claimed, symbols);
+        assert!(existing.is_empty());
+
+        let (claimed, existing) = cache.claim_subscriptions("coinbase_at", &symbols[..1]);
+        assert!(claimed.is_empty());
+        assert_eq!(existing, vec!["BTC-USD"]);
+
+        cache.release_subscriptions("coinbase_at", &symbols[1..]);
+        assert_eq!(
+            cache.status(0).subscriptions["coinbase_at"],
+            vec!["BTC-USD"]
+        );
+    }
+
+    #[test]
+    fn parses_market_data_symbols() {
+        let parsed =
+            parse_market_data_symbols("coinbase_at:BTC-USD, coinbase_at:ETH-USD,paper:SOL-USD")
+                .expect("valid symbols");
+        assert_eq!(parsed["coinbase_at"], vec!["BTC-USD", "ETH-USD"]);
+        assert_eq!(parsed["paper"], vec!["SOL-USD"]);
+        assert!(parse_market_data_symbols("BTC-USD").is_err());
+    }
+}
@@ -0,0 +1,399 @@
+//! Per-venue order pacing: token buckets sized to each venue's published order
+//! rate limits, plus an optional wait queue for non-urgent orders.
+
+use std::collections::HashMap;
+
+use exchange_core::TimeInForce;
+use trading_protocol::{OrderPriority, PacingStatusPayload, VenuePacingPayload};
+
+pub const DEFAULT_PACING_MAX_WAIT_MS: u64 = 5_000;
+pub const DEFAULT_PACING_MAX_QUEUE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VenueRateLimit {
+    pub burst: u32,
+    pub refill_per_sec: f64,
+}
+
+impl VenueRateLimit {
+    pub const fn new(burst: u32, refill_per_sec: f64) -> Self {
+        Self {
+            burst,
+            refill_per_sec,
+        }
+    }
+}
+
+/// Fallback for venues without a published limit (paper and simulated venues).
+pub const DEFAULT_VENUE_RATE_LIMIT: VenueRateLimit = VenueRateLimit::new(20, 20.0);
+
+/// Published private order-entry limits per venue.
+///
+/// - `coinbase_at`: Advanced Trade private REST endpoints allow 30 requests/second.
+/// - `kalshi`: basic API tier allows 10 write requests/second.
+pub fn default_venue_rate_limits() -> HashMap<String, VenueRateLimit> {
+    [
+        ("coinbase_at", VenueRateLimit::new(30, 30.0)),
+        ("kalshi", VenueRateLimit::new(10, 10.0)),
+    ]
+    .into_iter()
+    .map(|(venue, limit)| (venue.to_string(), limit))
+    .collect()
+}
+
+/// Parses `venue=burst/refill_per_sec` pairs separated by commas, for example
+/// `coinbase_at=30/30,kalshi=10/10`.
+pub fn parse_venue_rate_limits(raw: &str) -> Result<HashMap<String, VenueRateLimit>, String> {
+    let mut limits = HashMap::new();
+    for item in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (venue, spec) = item
+            .split_once('=')
+            .ok_or_else(|| format!("expected venue=burst/rate, got '{}'", item))?;
+        let (burst, rate) = spec
+            .split_once('/')
+            .ok_or_else(|| format!("expected burst/rate for '{}', got '{}'", venue, spec))?;
+        let burst = burst
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid burst for '{}': {}", venue, e))?;
+        let refill_per_sec = rate
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid rate for '{}': {}", venue, e))?;
+        if burst == 0 || !refill_per_sec.is_finite() || refill_per_sec <= 0.0 {
+            return Err(format!("rate limit for '{}' must be positive", venue));
+        }
+        limits.insert(
+            venue.trim().to_string(),
+            VenueRateLimit::new(burst, refill_per_sec),
+        );
+    }
+    Ok(limits)
+}
+
+/// Urgent orders are never queued: an explicit priority wins, otherwise
+/// immediate-or-cancel style time-in-force marks the order as urgent.
+pub fn order_is_urgent(priority: Option<OrderPriority>, tif: Option<&TimeInForce>) -> bool {
+    match priority {
+        Some(OrderPriority::Urgent) => true,
+        Some(OrderPriority::Normal) => false,
+        None => matches!(tif, Some(TimeInForce::Ioc) | Some(TimeInForce::Fok)),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    limit: VenueRateLimit,
+    tokens: f64,
+    last_refill_ms: i64,
+}
+
+impl TokenBucket {
+    pub fn new(limit: VenueRateLimit, now_ms: i64) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            last_refill_ms: now_ms,
+        }
+    }
+
+    fn refill(&mut self, now_ms: i64) {
+        let elapsed_ms = now_ms.saturating_sub(self.last_refill_ms);
+        if elapsed_ms <= 0 {
+            return;
+        }
+        let refilled = self.tokens + (elapsed_ms as f64 / 1_000.0) * self.limit.refill_per_sec;
+        self.tokens = refilled.min(f64::from(self.limit.burst));
+        self.last_refill_ms = now_ms;
+    }
+
+    /// Tokens that would be available at `now_ms`, without mutating the bucket.
+    pub fn available(&self, now_ms: i64) -> f64 {
+        let elapsed_ms = now_ms.saturating_sub(self.last_refill_ms).max(0);
+        let refilled = self.tokens + (elapsed_ms as f64 / 1_000.0) * self.limit.refill_per_sec;
+        refilled.min(f64::from(self.limit.burst))
+    }
+
+    /// Milliseconds until one token is available (0 when one is available now).
+    pub fn wait_ms(&mut self, now_ms: i64) -> i64 {
+        self.refill(now_ms);
+        if self.tokens >= 1.0 {
+            return 0;
+        }
+        let missing = 1.0 - self.tokens;
+        ((missing / self.limit.refill_per_sec) * 1_000.0)
+            .ceil()
+            .max(1.0) as i64
+    }
+
+    pub fn try_take(&mut self, now_ms: i64) -> bool {
+        self.refill(now_ms);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacingDecision {
+    Admit,
+    Wait { wait_ms: i64 },
+    Reject { reason: String },
+}
+
+#[derive(Debug)]
+pub struct OrderPacer {
+    limits: HashMap<String, VenueRateLimit>,
+    buckets: HashMap<String, TokenBucket>,
+    queue_depth: HashMap<String, usize>,
+    queue_enabled: bool,
+    max_wait_ms: u64,
+    max_queue_depth: usize,
+}
+
+impl OrderPacer {
+    pub fn new(
+        limits: HashMap<String, VenueRateLimit>,
+        queue_enabled: bool,
+        max_wait_ms: u64,
+        max_queue_depth: usize,
+    ) -> Self {
+        Self {
+            limits,
+            buckets: HashMap::new(),
+            queue_depth: HashMap::new(),
+            queue_enabled,
+            max_wait_ms,
+            max_queue_depth,
+        }
+    }
+
+    pub fn max_wait_ms(&self) -> u64 {
+        self.max_wait_ms
+    }
+
+    fn limit_for(&self, venue: &str) -> VenueRateLimit {
+        self.limits
+            .get(venue)
+            .copied()
+            .unwrap_or(DEFAULT_VENUE_RATE_LIMIT)
+    }
+
+    fn bucket(&mut self, venue: &str, now_ms: i64) -> &mut TokenBucket {
+        let limit = self.limit_for(venue);
+        self.buckets
+            .entry(venue.to_string())
+            .or_insert_with(|| TokenBucket::new(limit, now_ms))
+    }
+
+    /// Decides whether an order may go out now. `external_wait_ms` is extra
+    /// capacity wait imposed outside the venue bucket (the global per-minute
+    /// window); a token is only consumed when the order is admitted.
+    pub fn poll(
+        &mut self,
+        venue: &str,
+        urgent: bool,
+        external_wait_ms: i64,
+        now_ms: i64,
+        deadline_ms: i64,
+    ) -> PacingDecision {
+        let venue_wait_ms = self.bucket(venue, now_ms).wait_ms(now_ms);
+        let wait_ms = venue_wait_ms.max(external_wait_ms);
+        if wait_ms <= 0 {
+            self.bucket(venue, now_ms).try_take(now_ms);
+            return PacingDecision::Admit;
+        }
+
+        if urgent || !self.queue_enabled {
+            // Global window saturation stays with the hard safety cage.
+            if venue_wait_ms <= 0 {
+                self.bucket(venue, now_ms).try_take(now_ms);
+                return PacingDecision::Admit;
+            }
+            return PacingDecision::Reject {
+                reason: format!("venue rate limit reached for {}", venue),
+            };
+        }
+
+        if now_ms.saturating_add(wait_ms) > deadline_ms {
+            return PacingDecision::Reject {
+                reason: format!(
+                    "pacing deadline exceeded for {}: capacity in {}ms",
+                    venue, wait_ms
+                ),
+            };
+        }
+
+        PacingDecision::Wait { wait_ms }
+    }
+
+    /// Registers a waiting order; fails when the venue queue is full.
+    pub fn enqueue(&mut self, venue: &str) -> Result<(), String> {
+        let depth = self.queue_depth.entry(venue.to_string()).or_insert(0);
+        if *depth >= self.max_queue_depth {
+            return Err(format!(
+                "pacing queue full for {}: {} >= {}",
+                venue, depth, self.max_queue_depth
+            ));
+        }
+        *depth += 1;
+        Ok(())
+    }
+
+    pub fn dequeue(&mut self, venue: &str) {
+        if let Some(depth) = self.queue_depth.get_mut(venue) {
+            *depth = depth.saturating_sub(1);
+        }
+    }
+
+    pub fn status(&self, now_ms: i64) -> PacingStatusPayload {
+        let mut venue_ids: Vec<String> = self
+            .limits
+            .keys()
+            .chain(self.buckets.keys())
+            .cloned()
+            .collect();
+        venue_ids.sort();
+        venue_ids.dedup();
+
+        let venues: Vec<VenuePacingPayload> = venue_ids
+            .into_iter()
+            .map(|venue| {
+                let limit = self.limit_for(&venue);
+                let tokens_available = self
+                    .buckets
+                    .get(&venue)
+                    .map(|bucket| bucket.available(now_ms))
+                    .unwrap_or_else(|| f64::from(limit.burst));
+                VenuePacingPayload {
+                    queue_depth: self.queue_depth.get(&venue).copied().unwrap_or(0),
+                    venue,
+                    burst: limit.burst,
+                    refill_per_sec: limit.refill_per_sec,
+                    tokens_available,
+                }
+            })
+            .collect();
+
+        PacingStatusPayload {
+            queue_enabled: self.queue_enabled,
+            max_wait_ms: self.max_wait_ms,
+            max_queue_depth: self.max_queue_depth,
+            queued_total: venues.iter().map(|v| v.queue_depth).sum(),
+            venues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pacer(queue_enabled: bool) -> OrderPacer {
+        OrderPacer::new(
+            [("kalshi".to_string(), VenueRateLimit::new(2, 1.0))]
+                .into_iter()
+                .collect(),
+            queue_enabled,
+            DEFAULT_PACING_MAX_WAIT_MS,
+            1,
+        )
+    }
+
+    #[test]
+    fn bucket_refills_up_to_burst() {
+        let mut bucket = TokenBucket::new(VenueRateLimit::new(2, 2.0), 0);
+        assert!(bucket.try_take(0));
+        assert!(bucket.try_take(0));
+        assert!(!bucket.try_take(0));
+        assert_eq!(bucket.wait_ms(0), 500);
+        assert!(bucket.try_take(500));
+        assert!((bucket.available(10_000) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn non_urgent_order_waits_when_queue_enabled() {
+        let mut pacer = pacer(true);
+        assert_eq!(
+            pacer.poll("kalshi", false, 0, 0, 5_000),
+            PacingDecision::Admit
+        );
+        assert_eq!(
+            pacer.poll("kalshi", false, 0, 0, 5_000),
+            PacingDecision::Admit
+        );
+        assert_eq!(
+            pacer.poll("kalshi", false, 0, 0, 5_000),
+            PacingDecision::Wait { wait_ms: 1_000 }
+        );
+        assert_eq!(
+            pacer.poll("kalshi", false, 0, 1_000, 5_000),
+            PacingDecision::Admit
+        );
+    }
+
+    #[test]
+    fn urgent_order_is_rejected_instead_of_queued() {
+        let mut pacer = pacer(true);
+        pacer.poll("kalshi", true, 0, 0, 0);
+        pacer.poll("kalshi", true, 0, 0, 0);
+        assert!(matches!(
+            pacer.poll("kalshi", true, 0, 0, 5_000),
+            PacingDecision::Reject { .. }
+        ));
+    }
+
+    #[test]
+    fn wait_past_deadline_is_rejected() {
+        let mut pacer = pacer(true);
+        assert!(matches!(
+            pacer.poll("kalshi", false, 10_000, 0, 5_000),
+            PacingDecision::Reject { .. }
+        ));
+    }
+
+    #[test]
+    fn queue_depth_is_bounded_and_reported() {
+        let mut pacer = pacer(true);
+        pacer.enqueue("kalshi").expect("first waiter fits");
+        assert!(pacer.enqueue("kalshi").is_err());
+
+        let status = pacer.status(0);
+        assert_eq!(status.queued_total, 1);
+        let kalshi = status
+            .venues
+            .iter()
+            .find(|v| v.venue == "kalshi")
+            .expect("kalshi listed");
+        assert_eq!(kalshi.queue_depth, 1);
+
+        pacer.dequeue("kalshi");
+        assert_eq!(pacer.status(0).queued_total, 0);
+    }
+
+    #[test]
+    fn urgency_follows_priority_then_tif() {
+        assert!(order_is_urgent(None, Some(&TimeInForce::Ioc)));
+        assert!(!order_is_urgent(None, Some(&TimeInForce::Gtc)));
+        assert!(!order_is_urgent(
+            Some(OrderPriority::Normal),
+            Some(&TimeInForce::Fok)
+        ));
+        assert!(order_is_urgent(Some(OrderPriority::Urgent), None));
+    }
+
+    #[test]
+    fn parses_rate_limit_overrides() {
+        let limits = parse_venue_rate_limits("coinbase_at=15/10, kalshi=5/5").expect("valid");
+        assert_eq!(limits["coinbase_at"], VenueRateLimit::new(15, 10.0));
+        assert!(parse_venue_rate_limits("kalshi=0/5").is_err());
+        assert!(parse_venue_rate_limits("kalshi").is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Realized/unrealized PnL attribution from the daemon's fill history, using
+//! average-cost lots per strategy, venue and instrument.
+
+use std::collections::BTreeMap;
+
+use exchange_core::{FillReport, OrderSide};
+use trading_protocol::{
+    PnlBucketPayload, PnlPositionPayload, PortfolioPnlPayload, PortfolioPnlResultPayload,
+};
+
+const QTY_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Default)]
+struct Lot {
+    qty: f64,
+    avg_price: f64,
+    multiplier: f64,
+    last_fill_price: f64,
+}
+
+impl Lot {
+    /// Applies a signed fill and returns the PnL realized by any closed quantity.
+    fn apply(&mut self, signed_qty: f64, price: f64) -> f64 {
+        let mut realized = 0.0;
+        if self.qty.abs() < QTY_EPSILON || self.qty.signum() == signed_qty.signum() {
+            let total = self.qty.abs() + signed_qty.abs();
+            self.avg_price = (self.qty.abs() * self.avg_price + signed_qty.abs() * price) / total;
+            self.qty += signed_qty;
+        } else {
+            let closing = self.qty.abs().min(signed_qty.abs());
+            realized = closing * (price - self.avg_price) * self.qty.signum() * self.multiplier;
+            self.qty += signed_qty;
+            if self.qty.abs() < QTY_EPSILON {
+                self.qty = 0.0;
+                self.avg_price = 0.0;
+            } else if self.qty.signum() == signed_qty.signum() {
+                self.avg_price = price;
+            }
+        }
+        self.last_fill_price = price;
+        realized
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets(BTreeMap<String, PnlBucketPayload>);
+
+impl Buckets {
+    fn entry(&mut self, key: &str) -> &mut PnlBucketPayload {
+        self.0
+            .entry(key.to_string())
+            .or_insert_with(|| PnlBucketPayload {
+                key: key.to_string(),
+                ..PnlBucketPayload::default()
+            })
+    }
+
+    fn into_vec(self) -> Vec<PnlBucketPayload> {
+        self.0.into_values().map(finish).collect()
+    }
+}
+
+fn finish(mut bucket: PnlBucketPayload) -> PnlBucketPayload {
+    bucket.net_pnl = bucket.realized_pnl + bucket.unrealized_pnl - bucket.fees;
+    bucket
+}
+
+fn utc_day(ts_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts_ms)
+        .map(|ts| ts.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Computes PnL for `fills` matching `filter`. `mark_for(venue, venue_symbol)`
+/// returns the current mark and its source; open lots without one are marked
+/// at their last fill price.
+pub fn compute_pnl<F>(
+    fills: &[FillReport],
+    filter: &PortfolioPnlPayload,
+    mark_for: F,
+    as_of_ms: i64,
+) -> PortfolioPnlResultPayload
+where
+    F: Fn(&str, &str) -> Option<(f64, &'static str)>,
+{
+    let mut ordered: Vec<&FillReport> = fills
+        .iter()
+        .filter(|fill| {
+            filter
+                .strategy_id
+                .as_ref()
+                .is_none_or(|id| &fill.strategy_id == id)
+                && filter.venue.as_ref().is_none_or(|v| &fill.venue == v)
+        })
+        .collect();
+    ordered.sort_by_key(|fill| fill.ts_ms);
+
+    let since = filter.since_ts_ms.unwrap_or(i64::MIN);
+    let mut lots: BTreeMap<(String, String, String), Lot> = BTreeMap::new();
+    let mut totals = PnlBucketPayload {
+        key: "total".to_string(),
+        ..PnlBucketPayload::default()
+    };
+    let mut by_strategy = Buckets::default();
+    let mut by_venue = Buckets::default();
+    let mut by_instrument = Buckets::default();
+    let mut by_day = Buckets::default();
+
+    for fill in ordered {
+        let multiplier = fill
+            .instrument
+            .contract_multiplier
+            .filter(|m| m.is_finite() && *m > 0.0)
+            .unwrap_or(1.0);
+        let lot = lots
+            .entry((
+                fill.strategy_id.clone(),
+                fill.venue.clone(),
+                fill.instrument.venue_symbol.clone(),
+            ))
+            .or_insert_with(|| Lot {
+                multiplier,
+                ..Lot::default()
+            });
+        let signed_qty = match fill.side {
+            OrderSide::Buy => fill.qty,
+            OrderSide::Sell => -fill.qty,
+        };
+        let realized = lot.apply(signed_qty, fill.price);
+        if fill.ts_ms < since {
+            continue;
+        }
+
+        let turnover = fill.qty * fill.price * multiplier;
+        let instrument_key = format!("{}:{}", fill.venue, fill.instrument.venue_symbol);
+        let day_key = utc_day(fill.ts_ms);
+        for bucket in [
+            &mut totals,
+            by_strategy.entry(&fill.strategy_id),
+            by_venue.entry(&fill.venue),
+            by_instrument.entry(&instrument_key),
+            by_day.entry(&day_key),
+        ] {
+            bucket.realized_pnl += realized;
+            bucket.fees += fill.fee;
+            bucket.turnover += turnover;
+            bucket.fills += 1;
+        }
+    }
+
+    let as_of_day = utc_day(as_of_ms);
+    let mut positions = Vec::new();
+    for ((strategy_id, venue, venue_symbol), lot) in lots {
+        if lot.qty.abs() < QTY_EPSILON {
+            continue;
+        }
+        let (mark_price, mark_source) =
+            mark_for(&venue, &venue_symbol).unwrap_or((lot.last_fill_price, "last_fill"));
+        let unrealized = (mark_price - lot.avg_price) * lot.qty * lot.multiplier;
+        let instrument_key = format!("{}:{}", venue, venue_symbol);
+        for bucket in [
+            &mut totals,
+            by_strategy.entry(&strategy_id),
+            by_venue.entry(&venue),
+            by_instrument.entry(&instrument_key),
+            by_day.entry(&as_of_day),
+        ] {
+            bucket.unrealized_pnl += unrealized;
+        }
+        positions.push(PnlPositionPayload {
+            strategy_id,
+            venue,
+            venue_symbol,
+            qty: lot.qty,
+            avg_price: lot.avg_price,
+            mark_price: Some(mark_price),
+            mark_source: Some(mark_source.to_string()),
+            unrealized_pnl: unrealized,
+        });
+    }
+
+    PortfolioPnlResultPayload {
+        as_of_ms,
+        totals: finish(totals),
+        by_strategy: by_strategy.into_vec(),
+        by_venue: by_venue.into_vec(),
+        by_instrument: by_instrument.into_vec(),
+        by_day: by_day.into_vec(),
+        positions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exchange_core::{AssetClass, InstrumentRef, InstrumentType};
+
+    use super::*;
+
+    const DAY_MS: i64 = 86_400_000;
+
+    fn fill(strategy_id: &str, side: OrderSide, qty: f64, price: f64, ts_ms: i64) -> FillReport {
+        FillReport {
+            venue: "paper".to_string(),
+            venue_fill_id: format!("fill-{ts_ms}"),
+            venue_order_id: format!("order-{ts_ms}"),
+            client_order_id: format!("client-{ts_ms}"),
+            strategy_id: strategy_id.to_string(),
+            instrument: InstrumentRef {
+                venue: "paper".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                asset_class: AssetClass::Crypto,
+                instrument_type: InstrumentType::Spot,
+                base: Some("BTC".to_string()),
+                quote: Some("USD".to_string()),
+                expiry_ts_ms: None,
+                strike: None,
+                option_right: None,
+                contract_multiplier: Some(1.0),
+            },
+            side,
+            qty,
+            price,
+            fee: 1.0,
+            fee_asset: Some("USD".to_string()),
+            liquidity: None,
+            simulated: true,
+            ts_ms,
+        }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn realizes_against_average_cost_and_marks_remainder() {
+        let fills = vec![
+            fill("s1", OrderSide::Buy, 1.0, 100.0, 0),
+            fill("s1", OrderSide::Buy, 1.0, 110.0, 1),
+            fill("s1", OrderSide::Sell, 1.5, 120.0, DAY_MS),
+        ];
+
+        let pnl = compute_pnl(
+            &fills,
+            &PortfolioPnlPayload::default(),
+            |_, _| Some((130.0, "quote")),
+            DAY_MS,
+        );
+
+        assert!(approx(pnl.totals.realized_pnl, 22.5));
+        assert!(approx(pnl.totals.unrealized_pnl, 12.5));
+        assert!(approx(pnl.totals.fees, 3.0));
+        assert!(approx(pnl.totals.net_pnl, 32.0));
+        assert!(approx(pnl.totals.turnover, 390.0));
+        assert_eq!(pnl.totals.fills, 3);
+
+        assert_eq!(pnl.by_day.len(), 2);
+        assert_eq!(pnl.by_day[0].key, "1970-01-01");
+        assert!(approx(pnl.by_day[0].realized_pnl, 0.0));
+        assert!(approx(pnl.by_day[1].realized_pnl, 22.5));
+        assert!(approx(pnl.by_day[1].unrealized_pnl, 12.5));
+
+        assert_eq!(pnl.positions.len(), 1);
+        assert!(approx(pnl.positions[0].qty, 0.5));
+        assert!(approx(pnl.positions[0].avg_price, 105.0));
+        assert_eq!(pnl.positions[0].mark_source.as_deref(), Some("quote"));
+    }
+
+    #[test]
+    fn flipping_through_flat_resets_basis_and_attributes_by_strategy() {
+        let fills = vec![
+            fill("s1", OrderSide::Buy, 1.0, 100.0, 0),
+            fill("s1", OrderSide::Sell, 2.0, 90.0, 1),
+            fill("s2", OrderSide::Buy, 1.0, 50.0, 2),
+        ];
+
+        let pnl = compute_pnl(&fills, &PortfolioPnlPayload::default(), |_, _| None, 3);
+
+        let s1 = &pnl.by_strategy[0];
+        assert_eq!(s1.key, "s1");
+        assert!(approx(s1.realized_pnl, -10.0));
+        // Short 1 @ 90, marked at the last fill price.
+        assert!(approx(s1.unrealized_pnl, 0.0));
+        let short = pnl
+            .positions
+            .iter()
+            .find(|p| p.strategy_id == "s1")
+            .expect("s1 position");
+        assert!(approx(short.qty, -1.0));
+        assert!(approx(short.avg_price, 90.0));
+        assert_eq!(short.mark_source.as_deref(), Some("last_fill"));
+        assert_eq!(pnl.by_strategy[1].key, "s2");
+    }
+
+    #[test]
+    fn filters_by_strategy_and_since() {
+        let fills = vec![
+            fill("s1", OrderSide::Buy, 1.0, 100.0, 0),
+            fill("s1", OrderSide::Sell, 1.0, 110.0, 10),
+            fill("s2", OrderSide::Buy, 1.0, 100.0, 20),
+        ];
+        let filter = PortfolioPnlPayload {
+            strategy_id: Some("s1".to_string()),
+            venue: None,
+            since_ts_ms: Some(5),
+        };
+
+        let pnl = compute_pnl(&fills, &filter, |_, _| None, 30);
+
+        assert!(approx(pnl.totals.realized_pnl, 10.0));
+        assert_eq!(pnl.totals.fills, 1);
+        assert!(approx(pnl.totals.turnover, 110.0));
+        assert_eq!(pnl.by_strategy.len(), 1);
+        assert!(pnl.positions.is_empty());
+    }
+}
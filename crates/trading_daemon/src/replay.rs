@@ -0,0 +1,526 @@
+//! Journal replay: loads the orders, fills and risk journals for a time window
+//! in the order they were written, so the engine can re-run them through a
+//! sandboxed paper engine and compare outcomes with what was recorded.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use exchange_core::{FillReport, NormalizedOrderRequest, OrderSnapshot, OrderType, TimeInForce};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use trading_protocol::{ReplayDivergencePayload, RiskStatePayload};
+
+/// Relative tolerance when comparing recorded and replayed fill prices.
+const PRICE_TOLERANCE: f64 = 1e-9;
+const QTY_EPSILON: f64 = 1e-9;
+
+/// What the daemon decided for a journaled order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedOutcome {
+    Accepted,
+    RiskDenied {
+        reason: String,
+    },
+    /// Reduce-only shutdown flatten; these bypass the safety cage.
+    Flatten,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedOrder {
+    pub ts_ms: i64,
+    pub intent_id: String,
+    pub routed_to: String,
+    pub outcome: RecordedOutcome,
+    pub request: NormalizedOrderRequest,
+    /// Notional the cage evaluated; absent in journals written before replay existed.
+    pub notional_cents: Option<i64>,
+    pub order: Option<OrderSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRiskAction {
+    pub ts_ms: i64,
+    pub action: String,
+    pub venue: Option<String>,
+    pub strategy_id: Option<String>,
+    pub state: Option<RiskStatePayload>,
+}
+
+/// An order the engine saw canceled, by a client or by the venue.
+#[derive(Debug, Clone)]
+pub struct RecordedCancel {
+    pub ts_ms: i64,
+    pub venue_order_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayStep {
+    Order(Box<RecordedOrder>),
+    Cancel(RecordedCancel),
+    Risk(RecordedRiskAction),
+}
+
+impl ReplayStep {
+    pub fn ts_ms(&self) -> i64 {
+        match self {
+            ReplayStep::Order(order) => order.ts_ms,
+            ReplayStep::Cancel(cancel) => cancel.ts_ms,
+            ReplayStep::Risk(action) => action.ts_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayJournal {
+    /// Orders, cancels and risk actions sorted by timestamp, in write order within a millisecond.
+    pub steps: Vec<ReplayStep>,
+    /// Recorded fills keyed by venue order id.
+    pub fills: HashMap<String, Vec<(i64, FillReport)>>,
+    pub fill_count: usize,
+}
+
+#[derive(Deserialize)]
+struct OrderEntry {
+    ts_ms: i64,
+    #[serde(default)]
+    intent_id: Option<String>,
+    #[serde(default)]
+    routed_to: Option<String>,
+    #[serde(default)]
+    outcome: Option<String>,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    request: Option<NormalizedOrderRequest>,
+    #[serde(default)]
+    notional_cents: Option<i64>,
+    #[serde(default)]
+    order: Option<OrderSnapshot>,
+}
+
+#[derive(Deserialize)]
+struct FillEntry {
+    ts_ms: i64,
+    fill: FillReport,
+}
+
+#[derive(Deserialize)]
+struct RiskEntry {
+    ts_ms: i64,
+    action: String,
+    #[serde(default)]
+    venue: Option<String>,
+    #[serde(default)]
+    strategy_id: Option<String>,
+    #[serde(default)]
+    state: Option<RiskStatePayload>,
+}
+
+fn journal_files(journal_dir: &Path, stream: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}-", stream);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(journal_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension().is_some_and(|ext| ext == "jsonl")
+                        && path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.starts_with(&prefix))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// Entries of `stream` that decode as `T`; unreadable lines are skipped like
+/// journal recovery does.
+fn read_stream<T: DeserializeOwned>(journal_dir: &Path, stream: &str) -> Vec<T> {
+    let mut entries = Vec::new();
+    for path in journal_files(journal_dir, stream) {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(entry) = serde_json::from_str::<T>(&line) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// Request an order was most likely placed with, for entries that predate
+/// the `request` field.
+fn request_from_snapshot(
+    order: &OrderSnapshot,
+    intent_id: Option<String>,
+) -> NormalizedOrderRequest {
+    NormalizedOrderRequest {
+        venue: order.venue.clone(),
+        symbol: order.instrument.venue_symbol.clone(),
+        instrument: order.instrument.clone(),
+        strategy_id: order.strategy_id.clone(),
+        client_order_id: order.client_order_id.clone(),
+        intent_id,
+        side: order.side.clone(),
+        order_type: order.order_type.clone(),
+        qty: order.qty,
+        quote_size: None,
+        limit_price: order.limit_price,
+        stop_price: None,
+        tif: match order.order_type {
+            OrderType::Market => Some(TimeInForce::Ioc),
+            OrderType::Limit => Some(TimeInForce::Gtc),
+        },
+        post_only: false,
+        reduce_only: false,
+        requested_notional_cents: 0,
+    }
+}
+
+fn order_step(entry: OrderEntry) -> Option<ReplayStep> {
+    match entry.outcome.as_deref() {
+        Some("canceled") => entry.order.map(|order| {
+            ReplayStep::Cancel(RecordedCancel {
+                ts_ms: entry.ts_ms,
+                venue_order_id: order.venue_order_id,
+            })
+        }),
+        // Status changes the venue reported after placement; their fills are
+        // journaled on their own.
+        Some("updated") => None,
+        _ => recorded_order(entry).map(|order| ReplayStep::Order(Box::new(order))),
+    }
+}
+
+fn recorded_order(entry: OrderEntry) -> Option<RecordedOrder> {
+    let request = match (entry.request, &entry.order) {
+        (Some(request), _) => request,
+        (None, Some(order)) => request_from_snapshot(order, entry.intent_id.clone()),
+        (None, None) => return None,
+    };
+    let outcome = match entry.outcome.as_deref() {
+        Some("risk_denied") => RecordedOutcome::RiskDenied {
+            reason: entry.reason.unwrap_or_default(),
+        },
+        Some("flatten") => RecordedOutcome::Flatten,
+        _ => RecordedOutcome::Accepted,
+    };
+    Some(RecordedOrder {
+        ts_ms: entry.ts_ms,
+        intent_id: entry
+            .intent_id
+            .unwrap_or_else(|| request.client_order_id.clone()),
+        routed_to: entry.routed_to.unwrap_or_else(|| "paper".to_string()),
+        outcome,
+        request,
+        notional_cents: entry.notional_cents,
+        order: entry.order,
+    })
+}
+
+/// Loads journal entries with `from_ms <= ts_ms <= to_ms`.
+pub fn load_journal_window(journal_dir: &Path, from_ms: i64, to_ms: i64) -> ReplayJournal {
+    let in_window = |ts_ms: i64| ts_ms >= from_ms && ts_ms <= to_ms;
+
+    let mut steps: Vec<ReplayStep> = read_stream::<OrderEntry>(journal_dir, "orders")
+        .into_iter()
+        .filter(|entry| in_window(entry.ts_ms))
+        .filter_map(order_step)
+        .collect();
+    steps.extend(
+        read_stream::<RiskEntry>(journal_dir, "risk")
+            .into_iter()
+            .filter(|entry| in_window(entry.ts_ms))
+            .map(|entry| {
+                ReplayStep::Risk(RecordedRiskAction {
+                    ts_ms: entry.ts_ms,
+                    action: entry.action,
+                    venue: entry.venue,
+                    strategy_id: entry.strategy_id,
+                    state: entry.state,
+                })
+            }),
+    );
+    // Stable, so entries sharing a millisecond keep their journal order.
+    steps.sort_by_key(ReplayStep::ts_ms);
+
+    let mut fills: HashMap<String, Vec<(i64, FillReport)>> = HashMap::new();
+    let mut fill_count = 0;
+    for entry in read_stream::<FillEntry>(journal_dir, "fills") {
+        if in_window(entry.ts_ms) {
+            fill_count += 1;
+            fills
+                .entry(entry.fill.venue_order_id.clone())
+                .or_default()
+                .push((entry.ts_ms, entry.fill));
+        }
+    }
+
+    ReplayJournal {
+        steps,
+        fills,
+        fill_count,
+    }
+}
+
+pub fn divergence(
+    ts_ms: i64,
+    stream: &str,
+    key: &str,
+    kind: &str,
+    recorded: impl Into<String>,
+    replayed: impl Into<String>,
+) -> ReplayDivergencePayload {
+    ReplayDivergencePayload {
+        ts_ms,
+        stream: stream.to_string(),
+        key: key.to_string(),
+        kind: kind.to_string(),
+        recorded: recorded.into(),
+        replayed: replayed.into(),
+    }
+}
+
+/// One fill standing for all of an order's recorded fills: their summed
+/// quantity and fees at the notional-weighted price, stamped like the first.
+pub fn combined_fill(fills: Vec<(i64, FillReport)>) -> Option<FillReport> {
+    let mut fills = fills.into_iter().map(|(_, fill)| fill);
+    let mut combined = fills.next()?;
+    let mut notional = combined.qty * combined.price;
+    for fill in fills {
+        combined.qty += fill.qty;
+        combined.fee += fill.fee;
+        notional += fill.qty * fill.price;
+    }
+    if combined.qty > QTY_EPSILON {
+        combined.price = notional / combined.qty;
+    }
+    Some(combined)
+}
+
+fn describe_fill(fill: Option<&FillReport>) -> String {
+    match fill {
+        Some(fill) => format!("{:?} {} @ {}", fill.side, fill.qty, fill.price),
+        None => "no fill".to_string(),
+    }
+}
+
+/// Describes both sides when the replayed fill differs in side, quantity or
+/// price from the recorded one.
+pub fn fill_mismatch(
+    recorded: Option<&FillReport>,
+    replayed: Option<&FillReport>,
+) -> Option<(String, String)> {
+    let matches = match (recorded, replayed) {
+        (None, None) => true,
+        (Some(recorded), Some(replayed)) => {
+            recorded.side == replayed.side
+                && (recorded.qty - replayed.qty).abs() < QTY_EPSILON
+                && (recorded.price - replayed.price).abs()
+                    <= PRICE_TOLERANCE * recorded.price.abs().max(1.0)
+        }
+        _ => false,
+    };
+    (!matches).then(|| (describe_fill(recorded), describe_fill(replayed)))
+}
+
+/// Describes both sides when kill switches differ after a risk action.
+/// Counters are not compared: they depend on orders before the window.
+pub fn risk_state_mismatch(
+    recorded: &RiskStatePayload,
+    replayed: &RiskStatePayload,
+) -> Option<(String, String)> {
+    let describe = |state: &RiskStatePayload| {
+        let mut venues = state.scoped_kill_switches.venues.clone();
+        let mut strategies = state.scoped_kill_switches.strategies.clone();
+        venues.sort();
+        strategies.sort();
+        format!(
+            "kill_switch_engaged={} venues={:?} strategies={:?}",
+            state.kill_switch_engaged, venues, strategies
+        )
+    };
+    let (recorded, replayed) = (describe(recorded), describe(replayed));
+    (recorded != replayed).then_some((recorded, replayed))
+}
+
+#[cfg(test)]
+mod tests {
+    use exchange_core::{AssetClass, InstrumentRef, InstrumentType, OrderSide, OrderStatus};
+    use serde_json::json;
+    use trading_protocol::ScopedKillSwitchesPayload;
+
+    use super::*;
+
+    fn instrument() -> InstrumentRef {
+        InstrumentRef {
+            venue: "paper".to_string(),
+            venue_symbol: "BTC-USD".to_string(),
+            asset_class: AssetClass::Crypto,
+            instrument_type: InstrumentType::Spot,
+            base: Some("BTC".to_string()),
+            quote: Some("USD".to_string()),
+            expiry_ts_ms: None,
+            strike: None,
+            option_right: None,
+            contract_multiplier: None,
+        }
+    }
+
+    fn snapshot(venue_order_id: &str) -> OrderSnapshot {
+        OrderSnapshot {
+            venue: "paper".to_string(),
+            venue_order_id: venue_order_id.to_string(),
+            client_order_id: "c1".to_string(),
+            strategy_id: "s1".to_string(),
+            instrument: instrument(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            status: OrderStatus::Filled,
+            qty: 1.0,
+            filled_qty: 1.0,
+            limit_price: Some(100.0),
+            avg_fill_price: Some(100.05),
+            created_at_ms: 10,
+            updated_at_ms: 10,
+            simulated: true,
+        }
+    }
+
+    fn fill(price: f64) -> FillReport {
+        FillReport {
+            venue: "paper".to_string(),
+            venue_fill_id: "f1".to_string(),
+            venue_order_id: "o1".to_string(),
+            client_order_id: "c1".to_string(),
+            strategy_id: "s1".to_string(),
+            instrument: instrument(),
+            side: OrderSide::Buy,
+            qty: 1.0,
+            price,
+            fee: 0.0,
+            fee_asset: None,
+            liquidity: None,
+            simulated: true,
+            ts_ms: 10,
+        }
+    }
+
+    fn write_lines(dir: &Path, file_name: &str, lines: &[serde_json::Value]) {
+        let contents: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        std::fs::write(dir.join(file_name), contents).expect("write journal");
+    }
+
+    #[test]
+    fn loads_window_in_timestamp_order() {
+        let dir = std::env::temp_dir().join(format!("replay-load-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create journal dir");
+        write_lines(
+            &dir,
+            "orders-2026-01-01.jsonl",
+            &[
+                json!({"ts_ms": 5, "intent_id": "early", "order": snapshot("o0")}),
+                json!({"ts_ms": 10, "intent_id": "i1", "routed_to": "paper", "order": snapshot("o1")}),
+                json!({"ts_ms": 30, "intent_id": "i2", "outcome": "risk_denied", "reason": "limit",
+                       "request": request_from_snapshot(&snapshot("o2"), None), "notional_cents": 900}),
+                json!({"ts_ms": 30, "outcome": "updated", "order": snapshot("o1")}),
+                json!({"ts_ms": 30, "outcome": "canceled", "order": snapshot("o3")}),
+            ],
+        );
+        write_lines(
+            &dir,
+            "risk-2026-01-01.jsonl",
+            &[json!({"ts_ms": 20, "action": "kill_venue", "venue": "paper"})],
+        );
+        write_lines(
+            &dir,
+            "fills-2026-01-01.jsonl",
+            &[
+                json!({"ts_ms": 10, "intent_id": "i1", "fill": fill(100.05)}),
+                json!("garbage"),
+            ],
+        );
+
+        let journal = load_journal_window(&dir, 10, 30);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let timestamps: Vec<i64> = journal.steps.iter().map(ReplayStep::ts_ms).collect();
+        assert_eq!(timestamps, vec![10, 20, 30, 30]);
+        match &journal.steps[0] {
+            ReplayStep::Order(order) => {
+                assert_eq!(order.intent_id, "i1");
+                assert_eq!(order.outcome, RecordedOutcome::Accepted);
+                assert_eq!(order.request.limit_price, Some(100.0));
+                assert_eq!(order.notional_cents, None);
+            }
+            other => panic!("expected an order, got {:?}", other),
+        }
+        match &journal.steps[2] {
+            ReplayStep::Order(order) => {
+                assert_eq!(
+                    order.outcome,
+                    RecordedOutcome::RiskDenied {
+                        reason: "limit".to_string()
+                    }
+                );
+                assert_eq!(order.notional_cents, Some(900));
+            }
+            other => panic!("expected an order, got {:?}", other),
+        }
+        match &journal.steps[3] {
+            ReplayStep::Cancel(cancel) => assert_eq!(cancel.venue_order_id, "o3"),
+            other => panic!("expected a cancel, got {:?}", other),
+        }
+        assert_eq!(journal.fill_count, 1);
+        assert_eq!(journal.fills["o1"].len(), 1);
+    }
+
+    #[test]
+    fn combined_fills_sum_quantity_at_the_weighted_price() {
+        let mut second = fill(103.0);
+        second.qty = 3.0;
+        second.fee = 0.5;
+        let combined = combined_fill(vec![(10, fill(99.0)), (20, second)]).expect("fill");
+        assert_eq!((combined.qty, combined.fee), (4.0, 0.5));
+        assert!((combined.price - 102.0).abs() < 1e-9);
+        assert_eq!(combined.ts_ms, 10);
+        assert!(combined_fill(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn fill_and_risk_comparisons_flag_differences() {
+        assert_eq!(fill_mismatch(Some(&fill(100.0)), Some(&fill(100.0))), None);
+        assert_eq!(fill_mismatch(None, None), None);
+        assert!(fill_mismatch(Some(&fill(100.0)), Some(&fill(100.5))).is_some());
+        assert_eq!(
+            fill_mismatch(Some(&fill(100.0)), None),
+            Some(("Buy 1 @ 100".to_string(), "no fill".to_string()))
+        );
+
+        let state = |venues: Vec<&str>| RiskStatePayload {
+            kill_switch_engaged: false,
+            paused: false,
+            orders_last_minute: 0,
+            drawdown_cents: 0,
+            total_notional_cents: 0,
+            scoped_kill_switches: ScopedKillSwitchesPayload {
+                global: false,
+                venues: venues.into_iter().map(str::to_string).collect(),
+                strategies: Vec::new(),
+            },
+        };
+        assert_eq!(
+            risk_state_mismatch(&state(vec!["a", "b"]), &state(vec!["b", "a"])),
+            None
+        );
+        assert!(risk_state_mismatch(&state(vec!["a"]), &state(vec![])).is_some());
+    }
+}
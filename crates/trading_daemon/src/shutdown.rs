@@ -0,0 +1,134 @@
+//! Shutdown policy: what the daemon does with open orders and paper positions
+//! after SIGTERM/SIGINT, and the reduce-only orders used to flatten paper books.
+
+use std::collections::BTreeMap;
+
+use exchange_core::{
+    FillReport, InstrumentRef, NormalizedOrderRequest, OrderSide, OrderType, TimeInForce,
+};
+
+pub const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_MS: u64 = 10_000;
+
+const QTY_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownPolicy {
+    /// Cancel every open order on its venue before exiting.
+    pub cancel_open_orders: bool,
+    /// Close simulated positions with reduce-only market orders on the paper adapter.
+    pub flatten_paper_positions: bool,
+    /// Upper bound for each drain step (in-flight requests, journal writes).
+    pub drain_timeout_ms: u64,
+}
+
+/// Builds one reduce-only market order per strategy and instrument whose
+/// simulated fills leave a non-zero net position.
+pub fn paper_flatten_orders(fills: &[FillReport], ts_ms: i64) -> Vec<NormalizedOrderRequest> {
+    let mut net: BTreeMap<(String, String, String), (f64, InstrumentRef)> = BTreeMap::new();
+    for fill in fills.iter().filter(|fill| fill.simulated) {
+        let signed_qty = match fill.side {
+            OrderSide::Buy => fill.qty,
+            OrderSide::Sell => -fill.qty,
+        };
+        let entry = net
+            .entry((
+                fill.strategy_id.clone(),
+                fill.instrument.venue.clone(),
+                fill.instrument.venue_symbol.clone(),
+            ))
+            .or_insert_with(|| (0.0, fill.instrument.clone()));
+        entry.0 += signed_qty;
+    }
+
+    net.into_iter()
+        .filter(|(_, (qty, _))| qty.abs() >= QTY_EPSILON)
+        .enumerate()
+        .map(|(index, ((strategy_id, venue, _), (qty, instrument)))| {
+            let client_order_id = format!("shutdown-flatten-{}-{}", ts_ms, index);
+            NormalizedOrderRequest {
+                venue,
+                symbol: instrument.venue_symbol.clone(),
+                instrument,
+                strategy_id,
+                client_order_id: client_order_id.clone(),
+                intent_id: Some(client_order_id),
+                side: if qty > 0.0 {
+                    OrderSide::Sell
+                } else {
+                    OrderSide::Buy
+                },
+                order_type: OrderType::Market,
+                qty: qty.abs(),
+                quote_size: None,
+                limit_price: None,
+                stop_price: None,
+                tif: Some(TimeInForce::Ioc),
+                post_only: false,
+                reduce_only: true,
+                requested_notional_cents: 0,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use exchange_core::{AssetClass, InstrumentType};
+
+    use super::*;
+
+    fn fill(strategy_id: &str, side: OrderSide, qty: f64, simulated: bool) -> FillReport {
+        FillReport {
+            venue: "paper".to_string(),
+            venue_fill_id: "fill".to_string(),
+            venue_order_id: "order".to_string(),
+            client_order_id: "client".to_string(),
+            strategy_id: strategy_id.to_string(),
+            instrument: InstrumentRef {
+                venue: "coinbase_at".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                asset_class: AssetClass::Crypto,
+                instrument_type: InstrumentType::Spot,
+                base: Some("BTC".to_string()),
+                quote: Some("USD".to_string()),
+                expiry_ts_ms: None,
+                strike: None,
+                option_right: None,
+                contract_multiplier: None,
+            },
+            side,
+            qty,
+            price: 100.0,
+            fee: 0.0,
+            fee_asset: None,
+            liquidity: None,
+            simulated,
+            ts_ms: 0,
+        }
+    }
+
+    #[test]
+    fn flattens_net_simulated_positions_per_strategy() {
+        let fills = vec![
+            fill("s1", OrderSide::Buy, 2.0, true),
+            fill("s1", OrderSide::Sell, 0.5, true),
+            fill("s2", OrderSide::Sell, 1.0, true),
+            fill("s3", OrderSide::Buy, 1.0, true),
+            fill("s3", OrderSide::Sell, 1.0, true),
+            fill("live", OrderSide::Buy, 3.0, false),
+        ];
+
+        let orders = paper_flatten_orders(&fills, 42);
+
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].strategy_id, "s1");
+        assert_eq!(orders[0].side, OrderSide::Sell);
+        assert!((orders[0].qty - 1.5).abs() < 1e-9);
+        assert!(orders[0].reduce_only);
+        assert_eq!(orders[0].venue, "coinbase_at");
+        assert_eq!(orders[0].client_order_id, "shutdown-flatten-42-0");
+        assert_eq!(orders[1].strategy_id, "s2");
+        assert_eq!(orders[1].side, OrderSide::Buy);
+        assert!((orders[1].qty - 1.0).abs() < 1e-9);
+    }
+}
@@ -1,15 +1,26 @@
+mod schema;
+
+pub use schema::{command_schemas, protocol_schemas};
+
 use exchange_core::{
     AssetClass, BalanceSnapshot, FillReport, NormalizedOrderRequest, OpenOrderSnapshot, OrderAck,
-    OrderSnapshot, PositionSnapshot,
+    OrderSnapshot, PositionSnapshot, ProductInfo, TradePrint,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use strategy_core::{MarketRegime, RegimeContext};
 use tokio_util::codec::LengthDelimitedCodec;
 use uuid::Uuid;
 
 pub const PROTOCOL_VERSION: u8 = 1;
-pub const STATUS_SCHEMA_VERSION: u16 = 3;
+/// Protocol versions this build can speak, oldest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[u8] = &[1];
+pub const STATUS_SCHEMA_VERSION: u16 = 6;
 pub const DEFAULT_SOCKET_PATH: &str = "/var/run/openclaw/trading.sock";
 pub const MAX_FRAME_LENGTH: usize = 1024 * 1024; // 1 MiB
+/// Envelope kind of events pushed on a `Control.SubscribeEvents` connection.
+pub const EVENT_KIND: &str = "Engine.Event";
 
 /// 4-byte Big-Endian length-prefixed JSON codec.
 pub type JsonCodec = LengthDelimitedCodec;
@@ -21,7 +32,7 @@ pub fn create_codec() -> JsonCodec {
         .new_codec()
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct Envelope {
     pub v: u8,
     pub id: Uuid,
@@ -29,6 +40,13 @@ pub struct Envelope {
     pub kind: String,
     pub ts_ms: i64,
     pub payload: serde_json::Value,
+    /// Caller-chosen id tying a request to the journal records, events and
+    /// spans it produces; defaults to `id`. Echoed on the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// W3C trace context of the caller's span, so daemon spans join its trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
 }
 
 impl Envelope {
@@ -39,6 +57,8 @@ impl Envelope {
             kind: kind.to_string(),
             ts_ms: chrono::Utc::now().timestamp_millis(),
             payload,
+            correlation_id: None,
+            traceparent: None,
         }
     }
 
@@ -49,11 +69,30 @@ impl Envelope {
             kind: format!("{}.Response", req.kind),
             ts_ms: chrono::Utc::now().timestamp_millis(),
             payload,
+            correlation_id: req.correlation_id.clone(),
+            traceparent: None,
         }
     }
+
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    pub fn with_traceparent(mut self, traceparent: Option<String>) -> Self {
+        self.traceparent = traceparent;
+        self
+    }
+
+    /// `correlation_id` when the caller set one, otherwise the request id.
+    pub fn effective_correlation_id(&self) -> String {
+        self.correlation_id
+            .clone()
+            .unwrap_or_else(|| self.id.to_string())
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ControlCommand {
     #[serde(rename = "Control.Start")]
     Start,
@@ -65,6 +104,10 @@ pub enum ControlCommand {
     Ping,
     #[serde(rename = "Control.Capabilities")]
     Capabilities,
+    #[serde(rename = "Control.Hello")]
+    Hello,
+    #[serde(rename = "Control.SubscribeEvents")]
+    SubscribeEvents,
 }
 
 impl ControlCommand {
@@ -75,6 +118,8 @@ impl ControlCommand {
             Self::Status => "Control.Status",
             Self::Ping => "Control.Ping",
             Self::Capabilities => "Control.Capabilities",
+            Self::Hello => "Control.Hello",
+            Self::SubscribeEvents => "Control.SubscribeEvents",
         }
     }
 
@@ -85,12 +130,14 @@ impl ControlCommand {
             "Control.Status" => Some(Self::Status),
             "Control.Ping" => Some(Self::Ping),
             "Control.Capabilities" => Some(Self::Capabilities),
+            "Control.Hello" => Some(Self::Hello),
+            "Control.SubscribeEvents" => Some(Self::SubscribeEvents),
             _ => None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EngineMode {
     #[serde(rename = "paper")]
     Paper,
@@ -110,7 +157,7 @@ impl EngineMode {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EngineCommand {
     #[serde(rename = "Engine.Status")]
     Status,
@@ -124,6 +171,10 @@ pub enum EngineCommand {
     GetMode,
     #[serde(rename = "Engine.SetMode")]
     SetMode,
+    #[serde(rename = "Engine.ExportSnapshot")]
+    ExportSnapshot,
+    #[serde(rename = "Engine.Replay")]
+    Replay,
 }
 
 impl EngineCommand {
@@ -135,6 +186,8 @@ impl EngineCommand {
             Self::KillSwitch => "Engine.KillSwitch",
             Self::GetMode => "Engine.GetMode",
             Self::SetMode => "Engine.SetMode",
+            Self::ExportSnapshot => "Engine.ExportSnapshot",
+            Self::Replay => "Engine.Replay",
         }
     }
 
@@ -146,12 +199,14 @@ impl EngineCommand {
             "Engine.KillSwitch" => Some(Self::KillSwitch),
             "Engine.GetMode" => Some(Self::GetMode),
             "Engine.SetMode" => Some(Self::SetMode),
+            "Engine.ExportSnapshot" => Some(Self::ExportSnapshot),
+            "Engine.Replay" => Some(Self::Replay),
             _ => None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StrategyCommand {
     #[serde(rename = "Strategy.List")]
     List,
@@ -188,7 +243,7 @@ impl StrategyCommand {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RiskCommand {
     #[serde(rename = "Risk.Status")]
     Status,
@@ -213,7 +268,7 @@ impl RiskCommand {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExecutionCommand {
     #[serde(rename = "Execution.Place")]
     Place,
@@ -250,7 +305,7 @@ impl ExecutionCommand {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PortfolioCommand {
     #[serde(rename = "Portfolio.Positions")]
     Positions,
@@ -258,6 +313,11 @@ pub enum PortfolioCommand {
     Balances,
     #[serde(rename = "Portfolio.Exposure")]
     Exposure,
+    #[serde(rename = "Portfolio.Pnl")]
+    Pnl,
+    /// Resets or funds the simulated paper account.
+    #[serde(rename = "Portfolio.PaperAccount")]
+    PaperAccount,
 }
 
 impl PortfolioCommand {
@@ -266,6 +326,8 @@ impl PortfolioCommand {
             Self::Positions => "Portfolio.Positions",
             Self::Balances => "Portfolio.Balances",
             Self::Exposure => "Portfolio.Exposure",
+            Self::Pnl => "Portfolio.Pnl",
+            Self::PaperAccount => "Portfolio.PaperAccount",
         }
     }
 
@@ -274,6 +336,41 @@ impl PortfolioCommand {
             "Portfolio.Positions" => Some(Self::Positions),
             "Portfolio.Balances" => Some(Self::Balances),
             "Portfolio.Exposure" => Some(Self::Exposure),
+            "Portfolio.Pnl" => Some(Self::Pnl),
+            "Portfolio.PaperAccount" => Some(Self::PaperAccount),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketDataCommand {
+    #[serde(rename = "MarketData.Quote")]
+    Quote,
+    #[serde(rename = "MarketData.Subscribe")]
+    Subscribe,
+    #[serde(rename = "MarketData.Regime")]
+    Regime,
+    #[serde(rename = "MarketData.Product")]
+    Product,
+}
+
+impl MarketDataCommand {
+    pub fn as_kind(self) -> &'static str {
+        match self {
+            Self::Quote => "MarketData.Quote",
+            Self::Subscribe => "MarketData.Subscribe",
+            Self::Regime => "MarketData.Regime",
+            Self::Product => "MarketData.Product",
+        }
+    }
+
+    pub fn from_kind(kind: &str) -> Option<Self> {
+        match kind {
+            "MarketData.Quote" => Some(Self::Quote),
+            "MarketData.Subscribe" => Some(Self::Subscribe),
+            "MarketData.Regime" => Some(Self::Regime),
+            "MarketData.Product" => Some(Self::Product),
             _ => None,
         }
     }
@@ -287,6 +384,7 @@ pub enum RequestKind {
     Risk(RiskCommand),
     Execution(ExecutionCommand),
     Portfolio(PortfolioCommand),
+    MarketData(MarketDataCommand),
 }
 
 impl RequestKind {
@@ -306,24 +404,27 @@ impl RequestKind {
         if let Some(cmd) = ExecutionCommand::from_kind(kind) {
             return Some(Self::Execution(cmd));
         }
-        PortfolioCommand::from_kind(kind).map(Self::Portfolio)
+        if let Some(cmd) = PortfolioCommand::from_kind(kind) {
+            return Some(Self::Portfolio(cmd));
+        }
+        MarketDataCommand::from_kind(kind).map(Self::MarketData)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct RoutingCountersPayload {
     pub live_count: u64,
     pub paper_count: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ScopedKillSwitchesPayload {
     pub global: bool,
     pub venues: Vec<String>,
     pub strategies: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionStatsPayload {
     pub accepted: u64,
     pub rejected: u64,
@@ -331,26 +432,54 @@ pub struct ExecutionStatsPayload {
     pub fills: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct VenueExposurePayload {
     pub venue: String,
     pub notional_cents: i64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct AssetClassExposurePayload {
     pub asset_class: AssetClass,
     pub notional_cents: i64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct PortfolioSummaryPayload {
     pub total_notional_cents: i64,
     pub by_venue: Vec<VenueExposurePayload>,
     pub by_asset_class: Vec<AssetClassExposurePayload>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct VenuePacingPayload {
+    pub venue: String,
+    pub burst: u32,
+    pub refill_per_sec: f64,
+    pub tokens_available: f64,
+    pub queue_depth: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PacingStatusPayload {
+    pub queue_enabled: bool,
+    pub max_wait_ms: u64,
+    pub max_queue_depth: usize,
+    pub queued_total: usize,
+    pub venues: Vec<VenuePacingPayload>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataStatusPayload {
+    pub stale_after_ms: i64,
+    pub instruments: usize,
+    pub stale_instruments: usize,
+    /// Subscribed venue symbols keyed by venue.
+    pub subscriptions: BTreeMap<String, Vec<String>>,
+    pub disconnected_venues: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EngineStatePayload {
     pub running: bool,
     pub paused: bool,
@@ -361,27 +490,204 @@ pub struct EngineStatePayload {
     pub strategies_total: usize,
     pub strategies_enabled: usize,
     pub mode: EngineMode,
+    /// Set when the engine was started from an imported snapshot with live
+    /// routing disabled; the mode cannot leave `paper`.
+    #[serde(default)]
+    pub paper_only: bool,
     pub routing_counters: RoutingCountersPayload,
     pub scoped_kill_switches: ScopedKillSwitchesPayload,
     pub execution_stats: ExecutionStatsPayload,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Status document answered by `Engine.Status`, `Control.Status` and the
+/// lifecycle commands (`Start`, `Stop`, `Pause`, `Resume`, `KillSwitch`).
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EngineStatusPayload {
+    pub protocol_version: u8,
+    pub status_schema_version: u16,
+    pub daemon_build: DaemonBuildPayload,
+    pub state: EngineStatePayload,
+    pub risk: RiskStatusPayload,
+    pub portfolio_summary: PortfolioSummaryPayload,
+    pub pacing: PacingStatusPayload,
+    pub market_data: MarketDataStatusPayload,
+    pub strategies: Vec<StrategySummaryPayload>,
+    /// Latest engine events (`Event` values), oldest first.
+    pub recent_events: Vec<serde_json::Value>,
+}
+
+/// Machine-readable failure reason carried as `code` next to the `error`
+/// message in every `ok: false` response.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidPayload,
+    UnsupportedCommand,
+    UnsupportedProtocol,
+    NotFound,
+    EngineNotRunning,
+    KillSwitchEngaged,
+    ModeNotAllowed,
+    ApprovalRequired,
+    RiskDenied,
+    PacingRejected,
+    PromotionRejected,
+    VenueUnavailable,
+    VenueError,
+    ShuttingDown,
+    Internal,
+    /// A code introduced by a newer daemon than this client knows about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidPayload => "invalid_payload",
+            Self::UnsupportedCommand => "unsupported_command",
+            Self::UnsupportedProtocol => "unsupported_protocol",
+            Self::NotFound => "not_found",
+            Self::EngineNotRunning => "engine_not_running",
+            Self::KillSwitchEngaged => "kill_switch_engaged",
+            Self::ModeNotAllowed => "mode_not_allowed",
+            Self::ApprovalRequired => "approval_required",
+            Self::RiskDenied => "risk_denied",
+            Self::PacingRejected => "pacing_rejected",
+            Self::PromotionRejected => "promotion_rejected",
+            Self::VenueUnavailable => "venue_unavailable",
+            Self::VenueError => "venue_error",
+            Self::ShuttingDown => "shutting_down",
+            Self::Internal => "internal",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Body of an `ok: false` response. Handlers may add context fields next to
+/// these (for example `venue_code` or `notional`).
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ErrorPayload {
+    pub ok: bool,
+    pub code: ErrorCode,
+    pub error: String,
+}
+
+impl ErrorPayload {
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            code,
+            error: error.into(),
+        }
+    }
+}
+
+/// Highest protocol version supported by both sides, if any.
+pub fn negotiate_protocol_version(client_versions: &[u8]) -> Option<u8> {
+    client_versions
+        .iter()
+        .copied()
+        .filter(|version| SUPPORTED_PROTOCOL_VERSIONS.contains(version))
+        .max()
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct HelloPayload {
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Protocol versions the client speaks; defaults to the envelope `v`.
+    #[serde(default)]
+    pub protocol_versions: Vec<u8>,
+    /// Status schema the client was built against.
+    #[serde(default)]
+    pub status_schema_version: Option<u16>,
+    /// Optional features the client wants to rely on.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct HelloResultPayload {
+    pub protocol_version: u8,
+    pub supported_protocol_versions: Vec<u8>,
+    pub status_schema_version: u16,
+    pub features: Vec<String>,
+    /// Requested features this daemon does not provide.
+    pub unsupported_features: Vec<String>,
+    pub daemon_build: DaemonBuildPayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PingResultPayload {
+    pub connected: bool,
+}
+
+/// Turns the connection into an event stream: after the `ok` response every
+/// engine event is pushed as an `Engine.Event` envelope.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct EventSubscribePayload {
+    /// Send the buffered recent events before live ones.
+    #[serde(default)]
+    pub replay_recent: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EventSubscribeResultPayload {
+    /// Buffered events sent ahead of live ones.
+    pub replayed: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct DaemonBuildPayload {
     pub name: String,
     pub version: String,
     pub git_sha: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct CapabilitiesRequestPayload {
+    /// Attach the JSON Schemas of every protocol payload to the response.
+    #[serde(default)]
+    pub include_schemas: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct CapabilitiesPayload {
     pub protocol_version: u8,
     pub status_schema_version: u16,
     pub command_kinds_supported: Vec<String>,
     pub daemon_build: DaemonBuildPayload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schemas: Option<ProtocolSchemasPayload>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CapabilitiesResultPayload {
+    pub capabilities: CapabilitiesPayload,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Request/result payload type names for one command kind. `result` names the
+/// type returned under the response's `result` field; commands that answer
+/// with top-level fields instead name the type of those fields (all but `ok`)
+/// in `response`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct CommandSchemaPayload {
+    pub kind: String,
+    pub request: Option<String>,
+    pub result: Option<String>,
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ProtocolSchemasPayload {
+    pub commands: Vec<CommandSchemaPayload>,
+    /// JSON Schema (draft 2020-12) per payload type name.
+    pub types: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct StrategySummaryPayload {
     pub id: String,
     pub enabled: bool,
@@ -393,7 +699,12 @@ pub struct StrategySummaryPayload {
     pub active_code_hash: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct StrategyIdPayload {
+    pub strategy_id: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct CandidateUploadPayload {
     pub strategy_id: String,
     pub source: String,
@@ -406,7 +717,7 @@ pub struct CandidateUploadPayload {
     pub risk_passed: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct CandidatePromotePayload {
     pub strategy_id: String,
     pub code_hash: String,
@@ -414,7 +725,52 @@ pub struct CandidatePromotePayload {
     pub auto: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct StrategyListResultPayload {
+    pub strategies: Vec<StrategySummaryPayload>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct StrategyResultPayload {
+    pub strategy: StrategySummaryPayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CandidateSummaryPayload {
+    pub source: String,
+    pub code_hash: String,
+    pub requested_canary_notional_cents: i64,
+    pub all_gates_passed: bool,
+    pub uploaded_at_ms: i64,
+}
+
+/// The engine's own replay of recent journals, which a candidate's replay
+/// claim must agree with.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CandidateReplayPayload {
+    pub passed: bool,
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub orders: usize,
+    pub divergence_count: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CandidateUploadResultPayload {
+    pub strategy_id: String,
+    pub candidate: CandidateSummaryPayload,
+    pub replay: CandidateReplayPayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct CandidatePromoteResultPayload {
+    pub strategy: StrategySummaryPayload,
+    pub previous_version: u64,
+    /// Always `enforced`: promotion never lifts the hard safety cage.
+    pub hard_safety_floor: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct RiskLimitsPayload {
     pub max_total_notional_cents: i64,
     pub max_strategy_canary_notional_cents: i64,
@@ -423,7 +779,7 @@ pub struct RiskLimitsPayload {
     pub forced_cooldown_secs: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct RiskStatePayload {
     pub kill_switch_engaged: bool,
     pub paused: bool,
@@ -433,7 +789,21 @@ pub struct RiskStatePayload {
     pub scoped_kill_switches: ScopedKillSwitchesPayload,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct RiskStatusPayload {
+    pub limits: RiskLimitsPayload,
+    pub state: RiskStatePayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct RiskStatusResultPayload {
+    /// Always `hard_cage`.
+    pub safety_floor: String,
+    pub limits: RiskLimitsPayload,
+    pub state: RiskStatePayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct RiskOverridePayload {
     pub action: String,
     pub value: Option<serde_json::Value>,
@@ -441,7 +811,7 @@ pub struct RiskOverridePayload {
     pub strategy_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct RiskScopedOverridePayload {
     pub action: String,
     pub venue: Option<String>,
@@ -449,41 +819,226 @@ pub struct RiskScopedOverridePayload {
     pub value: Option<serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct RiskOverrideResultPayload {
+    pub action: String,
+    pub state: RiskStatePayload,
+    pub note: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EngineModePayload {
     pub mode: EngineMode,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EngineModeResultPayload {
+    pub mode: EngineModePayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct EngineExportSnapshotPayload {
+    /// When set, the export is written to `<data_dir>/exports/<file_name>` on the
+    /// daemon host instead of being returned inline.
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+/// `Engine.ExportSnapshot` result: the export bundle itself, or where it was
+/// written when `file_name` was set.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(untagged)]
+pub enum EngineExportSnapshotResultPayload {
+    Written {
+        path: String,
+        bytes: usize,
+        exported_at_ms: i64,
+    },
+    Inline(EngineSnapshotExportPayload),
+}
+
+/// Portable engine snapshot accepted by `trading_daemon import-snapshot`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EngineSnapshotExportPayload {
+    pub format: String,
+    pub exported_at_ms: i64,
+    pub daemon_build: DaemonBuildPayload,
+    /// The daemon's persisted engine state.
+    pub snapshot: serde_json::Value,
+}
+
+/// Journal window for `Engine.Replay`; both bounds are inclusive and default
+/// to the start of the journals and the daemon's current time.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct EngineReplayPayload {
+    #[serde(default)]
+    pub from_ms: Option<i64>,
+    #[serde(default)]
+    pub to_ms: Option<i64>,
+}
+
+/// A replayed outcome that differs from what the journals recorded.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ReplayDivergencePayload {
+    pub ts_ms: i64,
+    /// Journal stream the entry came from (`orders`, `fills` or `risk`).
+    pub stream: String,
+    /// Intent id, venue order id or risk action the entry is about.
+    pub key: String,
+    /// `risk_decision`, `fill`, `duplicate_intent`, `unmatched_fill` or `risk_state`.
+    pub kind: String,
+    pub recorded: String,
+    pub replayed: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct EngineReplayResultPayload {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    pub orders: usize,
+    pub fills: usize,
+    pub risk_actions: usize,
+    /// Orders the sandbox accepted and rejected.
+    pub accepted: u64,
+    pub rejected: u64,
+    /// Total divergences found; `divergences` may list only the first ones
+    /// to keep the response within one frame.
+    pub divergence_count: usize,
+    pub divergences: Vec<ReplayDivergencePayload>,
+    /// True when no divergences were found.
+    pub passed: bool,
+    /// Risk state of the sandbox after the last replayed entry.
+    pub risk_state: RiskStatePayload,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderPriority {
+    /// Never queued; rejected when the venue has no pacing capacity.
+    Urgent,
+    /// May wait in the pacing queue for capacity until its deadline.
+    Normal,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionPlacePayload {
     pub order: NormalizedOrderRequest,
     pub approval_token: Option<String>,
+    /// Overrides the TIF-derived urgency (IOC/FOK are urgent by default).
+    #[serde(default)]
+    pub priority: Option<OrderPriority>,
+    /// Maximum time a non-urgent order may wait for pacing capacity.
+    #[serde(default)]
+    pub max_queue_wait_ms: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionCancelPayload {
     pub venue_order_id: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ExecutionCancelResultPayload {
+    pub venue_order_id: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionGetPayload {
     pub venue_order_id: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ExecutionGetResultPayload {
+    /// `None` when the engine has no record of the order.
+    pub order: Option<OrderSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionFillsPayload {
     pub since_ts_ms: Option<i64>,
     pub limit: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct OrderNotionalPayload {
+    pub requested_notional_cents: i64,
+    pub derived_notional_cents: i64,
+    pub used_notional_cents: i64,
+    pub reference_price: Option<f64>,
+    pub price_source: Option<String>,
+    pub understated: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataQuotePayload {
+    pub venue: String,
+    pub venue_symbol: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataSubscribePayload {
+    pub venue: String,
+    pub venue_symbols: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataSubscribeResultPayload {
+    pub venue: String,
+    pub subscribed: Vec<String>,
+    pub already_subscribed: Vec<String>,
+}
+
+/// Filters for `MarketData.Regime`; omitted fields match every instrument.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct MarketDataRegimePayload {
+    #[serde(default)]
+    pub venue: Option<String>,
+    #[serde(default)]
+    pub venue_symbol: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataRegimeResultPayload {
+    pub contexts: Vec<RegimeContext>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataProductPayload {
+    pub venue: String,
+    pub venue_symbol: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketDataProductResultPayload {
+    pub product: ProductInfo,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MarketQuotePayload {
+    pub venue: String,
+    pub venue_symbol: String,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub last: Option<f64>,
+    pub mark: Option<f64>,
+    pub last_trade: Option<TradePrint>,
+    /// Venue timestamp of the latest book update.
+    pub ts_ms: i64,
+    /// Time since the daemon last received an update for the instrument.
+    pub age_ms: i64,
+    pub stale: bool,
+    /// `stream` when served from the subscription cache, `snapshot` when polled.
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionPlaceResultPayload {
     pub ack: OrderAck,
     pub order: Option<OrderSnapshot>,
     pub fill: Option<FillReport>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub enum Event {
     #[serde(rename = "Event.Alert")]
     Alert { level: String, message: String },
@@ -519,6 +1074,9 @@ pub enum Event {
         latency_ms: u64,
         simulated: bool,
         venue_order_id: Option<String>,
+        /// Correlation id of the request that caused the execution.
+        #[serde(default)]
+        correlation_id: Option<String>,
     },
     #[serde(rename = "Event.AgentCodegen")]
     AgentCodegen {
@@ -530,24 +1088,114 @@ pub enum Event {
     },
     #[serde(rename = "Event.PortfolioSync")]
     PortfolioSync { positions: usize, balances: usize },
+    #[serde(rename = "Event.MarketData")]
+    MarketData {
+        venue: String,
+        status: String,
+        message: Option<String>,
+    },
+    /// An instrument's classified regime changed.
+    #[serde(rename = "Event.Regime")]
+    Regime {
+        context: RegimeContext,
+        previous: MarketRegime,
+    },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct PortfolioPositionsPayload {
     pub positions: Vec<PositionSnapshot>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct PortfolioBalancesPayload {
     pub balances: Vec<BalanceSnapshot>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct PortfolioPnlPayload {
+    #[serde(default)]
+    pub strategy_id: Option<String>,
+    #[serde(default)]
+    pub venue: Option<String>,
+    /// Only fills at or after this time contribute realized PnL, fees and turnover;
+    /// earlier fills still build the cost basis.
+    #[serde(default)]
+    pub since_ts_ms: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct PnlBucketPayload {
+    pub key: String,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub fees: f64,
+    /// Realized plus unrealized PnL, net of fees.
+    pub net_pnl: f64,
+    pub turnover: f64,
+    pub fills: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PnlPositionPayload {
+    pub strategy_id: String,
+    pub venue: String,
+    pub venue_symbol: String,
+    pub qty: f64,
+    pub avg_price: f64,
+    pub mark_price: Option<f64>,
+    /// `quote`, `position` or `last_fill`.
+    pub mark_source: Option<String>,
+    pub unrealized_pnl: f64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PortfolioPnlResultPayload {
+    pub as_of_ms: i64,
+    pub totals: PnlBucketPayload,
+    pub by_strategy: Vec<PnlBucketPayload>,
+    pub by_venue: Vec<PnlBucketPayload>,
+    /// Keyed `venue:venue_symbol`.
+    pub by_instrument: Vec<PnlBucketPayload>,
+    /// Keyed by UTC date; unrealized PnL is attributed to the `as_of_ms` day.
+    pub by_day: Vec<PnlBucketPayload>,
+    pub positions: Vec<PnlPositionPayload>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperAccountAction {
+    /// Discard paper orders, fills and positions and restart from `balances`.
+    Reset,
+    /// Add `balances` to the paper account; negative amounts withdraw.
+    Fund,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PortfolioPaperAccountPayload {
+    pub action: PaperAccountAction,
+    /// Asset → amount. An empty reset restarts from the configured starting
+    /// balances.
+    #[serde(default)]
+    pub balances: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PortfolioPaperAccountResultPayload {
+    pub action: PaperAccountAction,
+    /// Paper balances after the action.
+    pub balances: Vec<BalanceSnapshot>,
+    /// Simulated orders and fills dropped from engine state by a reset.
+    pub cleared_orders: usize,
+    pub cleared_fills: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionOpenOrdersPayload {
     pub orders: Vec<OpenOrderSnapshot>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct ExecutionFillsResultPayload {
     pub fills: Vec<FillReport>,
 }
@@ -592,9 +1240,40 @@ mod tests {
             RequestKind::from_kind("Portfolio.Exposure"),
             Some(RequestKind::Portfolio(PortfolioCommand::Exposure))
         );
+        assert_eq!(
+            RequestKind::from_kind("MarketData.Subscribe"),
+            Some(RequestKind::MarketData(MarketDataCommand::Subscribe))
+        );
+        assert_eq!(
+            RequestKind::from_kind("Control.Hello"),
+            Some(RequestKind::Control(ControlCommand::Hello))
+        );
         assert_eq!(RequestKind::from_kind("Unknown.Command"), None);
     }
 
+    #[test]
+    fn error_codes_round_trip_and_tolerate_new_codes() {
+        let payload = ErrorPayload::new(ErrorCode::RiskDenied, "daily loss limit");
+        let encoded = serde_json::to_value(&payload).expect("encode");
+        assert_eq!(
+            encoded,
+            json!({"ok": false, "code": "risk_denied", "error": "daily loss limit"})
+        );
+        assert_eq!(ErrorCode::RiskDenied.as_str(), "risk_denied");
+
+        let decoded: ErrorPayload =
+            serde_json::from_value(json!({"ok": false, "code": "brand_new", "error": "x"}))
+                .expect("decode");
+        assert_eq!(decoded.code, ErrorCode::Unknown);
+    }
+
+    #[test]
+    fn negotiates_highest_common_protocol_version() {
+        assert_eq!(negotiate_protocol_version(&[1, 2, 3]), Some(1));
+        assert_eq!(negotiate_protocol_version(&[9]), None);
+        assert_eq!(negotiate_protocol_version(&[]), None);
+    }
+
     #[test]
     fn response_keeps_correlation_id() {
         let req = Envelope::new("Engine.Status", json!({}));
@@ -603,4 +1282,20 @@ mod tests {
         assert_eq!(resp.id, req.id);
         assert_eq!(resp.kind, "Engine.Status.Response");
     }
+
+    #[test]
+    fn correlation_id_defaults_to_request_id_and_is_echoed() {
+        let req = Envelope::new("Engine.Status", json!({}));
+        assert_eq!(req.effective_correlation_id(), req.id.to_string());
+        let encoded = serde_json::to_value(&req).expect("encode");
+        assert!(encoded.get("correlation_id").is_none());
+        assert!(encoded.get("traceparent").is_none());
+
+        let req = req
+            .with_correlation_id("bot-42")
+            .with_traceparent(Some("00-abc-def-01".to_string()));
+        let resp = Envelope::response_to(&req, json!({ "ok": true }));
+        assert_eq!(resp.correlation_id.as_deref(), Some("bot-42"));
+        assert_eq!(resp.traceparent, None);
+    }
 }
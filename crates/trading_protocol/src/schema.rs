@@ -0,0 +1,489 @@
+//! JSON Schemas derived from the protocol types. The checked-in copies under
+//! `schemas/` are compared against these in tests, so a payload change must
+//! regenerate them (`UPDATE_SCHEMAS=1 cargo test -p trading_protocol`).
+
+use std::collections::BTreeMap;
+
+use crate::*;
+
+macro_rules! schema_map {
+    ($($ty:ident),* $(,)?) => {{
+        let mut schemas = BTreeMap::new();
+        $(
+            schemas.insert(
+                stringify!($ty).to_string(),
+                schemars::schema_for!($ty).to_value(),
+            );
+        )*
+        schemas
+    }};
+}
+
+/// JSON Schema (draft 2020-12) for the envelope and every typed payload,
+/// keyed by Rust type name.
+pub fn protocol_schemas() -> BTreeMap<String, serde_json::Value> {
+    schema_map!(
+        Envelope,
+        ErrorPayload,
+        Event,
+        CapabilitiesRequestPayload,
+        CapabilitiesPayload,
+        CapabilitiesResultPayload,
+        PingResultPayload,
+        HelloPayload,
+        HelloResultPayload,
+        EventSubscribePayload,
+        EventSubscribeResultPayload,
+        EngineModePayload,
+        EngineModeResultPayload,
+        EngineStatePayload,
+        EngineStatusPayload,
+        EngineExportSnapshotPayload,
+        EngineExportSnapshotResultPayload,
+        EngineReplayPayload,
+        EngineReplayResultPayload,
+        StrategyIdPayload,
+        StrategySummaryPayload,
+        StrategyListResultPayload,
+        StrategyResultPayload,
+        CandidateUploadPayload,
+        CandidateUploadResultPayload,
+        CandidatePromotePayload,
+        CandidatePromoteResultPayload,
+        RiskStatusResultPayload,
+        RiskOverridePayload,
+        RiskOverrideResultPayload,
+        RiskScopedOverridePayload,
+        ExecutionPlacePayload,
+        ExecutionPlaceResultPayload,
+        ExecutionCancelPayload,
+        ExecutionCancelResultPayload,
+        ExecutionGetPayload,
+        ExecutionGetResultPayload,
+        ExecutionOpenOrdersPayload,
+        ExecutionFillsPayload,
+        ExecutionFillsResultPayload,
+        PortfolioPositionsPayload,
+        PortfolioBalancesPayload,
+        PortfolioSummaryPayload,
+        PortfolioPnlPayload,
+        PortfolioPnlResultPayload,
+        PortfolioPaperAccountPayload,
+        PortfolioPaperAccountResultPayload,
+        MarketDataQuotePayload,
+        MarketQuotePayload,
+        MarketDataSubscribePayload,
+        MarketDataSubscribeResultPayload,
+        MarketDataRegimePayload,
+        MarketDataRegimeResultPayload,
+        MarketDataProductPayload,
+        MarketDataProductResultPayload,
+    )
+}
+
+/// Request and response payload types per command kind: the type under the
+/// response's `result` field, or of its top-level fields for commands that
+/// answer without one.
+pub fn command_schemas() -> Vec<CommandSchemaPayload> {
+    let entry = |kind: &str, request: Option<&str>, result: &str| CommandSchemaPayload {
+        kind: kind.to_string(),
+        request: request.map(str::to_string),
+        result: Some(result.to_string()),
+        response: None,
+    };
+    let fields = |kind: &str, request: Option<&str>, response: &str| CommandSchemaPayload {
+        kind: kind.to_string(),
+        request: request.map(str::to_string),
+        result: None,
+        response: Some(response.to_string()),
+    };
+
+    vec![
+        fields(ControlCommand::Start.as_kind(), None, "EngineStatusPayload"),
+        fields(ControlCommand::Stop.as_kind(), None, "EngineStatusPayload"),
+        fields(
+            ControlCommand::Status.as_kind(),
+            None,
+            "EngineStatusPayload",
+        ),
+        fields(ControlCommand::Ping.as_kind(), None, "PingResultPayload"),
+        fields(
+            ControlCommand::Capabilities.as_kind(),
+            Some("CapabilitiesRequestPayload"),
+            "CapabilitiesResultPayload",
+        ),
+        entry(
+            ControlCommand::Hello.as_kind(),
+            Some("HelloPayload"),
+            "HelloResultPayload",
+        ),
+        fields(
+            ControlCommand::SubscribeEvents.as_kind(),
+            Some("EventSubscribePayload"),
+            "EventSubscribeResultPayload",
+        ),
+        fields(EngineCommand::Status.as_kind(), None, "EngineStatusPayload"),
+        fields(EngineCommand::Pause.as_kind(), None, "EngineStatusPayload"),
+        fields(EngineCommand::Resume.as_kind(), None, "EngineStatusPayload"),
+        fields(
+            EngineCommand::KillSwitch.as_kind(),
+            None,
+            "EngineStatusPayload",
+        ),
+        fields(
+            EngineCommand::GetMode.as_kind(),
+            None,
+            "EngineModeResultPayload",
+        ),
+        fields(
+            EngineCommand::SetMode.as_kind(),
+            Some("EngineModePayload"),
+            "EngineModeResultPayload",
+        ),
+        entry(
+            EngineCommand::ExportSnapshot.as_kind(),
+            Some("EngineExportSnapshotPayload"),
+            "EngineExportSnapshotResultPayload",
+        ),
+        entry(
+            EngineCommand::Replay.as_kind(),
+            Some("EngineReplayPayload"),
+            "EngineReplayResultPayload",
+        ),
+        fields(
+            StrategyCommand::List.as_kind(),
+            None,
+            "StrategyListResultPayload",
+        ),
+        fields(
+            StrategyCommand::Enable.as_kind(),
+            Some("StrategyIdPayload"),
+            "StrategyResultPayload",
+        ),
+        fields(
+            StrategyCommand::Disable.as_kind(),
+            Some("StrategyIdPayload"),
+            "StrategyResultPayload",
+        ),
+        fields(
+            StrategyCommand::UploadCandidate.as_kind(),
+            Some("CandidateUploadPayload"),
+            "CandidateUploadResultPayload",
+        ),
+        fields(
+            StrategyCommand::PromoteCandidate.as_kind(),
+            Some("CandidatePromotePayload"),
+            "CandidatePromoteResultPayload",
+        ),
+        fields(
+            RiskCommand::Status.as_kind(),
+            None,
+            "RiskStatusResultPayload",
+        ),
+        fields(
+            RiskCommand::Override.as_kind(),
+            Some("RiskOverridePayload"),
+            "RiskOverrideResultPayload",
+        ),
+        entry(
+            ExecutionCommand::Place.as_kind(),
+            Some("ExecutionPlacePayload"),
+            "ExecutionPlaceResultPayload",
+        ),
+        fields(
+            ExecutionCommand::Cancel.as_kind(),
+            Some("ExecutionCancelPayload"),
+            "ExecutionCancelResultPayload",
+        ),
+        fields(
+            ExecutionCommand::Get.as_kind(),
+            Some("ExecutionGetPayload"),
+            "ExecutionGetResultPayload",
+        ),
+        entry(
+            ExecutionCommand::OpenOrders.as_kind(),
+            None,
+            "ExecutionOpenOrdersPayload",
+        ),
+        entry(
+            ExecutionCommand::Fills.as_kind(),
+            Some("ExecutionFillsPayload"),
+            "ExecutionFillsResultPayload",
+        ),
+        entry(
+            PortfolioCommand::Positions.as_kind(),
+            None,
+            "PortfolioPositionsPayload",
+        ),
+        entry(
+            PortfolioCommand::Balances.as_kind(),
+            None,
+            "PortfolioBalancesPayload",
+        ),
+        entry(
+            PortfolioCommand::Exposure.as_kind(),
+            None,
+            "PortfolioSummaryPayload",
+        ),
+        entry(
+            PortfolioCommand::Pnl.as_kind(),
+            Some("PortfolioPnlPayload"),
+            "PortfolioPnlResultPayload",
+        ),
+        entry(
+            PortfolioCommand::PaperAccount.as_kind(),
+            Some("PortfolioPaperAccountPayload"),
+            "PortfolioPaperAccountResultPayload",
+        ),
+        entry(
+            MarketDataCommand::Quote.as_kind(),
+            Some("MarketDataQuotePayload"),
+            "MarketQuotePayload",
+        ),
+        entry(
+            MarketDataCommand::Subscribe.as_kind(),
+            Some("MarketDataSubscribePayload"),
+            "MarketDataSubscribeResultPayload",
+        ),
+        entry(
+            MarketDataCommand::Regime.as_kind(),
+            Some("MarketDataRegimePayload"),
+            "MarketDataRegimeResultPayload",
+        ),
+        entry(
+            MarketDataCommand::Product.as_kind(),
+            Some("MarketDataProductPayload"),
+            "MarketDataProductResultPayload",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use exchange_core::{
+        AssetClass, InstrumentRef, InstrumentType, NormalizedOrderRequest, OrderSide, OrderType,
+        TimeInForce,
+    };
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+    use strategy_core::{MarketRegime, RegimeContext};
+
+    use super::*;
+
+    fn schema_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas")
+    }
+
+    fn render(value: &serde_json::Value) -> String {
+        let mut rendered = serde_json::to_string_pretty(value).expect("schema encodes");
+        rendered.push('\n');
+        rendered
+    }
+
+    /// Serializes `value`, validates it against its schema and checks that it
+    /// decodes back to the same JSON.
+    fn assert_round_trip<T>(name: &str, value: &T)
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let schemas = protocol_schemas();
+        let schema = schemas
+            .get(name)
+            .unwrap_or_else(|| panic!("no schema for {}", name));
+        let encoded = serde_json::to_value(value).expect("value encodes");
+        let validator = jsonschema::validator_for(schema).expect("schema compiles");
+        let errors: Vec<String> = validator
+            .iter_errors(&encoded)
+            .map(|err| err.to_string())
+            .collect();
+        assert!(
+            errors.is_empty(),
+            "{} failed its schema: {:?}",
+            name,
+            errors
+        );
+
+        let decoded: T = serde_json::from_value(encoded.clone()).expect("value decodes");
+        assert_eq!(serde_json::to_value(decoded).expect("re-encodes"), encoded);
+    }
+
+    #[test]
+    fn checked_in_schemas_match_protocol_types() {
+        let dir = schema_dir();
+        let update = std::env::var_os("UPDATE_SCHEMAS").is_some();
+        let mut expected = protocol_schemas()
+            .into_iter()
+            .map(|(name, schema)| (format!("{}.schema.json", name), render(&schema)))
+            .collect::<BTreeMap<_, _>>();
+        expected.insert(
+            "commands.json".to_string(),
+            render(&serde_json::to_value(command_schemas()).expect("commands encode")),
+        );
+
+        if update {
+            std::fs::create_dir_all(&dir).expect("create schema dir");
+            for entry in std::fs::read_dir(&dir).expect("read schema dir").flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if !expected.contains_key(&file_name) {
+                    std::fs::remove_file(entry.path()).expect("remove stale schema");
+                }
+            }
+            for (file_name, rendered) in &expected {
+                std::fs::write(dir.join(file_name), rendered).expect("write schema");
+            }
+            return;
+        }
+
+        for (file_name, rendered) in &expected {
+            let checked_in = std::fs::read_to_string(dir.join(file_name)).unwrap_or_default();
+            assert!(
+                &checked_in == rendered,
+                "schemas/{} is out of date; run UPDATE_SCHEMAS=1 cargo test -p trading_protocol",
+                file_name
+            );
+        }
+        let stale: Vec<String> = std::fs::read_dir(&dir)
+            .expect("read schema dir")
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|file_name| !expected.contains_key(file_name))
+            .collect();
+        assert!(stale.is_empty(), "stale schema files: {:?}", stale);
+    }
+
+    #[test]
+    fn command_table_references_known_types() {
+        let schemas = protocol_schemas();
+        for command in command_schemas() {
+            assert!(
+                RequestKind::from_kind(&command.kind).is_some(),
+                "{}",
+                command.kind
+            );
+            let typed = [&command.request, &command.result, &command.response];
+            for name in typed.into_iter().flatten() {
+                assert!(schemas.contains_key(name), "{} has no schema", name);
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_declares_its_response_type() {
+        for command in command_schemas() {
+            assert!(
+                command.result.is_some() != command.response.is_some(),
+                "{} must name exactly one of a result or a response type",
+                command.kind
+            );
+        }
+    }
+
+    #[test]
+    fn payloads_round_trip_through_their_schemas() {
+        assert_round_trip(
+            "Envelope",
+            &Envelope::new("Engine.Status", json!({"verbose": true})),
+        );
+        assert_round_trip(
+            "ErrorPayload",
+            &ErrorPayload::new(ErrorCode::VenueError, "rate limited"),
+        );
+        assert_round_trip(
+            "HelloPayload",
+            &HelloPayload {
+                client_name: Some("test".to_string()),
+                protocol_versions: vec![1],
+                status_schema_version: Some(STATUS_SCHEMA_VERSION),
+                features: vec!["market_data".to_string()],
+            },
+        );
+        assert_round_trip(
+            "ExecutionPlacePayload",
+            &ExecutionPlacePayload {
+                order: NormalizedOrderRequest {
+                    venue: "coinbase_at".to_string(),
+                    symbol: "BTC-USD".to_string(),
+                    instrument: InstrumentRef {
+                        venue: "coinbase_at".to_string(),
+                        venue_symbol: "BTC-USD".to_string(),
+                        asset_class: AssetClass::Crypto,
+                        instrument_type: InstrumentType::Spot,
+                        base: Some("BTC".to_string()),
+                        quote: Some("USD".to_string()),
+                        expiry_ts_ms: None,
+                        strike: None,
+                        option_right: None,
+                        contract_multiplier: None,
+                    },
+                    strategy_id: "s1".to_string(),
+                    client_order_id: "c1".to_string(),
+                    intent_id: None,
+                    side: OrderSide::Buy,
+                    order_type: OrderType::Limit,
+                    qty: 0.5,
+                    quote_size: None,
+                    limit_price: Some(100.0),
+                    stop_price: None,
+                    tif: Some(TimeInForce::Gtc),
+                    post_only: false,
+                    reduce_only: false,
+                    requested_notional_cents: 5_000,
+                },
+                approval_token: None,
+                priority: Some(OrderPriority::Urgent),
+                max_queue_wait_ms: Some(250),
+            },
+        );
+        assert_round_trip(
+            "RiskOverridePayload",
+            &RiskOverridePayload {
+                action: "kill_venue".to_string(),
+                value: Some(json!(true)),
+                venue: Some("coinbase_at".to_string()),
+                strategy_id: None,
+            },
+        );
+        assert_round_trip(
+            "Event",
+            &Event::MarketData {
+                venue: "coinbase_at".to_string(),
+                status: "disconnected".to_string(),
+                message: Some("socket closed".to_string()),
+            },
+        );
+        assert_round_trip(
+            "Event",
+            &Event::Regime {
+                context: RegimeContext {
+                    venue: "coinbase_at".to_string(),
+                    symbol: "BTC-USD".to_string(),
+                    regime: MarketRegime::Trending,
+                    spread_bps: 1.5,
+                    realized_volatility: 0.001,
+                    momentum_lookback_return: 0.01,
+                    order_book_imbalance: 0.2,
+                    ts_ms: 1,
+                },
+                previous: MarketRegime::RangeBound,
+            },
+        );
+        assert_round_trip(
+            "PortfolioPnlResultPayload",
+            &PortfolioPnlResultPayload {
+                as_of_ms: 1,
+                totals: PnlBucketPayload {
+                    key: "total".to_string(),
+                    realized_pnl: 1.5,
+                    ..PnlBucketPayload::default()
+                },
+                by_strategy: Vec::new(),
+                by_venue: Vec::new(),
+                by_instrument: Vec::new(),
+                by_day: Vec::new(),
+                positions: Vec::new(),
+            },
+        );
+    }
+}
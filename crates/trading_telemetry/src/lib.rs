@@ -0,0 +1,147 @@
+//! Tracing setup shared by the daemon and its clients: optional console logs,
+//! OTLP span export and W3C `traceparent` propagation across the UDS protocol.
+//!
+//! Spans are exported only when `OTEL_EXPORTER_OTLP_ENDPOINT` (or
+//! `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set, e.g. `http://localhost:4318`
+//! for a local collector; the exporter reads the standard `OTEL_*` variables.
+
+use std::collections::HashMap;
+
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+const TRACEPARENT: &str = "traceparent";
+
+/// Flushes and shuts down span export when dropped; keep it alive for the
+/// lifetime of the process.
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl TelemetryGuard {
+    pub fn exporting(&self) -> bool {
+        self.provider.is_some()
+    }
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(err) = provider.shutdown() {
+                eprintln!("failed to flush OTLP spans: {}", err);
+            }
+        }
+    }
+}
+
+fn otlp_configured() -> bool {
+    [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .iter()
+    .any(|name| std::env::var(name).is_ok_and(|value| !value.trim().is_empty()))
+}
+
+/// Installs the global subscriber: INFO console logs when `console_logs` is
+/// set, plus an OTLP span layer when an OTLP endpoint is configured. An
+/// exporter that fails to build is reported and skipped.
+pub fn init(service_name: &'static str, console_logs: bool) -> TelemetryGuard {
+    let provider = if otlp_configured() {
+        match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(Resource::builder().with_service_name(service_name).build())
+                    .build(),
+            ),
+            Err(err) => {
+                eprintln!("OTLP span export disabled: {}", err);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(service_name))
+            .with_filter(LevelFilter::INFO)
+    });
+    let console_layer =
+        console_logs.then(|| tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO));
+
+    if let Err(err) = tracing_subscriber::registry()
+        .with(console_layer)
+        .with(otel_layer)
+        .try_init()
+    {
+        eprintln!("tracing already initialized: {}", err);
+    }
+
+    TelemetryGuard { provider }
+}
+
+/// W3C `traceparent` of the current span, when it is being exported.
+pub fn current_traceparent() -> Option<String> {
+    let context = tracing::Span::current().context();
+    if !context.span().span_context().is_valid() {
+        return None;
+    }
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(&context, &mut carrier);
+    carrier.remove(TRACEPARENT)
+}
+
+/// Makes `span` a child of the remote span described by `traceparent`.
+/// Malformed values are ignored and the span stays a root.
+pub fn set_remote_parent(span: &tracing::Span, traceparent: &str) {
+    let carrier = HashMap::from([(TRACEPARENT.to_string(), traceparent.to_string())]);
+    let context = TraceContextPropagator::new().extract(&carrier);
+    if context.span().span_context().is_valid() {
+        let _ = span.set_parent(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::subscriber::with_default;
+
+    use super::*;
+
+    #[test]
+    fn traceparent_round_trips_through_a_remote_parent() {
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        with_default(subscriber, || {
+            assert_eq!(current_traceparent(), None);
+
+            let client = tracing::info_span!("client");
+            let traceparent = client.in_scope(current_traceparent).expect("traceparent");
+            assert!(traceparent.starts_with("00-"));
+
+            let server = tracing::info_span!("server");
+            set_remote_parent(&server, &traceparent);
+            let trace_id = |span: &tracing::Span| span.context().span().span_context().trace_id();
+            assert_eq!(trace_id(&server), trace_id(&client));
+
+            let orphan = tracing::info_span!("orphan");
+            set_remote_parent(&orphan, "not-a-traceparent");
+            assert_ne!(trace_id(&orphan), trace_id(&client));
+        });
+    }
+}
@@ -4,15 +4,19 @@ use exchange_core::{
     AssetClass, InstrumentRef, InstrumentType, NormalizedOrderRequest, OptionRight, OrderSide,
     OrderType, TimeInForce,
 };
-use futures::{SinkExt, StreamExt};
-use tokio::net::UnixStream;
-use tokio_util::codec::Framed;
+use futures::StreamExt;
+use trading_client::{ClientConfig, ClientError, TradingClient};
 use trading_protocol::{
-    create_codec, CandidatePromotePayload, CandidateUploadPayload, ControlCommand, EngineCommand,
-    EngineMode, EngineModePayload, Envelope, ExecutionCancelPayload, ExecutionCommand,
-    ExecutionFillsPayload, ExecutionGetPayload, ExecutionPlacePayload, PortfolioCommand,
-    RiskCommand, RiskOverridePayload, RiskScopedOverridePayload, StrategyCommand,
-    DEFAULT_SOCKET_PATH,
+    command_schemas, protocol_schemas, CandidatePromotePayload, CandidateUploadPayload,
+    CapabilitiesRequestPayload, ControlCommand, EngineCommand, EngineExportSnapshotPayload,
+    EngineMode, EngineModePayload, EngineReplayPayload, Envelope, ExecutionCancelPayload,
+    ExecutionCommand, ExecutionFillsPayload, ExecutionGetPayload, ExecutionPlacePayload,
+    HelloPayload, MarketDataCommand, MarketDataProductPayload, MarketDataQuotePayload,
+    MarketDataRegimePayload, MarketDataSubscribePayload, OrderPriority, PaperAccountAction,
+    PnlBucketPayload, PortfolioCommand, PortfolioPaperAccountPayload, PortfolioPnlPayload,
+    PortfolioPnlResultPayload, ProtocolSchemasPayload, RiskCommand, RiskOverridePayload,
+    RiskScopedOverridePayload, StrategyCommand, DEFAULT_SOCKET_PATH, STATUS_SCHEMA_VERSION,
+    SUPPORTED_PROTOCOL_VERSIONS,
 };
 use uuid::Uuid;
 
@@ -24,6 +28,14 @@ struct Cli {
     #[arg(long, default_value = DEFAULT_SOCKET_PATH)]
     socket: String,
 
+    /// Per-request timeout in milliseconds.
+    #[arg(long, default_value_t = 10_000)]
+    timeout_ms: u64,
+
+    /// Correlation id recorded with the request's journal entries, events and spans.
+    #[arg(long)]
+    correlation_id: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -157,7 +169,38 @@ impl From<OptionRightArg> for OptionRight {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum PriorityArg {
+    Urgent,
+    Normal,
+}
+
+impl From<PriorityArg> for OrderPriority {
+    fn from(value: PriorityArg) -> Self {
+        match value {
+            PriorityArg::Urgent => OrderPriority::Urgent,
+            PriorityArg::Normal => OrderPriority::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum PaperAccountActionArg {
+    Reset,
+    Fund,
+}
+
+impl From<PaperAccountActionArg> for PaperAccountAction {
+    fn from(value: PaperAccountActionArg) -> Self {
+        match value {
+            PaperAccountActionArg::Reset => PaperAccountAction::Reset,
+            PaperAccountActionArg::Fund => PaperAccountAction::Fund,
+        }
+    }
+}
+
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Legacy status command (maps to Engine.Status)
     Status,
@@ -168,7 +211,23 @@ enum Commands {
     /// Send Control.Ping command
     Ping,
     /// Send Control.Capabilities command
-    Capabilities,
+    Capabilities {
+        /// Include the daemon's protocol JSON Schemas.
+        #[arg(long)]
+        include_schemas: bool,
+    },
+    /// Print the protocol JSON Schemas this client was built with (no daemon needed)
+    Schema {
+        /// Print only the schema for this payload type, e.g. ExecutionPlacePayload.
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Send Control.Hello handshake
+    Hello {
+        /// Optional feature to request; repeatable.
+        #[arg(long = "feature")]
+        features: Vec<String>,
+    },
     /// Send Engine.Status command
     EngineStatus,
     /// Send Engine.Pause command
@@ -184,6 +243,24 @@ enum Commands {
         #[arg(long, value_enum)]
         mode: ModeArg,
     },
+    /// Send Engine.ExportSnapshot command
+    EngineExportSnapshot {
+        /// Write the bundle under the daemon's exports directory with this name.
+        #[arg(long)]
+        file_name: Option<String>,
+        /// Fetch the bundle inline and write it to this local path.
+        #[arg(long, conflicts_with = "file_name")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Send Engine.Replay command: replay journals and report divergences
+    EngineReplay {
+        /// Start of the journal window (ms since epoch); defaults to the oldest entry.
+        #[arg(long)]
+        from_ms: Option<i64>,
+        /// End of the journal window (ms since epoch); defaults to now.
+        #[arg(long)]
+        to_ms: Option<i64>,
+    },
     /// Send Strategy.List command
     StrategyList,
     /// Send Strategy.Enable command
@@ -271,8 +348,14 @@ enum Commands {
         order_type: OrderTypeArg,
         #[arg(long)]
         qty: f64,
+        /// Quote amount to spend or receive on a market order, instead of `qty`.
+        #[arg(long)]
+        quote_size: Option<f64>,
         #[arg(long)]
         limit_price: Option<f64>,
+        /// Trigger price; makes a limit order a stop-limit.
+        #[arg(long)]
+        stop_price: Option<f64>,
         #[arg(long, value_enum)]
         tif: Option<TifArg>,
         #[arg(long, default_value_t = false)]
@@ -299,6 +382,12 @@ enum Commands {
         requested_notional_cents: i64,
         #[arg(long)]
         approval_token: Option<String>,
+        /// Pacing priority; defaults to urgent for IOC/FOK, normal otherwise
+        #[arg(long, value_enum)]
+        priority: Option<PriorityArg>,
+        /// Maximum time to wait in the venue pacing queue
+        #[arg(long)]
+        max_queue_wait_ms: Option<u64>,
     },
     /// Send Execution.Cancel command
     ExecutionCancel {
@@ -325,6 +414,61 @@ enum Commands {
     PortfolioBalances,
     /// Send Portfolio.Exposure command
     PortfolioExposure,
+    /// Send Portfolio.Pnl command and render the PnL breakdown
+    PortfolioPnl {
+        #[arg(long)]
+        strategy_id: Option<String>,
+        #[arg(long)]
+        venue: Option<String>,
+        #[arg(long)]
+        since_ts_ms: Option<i64>,
+        /// Print the raw response instead of tables
+        #[arg(long)]
+        json: bool,
+    },
+    /// Send Portfolio.PaperAccount command to reset or fund the paper account
+    PortfolioPaperAccount {
+        #[arg(long, value_enum)]
+        action: PaperAccountActionArg,
+        /// Comma-separated ASSET=AMOUNT pairs, e.g. USD=250000,BTC=2
+        #[arg(long, value_delimiter = ',', value_parser = parse_balance)]
+        balances: Vec<(String, f64)>,
+    },
+    /// Send MarketData.Quote command
+    MarketDataQuote {
+        #[arg(long)]
+        venue: String,
+        #[arg(long)]
+        venue_symbol: String,
+    },
+    /// Send MarketData.Product command
+    MarketDataProduct {
+        #[arg(long)]
+        venue: String,
+        #[arg(long)]
+        venue_symbol: String,
+    },
+    /// Send MarketData.Regime command
+    MarketDataRegime {
+        #[arg(long)]
+        venue: Option<String>,
+        #[arg(long)]
+        venue_symbol: Option<String>,
+    },
+    /// Send MarketData.Subscribe command
+    MarketDataSubscribe {
+        #[arg(long)]
+        venue: String,
+        /// Comma-separated venue symbols, e.g. BTC-USD,ETH-USD
+        #[arg(long, value_delimiter = ',', required = true)]
+        venue_symbols: Vec<String>,
+    },
+    /// Stream daemon events (Control.SubscribeEvents) until interrupted
+    Events {
+        /// Replay the daemon's recent events first.
+        #[arg(long)]
+        replay_recent: bool,
+    },
     /// Send a raw JSON command
     Raw {
         #[arg(short, long)]
@@ -332,6 +476,45 @@ enum Commands {
     },
 }
 
+/// All protocol schemas with the command table, or the schema of one type.
+fn schema_output(name: Option<&str>) -> Result<serde_json::Value> {
+    let mut types = protocol_schemas();
+    match name {
+        Some(name) => types.remove(name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown payload type '{}'; known types: {}",
+                name,
+                types.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        }),
+        None => Ok(serde_json::to_value(ProtocolSchemasPayload {
+            commands: command_schemas(),
+            types,
+        })?),
+    }
+}
+
+/// Adds a hint to connection failures; other errors pass through.
+fn client_error(err: ClientError) -> anyhow::Error {
+    match err {
+        ClientError::Connect { .. } => anyhow::Error::new(err)
+            .context("Failed to connect to daemon socket. Is the daemon running?"),
+        other => other.into(),
+    }
+}
+
+/// Parses an `ASSET=AMOUNT` balance argument.
+fn parse_balance(raw: &str) -> std::result::Result<(String, f64), String> {
+    let (asset, amount) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected ASSET=AMOUNT, got '{}'", raw))?;
+    let amount = amount
+        .trim()
+        .parse::<f64>()
+        .map_err(|err| format!("invalid amount for {}: {}", asset.trim(), err))?;
+    Ok((asset.trim().to_ascii_uppercase(), amount))
+}
+
 fn parse_risk_override_value(raw: &str) -> serde_json::Value {
     match serde_json::from_str(raw) {
         Ok(value) => value,
@@ -345,15 +528,124 @@ fn parse_risk_override_value(raw: &str) -> serde_json::Value {
     }
 }
 
+fn format_pnl_rows(title: &str, rows: &[PnlBucketPayload]) -> String {
+    let mut out = format!(
+        "{}\n{:<28} {:>14} {:>14} {:>12} {:>14} {:>16} {:>6}\n",
+        title, "KEY", "REALIZED", "UNREALIZED", "FEES", "NET", "TURNOVER", "FILLS"
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "{:<28} {:>14.2} {:>14.2} {:>12.2} {:>14.2} {:>16.2} {:>6}\n",
+            row.key,
+            row.realized_pnl,
+            row.unrealized_pnl,
+            row.fees,
+            row.net_pnl,
+            row.turnover,
+            row.fills
+        ));
+    }
+    out
+}
+
+fn render_pnl(result: &PortfolioPnlResultPayload) -> String {
+    let mut out = format!("PnL as of {} ms\n\n", result.as_of_ms);
+    out.push_str(&format_pnl_rows(
+        "Totals",
+        std::slice::from_ref(&result.totals),
+    ));
+    for (title, rows) in [
+        ("By strategy", &result.by_strategy),
+        ("By venue", &result.by_venue),
+        ("By instrument", &result.by_instrument),
+        ("By day (UTC)", &result.by_day),
+    ] {
+        out.push('\n');
+        out.push_str(&format_pnl_rows(title, rows));
+    }
+
+    out.push_str(&format!(
+        "\nOpen positions\n{:<28} {:<14} {:<16} {:>12} {:>14} {:>14} {:<10} {:>14}\n",
+        "STRATEGY", "VENUE", "SYMBOL", "QTY", "AVG", "MARK", "SOURCE", "UNREALIZED"
+    ));
+    for position in &result.positions {
+        out.push_str(&format!(
+            "{:<28} {:<14} {:<16} {:>12.6} {:>14.4} {:>14} {:<10} {:>14.2}\n",
+            position.strategy_id,
+            position.venue,
+            position.venue_symbol,
+            position.qty,
+            position.avg_price,
+            position
+                .mark_price
+                .map(|mark| format!("{mark:.4}"))
+                .unwrap_or_else(|| "-".to_string()),
+            position.mark_source.as_deref().unwrap_or("-"),
+            position.unrealized_pnl
+        ));
+    }
+    out
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let stream = UnixStream::connect(&cli.socket)
-        .await
-        .context("Failed to connect to daemon socket. Is the daemon running?")?;
+    if let Commands::Schema { name } = &cli.command {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema_output(name.as_deref())?)?
+        );
+        return Ok(());
+    }
 
-    let mut framed = Framed::new(stream, create_codec());
+    // Spans are exported when OTEL_EXPORTER_OTLP_ENDPOINT is set; flushed on exit.
+    let _telemetry = trading_telemetry::init("tradingctl", false);
+    let client = TradingClient::new(
+        ClientConfig::new(&cli.socket)
+            .with_request_timeout(std::time::Duration::from_millis(cli.timeout_ms)),
+    );
+
+    match &cli.command {
+        Commands::Events { replay_recent } => {
+            let mut events = client
+                .subscribe_events(*replay_recent)
+                .await
+                .map_err(client_error)?;
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) => println!("{}", serde_json::to_string(&event)?),
+                    Err(err) => eprintln!("Event stream: {}", err),
+                }
+            }
+            return Ok(());
+        }
+        Commands::EngineExportSnapshot {
+            output: Some(path), ..
+        } => {
+            let bundle = client.export_snapshot(None).await.map_err(client_error)?;
+            std::fs::write(path, serde_json::to_vec_pretty(&bundle)?)
+                .with_context(|| format!("Failed to write snapshot to {}", path.display()))?;
+            println!("Snapshot written to {}", path.display());
+            return Ok(());
+        }
+        Commands::PortfolioPnl {
+            strategy_id,
+            venue,
+            since_ts_ms,
+            json: false,
+        } => {
+            let filter = PortfolioPnlPayload {
+                strategy_id: strategy_id.clone(),
+                venue: venue.clone(),
+                since_ts_ms: *since_ts_ms,
+            };
+            let pnl = client.pnl(&filter).await.map_err(client_error)?;
+            print!("{}", render_pnl(&pnl));
+            return Ok(());
+        }
+        _ => {}
+    }
 
     let (kind, payload) = match cli.command {
         Commands::Status | Commands::EngineStatus => {
@@ -362,10 +654,28 @@ async fn main() -> Result<()> {
         Commands::Start => (ControlCommand::Start.as_kind(), serde_json::json!({})),
         Commands::Stop => (ControlCommand::Stop.as_kind(), serde_json::json!({})),
         Commands::Ping => (ControlCommand::Ping.as_kind(), serde_json::json!({})),
-        Commands::Capabilities => (
-            ControlCommand::Capabilities.as_kind(),
-            serde_json::json!({}),
-        ),
+        Commands::Capabilities { include_schemas } => {
+            let payload = CapabilitiesRequestPayload { include_schemas };
+            (
+                ControlCommand::Capabilities.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::Schema { .. } | Commands::Events { .. } => {
+            unreachable!("handled before sending a request")
+        }
+        Commands::Hello { features } => {
+            let payload = HelloPayload {
+                client_name: Some("tradingctl".to_string()),
+                protocol_versions: SUPPORTED_PROTOCOL_VERSIONS.to_vec(),
+                status_schema_version: Some(STATUS_SCHEMA_VERSION),
+                features,
+            };
+            (
+                ControlCommand::Hello.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
         Commands::Pause => (EngineCommand::Pause.as_kind(), serde_json::json!({})),
         Commands::Resume => (EngineCommand::Resume.as_kind(), serde_json::json!({})),
         Commands::KillSwitch => (EngineCommand::KillSwitch.as_kind(), serde_json::json!({})),
@@ -377,6 +687,17 @@ async fn main() -> Result<()> {
                 serde_json::to_value(payload)?,
             )
         }
+        Commands::EngineExportSnapshot { file_name, .. } => {
+            let payload = EngineExportSnapshotPayload { file_name };
+            (
+                EngineCommand::ExportSnapshot.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::EngineReplay { from_ms, to_ms } => (
+            EngineCommand::Replay.as_kind(),
+            serde_json::to_value(EngineReplayPayload { from_ms, to_ms })?,
+        ),
         Commands::StrategyList => (StrategyCommand::List.as_kind(), serde_json::json!({})),
         Commands::StrategyEnable { strategy_id } => (
             StrategyCommand::Enable.as_kind(),
@@ -477,7 +798,9 @@ async fn main() -> Result<()> {
             side,
             order_type,
             qty,
+            quote_size,
             limit_price,
+            stop_price,
             tif,
             post_only,
             reduce_only,
@@ -491,6 +814,8 @@ async fn main() -> Result<()> {
             contract_multiplier,
             requested_notional_cents,
             approval_token,
+            priority,
+            max_queue_wait_ms,
         } => {
             let client_order_id =
                 client_order_id.unwrap_or_else(|| format!("ctl-{}", Uuid::new_v4().as_simple()));
@@ -516,7 +841,9 @@ async fn main() -> Result<()> {
                 side: side.into(),
                 order_type: order_type.into(),
                 qty,
+                quote_size,
                 limit_price,
+                stop_price,
                 tif: tif.map(Into::into),
                 post_only,
                 reduce_only,
@@ -525,6 +852,8 @@ async fn main() -> Result<()> {
             let payload = ExecutionPlacePayload {
                 order,
                 approval_token,
+                priority: priority.map(Into::into),
+                max_queue_wait_ms,
             };
 
             (
@@ -569,29 +898,102 @@ async fn main() -> Result<()> {
         Commands::PortfolioExposure => {
             (PortfolioCommand::Exposure.as_kind(), serde_json::json!({}))
         }
+        Commands::PortfolioPnl {
+            strategy_id,
+            venue,
+            since_ts_ms,
+            json: _,
+        } => {
+            let payload = PortfolioPnlPayload {
+                strategy_id,
+                venue,
+                since_ts_ms,
+            };
+            (
+                PortfolioCommand::Pnl.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::PortfolioPaperAccount { action, balances } => {
+            let payload = PortfolioPaperAccountPayload {
+                action: action.into(),
+                balances: balances.into_iter().collect(),
+            };
+            (
+                PortfolioCommand::PaperAccount.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::MarketDataQuote {
+            venue,
+            venue_symbol,
+        } => {
+            let payload = MarketDataQuotePayload {
+                venue,
+                venue_symbol,
+            };
+            (
+                MarketDataCommand::Quote.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::MarketDataProduct {
+            venue,
+            venue_symbol,
+        } => {
+            let payload = MarketDataProductPayload {
+                venue,
+                venue_symbol,
+            };
+            (
+                MarketDataCommand::Product.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::MarketDataRegime {
+            venue,
+            venue_symbol,
+        } => {
+            let payload = MarketDataRegimePayload {
+                venue,
+                venue_symbol,
+            };
+            (
+                MarketDataCommand::Regime.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
+        Commands::MarketDataSubscribe {
+            venue,
+            venue_symbols,
+        } => {
+            let payload = MarketDataSubscribePayload {
+                venue,
+                venue_symbols,
+            };
+            (
+                MarketDataCommand::Subscribe.as_kind(),
+                serde_json::to_value(payload)?,
+            )
+        }
         Commands::Raw { json } => ("Control.Raw", serde_json::from_str(&json)?),
     };
 
-    let envelope = Envelope::new(kind, payload);
-    let bytes = serde_json::to_vec(&envelope)?;
-
-    framed.send(bytes.into()).await?;
-
-    if let Some(resp) = framed.next().await {
-        let resp_bytes = resp?;
-        let resp_env: Envelope = serde_json::from_slice(&resp_bytes)?;
-        println!("{}", serde_json::to_string_pretty(&resp_env)?);
-    } else {
-        println!("No response received.");
+    let mut request = Envelope::new(kind, payload);
+    if let Some(correlation_id) = cli.correlation_id {
+        request = request.with_correlation_id(correlation_id);
     }
+    let response = client.send_envelope(request).await.map_err(client_error)?;
+    println!("{}", serde_json::to_string_pretty(&response)?);
 
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_risk_override_value;
+    use super::{parse_balance, parse_risk_override_value, render_pnl, schema_output};
     use serde_json::json;
+    use trading_protocol::{PnlBucketPayload, PnlPositionPayload, PortfolioPnlResultPayload};
 
     #[test]
     fn parse_risk_override_value_parses_json() {
@@ -604,4 +1006,64 @@ mod tests {
         let parsed = parse_risk_override_value("{");
         assert_eq!(parsed, serde_json::Value::String("{".to_string()));
     }
+
+    #[test]
+    fn parse_balance_reads_asset_amount_pairs() {
+        assert_eq!(parse_balance("usd=2500.5"), Ok(("USD".to_string(), 2500.5)));
+        assert_eq!(parse_balance("BTC=-0.25"), Ok(("BTC".to_string(), -0.25)));
+        assert!(parse_balance("USD").is_err());
+        assert!(parse_balance("USD=lots").is_err());
+    }
+
+    #[test]
+    fn schema_output_selects_one_type_or_all() {
+        let place = schema_output(Some("ExecutionPlacePayload")).expect("known type");
+        assert_eq!(place["title"], "ExecutionPlacePayload");
+
+        let all = schema_output(None).expect("all schemas");
+        assert!(all["types"]["Envelope"].is_object());
+        assert!(all["commands"].as_array().is_some_and(|c| !c.is_empty()));
+
+        assert!(schema_output(Some("NoSuchPayload")).is_err());
+    }
+
+    #[test]
+    fn render_pnl_lists_breakdowns_and_positions() {
+        let bucket = |key: &str| PnlBucketPayload {
+            key: key.to_string(),
+            realized_pnl: 12.5,
+            unrealized_pnl: -2.0,
+            fees: 0.5,
+            net_pnl: 10.0,
+            turnover: 250.0,
+            fills: 2,
+        };
+        let result = PortfolioPnlResultPayload {
+            as_of_ms: 1_000,
+            totals: bucket("total"),
+            by_strategy: vec![bucket("crypto.momentum")],
+            by_venue: vec![bucket("paper")],
+            by_instrument: vec![bucket("paper:BTC-USD")],
+            by_day: vec![bucket("2024-01-01")],
+            positions: vec![PnlPositionPayload {
+                strategy_id: "crypto.momentum".to_string(),
+                venue: "paper".to_string(),
+                venue_symbol: "BTC-USD".to_string(),
+                qty: 0.5,
+                avg_price: 100.0,
+                mark_price: Some(96.0),
+                mark_source: Some("quote".to_string()),
+                unrealized_pnl: -2.0,
+            }],
+        };
+
+        let rendered = render_pnl(&result);
+        assert!(rendered.contains("By strategy"));
+        assert!(rendered.contains("paper:BTC-USD"));
+        assert!(rendered.contains("2024-01-01"));
+        assert!(rendered.contains("96.0000"));
+        assert!(rendered
+            .lines()
+            .any(|line| line.starts_with("total") && line.contains("10.00")));
+    }
 }
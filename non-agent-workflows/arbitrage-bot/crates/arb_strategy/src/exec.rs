@@ -118,6 +118,7 @@ impl ArbExecutor {
                 no_price,
                 expiration_ts: None,
                 time_in_force: Some("fill_or_kill".into()),
+                post_only: false,
             });
         }
 
@@ -305,6 +306,7 @@ impl ArbExecutor {
                     no_price: None,
                     expiration_ts: None,
                     time_in_force: None, // Market orders don't need TIF
+                    post_only: false,
                 };
 
                 // Use batch endpoint so we can pass true Market order payload.
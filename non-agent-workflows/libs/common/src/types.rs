@@ -335,6 +335,9 @@ pub struct CreateOrderRequest {
     /// "immediate_or_cancel".  Omitted ⇒ API default (GTC).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_in_force: Option<String>,
+    /// Reject the order instead of letting it take liquidity.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub post_only: bool,
 }
 
 /// Response from POST /trade-api/v2/portfolio/orders.
@@ -378,6 +381,55 @@ pub struct OrderInfo {
     pub taker_fees: i64,
     #[serde(default, deserialize_with = "flexible_i64::deserialize")]
     pub maker_fees: i64,
+    #[serde(default)]
+    pub created_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_update_time: Option<DateTime<Utc>>,
+}
+
+/// Response from GET /trade-api/v2/portfolio/orders.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrdersResponse {
+    #[serde(default)]
+    pub orders: Vec<OrderInfo>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// A fill as returned by GET /trade-api/v2/portfolio/fills.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillInfo {
+    #[serde(default, deserialize_with = "flexible_string::deserialize")]
+    pub trade_id: String,
+    #[serde(default, deserialize_with = "flexible_string::deserialize")]
+    pub order_id: String,
+    #[serde(default, deserialize_with = "flexible_string::deserialize")]
+    pub ticker: String,
+    #[serde(default, deserialize_with = "flexible_side::deserialize")]
+    pub side: Side,
+    #[serde(default, deserialize_with = "flexible_action::deserialize")]
+    pub action: Action,
+    #[serde(default, deserialize_with = "flexible_i64::deserialize")]
+    pub count: i64,
+    /// YES price of the trade in cents.
+    #[serde(default, deserialize_with = "flexible_i64::deserialize")]
+    pub yes_price: i64,
+    /// NO price of the trade in cents.
+    #[serde(default, deserialize_with = "flexible_i64::deserialize")]
+    pub no_price: i64,
+    #[serde(default)]
+    pub is_taker: bool,
+    #[serde(default)]
+    pub created_time: Option<DateTime<Utc>>,
+}
+
+/// Response from GET /trade-api/v2/portfolio/fills.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FillsResponse {
+    #[serde(default)]
+    pub fills: Vec<FillInfo>,
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -431,6 +483,9 @@ pub struct Position {
     /// Number of NO contracts held.
     #[serde(default, deserialize_with = "flexible_i64::deserialize")]
     pub no_count: i64,
+    /// Net contracts as the API reports them: positive = YES, negative = NO.
+    #[serde(default, deserialize_with = "flexible_i64::deserialize")]
+    pub position: i64,
     /// Total cost basis in cents.
     #[serde(default, deserialize_with = "flexible_i64::deserialize")]
     pub market_exposure: i64,
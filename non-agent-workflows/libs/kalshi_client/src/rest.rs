@@ -4,8 +4,9 @@
 //! All methods are rate-limited and authenticated via RSA-PSS.
 
 use common::{
-    Action, BalanceResponse, CreateOrderRequest, CreateOrderResponse, Error, MarketInfo,
-    MarketsResponse, OrderIntent, OrderType, PositionsResponse, Side,
+    Action, BalanceResponse, CreateOrderRequest, CreateOrderResponse, Error, FillInfo,
+    FillsResponse, MarketInfo, MarketsResponse, OrderInfo, OrderIntent, OrderType, OrdersResponse,
+    PositionsResponse, Side,
 };
 use std::error::Error as StdError;
 use tracing::{debug, info, warn};
@@ -79,7 +80,12 @@ impl KalshiRestClient {
     ///
     /// * `use_demo` — if true, points to demo URL unless `KALSHI_API_BASE_URL` is set.
     pub fn new(auth: KalshiAuth, use_demo: bool) -> Self {
-        let base_url = resolve_rest_base_url(use_demo);
+        Self::with_base_url(auth, &resolve_rest_base_url(use_demo))
+    }
+
+    /// Create a REST client for an explicit API origin, e.g. a test server.
+    pub fn with_base_url(auth: KalshiAuth, base_url: &str) -> Self {
+        let base_url = normalize_base_url(base_url);
 
         let client = reqwest::Client::builder()
             .pool_max_idle_per_host(4)
@@ -294,17 +300,162 @@ impl KalshiRestClient {
         Ok(all_positions)
     }
 
-    // ── Write endpoints ───────────────────────────────────────────────
+    /// Fetch one order; `None` when the API does not know it.
+    pub async fn get_order(&self, order_id: &str) -> Result<Option<OrderInfo>, Error> {
+        self.limiter.wait_read().await;
 
-    /// Place an order via the Kalshi API.
-    pub async fn create_order(&self, intent: &OrderIntent) -> Result<CreateOrderResponse, Error> {
-        self.limiter.wait_write().await;
+        let path = format!("/trade-api/v2/portfolio/orders/{}", order_id);
+        let headers = self.auth.headers("GET", &path);
 
-        let path = "/trade-api/v2/portfolio/orders";
-        let headers = self.auth.headers("POST", path);
+        let resp = self
+            .client
+            .get(self.url(&path))
+            .headers(headers)
+            .send()
+            .await
+            .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
+
+        let status_code = resp.status().as_u16();
+        if status_code == 404 {
+            return Ok(None);
+        }
+        if status_code != 200 {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(Error::KalshiApi {
+                status: status_code,
+                message: body,
+            });
+        }
+
+        let body: CreateOrderResponse = resp
+            .json()
+            .await
+            .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
+
+        Ok(Some(body.order))
+    }
+
+    /// Fetch orders, optionally filtered by status (`resting`, `canceled`,
+    /// `executed`). Handles pagination automatically.
+    pub async fn get_orders(&self, status: Option<&str>) -> Result<Vec<OrderInfo>, Error> {
+        let mut all_orders = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            self.limiter.wait_read().await;
+
+            let path = "/trade-api/v2/portfolio/orders";
+            let headers = self.auth.headers("GET", path);
+
+            let mut req = self
+                .client
+                .get(self.url(path))
+                .headers(headers)
+                .query(&[("limit", "200")]);
+
+            if let Some(s) = status {
+                req = req.query(&[("status", s)]);
+            }
+            if let Some(ref c) = cursor {
+                req = req.query(&[("cursor", c.as_str())]);
+            }
+
+            let resp = req
+                .send()
+                .await
+                .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
+
+            let status_code = resp.status().as_u16();
+            if status_code != 200 {
+                let body = resp.text().await.unwrap_or_default();
+                return Err(Error::KalshiApi {
+                    status: status_code,
+                    message: body,
+                });
+            }
+
+            let body: OrdersResponse = resp
+                .json()
+                .await
+                .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
+
+            all_orders.extend(body.orders);
+
+            match body.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        Ok(all_orders)
+    }
+
+    /// Fetch fills, optionally for one order and/or since `min_ts` (Unix
+    /// seconds). Handles pagination automatically.
+    pub async fn get_fills(
+        &self,
+        order_id: Option<&str>,
+        min_ts: Option<i64>,
+    ) -> Result<Vec<FillInfo>, Error> {
+        let mut all_fills = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            self.limiter.wait_read().await;
+
+            let path = "/trade-api/v2/portfolio/fills";
+            let headers = self.auth.headers("GET", path);
+
+            let mut req = self
+                .client
+                .get(self.url(path))
+                .headers(headers)
+                .query(&[("limit", "200")]);
+
+            if let Some(id) = order_id {
+                req = req.query(&[("order_id", id)]);
+            }
+            if let Some(ts) = min_ts {
+                req = req.query(&[("min_ts", ts.to_string())]);
+            }
+            if let Some(ref c) = cursor {
+                req = req.query(&[("cursor", c.as_str())]);
+            }
+
+            let resp = req
+                .send()
+                .await
+                .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
+
+            let status_code = resp.status().as_u16();
+            if status_code != 200 {
+                let body = resp.text().await.unwrap_or_default();
+                return Err(Error::KalshiApi {
+                    status: status_code,
+                    message: body,
+                });
+            }
+
+            let body: FillsResponse = resp
+                .json()
+                .await
+                .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
 
-        let client_order_id = Uuid::new_v4().to_string();
+            all_fills.extend(body.fills);
 
+            match body.cursor {
+                Some(c) if !c.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        Ok(all_fills)
+    }
+
+    // ── Write endpoints ───────────────────────────────────────────────
+
+    /// Place an order via the Kalshi API.
+    pub async fn create_order(&self, intent: &OrderIntent) -> Result<CreateOrderResponse, Error> {
         let (yes_price, no_price) = match intent.side {
             Side::Yes => (Some(intent.price_cents), None),
             Side::No => (None, Some(intent.price_cents)),
@@ -314,13 +465,14 @@ impl KalshiRestClient {
             ticker: intent.ticker.clone(),
             side: intent.side,
             action: intent.action,
-            client_order_id: client_order_id.clone(),
+            client_order_id: Uuid::new_v4().to_string(),
             count: intent.count,
             order_type: OrderType::Limit,
             yes_price,
             no_price,
             expiration_ts: None,
             time_in_force: None,
+            post_only: false,
         };
 
         debug!(
@@ -339,11 +491,24 @@ impl KalshiRestClient {
             intent.reason,
         );
 
+        self.submit_order(&body).await
+    }
+
+    /// Place a fully specified order, keeping the caller's `client_order_id`.
+    pub async fn submit_order(
+        &self,
+        body: &CreateOrderRequest,
+    ) -> Result<CreateOrderResponse, Error> {
+        self.limiter.wait_write().await;
+
+        let path = "/trade-api/v2/portfolio/orders";
+        let headers = self.auth.headers("POST", path);
+
         let resp = self
             .client
             .post(self.url(path))
             .headers(headers)
-            .json(&body)
+            .json(body)
             .send()
             .await
             .map_err(|e| Error::Http(format_reqwest_error(&e)))?;
@@ -181,11 +181,14 @@ impl KalshiWsClient {
     }
 
     pub fn new(auth: KalshiAuth, use_demo: bool, price_cache: PriceCache) -> Self {
-        let ws_url = resolve_ws_url(use_demo);
+        Self::with_url(auth, &resolve_ws_url(use_demo), price_cache)
+    }
 
+    /// Create a client for an explicit WebSocket endpoint, e.g. a test server.
+    pub fn with_url(auth: KalshiAuth, ws_url: &str, price_cache: PriceCache) -> Self {
         Self {
             auth,
-            ws_url,
+            ws_url: normalize_ws_url(ws_url),
             price_cache,
         }
     }
@@ -273,6 +273,7 @@ mod tests {
             ticker: ticker.into(),
             yes_count,
             no_count: 0,
+            position: yes_count,
             market_exposure: exposure,
             realized_pnl: 0,
         }